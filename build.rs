@@ -0,0 +1,53 @@
+// Embeds build information for `/version` and the `version` export (see src/buildinfo.rs).
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !text.trim().is_empty()).then(|| text.trim().to_string())
+}
+
+/// `component:ai-agent/ai-agent-world`, with the package version when the WIT declares one.
+fn wit_world(wit: &str) -> String {
+    let decl = |keyword: &str| {
+        wit.lines().find_map(|line| line.trim().strip_prefix(keyword).map(|rest| rest.trim_end_matches(['{', ';']).trim().to_string()))
+    };
+    let package = decl("package ").unwrap_or_default();
+    let world = decl("world ").unwrap_or_default();
+    match package.split_once('@') {
+        Some((name, version)) => format!("{name}/{world}@{version}"),
+        None => format!("{package}/{world}"),
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=wit/world.wit");
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // A path that does not exist would rebuild every time
+    for path in [".git/HEAD", ".git/refs", ".git/packed-refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+
+    // GIT_SHA covers builds from a source archive, where there is no repository to ask
+    let sha = std::env::var("GIT_SHA").ok().filter(|s| !s.is_empty()).or_else(|| git(&["rev-parse", "--short=12", "HEAD"]));
+    // SOURCE_DATE_EPOCH keeps reproducible builds reproducible
+    let built_at = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()));
+    let wit = std::fs::read_to_string("wit/world.wit").unwrap_or_default();
+    let digest: String = Sha256::digest(wit.as_bytes()).iter().take(6).map(|b| format!("{b:02x}")).collect();
+
+    println!("cargo:rustc-env=BUILD_GIT_SHA={}", sha.unwrap_or_else(|| "unknown".into()));
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={built_at}");
+    println!("cargo:rustc-env=BUILD_WIT_WORLD={}", wit_world(&wit));
+    println!("cargo:rustc-env=BUILD_WIT_DIGEST={digest}");
+}
@@ -0,0 +1,270 @@
+//! Runs the built component under wasmtime, drives `wasi:http/incoming-handler.handle` with
+//! synthetic requests and records every outgoing `wasi:http` call against canned stub replies.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime::{Config, Engine, Store};
+use wasmtime_wasi::{IoView, WasiCtx, WasiCtxBuilder, WasiView};
+use wasmtime_wasi_http::bindings::http::types::{ErrorCode, Scheme};
+use wasmtime_wasi_http::bindings::ProxyPre;
+use wasmtime_wasi_http::body::HyperOutgoingBody;
+use wasmtime_wasi_http::types::{HostFutureIncomingResponse, IncomingResponse, OutgoingRequestConfig};
+use wasmtime_wasi_http::{HttpResult, WasiHttpCtx, WasiHttpView};
+use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+
+/* ---- Recorded outgoing calls and canned replies ---- */
+/// An outgoing request the component made, as seen by the stub handler.
+#[derive(Debug, Clone)]
+pub struct OutgoingCall {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl OutgoingCall {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Canned reply for outgoing requests whose URL starts with `prefix`; the first match wins.
+#[derive(Debug, Clone)]
+struct Stub {
+    prefix: String,
+    status: u16,
+    content_type: String,
+    body: String,
+}
+
+/// The component's response to one incoming request.
+#[derive(Debug)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Reply {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+/* ---- Host state for one component instance ---- */
+struct Host {
+    table: ResourceTable,
+    wasi: WasiCtx,
+    http: WasiHttpCtx,
+    keyvalue: WasiKeyValueCtx,
+    stubs: Arc<Vec<Stub>>,
+    calls: Arc<Mutex<Vec<OutgoingCall>>>,
+}
+
+impl IoView for Host {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for Host {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl WasiHttpView for Host {
+    fn ctx(&mut self) -> &mut WasiHttpCtx {
+        &mut self.http
+    }
+
+    // Outgoing requests never reach the network: they are recorded and answered from the stubs
+    fn send_request(
+        &mut self,
+        request: hyper::Request<HyperOutgoingBody>,
+        _config: OutgoingRequestConfig,
+    ) -> HttpResult<HostFutureIncomingResponse> {
+        let stubs = self.stubs.clone();
+        let calls = self.calls.clone();
+        let handle = wasmtime_wasi::runtime::spawn(async move {
+            let (parts, body) = request.into_parts();
+            let body = match body.collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(e) => return Ok(Err(e)),
+            };
+            let url = parts.uri.to_string();
+            calls.lock().unwrap().push(OutgoingCall {
+                method: parts.method.to_string(),
+                url: url.clone(),
+                headers: parts
+                    .headers
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+                    .collect(),
+                body: String::from_utf8_lossy(&body).into_owned(),
+            });
+
+            let (status, content_type, text) = match stubs.iter().find(|s| url.starts_with(&s.prefix)) {
+                Some(s) => (s.status, s.content_type.clone(), s.body.clone()),
+                None => (404, "text/plain".to_string(), format!("no stub for {url}")),
+            };
+            let resp = hyper::Response::builder()
+                .status(status)
+                .header("content-type", content_type)
+                .body(Full::new(Bytes::from(text)).map_err(|never| match never {}).boxed())
+                .expect("stub response");
+            let resp: anyhow::Result<Result<IncomingResponse, ErrorCode>> =
+                Ok(Ok(IncomingResponse { resp, worker: None, between_bytes_timeout: Duration::from_secs(5) }));
+            resp
+        });
+        Ok(HostFutureIncomingResponse::pending(handle))
+    }
+}
+
+/* ---- Harness ---- */
+/// A compiled component plus the environment and stubs each request runs with. Like
+/// `wasmtime serve`, every request gets a fresh instance (and a fresh in-memory key-value store).
+pub struct Harness {
+    engine: Engine,
+    pre: ProxyPre<Host>,
+    env: Vec<(String, String)>,
+    stubs: Vec<Stub>,
+    calls: Arc<Mutex<Vec<OutgoingCall>>>,
+}
+
+impl Harness {
+    /// Loads the component from `AI_AGENT_WASM`, or the default `cargo component build --release` output.
+    pub fn new() -> Result<Harness> {
+        let path = std::env::var_os("AI_AGENT_WASM").map(PathBuf::from).unwrap_or_else(|| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../target/wasm32-wasip1/release/ai_agent_rust_slack.wasm")
+        });
+        Harness::from_file(path)
+    }
+
+    pub fn from_file(path: PathBuf) -> Result<Harness> {
+        let mut config = Config::new();
+        config.async_support(true);
+        config.wasm_component_model(true);
+        let engine = Engine::new(&config)?;
+        let component = Component::from_file(&engine, &path).with_context(|| {
+            format!("loading {} (build it with `cargo component build --release` first)", path.display())
+        })?;
+
+        let mut linker = Linker::<Host>::new(&engine);
+        wasmtime_wasi::add_to_linker_async(&mut linker)?;
+        wasmtime_wasi_http::add_only_http_to_linker_async(&mut linker)?;
+        wasmtime_wasi_keyvalue::add_to_linker(&mut linker, |h: &mut Host| {
+            WasiKeyValue::new(&h.keyvalue, &mut h.table)
+        })?;
+        let pre = ProxyPre::new(linker.instantiate_pre(&component)?)?;
+
+        Ok(Harness { engine, pre, env: Vec::new(), stubs: Vec::new(), calls: Arc::default() })
+    }
+
+    /// Sets an environment variable visible to the component.
+    pub fn env(mut self, name: &str, value: &str) -> Harness {
+        self.env.retain(|(k, _)| k != name);
+        self.env.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Answers outgoing requests whose URL starts with `prefix`. Unmatched requests get a 404.
+    pub fn stub(mut self, prefix: &str, status: u16, content_type: &str, body: &str) -> Harness {
+        self.stubs.push(Stub {
+            prefix: prefix.to_string(),
+            status,
+            content_type: content_type.to_string(),
+            body: body.to_string(),
+        });
+        self
+    }
+
+    /// Every outgoing call recorded so far, in order.
+    pub fn calls(&self) -> Vec<OutgoingCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Recorded outgoing calls whose URL starts with `prefix`.
+    pub fn calls_to(&self, prefix: &str) -> Vec<OutgoingCall> {
+        self.calls().into_iter().filter(|c| c.url.starts_with(prefix)).collect()
+    }
+
+    /// Sends one request through `incoming-handler.handle` and waits for the handler to return,
+    /// so work deferred past the response (Slack deliveries, scheduled tasks) has also run.
+    pub async fn send(&self, method: &str, path: &str, headers: &[(&str, &str)], body: &str) -> Result<Reply> {
+        let mut wasi = WasiCtxBuilder::new();
+        wasi.inherit_stderr();
+        for (name, value) in &self.env {
+            wasi.env(name, value);
+        }
+        let host = Host {
+            table: ResourceTable::new(),
+            wasi: wasi.build(),
+            http: WasiHttpCtx::new(),
+            keyvalue: WasiKeyValueCtxBuilder::new().build(),
+            stubs: Arc::new(self.stubs.clone()),
+            calls: self.calls.clone(),
+        };
+        let mut store = Store::new(&self.engine, host);
+
+        let mut request = hyper::Request::builder().method(method).uri(format!("http://localhost{path}"));
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = request.body(Full::new(Bytes::from(body.to_string())).map_err(|never| match never {}))?;
+
+        let req = store.data_mut().new_incoming_request(Scheme::Http, request)?;
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let out = store.data_mut().new_response_outparam(sender)?;
+        let proxy = self.pre.instantiate_async(&mut store).await?;
+
+        // The handler keeps running after it sets the response, so read the body concurrently
+        let task = tokio::spawn(async move { proxy.wasi_http_incoming_handler().call_handle(&mut store, req, out).await });
+
+        let resp = receiver
+            .await
+            .context("component did not set a response")?
+            .map_err(|e| anyhow!("component responded with error code {e:?}"))?;
+        let (parts, body) = resp.into_parts();
+        let body = body.collect().await.map_err(|e| anyhow!("reading response body: {e:?}"))?.to_bytes();
+        task.await??;
+
+        Ok(Reply {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+                .collect(),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+
+    /// Posts a Slack slash command (`application/x-www-form-urlencoded`) to `/slack/command`.
+    pub async fn slash_command(&self, command: &str, text: &str, response_url: &str) -> Result<Reply> {
+        let form = [("command", command), ("text", text), ("response_url", response_url), ("team_id", "T0001"), ("user_id", "U0001")]
+            .iter()
+            .map(|(k, v)| format!("{k}={}", form_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        self.send("POST", "/slack/command", &[("content-type", "application/x-www-form-urlencoded")], &form).await
+    }
+}
+
+fn form_encode(s: &str) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
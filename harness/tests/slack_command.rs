@@ -0,0 +1,89 @@
+use ai_agent_harness::Harness;
+
+const OPENAI: &str = "https://api.openai.com/v1/chat/completions";
+const RESPONSE_URL: &str = "https://hooks.slack.com/commands/T0001/42/abc";
+
+fn completion(content: &str) -> String {
+    serde_json::json!({"choices": [{"message": {"role": "assistant", "content": content}}]}).to_string()
+}
+
+fn harness() -> Harness {
+    Harness::new()
+        .expect("component")
+        .env("OPENAI_API_KEY", "sk-test")
+        .env("SLACK_BOT_TOKEN", "xoxb-test")
+}
+
+#[tokio::test]
+async fn health_is_served_without_outgoing_calls_to_slack() {
+    let h = harness();
+    let reply = h.send("GET", "/health", &[], "").await.unwrap();
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.body, "ok");
+    assert!(h.calls_to("https://hooks.slack.com/").is_empty());
+}
+
+#[tokio::test]
+async fn slash_command_acks_then_delivers_the_answer_to_response_url() {
+    let h = harness()
+        .stub(OPENAI, 200, "application/json", &completion("Paris is the capital of France."))
+        .stub("https://hooks.slack.com/", 200, "text/plain", "ok");
+
+    let reply = h.slash_command("/ask", "What is the capital of France?", RESPONSE_URL).await.unwrap();
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.body, "ack");
+
+    let asked = h.calls_to(OPENAI);
+    let question = asked
+        .iter()
+        .find(|c| c.body.contains("What is the capital of France?"))
+        .expect("OpenAI was asked the question");
+    assert_eq!(question.method, "POST");
+    assert_eq!(question.header("authorization"), Some("Bearer sk-test"));
+
+    let delivered = h.calls_to(RESPONSE_URL);
+    assert_eq!(delivered.len(), 1);
+    let payload: serde_json::Value = serde_json::from_str(&delivered[0].body).unwrap();
+    assert_eq!(payload, serde_json::json!({"response_type": "in_channel", "text": "Paris is the capital of France."}));
+}
+
+#[tokio::test]
+async fn slash_command_falls_back_when_openai_fails() {
+    let h = harness()
+        .stub(OPENAI, 500, "application/json", r#"{"error":{"message":"upstream down"}}"#)
+        .stub("https://hooks.slack.com/", 200, "text/plain", "ok");
+
+    let reply = h.slash_command("/ask", "hello", RESPONSE_URL).await.unwrap();
+    assert_eq!(reply.body, "ack");
+
+    let delivered = h.calls_to(RESPONSE_URL);
+    let payload: serde_json::Value = serde_json::from_str(&delivered[0].body).unwrap();
+    let text = payload["text"].as_str().unwrap();
+    assert!(text.starts_with("You said: hello (AI unavailable:"), "{text}");
+}
+
+#[tokio::test]
+async fn translate_command_renders_the_structured_reply() {
+    let translation = serde_json::json!({"detected_language": "French", "translation": "Where is the station?"});
+    let h = harness()
+        .stub(OPENAI, 200, "application/json", &completion(&translation.to_string()))
+        .stub("https://hooks.slack.com/", 200, "text/plain", "ok");
+
+    h.slash_command("/translate", "en Où est la gare ?", RESPONSE_URL).await.unwrap();
+
+    let delivered = h.calls_to(RESPONSE_URL);
+    let payload: serde_json::Value = serde_json::from_str(&delivered[0].body).unwrap();
+    assert_eq!(payload["text"], "*Translation* (French → en)\n>Where is the station?");
+}
+
+#[tokio::test]
+async fn failed_delivery_is_kept_for_retry_instead_of_dropped() {
+    let h = harness()
+        .stub(OPENAI, 200, "application/json", &completion("Hi!"))
+        .stub("https://hooks.slack.com/", 503, "text/plain", "unavailable");
+
+    let reply = h.slash_command("/ask", "hi", RESPONSE_URL).await.unwrap();
+    assert_eq!(reply.body, "ack");
+    // One attempt per request; the retry is scheduled with backoff rather than hammered inline
+    assert_eq!(h.calls_to(RESPONSE_URL).len(), 1);
+}
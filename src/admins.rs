@@ -0,0 +1,128 @@
+//! Slack admin role for admin slash commands: listed user IDs or members of a usergroup.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::messages::{Msg, message};
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::api::SlackApi;
+use crate::store::{store_get, store_set};
+
+/* ---- Admin commands (SLACK_ADMIN_USERS, SLACK_ADMIN_USERGROUP) ---- */
+// Commands that expose workspace-wide data or change shared behaviour are limited to admins:
+// the comma-separated user IDs in SLACK_ADMIN_USERS and the members of the usergroup
+// SLACK_ADMIN_USERGROUP (e.g. `S0123ABCD`, resolved with usergroups.users.list). With neither
+// set, admin commands are refused for everyone. The user ID comes from the command's form, which
+// the router only reads once Slack's signature on it has been verified (see slack::signature).
+pub(crate) const ADMIN_COMMANDS: &[&str] = &["/prompt-report", "/agent-quality", "/agent-usage", "/agent-broadcast"];
+
+// Usergroup members are cached in the store so every admin command is not a Slack API call
+const USERGROUP_CACHE_SECS: u64 = 300;
+
+#[derive(Serialize, Deserialize)]
+struct CachedMembers {
+    users: Vec<String>,
+    fetched_at: u64,
+}
+
+fn admin_users() -> Vec<String> {
+    get_env_var("SLACK_ADMIN_USERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .collect()
+}
+
+fn usergroup_members(group: &str) -> Result<Vec<String>, AgentError> {
+    let key = format!("admin-usergroup:{group}");
+    let cached = store_get(&key).ok().flatten().and_then(|raw| serde_json::from_str::<CachedMembers>(&raw).ok());
+    if let Some(cached) = cached.filter(|c| now_secs() < c.fetched_at + USERGROUP_CACHE_SECS) {
+        return Ok(cached.users);
+    }
+
+    let json = SlackApi::from_env()?.get("usergroups.users.list", &[("usergroup", group)])?;
+    let users: Vec<String> = json["users"].as_array().into_iter().flatten().filter_map(|u| u.as_str().map(str::to_string)).collect();
+    if let Ok(raw) = serde_json::to_string(&CachedMembers { users: users.clone(), fetched_at: now_secs() }) {
+        let _ = store_set(&key, &raw);
+    }
+    Ok(users)
+}
+
+fn is_slack_admin(user_id: &str) -> Result<bool, AgentError> {
+    if user_id.is_empty() {
+        return Ok(false);
+    }
+    if admin_users().iter().any(|u| u == user_id) {
+        return Ok(true);
+    }
+    match get_env_var("SLACK_ADMIN_USERGROUP").filter(|g| !g.trim().is_empty()) {
+        Some(group) => Ok(usergroup_members(group.trim())?.iter().any(|u| u == user_id)),
+        None => Ok(false),
+    }
+}
+
+/// The reply refusing `command` to `user_id`, or `None` when the user may run it.
+pub(crate) fn admin_command_denial(command: &str, user_id: &str) -> Option<String> {
+    if !ADMIN_COMMANDS.contains(&command) {
+        return None;
+    }
+    admin_denial(command, user_id)
+}
+
+/// Like [`admin_command_denial`] for an admin-only form of a command, e.g. `/agent-forget channel`.
+pub(crate) fn admin_denial(action: &str, user_id: &str) -> Option<String> {
+    if admin_users().is_empty() && get_env_var("SLACK_ADMIN_USERGROUP").is_none_or(|g| g.trim().is_empty()) {
+        return Some(format!("`{action}` is disabled: no Slack admins are configured (SLACK_ADMIN_USERS or SLACK_ADMIN_USERGROUP)."));
+    }
+    match is_slack_admin(user_id) {
+        Ok(true) => None,
+        Ok(false) => Some(message(Msg::AdminsOnly, &[("action", action)])),
+        Err(e) => Some(with_ref(&format!("Couldn't check whether you may run `{action}`: {e}"), &current_request_id())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+    use std::env;
+
+    #[test]
+    fn admin_commands_need_a_listed_user_or_a_usergroup_member() {
+        with_openai_env();
+        let clock = MockClock::install(1_700_000_000);
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        env::set_var("SLACK_ADMIN_USERS", "U0ADMIN, U0OPS");
+        env::set_var("SLACK_ADMIN_USERGROUP", "S0ADMINS");
+        mock.respond(200, "application/json", r#"{"ok":true,"users":["U0LEAD"]}"#);
+        mock.respond(200, "application/json", r#"{"ok":false,"error":"missing_scope"}"#);
+
+        assert_eq!(admin_command_denial("/ask", "U0ANYONE"), None);
+        assert_eq!(admin_command_denial("/agent-usage", "U0OPS"), None);
+        assert!(mock.requests.borrow().is_empty());
+
+        assert_eq!(admin_command_denial("/prompt-report", "U0LEAD"), None);
+        assert_eq!(
+            admin_command_denial("/prompt-report", "U0ANYONE").unwrap(),
+            "Sorry, `/prompt-report` is limited to workspace admins. Ask an admin to run it for you."
+        );
+        assert_eq!(mock.requests.borrow().len(), 1);
+        assert_eq!(mock.requests.borrow()[0].url, "https://slack.com/api/usergroups.users.list?usergroup=S0ADMINS");
+
+        // Membership is looked up again once the cache expires
+        clock.advance(USERGROUP_CACHE_SECS);
+        assert_eq!(
+            admin_command_denial("/prompt-report", "U0LEAD").unwrap(),
+            "Couldn't check whether you may run `/prompt-report`: usergroups.users.list: missing_scope"
+        );
+        env::remove_var("SLACK_ADMIN_USERS");
+        env::remove_var("SLACK_ADMIN_USERGROUP");
+    }
+}
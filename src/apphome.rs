@@ -0,0 +1,144 @@
+//! The App Home tab: usage, recent conversations and preference buttons, published with views.publish.
+
+use crate::error::AgentError;
+use crate::history::{format_time, recent_exchanges_by};
+use crate::jobs::{DeferredTask, defer_task};
+use crate::payloads::{BlockAction, SlackInteraction};
+use crate::prefs::{Verbosity, update_prefs, user_prefs};
+use crate::slack::api::SlackApi;
+use crate::slack::blocks::{Block, Button, ButtonStyle, Text, View};
+use crate::usage::user_usage;
+use crate::workspaces::{CAPABILITIES, set_workspace};
+
+/* ---- Home tab (app_home_opened) ---- */
+// Slack sends `app_home_opened` each time a user opens the app's Home tab. The tab is rebuilt and
+// published after the ack, so it always shows current numbers: the user's own usage for the last
+// week, their latest exchanges in any channel, and buttons for the preferences /agent-prefs also
+// sets. A click on one saves the preference and publishes the tab again. Each button's value is
+// the `/agent-prefs` text it applies, e.g. `verbosity brief`.
+pub(crate) const HOME_PREFS_ACTION_PREFIX: &str = "home_pref_";
+const USAGE_DAYS: u64 = 7;
+const RECENT_EXCHANGES: usize = 5;
+const SNIPPET_CHARS: usize = 150;
+
+/// The first line of `text`, cut to SNIPPET_CHARS and ending with "…" when anything was left out.
+fn snippet(text: &str) -> String {
+    let text = text.trim();
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > SNIPPET_CHARS {
+        format!("{}…", line.chars().take(SNIPPET_CHARS - 1).collect::<String>())
+    } else if line.len() < text.len() {
+        format!("{line} …")
+    } else {
+        line.to_string()
+    }
+}
+
+/// One button per choice of `setting`; the current choice is highlighted.
+fn choice_buttons(setting: &str, choices: &[(&str, &str, bool)]) -> Block {
+    Block::actions(choices.iter().map(|(label, value, current)| {
+        let button = Button::new(label, &format!("{HOME_PREFS_ACTION_PREFIX}{setting}_{value}")).value(&format!("{setting} {value}"));
+        if *current { button.style(ButtonStyle::Primary) } else { button }
+    }))
+}
+
+fn home_view(user: &str) -> View {
+    let mut blocks = vec![Block::header("Your AI assistant"), Block::section(&format!("Here is what I can do:\n{CAPABILITIES}")), Block::divider()];
+
+    blocks.push(Block::header(&format!("Your usage, last {USAGE_DAYS} days")));
+    blocks.push(match user_usage(user, USAGE_DAYS) {
+        Ok((requests, tokens)) => Block::fields([format!("*Requests*\n{requests}"), format!("*Tokens*\n{tokens}")]),
+        Err(e) => Block::section(&format!("_Usage is unavailable: {e}_")),
+    });
+
+    blocks.push(Block::header("Recent conversations"));
+    match recent_exchanges_by(user, RECENT_EXCHANGES) {
+        Ok(recent) if recent.is_empty() => blocks.push(Block::section("_Nothing yet. Ask me with `/ai ask` or mention me in a channel._")),
+        Ok(recent) => {
+            for (channel, exchange) in &recent {
+                blocks.push(Block::section(&format!("*{}*\n{}", snippet(&exchange.question), snippet(&exchange.answer))));
+                blocks.push(Block::context([Text::mrkdwn(&format!("<#{channel}> · {}", format_time(exchange.at)))]));
+            }
+        }
+        Err(e) => blocks.push(Block::section(&format!("_Conversations are unavailable: {e}_"))),
+    }
+
+    let prefs = user_prefs(user);
+    blocks.push(Block::divider());
+    blocks.push(Block::header("Preferences"));
+    blocks.push(Block::section(&prefs.describe()));
+    blocks.push(choice_buttons("verbosity", &[
+        ("Brief", "brief", prefs.verbosity == Verbosity::Brief),
+        ("Normal", "normal", prefs.verbosity == Verbosity::Normal),
+        ("Detailed", "detailed", prefs.verbosity == Verbosity::Detailed),
+    ]));
+    blocks.push(choice_buttons("visibility", &[("Answer in the channel", "channel", !prefs.private), ("Answer only me", "private", prefs.private)]));
+    View::home(blocks)
+}
+
+/// Publishes `user`'s Home tab with the workspace's bot token.
+pub(crate) fn publish_home(team_id: &str, user: &str) -> Result<(), AgentError> {
+    set_workspace(team_id);
+    SlackApi::from_env()?.publish_view(user, &home_view(user))
+}
+
+/// Applies a preference button from the Home tab, then publishes the tab again after the ack.
+pub(crate) fn handle_home_action(interaction: &SlackInteraction, action: &BlockAction) -> Result<(), AgentError> {
+    let missing = |field: &str| AgentError::Validation(format!("{}: missing {field}", action.action_id));
+    let user = &interaction.user.as_ref().ok_or_else(|| missing("user"))?.id;
+    update_prefs(user, action.value().ok_or_else(|| missing("value"))?)?;
+    let team_id = interaction.team.as_ref().map(|t| t.id.clone()).unwrap_or_default();
+    defer_task(DeferredTask::PublishHome { team_id, user: user.clone() });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::history::{Exchange, record_test_exchange};
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+
+    #[test]
+    fn home_tab_shows_the_users_conversations_and_preferences() {
+        with_openai_env();
+        crate::clock::MockClock::install(1_700_000_000);
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+        let exchange = |user: &str, question: &str, at: u64| Exchange {
+            user_id: user.into(),
+            question: question.into(),
+            answer: "Paris.\nIt has been the capital since 987.".into(),
+            private: false,
+            thread_ts: None,
+            at,
+            region: None,
+        };
+        record_test_exchange("C1", &exchange("U1", "Capital of France?", 1_699_990_000));
+        record_test_exchange("C2", &exchange("U2", "Someone else's question", 1_699_995_000));
+        update_prefs("U1", "verbosity brief").unwrap();
+
+        publish_home("T1", "U1").unwrap();
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/views.publish");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!((body["user_id"].as_str(), body["view"]["type"].as_str()), (Some("U1"), Some("home")));
+        let blocks = body["view"]["blocks"].as_array().unwrap();
+        let texts: Vec<&str> = blocks.iter().filter_map(|b| b["text"]["text"].as_str().or(b["elements"][0]["text"].as_str())).collect();
+        assert!(texts.contains(&"*Capital of France?*\nParis. …"), "{texts:?}");
+        assert!(texts.contains(&"<#C1> · 2023-11-14 19:26 UTC"), "{texts:?}");
+        assert!(!texts.iter().any(|t| t.contains("Someone else")));
+        let verbosity = &blocks[blocks.len() - 2]["elements"];
+        assert_eq!((verbosity[0]["action_id"].as_str(), verbosity[0]["value"].as_str()), (Some("home_pref_verbosity_brief"), Some("verbosity brief")));
+        assert_eq!((verbosity[0]["style"].as_str(), verbosity[1]["style"].as_str()), (Some("primary"), None));
+    }
+
+    #[test]
+    fn snippets_keep_the_first_line() {
+        assert_eq!(snippet("  One line  "), "One line");
+        assert_eq!(snippet("First\nSecond"), "First …");
+        assert_eq!(snippet(&"x".repeat(200)).chars().count(), SNIPPET_CHARS);
+    }
+}
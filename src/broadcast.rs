@@ -0,0 +1,112 @@
+//! `/agent-broadcast`: one message to the configured channels, with delivery status in a thread.
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::llm::call_openai_with_system;
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::slack_post_message_ts;
+
+/* ---- Channels (BROADCAST_CHANNELS) and --polish ---- */
+const USAGE: &str = "Usage: `/agent-broadcast [--polish] <message>`";
+
+const POLISH_PROMPT: &str = "You edit internal announcements for Slack. Fix grammar and spelling, make the message \
+clear and friendly, and keep its meaning, facts, links, names and Slack formatting. Keep it about as long as the \
+original. Respond with the edited message only.";
+
+fn broadcast_channels() -> Vec<String> {
+    get_env_var("BROADCAST_CHANNELS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+/* ---- Delivery ---- */
+// Each channel is posted to in turn; one failing (e.g. the bot is not a member) does not stop
+// the rest. The status of every channel goes into a thread under a summary message in the
+// channel the command was run in, so the outcome stays next to the request.
+fn broadcast(text: &str, user: &str, channel: &str) -> Result<String, AgentError> {
+    let (text, polish) = match text.trim().strip_prefix("--polish") {
+        Some(rest) => (rest.trim(), true),
+        None => (text.trim(), false),
+    };
+    if text.is_empty() {
+        return Err(AgentError::Validation("a message is required".into()));
+    }
+    let channels = broadcast_channels();
+    if channels.is_empty() {
+        return Err(AgentError::Config("BROADCAST_CHANNELS is not set".into()));
+    }
+    let message = if polish { call_openai_with_system(POLISH_PROMPT, text, 800)? } else { text.to_string() };
+
+    let statuses: Vec<(&String, Result<String, AgentError>)> =
+        channels.iter().map(|c| (c, slack_post_message_ts(c, &message, None))).collect();
+    let delivered = statuses.iter().filter(|(_, sent)| sent.is_ok()).count();
+    let lines: Vec<String> = statuses
+        .iter()
+        .map(|(c, sent)| match sent {
+            Ok(_) => format!(":white_check_mark: <#{c}>"),
+            Err(e) => format!(":x: <#{c}>: {e}"),
+        })
+        .collect();
+    let summary = format!("<@{user}> broadcast to {delivered} of {} channels:\n>{}", statuses.len(), message.replace('\n', "\n>"));
+
+    let thread = slack_post_message_ts(channel, &summary, None).and_then(|ts| slack_post_message_ts(channel, &lines.join("\n"), Some(&ts)));
+    Ok(match thread {
+        Ok(_) => format!("_Broadcast delivered to {delivered} of {} channels; per-channel status is in the thread._", statuses.len()),
+        // Without a thread (e.g. the bot is not in this channel) the status comes back here
+        Err(_) => format!("_Broadcast delivered to {delivered} of {} channels._\n{}", statuses.len(), lines.join("\n")),
+    })
+}
+
+pub(crate) fn slack_broadcast_reply(text: &str, user: &str, channel: &str) -> String {
+    match broadcast(text, user, channel) {
+        Ok(reply) => reply,
+        Err(AgentError::Validation(message)) => format!("Couldn't broadcast: {message}\n{USAGE}"),
+        Err(e) => with_ref(&format!("Couldn't broadcast: {e}"), &current_request_id()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+    use std::env;
+
+    fn sent(mock: &MockHttpClient, i: usize) -> serde_json::Value {
+        serde_json::from_slice(&mock.requests.borrow()[i].body).unwrap()
+    }
+
+    #[test]
+    fn polished_message_goes_to_every_channel_with_status_in_a_thread() {
+        with_openai_env();
+        env::set_var("BROADCAST_CHANNELS", "C0ENG, C0OPS");
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("The office is closed on Friday."));
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "1.1"}"#);
+        mock.respond(200, "application/json", r#"{"ok": false, "error": "not_in_channel"}"#);
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "2.1"}"#);
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "2.2"}"#);
+
+        let reply = slack_broadcast_reply("--polish office closed friday", "U0ADMIN", "C0HQ");
+        assert_eq!(reply, "_Broadcast delivered to 1 of 2 channels; per-channel status is in the thread._");
+
+        assert_eq!(sent(&mock, 0)["messages"][1]["content"], "office closed friday");
+        assert_eq!(sent(&mock, 1), serde_json::json!({"channel": "C0ENG", "text": "The office is closed on Friday."}));
+        assert_eq!(sent(&mock, 2)["channel"], "C0OPS");
+        assert_eq!(sent(&mock, 3)["text"], "<@U0ADMIN> broadcast to 1 of 2 channels:\n>The office is closed on Friday.");
+        assert_eq!(sent(&mock, 4), serde_json::json!({
+            "channel": "C0HQ",
+            "text": ":white_check_mark: <#C0ENG>\n:x: <#C0OPS>: chat.postMessage: not_in_channel",
+            "thread_ts": "2.1",
+        }));
+    }
+
+    #[test]
+    fn empty_messages_are_refused() {
+        assert_eq!(slack_broadcast_reply("--polish ", "U1", "C1"), format!("Couldn't broadcast: a message is required\n{USAGE}"));
+    }
+}
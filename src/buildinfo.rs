@@ -0,0 +1,53 @@
+//! Build information embedded at compile time (see build.rs), for `/version` and the `version` export.
+
+use chrono::DateTime;
+
+/* ---- Build information (GIT_SHA, SOURCE_DATE_EPOCH at build time) ---- */
+// Tells operators exactly which build is serving. The commit comes from the repository the
+// component was built in, or GIT_SHA when building from a source archive; the build time is
+// SOURCE_DATE_EPOCH when set, so reproducible builds stay reproducible. The WIT digest changes
+// whenever the world does, even when its name and version do not.
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+const GIT_SHA: &str = env!("BUILD_GIT_SHA");
+const BUILD_TIMESTAMP: &str = env!("BUILD_TIMESTAMP");
+const WIT_WORLD: &str = env!("BUILD_WIT_WORLD");
+const WIT_DIGEST: &str = env!("BUILD_WIT_DIGEST");
+
+fn built_at() -> String {
+    BUILD_TIMESTAMP
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map_or_else(|| "unknown".to_string(), |t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+/// The `/version` body.
+pub(crate) fn build_info() -> serde_json::Value {
+    serde_json::json!({
+        "version": CRATE_VERSION,
+        "git_sha": GIT_SHA,
+        "built_at": built_at(),
+        "wit_world": WIT_WORLD,
+        "wit_digest": WIT_DIGEST,
+    })
+}
+
+/// One line for the `version` export, e.g. `0.1.0 (3f2a9c1d0e4b, built 2026-10-16T10:57:27Z, world component:ai-agent/ai-agent-world 1a2b3c4d5e6f)`.
+pub(crate) fn version_line() -> String {
+    format!("{CRATE_VERSION} ({GIT_SHA}, built {}, world {WIT_WORLD} {WIT_DIGEST})", built_at())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_info_names_the_crate_version_and_wit_world() {
+        let info = build_info();
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(info["wit_world"], "component:ai-agent/ai-agent-world");
+        assert_eq!(info["wit_digest"].as_str().unwrap().len(), 12);
+        assert!(info["built_at"].as_str().unwrap().ends_with('Z'));
+        assert!(version_line().starts_with(&format!("{} (", env!("CARGO_PKG_VERSION"))));
+    }
+}
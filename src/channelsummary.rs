@@ -0,0 +1,131 @@
+//! `/ai summarize-channel [count]`: a summary with action items of a channel's latest messages.
+
+use crate::error::AgentError;
+use crate::history::format_time;
+use crate::messages::{Msg, message};
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::SlashAnswer;
+use crate::slack::api::{SlackApi, SlackMessage};
+use crate::summarize::summarize_document;
+
+/* ---- Channel digest (conversations.history) ---- */
+// The latest messages of the channel the command runs in are read with conversations.history,
+// which follows Slack's cursors, so counts over one page work. Messages from bots (including this
+// one) and system messages such as joins and topic changes are dropped; thread replies are not
+// part of the channel history. The rest goes to the model oldest first, one line per message
+// with its time and author as a Slack mention, so the summary can name owners in a way Slack
+// renders as names. Long channels go through the map-reduce summarizer. The bot must be a member
+// of the channel, and the token needs channels:history (groups:history for private channels).
+const DEFAULT_MESSAGES: usize = 100;
+const MAX_MESSAGES: usize = 1000;
+const SUMMARY_MAX_TOKENS: u32 = 700;
+const CHANNEL_SUMMARY_PROMPT: &str = "You summarize Slack channel conversations. The input is the channel's messages, \
+oldest first, as `[time] <@author>: text`. Write a short summary of the topics discussed and the decisions made, then \
+a list titled *Action items* with one bullet per task: what has to be done, the owner as the <@author> mention when one \
+is clear, and the due date if one was given. Write 'No action items.' when there are none. Do not invent anything that \
+is not in the messages. Format for Slack mrkdwn.";
+// Message subtypes that are written by people; every other subtype is a system or bot event
+const PERSON_SUBTYPES: &[&str] = &["thread_broadcast", "file_share", "me_message"];
+
+/// Whether `message` was written by a person rather than a bot or Slack itself.
+fn is_conversation(message: &SlackMessage) -> bool {
+    message.bot_id.is_none()
+        && message.user.is_some()
+        && message.subtype.as_deref().is_none_or(|s| PERSON_SUBTYPES.contains(&s))
+        && !message.text.trim().is_empty()
+}
+
+/// The messages as prompt lines, oldest first; Slack lists them newest first.
+fn transcript(messages: &[SlackMessage]) -> String {
+    let lines: Vec<String> = messages
+        .iter()
+        .rev()
+        .filter(|m| is_conversation(m))
+        .map(|m| {
+            let at = m.ts.split('.').next().and_then(|secs| secs.parse::<u64>().ok()).map(format_time).unwrap_or_default();
+            format!("[{at}] <@{}>: {}", m.user.as_deref().unwrap_or_default(), m.text.trim())
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn summarize_channel(channel_id: &str, count: usize) -> Result<String, AgentError> {
+    let messages = SlackApi::from_env()?.conversation_history(channel_id, None, count)?;
+    let transcript = transcript(&messages);
+    if transcript.is_empty() {
+        return Ok(format!("There are no messages from people among the last {count} in <#{channel_id}> to summarize."));
+    }
+    let lines = transcript.lines().count();
+    let summary = summarize_document(&transcript, CHANNEL_SUMMARY_PROMPT, SUMMARY_MAX_TOKENS)?;
+    Ok(format!("*Summary of the last {lines} messages in <#{channel_id}>*\n{summary}"))
+}
+
+/// Answers `/ai summarize-channel [count]` in the channel it was run in.
+pub(crate) fn slack_summarize_channel(text: &str, _user_id: &str, channel_id: &str, _private: bool) -> SlashAnswer {
+    let usage = format!("Usage: `/ai summarize-channel [count]`, with a count from 1 to {MAX_MESSAGES} (default {DEFAULT_MESSAGES})");
+    let count = match text.split_whitespace().next() {
+        None => DEFAULT_MESSAGES,
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if (1..=MAX_MESSAGES).contains(&n) => n,
+            _ => return usage.into(),
+        },
+    };
+    if channel_id.is_empty() {
+        return "Run `/ai summarize-channel` in the channel to summarize.".to_string().into();
+    }
+    match summarize_channel(channel_id, count) {
+        Ok(summary) => summary.into(),
+        Err(e) => with_ref(&message(Msg::RequestFailed, &[("error", &e.to_string())]), &current_request_id()).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+
+    #[test]
+    fn people_are_summarized_oldest_first_without_bot_noise() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        let page = |messages: serde_json::Value, cursor: &str| {
+            serde_json::json!({"ok": true, "messages": messages, "response_metadata": {"next_cursor": cursor}}).to_string()
+        };
+        mock.respond(200, "application/json", &page(serde_json::json!([
+            {"ts": "1700000300.000100", "user": "U2", "text": "I'll send the deck by Friday"},
+            {"ts": "1700000200.000100", "bot_id": "B1", "text": "Deploy finished"},
+            {"ts": "1700000150.000100", "user": "U3", "subtype": "channel_join", "text": "<@U3> has joined the channel"},
+        ]), "bmV4dA=="));
+        mock.respond(200, "application/json", &page(serde_json::json!([
+            {"ts": "1700000100.000100", "user": "U1", "text": "Can someone prepare the launch deck?"},
+        ]), ""));
+        mock.respond(200, "application/json", &completion("Launch prep.\n*Action items*\n• Deck, <@U2>, Friday"));
+
+        let answer = slack_summarize_channel("4", "U1", "C1", false).text;
+        assert_eq!(answer, "*Summary of the last 2 messages in <#C1>*\nLaunch prep.\n*Action items*\n• Deck, <@U2>, Friday");
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/conversations.history?channel=C1&limit=4");
+        assert_eq!(requests[1].url, "https://slack.com/api/conversations.history?channel=C1&limit=1&cursor=bmV4dA%3D%3D");
+        let prompt: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(
+            prompt["messages"][1]["content"],
+            "[2023-11-14 22:15 UTC] <@U1>: Can someone prepare the launch deck?\n[2023-11-14 22:18 UTC] <@U2>: I'll send the deck by Friday"
+        );
+    }
+
+    #[test]
+    fn counts_are_checked_and_channels_without_people_say_so() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true, "messages": [{"ts": "1.0", "bot_id": "B1", "text": "beep"}]}"#);
+        assert!(slack_summarize_channel("lots", "U1", "C1", false).text.starts_with("Usage: `/ai summarize-channel [count]`"));
+        assert!(slack_summarize_channel("0", "U1", "C1", false).text.starts_with("Usage:"));
+        assert_eq!(
+            slack_summarize_channel("", "U1", "C1", false).text,
+            "There are no messages from people among the last 100 in <#C1> to summarize."
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Wall-clock and monotonic time behind a swappable clock.
+
+use std::cell::RefCell;
+
+use crate::bindings::wasi::io::poll;
+use crate::bindings::wasi::clocks::{monotonic_clock, wall_clock};
+
+pub(crate) fn now_secs() -> u64 {
+    clock().now()
+}
+
+/* ---- Clock abstraction ---- */
+// Time flows through `clock()` so backoff, scheduling and timing logic can be tested with a
+// `MockClock` that only moves when told to.
+pub(crate) trait Clock {
+    /// Wall-clock time in Unix seconds.
+    fn now(&self) -> u64;
+    /// Monotonic nanoseconds, for measuring durations.
+    fn monotonic_ns(&self) -> u64;
+    /// Blocks until the wall clock reaches `unix_secs`.
+    fn sleep_until(&self, unix_secs: u64);
+}
+
+struct WasiClock;
+
+impl Clock for WasiClock {
+    fn now(&self) -> u64 {
+        wall_clock::now().seconds
+    }
+
+    fn monotonic_ns(&self) -> u64 {
+        monotonic_clock::now()
+    }
+
+    fn sleep_until(&self, unix_secs: u64) {
+        let now = self.now();
+        if unix_secs > now {
+            let pollable = monotonic_clock::subscribe_duration((unix_secs - now) * 1_000_000_000);
+            let _ = poll::poll(&[&pollable]);
+        }
+    }
+}
+
+#[cfg(not(test))]
+thread_local! {
+    static CLOCK: RefCell<std::rc::Rc<dyn Clock>> = RefCell::new(std::rc::Rc::new(WasiClock));
+}
+
+// Native tests have no wasi clocks; time stands still unless a test installs its own MockClock
+#[cfg(test)]
+thread_local! {
+    static CLOCK: RefCell<std::rc::Rc<dyn Clock>> =
+        RefCell::new(std::rc::Rc::new(MockClock { now: 0.into(), monotonic_ns: 0.into() }));
+}
+
+pub(crate) fn clock() -> std::rc::Rc<dyn Clock> {
+    CLOCK.with(|c| c.borrow().clone())
+}
+
+#[cfg(test)]
+fn set_clock(c: std::rc::Rc<dyn Clock>) {
+    CLOCK.with(|slot| *slot.borrow_mut() = c);
+}
+
+#[cfg(test)]
+pub(crate) struct MockClock {
+    now: std::cell::Cell<u64>,
+    monotonic_ns: std::cell::Cell<u64>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    /// Installs a mock clock starting at `unix_secs` as this thread's clock.
+    pub(crate) fn install(unix_secs: u64) -> std::rc::Rc<MockClock> {
+        let mock = std::rc::Rc::new(MockClock { now: unix_secs.into(), monotonic_ns: 0.into() });
+        set_clock(mock.clone());
+        mock
+    }
+
+    pub(crate) fn advance(&self, secs: u64) {
+        self.now.set(self.now.get() + secs);
+        self.monotonic_ns.set(self.monotonic_ns.get() + secs * 1_000_000_000);
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+
+    fn monotonic_ns(&self) -> u64 {
+        self.monotonic_ns.get()
+    }
+
+    fn sleep_until(&self, unix_secs: u64) {
+        if unix_secs > self.now.get() {
+            self.advance(unix_secs - self.now.get());
+        }
+    }
+}
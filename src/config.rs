@@ -0,0 +1,16 @@
+//! Environment configuration.
+
+use std::env;
+
+
+
+/* ---- Environment variable helper ---- */
+pub(crate) fn get_env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+pub(crate) fn with_openai_env() {
+    env::set_var("OPENAI_API_KEY", "sk-test");
+    env::set_var("SLACK_BOT_TOKEN", "xoxb-test");
+}
@@ -0,0 +1,75 @@
+//! Connect protocol (unary, JSON codec) access to the agent operations.
+
+use crate::bindings::exports::component::ai_agent::ai_agent;
+use crate::error::AgentError;
+use crate::router::Response;
+use crate::{Component, fetch_and_process, multi_source_response, process_query};
+
+/* ---- AgentService over Connect (https://connectrpc.com/docs/protocol) ---- */
+// Unary calls are a POST of the request message as JSON to `/{service}/{Method}`. Success is a
+// 200 with the response message; failures carry `{"code", "message"}` with the HTTP status the
+// protocol assigns to the code. The schema for generating stubs is in proto/.
+pub(crate) const CONNECT_SERVICE_PATH: &str = "/component.ai_agent.v1.AgentService/";
+
+/// The Connect error code for an agent error, and the HTTP status the protocol pairs with it.
+fn connect_code(error: &AgentError) -> (&'static str, u16) {
+    match error {
+        AgentError::Validation(_) => ("invalid_argument", 400),
+        AgentError::RateLimited { .. } | AgentError::Provider { status: 429, .. } => ("resource_exhausted", 429),
+        AgentError::Timeout(_) => ("deadline_exceeded", 504),
+        AgentError::Config(_) => ("internal", 500),
+        AgentError::EgressDenied(_) => ("permission_denied", 403),
+        AgentError::Dns(_) | AgentError::Tcp(_) | AgentError::Http(_) | AgentError::Provider { .. } | AgentError::Slack(_) | AgentError::Store(_) => {
+            ("unavailable", 503)
+        }
+    }
+}
+
+fn connect_error(code: &str, status: u16, message: &str) -> Response {
+    Response::new(status, "application/json", serde_json::json!({"code": code, "message": message}).to_string())
+}
+
+fn string_field(request: &serde_json::Value, name: &str) -> Result<String, AgentError> {
+    request[name].as_str().map(str::to_string).ok_or_else(|| AgentError::Validation(format!("missing field: {name}")))
+}
+
+fn call(method: &str, request: &serde_json::Value) -> Option<Result<serde_json::Value, AgentError>> {
+    let text = |result: Result<String, AgentError>| result.map(|text| serde_json::json!({"text": text}));
+    Some(match method {
+        "ProcessQuery" => string_field(request, "query").and_then(|query| text(process_query(&query, request["context"].as_str()))),
+        "FetchAndProcess" => string_field(request, "url").and_then(|url| text(fetch_and_process(&url))),
+        "MultiSourceResponse" => string_field(request, "query").and_then(|query| {
+            let urls: Vec<String> = request["urls"].as_array().into_iter().flatten().filter_map(|u| u.as_str().map(str::to_string)).collect();
+            text(multi_source_response(&query, &urls))
+        }),
+        "HealthCheck" => Ok(serde_json::json!({"status": <Component as ai_agent::Guest>::health_check()})),
+        "Version" => Ok(serde_json::json!({"version": <Component as ai_agent::Guest>::version()})),
+        _ => return None,
+    })
+}
+
+/// Handles `POST {CONNECT_SERVICE_PATH}{method}` with a JSON body.
+pub(crate) fn handle_connect(method: &str, http_method: &str, content_type: Option<&str>, body: &str) -> Response {
+    if http_method != "POST" {
+        return connect_error("unimplemented", 405, "only POST is supported").header("allow", "POST");
+    }
+    let codec = content_type.and_then(|ct| ct.split(';').next()).map(|ct| ct.trim().to_ascii_lowercase());
+    if codec.as_deref() != Some("application/json") {
+        return Response::new(415, "application/json", "").header("accept-post", "application/json");
+    }
+    let request = match body.trim() {
+        "" => serde_json::json!({}),
+        body => match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(request) if request.is_object() => request,
+            _ => return connect_error("invalid_argument", 400, "request body must be a JSON object"),
+        },
+    };
+    match call(method, &request) {
+        Some(Ok(reply)) => Response::new(200, "application/json", reply.to_string()),
+        Some(Err(e)) => {
+            let (code, status) = connect_code(&e);
+            connect_error(code, status, &e.to_string())
+        }
+        None => connect_error("unimplemented", 404, &format!("component.ai_agent.v1.AgentService/{method} is not implemented")),
+    }
+}
@@ -0,0 +1,206 @@
+//! Per-operation cookie jar for fetch flows behind cookie-based session gates.
+
+use crate::clock::now_secs;
+
+/* ---- Cookie jar ---- */
+// A jar lives for one operation (a summary, a research query, a scheduled job run) and is
+// dropped with it; nothing is persisted, so sessions never leak between users or jobs. Matching
+// follows RFC 6265 closely enough for login redirects and consent walls: host-only vs. domain
+// cookies, path prefixes, `Secure`, and `Max-Age`/`Expires`.
+#[derive(Debug, Clone, PartialEq)]
+struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// Stores every `Set-Cookie` header of a response to `url`.
+    pub(crate) fn capture<'a>(&mut self, url: &str, headers: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let Some((secure_origin, host, path)) = url_parts(url) else { return };
+        for (name, value) in headers {
+            if name.eq_ignore_ascii_case("set-cookie") {
+                if let Some(cookie) = parse_set_cookie(value, &host, &path) {
+                    if cookie.secure && !secure_origin {
+                        continue;
+                    }
+                    self.store(cookie);
+                }
+            }
+        }
+    }
+
+    /// The `Cookie` header value for a request to `url`, if any cookie applies.
+    pub(crate) fn header_for(&self, url: &str) -> Option<String> {
+        if self.cookies.is_empty() {
+            return None;
+        }
+        let (secure_origin, host, path) = url_parts(url)?;
+        let now = now_secs();
+        let mut matching: Vec<&Cookie> = self
+            .cookies
+            .iter()
+            .filter(|c| c.expires.is_none_or(|t| t > now))
+            .filter(|c| !c.secure || secure_origin)
+            .filter(|c| if c.host_only { c.domain == host } else { domain_matches(&host, &c.domain) })
+            .filter(|c| path_matches(&path, &c.path))
+            .collect();
+        // Longer paths first, as browsers send them
+        matching.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+        let pairs: Vec<String> = matching.iter().map(|c| format!("{}={}", c.name, c.value)).collect();
+        (!pairs.is_empty()).then(|| pairs.join("; "))
+    }
+
+    fn store(&mut self, cookie: Cookie) {
+        self.cookies.retain(|c| !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path));
+        // An expiry in the past is how servers delete a cookie
+        if cookie.expires.is_none_or(|t| t > now_secs()) {
+            self.cookies.push(cookie);
+        }
+    }
+}
+
+fn parse_set_cookie(header: &str, host: &str, request_path: &str) -> Option<Cookie> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let mut cookie = Cookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        domain: host.to_string(),
+        host_only: true,
+        path: default_path(request_path),
+        secure: false,
+        expires: None,
+    };
+    let mut max_age = None;
+    for attr in parts {
+        let (key, val) = attr.split_once('=').map_or((attr.trim(), ""), |(k, v)| (k.trim(), v.trim()));
+        match key.to_ascii_lowercase().as_str() {
+            "domain" if !val.is_empty() => {
+                let domain = val.trim_start_matches('.').to_ascii_lowercase();
+                // A server may only widen a cookie to a parent domain of itself
+                if !domain_matches(host, &domain) {
+                    return None;
+                }
+                cookie.domain = domain;
+                cookie.host_only = false;
+            }
+            "path" if val.starts_with('/') => cookie.path = val.to_string(),
+            "secure" => cookie.secure = true,
+            "max-age" => max_age = val.parse::<i64>().ok(),
+            "expires" => {
+                if let Ok(t) = chrono::DateTime::parse_from_rfc2822(&val.replace("GMT", "+0000")) {
+                    cookie.expires = Some(t.timestamp().max(0) as u64);
+                }
+            }
+            _ => {}
+        }
+    }
+    // Max-Age takes precedence over Expires
+    if let Some(secs) = max_age {
+        cookie.expires = Some(if secs <= 0 { 0 } else { now_secs() + secs as u64 });
+    }
+    Some(cookie)
+}
+
+/// (is https, lowercase host without port or credentials, path without query)
+fn url_parts(url: &str) -> Option<(bool, String, String)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let authority = &rest[..end];
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match authority.rfind(':') {
+        Some(i) if !authority[i..].contains(']') => &authority[..i],
+        _ => authority,
+    };
+    let tail = &rest[end..];
+    let path = &tail[..tail.find(['?', '#']).unwrap_or(tail.len())];
+    let path = if path.is_empty() { "/" } else { path };
+    Some((scheme.eq_ignore_ascii_case("https"), host.to_ascii_lowercase(), path.to_string()))
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || (host.ends_with(domain) && host[..host.len() - domain.len()].ends_with('.'))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(i) => request_path[..i].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    fn jar_with(url: &str, set_cookies: &[&str]) -> CookieJar {
+        let mut jar = CookieJar::default();
+        jar.capture(url, set_cookies.iter().map(|v| ("Set-Cookie", *v)));
+        jar
+    }
+
+    #[test]
+    fn host_only_and_domain_cookies_match_the_right_hosts() {
+        MockClock::install(1_700_000_000);
+        let jar = jar_with("https://login.example.com/auth", &[
+            "session=abc; Path=/; HttpOnly",
+            "sso=xyz; Domain=.example.com; Path=/",
+        ]);
+        assert_eq!(jar.header_for("https://login.example.com/account").as_deref(), Some("session=abc; sso=xyz"));
+        assert_eq!(jar.header_for("https://docs.example.com/").as_deref(), Some("sso=xyz"));
+        assert_eq!(jar.header_for("https://example.org/"), None);
+        assert_eq!(jar.header_for("https://badexample.com/"), None);
+    }
+
+    #[test]
+    fn cookies_for_unrelated_domains_are_rejected() {
+        let jar = jar_with("https://evil.example.net/", &["steal=1; Domain=example.com"]);
+        assert!(jar.cookies.is_empty());
+    }
+
+    #[test]
+    fn paths_default_to_the_request_directory_and_longer_paths_come_first() {
+        MockClock::install(1_700_000_000);
+        let jar = jar_with("https://example.com/app/login", &["a=1", "b=2; Path=/"]);
+        assert_eq!(jar.header_for("https://example.com/app/page?x=1").as_deref(), Some("a=1; b=2"));
+        assert_eq!(jar.header_for("https://example.com/application").as_deref(), Some("b=2"));
+    }
+
+    #[test]
+    fn secure_cookies_stay_on_https_and_expiry_is_honoured() {
+        let clock = MockClock::install(1_700_000_000);
+        let mut jar = jar_with("https://example.com/", &[
+            "s=1; Secure",
+            "short=1; Max-Age=60",
+            "old=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+        ]);
+        assert_eq!(jar.header_for("http://example.com/").as_deref(), Some("short=1"));
+        clock.advance(61);
+        assert_eq!(jar.header_for("https://example.com/").as_deref(), Some("s=1"));
+
+        // Max-Age=0 deletes an existing cookie
+        jar.capture("https://example.com/", [("set-cookie", "s=; Max-Age=0")]);
+        assert_eq!(jar.header_for("https://example.com/"), None);
+    }
+}
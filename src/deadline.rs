@@ -0,0 +1,107 @@
+//! Per-route processing deadlines and the time each request spends waiting on upstreams.
+
+use std::cell::RefCell;
+
+use crate::clock::clock;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+
+/* ---- Route deadlines (REQUEST_DEADLINE_MS, ROUTE_DEADLINES) ---- */
+// A request that outlives its deadline is answered (504, or a short note in Slack) instead of
+// being left to the host's own limit. Outgoing calls get only the time that is left and fail
+// at once when none is, so handlers still run their error paths. ROUTE_DEADLINES overrides the
+// default per registered path, e.g. `/api/v1/query=20000,/slack/command=2500`; 0 turns it off.
+const DEFAULT_DEADLINE_MS: u64 = 30_000;
+
+struct Deadline {
+    route: String,
+    limit_ms: u64,
+    started_ns: u64,
+    /// Time spent in each outgoing call, by host
+    waits: Vec<(String, u64)>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Deadline>> = const { RefCell::new(None) };
+}
+
+/// The configured deadline for a registered route path; 0 when it has none.
+pub(crate) fn route_deadline_ms(route: &str) -> u64 {
+    let configured = get_env_var("ROUTE_DEADLINES").and_then(|spec| {
+        spec.split(',').find_map(|entry| entry.split_once('=').filter(|(path, _)| path.trim() == route).and_then(|(_, ms)| ms.trim().parse().ok()))
+    });
+    configured.or_else(|| get_env_var("REQUEST_DEADLINE_MS").and_then(|ms| ms.trim().parse().ok())).unwrap_or(DEFAULT_DEADLINE_MS)
+}
+
+fn elapsed_ms(started_ns: u64) -> u64 {
+    clock().monotonic_ns().saturating_sub(started_ns) / 1_000_000
+}
+
+/// Starts the clock for a request to `route`; a `limit_ms` of 0 means no deadline.
+pub(crate) fn begin_deadline(route: &str, limit_ms: u64) {
+    let deadline = (limit_ms > 0).then(|| Deadline { route: route.to_string(), limit_ms, started_ns: clock().monotonic_ns(), waits: Vec::new() });
+    CURRENT.with(|current| *current.borrow_mut() = deadline);
+}
+
+/// The time left for an outgoing call (`None` without a deadline), or a timeout once it is spent.
+pub(crate) fn time_left_for(call: &str) -> Result<Option<u64>, AgentError> {
+    CURRENT.with(|current| match current.borrow().as_ref() {
+        None => Ok(None),
+        Some(d) => match d.limit_ms.checked_sub(elapsed_ms(d.started_ns)).filter(|&left| left > 0) {
+            Some(left) => Ok(Some(left)),
+            None => Err(AgentError::Timeout(format!("{call}: the {} ms allowed for {} ran out", d.limit_ms, d.route))),
+        },
+    })
+}
+
+pub(crate) fn record_wait(host: &str, ms: u64) {
+    CURRENT.with(|current| {
+        if let Some(d) = current.borrow_mut().as_mut() {
+            match d.waits.iter_mut().find(|(h, _)| h == host) {
+                Some((_, total)) => *total += ms,
+                None => d.waits.push((host.to_string(), ms)),
+            }
+        }
+    });
+}
+
+/// Ends the request's deadline; when it was exceeded, where the time went.
+pub(crate) fn end_deadline() -> Option<String> {
+    let d = CURRENT.with(|current| current.borrow_mut().take())?;
+    let elapsed = elapsed_ms(d.started_ns);
+    if elapsed < d.limit_ms {
+        return None;
+    }
+    let waits: Vec<String> = d.waits.iter().map(|(host, ms)| format!("{host} {ms} ms")).collect();
+    let upstream: u64 = d.waits.iter().map(|(_, ms)| ms).sum();
+    Some(format!(
+        "{} took {elapsed} ms of {} ms: {} ms upstream ({}), {} ms in the component",
+        d.route,
+        d.limit_ms,
+        upstream,
+        if waits.is_empty() { "no calls".to_string() } else { waits.join(", ") },
+        elapsed.saturating_sub(upstream)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn spent_deadlines_fail_calls_and_report_the_breakdown() {
+        let clock = MockClock::install(1_700_000_000);
+        begin_deadline("/api/v1/query", 2_000);
+        assert_eq!(time_left_for("POST https://api.openai.com/v1/chat/completions"), Ok(Some(2_000)));
+        clock.advance(2);
+        record_wait("api.openai.com", 1_800);
+        let err = time_left_for("POST https://api.openai.com/v1/chat/completions").unwrap_err();
+        assert_eq!(err.http_status(), 504);
+        assert_eq!(
+            end_deadline().as_deref(),
+            Some("/api/v1/query took 2000 ms of 2000 ms: 1800 ms upstream (api.openai.com 1800 ms), 200 ms in the component")
+        );
+        assert_eq!(time_left_for("GET https://example.com/"), Ok(None));
+    }
+}
@@ -0,0 +1,261 @@
+//! Plain-text extraction from fetched documents.
+
+use crate::tables::cell_text;
+
+/* ---- Document text extraction ---- */
+// Upper bound on extracted text handed to the model
+pub(crate) const MAX_DOCUMENT_CHARS: usize = 12_000;
+
+/// Converts a fetched document of a given media type into plain text.
+/// Register new formats (e.g. PDF) by adding an implementation to `EXTRACTORS`.
+trait TextExtractor {
+    fn handles(&self, media_type: &str) -> bool;
+    fn extract(&self, body: &str) -> String;
+}
+
+struct HtmlExtractor;
+struct JsonExtractor;
+struct MarkdownExtractor;
+struct PlainTextExtractor;
+
+const EXTRACTORS: &[&dyn TextExtractor] = &[&HtmlExtractor, &JsonExtractor, &MarkdownExtractor, &PlainTextExtractor];
+
+pub(crate) fn extract_document_text(content_type: Option<&str>, url: &str, body: &str) -> String {
+    let media_type = detect_media_type(content_type, url, body);
+    let raw = EXTRACTORS
+        .iter()
+        .find(|x| x.handles(&media_type))
+        .map(|x| x.extract(body))
+        .unwrap_or_else(|| body.to_string());
+    normalize_text(&raw)
+}
+
+/// Uses the Content-Type header when it is specific, otherwise sniffs the URL and body.
+pub(crate) fn detect_media_type(content_type: Option<&str>, url: &str, body: &str) -> String {
+    let declared = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_lowercase())
+        .unwrap_or_default();
+    let generic = declared.is_empty() || declared == "application/octet-stream" || declared == "text/plain";
+    if !generic {
+        return declared;
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or("").to_lowercase();
+    if path.ends_with(".md") || path.ends_with(".markdown") {
+        return "text/markdown".into();
+    }
+    let head = body.trim_start().get(..64).unwrap_or(body.trim_start()).to_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return "text/html".into();
+    }
+    if (head.starts_with('{') || head.starts_with('[')) && serde_json::from_str::<serde_json::Value>(body).is_ok() {
+        return "application/json".into();
+    }
+    if declared.is_empty() { "text/plain".into() } else { declared }
+}
+
+impl TextExtractor for HtmlExtractor {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type == "text/html" || media_type == "application/xhtml+xml"
+    }
+
+    fn extract(&self, body: &str) -> String {
+        let mut out = String::with_capacity(body.len() / 2);
+        // ASCII lowercasing keeps byte offsets aligned with `body`
+        let lower = body.to_ascii_lowercase();
+        let mut i = 0;
+        while i < body.len() {
+            let rest = &body[i..];
+            if rest.starts_with("<!--") {
+                i += rest.find("-->").map(|e| e + 3).unwrap_or(rest.len());
+                continue;
+            }
+            if rest.starts_with('<') {
+                let end = rest.find('>').map(|e| e + 1).unwrap_or(rest.len());
+                let tag = lower[i + 1..i + end].trim_start_matches('/');
+                let name: String = tag.chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+                // Skip non-content elements entirely
+                if matches!(name.as_str(), "script" | "style" | "noscript" | "template" | "svg") && !rest.starts_with("</") {
+                    let close = format!("</{name}");
+                    i += lower[i..].find(&close).unwrap_or(rest.len());
+                    i += lower[i..].find('>').map(|e| e + 1).unwrap_or(0);
+                    continue;
+                }
+                if matches!(
+                    name.as_str(),
+                    "p" | "br" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "section" | "article" | "title" | "blockquote" | "pre" | "table"
+                ) {
+                    out.push('\n');
+                } else if matches!(name.as_str(), "td" | "th") {
+                    out.push(' ');
+                }
+                i += end;
+                continue;
+            }
+            let next = rest.find('<').unwrap_or(rest.len());
+            out.push_str(&decode_html_entities(&rest[..next]));
+            i += next;
+        }
+        out
+    }
+}
+
+fn decode_html_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';').filter(|&p| p <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" | "#39" => Some('\''),
+            "nbsp" => Some(' '),
+            _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+            }
+            _ if entity.starts_with('#') => entity[1..].parse::<u32>().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+impl TextExtractor for JsonExtractor {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type == "application/json" || media_type.ends_with("+json")
+    }
+
+    fn extract(&self, body: &str) -> String {
+        fn walk(value: &serde_json::Value, path: &str, out: &mut Vec<String>) {
+            match value {
+                serde_json::Value::Object(map) => {
+                    for (k, v) in map {
+                        let p = if path.is_empty() { k.clone() } else { format!("{path}.{k}") };
+                        walk(v, &p, out);
+                    }
+                }
+                serde_json::Value::Array(items) => {
+                    for (i, v) in items.iter().enumerate() {
+                        walk(v, &format!("{path}[{i}]"), out);
+                    }
+                }
+                serde_json::Value::Null => {}
+                leaf => out.push(format!("{path}: {}", cell_text(leaf))),
+            }
+        }
+        match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(value) => {
+                let mut lines = Vec::new();
+                walk(&value, "", &mut lines);
+                lines.join("\n")
+            }
+            Err(_) => body.to_string(),
+        }
+    }
+}
+
+impl TextExtractor for MarkdownExtractor {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type == "text/markdown" || media_type == "text/x-markdown"
+    }
+
+    fn extract(&self, body: &str) -> String {
+        let mut lines = Vec::new();
+        for line in body.lines() {
+            let trimmed = line.trim_start();
+            // Fence markers carry no content; keep the code itself
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                continue;
+            }
+            let trimmed = trimmed.trim_start_matches('#').trim_start_matches('>').trim_start();
+            lines.push(strip_markdown_inline(trimmed));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Drops emphasis/code markers and reduces links and images to their text.
+fn strip_markdown_inline(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '!' && chars.get(i + 1) == Some(&'[') {
+            i += 1;
+            continue;
+        }
+        if c == '[' {
+            if let Some(close) = chars[i..].iter().position(|&x| x == ']').map(|p| p + i) {
+                if chars.get(close + 1) == Some(&'(') {
+                    if let Some(end) = chars[close..].iter().position(|&x| x == ')').map(|p| p + close) {
+                        out.extend(&chars[i + 1..close]);
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        if matches!(c, '*' | '_' | '`') {
+            i += 1;
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+impl TextExtractor for PlainTextExtractor {
+    fn handles(&self, media_type: &str) -> bool {
+        media_type.starts_with("text/")
+    }
+
+    fn extract(&self, body: &str) -> String {
+        body.to_string()
+    }
+}
+
+/// Collapses runs of whitespace, trims lines and keeps at most one blank line between paragraphs.
+fn normalize_text(raw: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() && out.last().map_or(true, |l| l.is_empty()) {
+            continue;
+        }
+        out.push(line);
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+pub(crate) fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => s[..idx].to_string(),
+        None => s.to_string(),
+    }
+}
@@ -0,0 +1,233 @@
+//! Outbound egress policy: where HTTP and TCP calls may connect.
+
+use std::cell::RefCell;
+use std::net::IpAddr;
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+
+/* ---- Egress policy (EGRESS_ALLOW, EGRESS_ROUTE_ALLOW) ---- */
+// Without EGRESS_ALLOW every destination is allowed. With it, each outgoing HTTP request and TCP
+// connection must match one of its rules: `api.openai.com:443`, `*.slack.com` (subdomains, any
+// port), `10.0.0.0/8:6379`, `[fd00::/8]:8000-8999` or `*` (anything). EGRESS_ROUTE_ALLOW adds
+// rules for requests to one route, e.g. `/debug/httpget=*;/tcp/send=127.0.0.1:9090`. Domain
+// rules match the name being called; CIDR rules match IP literals and, for TCP, the resolved
+// address (wasi:http resolves names itself, so HTTP calls by name only match domain rules). A
+// policy that does not parse denies everything rather than falling open.
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Any,
+    /// Lowercase; `*.example.com` matches subdomains only
+    Domain(String),
+    Cidr(IpAddr, u8),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    target: Target,
+    /// Inclusive port range; `None` for any port
+    ports: Option<(u16, u16)>,
+}
+
+thread_local! {
+    static ROUTE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Records the route being handled, for EGRESS_ROUTE_ALLOW.
+pub(crate) fn begin_egress(route: &str) {
+    ROUTE.with(|current| *current.borrow_mut() = route.to_string());
+}
+
+/// Ends the route's scope once its response is built. Work run after it (deferred tasks, warmup,
+/// scheduled jobs) is not part of the route, so only EGRESS_ALLOW applies to it.
+pub(crate) fn end_egress() {
+    begin_egress("");
+}
+
+/// Ends the route's scope when dropped, for handlers that are not served through `route()`.
+pub(crate) struct EgressScope;
+
+impl Drop for EgressScope {
+    fn drop(&mut self) {
+        end_egress();
+    }
+}
+
+pub(crate) fn egress_scope(route: &str) -> EgressScope {
+    begin_egress(route);
+    EgressScope
+}
+
+// The environment is shared by tests running in parallel, so tests set a policy per thread
+#[cfg(test)]
+thread_local! {
+    static TEST_POLICY: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+#[cfg(test)]
+pub(crate) fn set_test_policy(allow: &str, route_allow: &str) {
+    TEST_POLICY.with(|policy| *policy.borrow_mut() = Some((allow.to_string(), route_allow.to_string())));
+}
+
+/// EGRESS_ALLOW and EGRESS_ROUTE_ALLOW.
+fn policy_vars() -> (Option<String>, Option<String>) {
+    #[cfg(test)]
+    if let Some((allow, route_allow)) = TEST_POLICY.with(|policy| policy.borrow().clone()) {
+        return (Some(allow), Some(route_allow));
+    }
+    (get_env_var("EGRESS_ALLOW"), get_env_var("EGRESS_ROUTE_ALLOW"))
+}
+
+fn parse_ports(spec: &str) -> Option<(u16, u16)> {
+    let (low, high) = spec.split_once('-').unwrap_or((spec, spec));
+    let (low, high) = (low.trim().parse().ok()?, high.trim().parse().ok()?);
+    (low <= high).then_some((low, high))
+}
+
+fn parse_target(spec: &str) -> Option<Target> {
+    if spec == "*" {
+        return Some(Target::Any);
+    }
+    let (addr, prefix) = match spec.split_once('/') {
+        Some((addr, bits)) => (addr, Some(bits.parse::<u8>().ok()?)),
+        None => (spec, None),
+    };
+    match addr.parse::<IpAddr>() {
+        Ok(ip) => {
+            let max = if ip.is_ipv4() { 32 } else { 128 };
+            let prefix = prefix.unwrap_or(max);
+            (prefix <= max).then_some(Target::Cidr(ip, prefix))
+        }
+        Err(_) if prefix.is_none() && !spec.contains(':') && !spec.is_empty() => {
+            let domain = spec.to_ascii_lowercase();
+            let name = domain.strip_prefix("*.").unwrap_or(&domain);
+            (!name.is_empty() && !name.contains('*')).then_some(Target::Domain(domain))
+        }
+        Err(_) => None,
+    }
+}
+
+fn parse_rule(spec: &str) -> Result<Rule, AgentError> {
+    let invalid = || AgentError::Config(format!("egress rule '{spec}': expected host, *.domain, CIDR or *, with an optional :port or :low-high"));
+    let (target, ports) = match spec.strip_prefix('[') {
+        // IPv6 addresses and ranges are bracketed, so their colons are not taken for a port
+        Some(rest) => {
+            let (target, after) = rest.split_once(']').ok_or_else(invalid)?;
+            let ports = if after.is_empty() { None } else { Some(after.strip_prefix(':').ok_or_else(invalid)?) };
+            (target, ports)
+        }
+        None => match spec.rsplit_once(':') {
+            Some((target, _)) if target.contains(':') => return Err(invalid()),
+            Some((target, ports)) => (target, Some(ports)),
+            None => (spec, None),
+        },
+    };
+    let ports = ports.map(|p| parse_ports(p).ok_or_else(invalid)).transpose()?;
+    let target = parse_target(target.trim()).filter(|t| !spec.starts_with('[') || matches!(t, Target::Cidr(IpAddr::V6(_), _)));
+    Ok(Rule { target: target.ok_or_else(invalid)?, ports })
+}
+
+fn parse_rules(spec: &str) -> Result<Vec<Rule>, AgentError> {
+    spec.split(',').map(str::trim).filter(|r| !r.is_empty()).map(parse_rule).collect()
+}
+
+fn in_cidr(ip: IpAddr, net: IpAddr, prefix: u8) -> bool {
+    match (ip, net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+impl Rule {
+    fn allows(&self, host: &str, ip: Option<IpAddr>, port: u16) -> bool {
+        if self.ports.is_some_and(|(low, high)| port < low || port > high) {
+            return false;
+        }
+        match &self.target {
+            Target::Any => true,
+            Target::Domain(domain) => match domain.strip_prefix("*.") {
+                Some(parent) => host.strip_suffix(parent).is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+                None => host == domain,
+            },
+            Target::Cidr(net, prefix) => ip.is_some_and(|ip| in_cidr(ip, *net, *prefix)),
+        }
+    }
+}
+
+/// The rules in force for `route`: EGRESS_ALLOW plus the route's EGRESS_ROUTE_ALLOW entry.
+/// `None` when no policy is configured.
+fn policy_for(allow: Option<&str>, route_allow: Option<&str>, route: &str) -> Result<Option<Vec<Rule>>, AgentError> {
+    let Some(allow) = allow else { return Ok(None) };
+    let mut rules = parse_rules(allow)?;
+    for entry in route_allow.unwrap_or_default().split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (path, extra) = entry.split_once('=').ok_or_else(|| AgentError::Config(format!("EGRESS_ROUTE_ALLOW entry '{entry}': expected /route=rules")))?;
+        let extra = parse_rules(extra)?;
+        if path.trim() == route {
+            rules.extend(extra);
+        }
+    }
+    Ok(Some(rules))
+}
+
+fn check(rules: Option<&[Rule]>, host: &str, ip: Option<IpAddr>, port: u16) -> Result<(), AgentError> {
+    let host = host.trim_start_matches('[').trim_end_matches(']').trim_end_matches('.').to_ascii_lowercase();
+    // An IP literal is its own address
+    let ip = ip.or_else(|| host.parse().ok());
+    match rules {
+        None => Ok(()),
+        Some(rules) if rules.iter().any(|rule| rule.allows(&host, ip, port)) => Ok(()),
+        Some(_) => Err(AgentError::EgressDenied(format!("{host}:{port} is not allowed by EGRESS_ALLOW"))),
+    }
+}
+
+/// Whether the current request may connect to `host` (a name or IP literal) on `port`; `ip` is
+/// the address a name resolved to, when the caller resolved it.
+pub(crate) fn check_egress(host: &str, ip: Option<IpAddr>, port: u16) -> Result<(), AgentError> {
+    let route = ROUTE.with(|current| current.borrow().clone());
+    let (allow, route_allow) = policy_vars();
+    let rules = policy_for(allow.as_deref(), route_allow.as_deref(), &route)?;
+    check(rules.as_deref(), host, ip, port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_match_domains_cidrs_and_port_ranges() {
+        let rules = parse_rules("api.openai.com:443, *.slack.com, 10.0.0.0/8:6379, [fd00::/8]:8000-8999").unwrap();
+        let allowed = |host: &str, ip: Option<&str>, port: u16| check(Some(&rules), host, ip.map(|ip| ip.parse().unwrap()), port).is_ok();
+        assert!(allowed("api.openai.com", None, 443));
+        assert!(allowed("API.OpenAI.com.", None, 443));
+        assert!(!allowed("api.openai.com", None, 80));
+        assert!(!allowed("evil-api.openai.com.attacker.net", None, 443));
+        assert!(allowed("hooks.slack.com", None, 443) && !allowed("slack.com", None, 443) && !allowed("notslack.com", None, 443));
+        assert!(allowed("10.1.2.3", None, 6379) && allowed("redis.internal", Some("10.9.9.9"), 6379));
+        assert!(!allowed("redis.internal", Some("192.168.1.5"), 6379) && !allowed("10.1.2.3", None, 22));
+        assert!(allowed("[fd00::17]", None, 8080) && !allowed("[fe80::1]", None, 8080));
+        let denied = check(Some(&rules), "169.254.169.254", None, 80).unwrap_err();
+        assert_eq!((denied.http_status(), denied.to_string().as_str()), (403, "169.254.169.254:80 is not allowed by EGRESS_ALLOW"));
+        assert!(check(None, "anywhere.example", None, 1).is_ok());
+    }
+
+    #[test]
+    fn route_exceptions_extend_the_policy_and_bad_rules_deny_everything() {
+        let route_allow = Some("/debug/httpget=*; /tcp/send=127.0.0.1:9090");
+        let scan = policy_for(Some("api.openai.com:443"), route_allow, "/tcp/send").unwrap().unwrap();
+        assert!(check(Some(&scan), "127.0.0.1", None, 9090).is_ok());
+        assert!(check(Some(&scan), "127.0.0.1", None, 22).is_err());
+        let query = policy_for(Some("api.openai.com:443"), route_allow, "/api/v1/query").unwrap().unwrap();
+        assert!(check(Some(&query), "example.com", None, 443).is_err());
+        for bad in ["api.openai.com:https", "10.0.0.0/33", "fd00::1:443", "*.*.example.com", "a.com:9-1"] {
+            assert!(policy_for(Some(bad), None, "/").is_err(), "{bad}");
+        }
+        assert!(policy_for(Some("*"), Some("/x"), "/").is_err());
+    }
+}
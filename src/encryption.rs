@@ -0,0 +1,93 @@
+//! Encryption at rest (AES-256-GCM) for conversation data and credentials in the store.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::store::random_bytes;
+
+/* ---- Encryption at rest (STORE_ENCRYPTION_KEY) ---- */
+// Records under these prefixes hold message content (history, queued and failed work) or Slack
+// credentials (OAuth installs). With a key configured they are sealed with AES-256-GCM using a
+// fresh nonce per write and the record's store key as associated data, so a value copied under
+// another key does not open. Records written before the key was set are still read as plaintext
+// and are sealed the next time they are written.
+const SEALED_PREFIXES: &[&str] = &["history:", "workspace:", "job:", "inflight:", "deadletter:"];
+const SEALED_MARKER: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+fn sealed_key(key: &str) -> bool {
+    SEALED_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok())).collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The cipher for STORE_ENCRYPTION_KEY (64 hex digits); `None` when encryption is off.
+fn cipher() -> Result<Option<Aes256Gcm>, AgentError> {
+    let Some(hex) = get_env_var("STORE_ENCRYPTION_KEY") else { return Ok(None) };
+    let key = decode_hex(hex.trim())
+        .filter(|key| key.len() == 32)
+        .ok_or_else(|| AgentError::Config("STORE_ENCRYPTION_KEY: expected 64 hex digits (a 256-bit key)".into()))?;
+    Ok(Some(Aes256Gcm::new_from_slice(&key).expect("key is 32 bytes")))
+}
+
+fn seal_with(cipher: &Aes256Gcm, key: &str, value: &str) -> Result<String, AgentError> {
+    let nonce = random_bytes(NONCE_LEN);
+    let sealed = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: value.as_bytes(), aad: key.as_bytes() })
+        .map_err(|_| AgentError::Store(format!("{key}: encryption failed")))?;
+    Ok(format!("{SEALED_MARKER}{}{}", encode_hex(&nonce), encode_hex(&sealed)))
+}
+
+fn open_with(cipher: &Aes256Gcm, key: &str, sealed: &str) -> Result<String, AgentError> {
+    let unreadable = || AgentError::Store(format!("{key}: cannot decrypt (wrong STORE_ENCRYPTION_KEY or altered record)"));
+    let bytes = decode_hex(sealed).filter(|b| b.len() > NONCE_LEN).ok_or_else(unreadable)?;
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let plain = cipher.decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: key.as_bytes() }).map_err(|_| unreadable())?;
+    String::from_utf8(plain).map_err(|_| unreadable())
+}
+
+/// The form of `value` to store under `key`: sealed when the key holds sensitive data and a key is set.
+pub(crate) fn seal(key: &str, value: &str) -> Result<String, AgentError> {
+    match cipher()? {
+        Some(cipher) if sealed_key(key) => seal_with(&cipher, key, value),
+        _ => Ok(value.to_string()),
+    }
+}
+
+/// The plaintext of a stored value; sealed values need the key they were written with.
+pub(crate) fn open(key: &str, stored: String) -> Result<String, AgentError> {
+    let Some(sealed) = stored.strip_prefix(SEALED_MARKER) else { return Ok(stored) };
+    let cipher = cipher()?.ok_or_else(|| AgentError::Config(format!("{key} is encrypted but STORE_ENCRYPTION_KEY is not set")))?;
+    open_with(&cipher, key, sealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_records_open_only_under_their_own_key() {
+        let cipher = Aes256Gcm::new_from_slice(&[7; 32]).unwrap();
+        let record = r#"{"question": "What is our Q3 revenue?"}"#;
+        let sealed = seal_with(&cipher, "history:C1:1", record).unwrap();
+        let body = sealed.strip_prefix(SEALED_MARKER).unwrap();
+        assert!(!sealed.contains("revenue"));
+        assert_ne!(seal_with(&cipher, "history:C1:1", record).unwrap(), sealed);
+        assert_eq!(open_with(&cipher, "history:C1:1", body).unwrap(), record);
+        assert!(open_with(&cipher, "history:C2:1", body).is_err());
+        assert!(open_with(&Aes256Gcm::new_from_slice(&[8; 32]).unwrap(), "history:C1:1", body).is_err());
+        assert_eq!(open("prefs:U1", r#"{"private":true}"#.to_string()).unwrap(), r#"{"private":true}"#);
+        assert!(sealed_key("workspace:T1") && !sealed_key("workspace-config:T1"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Typed errors shared by every module, with HTTP status and WIT mappings.
+
+use crate::bindings::exports::component::ai_agent::ai_agent;
+
+/* ---- Typed errors ---- */
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AgentError {
+    /// Missing or invalid configuration (env vars, secrets)
+    Config(String),
+    Dns(String),
+    Tcp(String),
+    /// Outgoing HTTP transport failures and unexpected statuses
+    Http(String),
+    /// The model provider rejected the call or returned an unusable reply
+    /// `retry_after` is the provider's raw Retry-After header, forwarded to API callers
+    Provider { status: u16, message: String, retry_after: Option<String> },
+    Slack(String),
+    Timeout(String),
+    /// Bad input from the caller
+    Validation(String),
+    /// wasi:keyvalue failures
+    Store(String),
+    /// An outbound rate limit (OUTBOUND_RATE_LIMITS) is exhausted for `host`
+    RateLimited { host: String, retry_after: u64 },
+    /// The egress policy (EGRESS_ALLOW) does not allow the destination
+    EgressDenied(String),
+}
+
+impl AgentError {
+    /// Status code for HTTP responses that surface this error.
+    pub(crate) fn http_status(&self) -> u16 {
+        match self {
+            AgentError::Validation(_) => 400,
+            AgentError::Provider { status: 429, .. } | AgentError::RateLimited { .. } => 429,
+            AgentError::EgressDenied(_) => 403,
+            AgentError::Config(_) => 500,
+            AgentError::Dns(_) | AgentError::Tcp(_) | AgentError::Http(_) | AgentError::Provider { .. } | AgentError::Slack(_) => 502,
+            AgentError::Store(_) => 503,
+            AgentError::Timeout(_) => 504,
+        }
+    }
+
+    /// Problem type slug (`/problems/{slug}`) and fixed title for RFC 7807 responses.
+    pub(crate) fn problem_type(&self) -> (&'static str, &'static str) {
+        match self {
+            AgentError::Validation(_) => ("validation", "Invalid request"),
+            AgentError::Config(_) => ("configuration", "Server configuration error"),
+            AgentError::Dns(_) | AgentError::Tcp(_) | AgentError::Http(_) => ("upstream", "Upstream request failed"),
+            AgentError::Provider { status: 429, .. } => ("provider-rate-limited", "Model provider rate limit reached"),
+            AgentError::Provider { .. } => ("provider", "Model provider error"),
+            AgentError::Slack(_) => ("slack", "Slack API error"),
+            AgentError::Timeout(_) => ("timeout", "Upstream timeout"),
+            AgentError::Store(_) => ("store", "Store unavailable"),
+            AgentError::RateLimited { .. } => ("rate-limited", "Outbound rate limit reached"),
+            AgentError::EgressDenied(_) => ("egress-denied", "Destination not allowed by the egress policy"),
+        }
+    }
+
+    /// Prefixes the message (e.g. with the URL being fetched), keeping the variant.
+    pub(crate) fn context(self, ctx: &str) -> AgentError {
+        match self {
+            AgentError::Config(m) => AgentError::Config(format!("{ctx}: {m}")),
+            AgentError::Dns(m) => AgentError::Dns(format!("{ctx}: {m}")),
+            AgentError::Tcp(m) => AgentError::Tcp(format!("{ctx}: {m}")),
+            AgentError::Http(m) => AgentError::Http(format!("{ctx}: {m}")),
+            AgentError::Provider { status, message, retry_after } => {
+                AgentError::Provider { status, message: format!("{ctx}: {message}"), retry_after }
+            }
+            AgentError::Slack(m) => AgentError::Slack(format!("{ctx}: {m}")),
+            AgentError::Timeout(m) => AgentError::Timeout(format!("{ctx}: {m}")),
+            AgentError::Validation(m) => AgentError::Validation(format!("{ctx}: {m}")),
+            AgentError::Store(m) => AgentError::Store(format!("{ctx}: {m}")),
+            AgentError::EgressDenied(m) => AgentError::EgressDenied(format!("{ctx}: {m}")),
+            AgentError::RateLimited { .. } => self,
+        }
+    }
+}
+
+impl std::fmt::Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgentError::Provider { status, message, .. } => write!(f, "provider HTTP {status}: {message}"),
+            AgentError::RateLimited { host, retry_after } => {
+                write!(f, "outbound rate limit for {host} reached, retry in {retry_after}s")
+            }
+            AgentError::Config(m)
+            | AgentError::Dns(m)
+            | AgentError::Tcp(m)
+            | AgentError::Http(m)
+            | AgentError::Slack(m)
+            | AgentError::Timeout(m)
+            | AgentError::Validation(m)
+            | AgentError::Store(m)
+            | AgentError::EgressDenied(m) => f.write_str(m),
+        }
+    }
+}
+
+impl From<AgentError> for ai_agent::AgentError {
+    fn from(e: AgentError) -> Self {
+        match e {
+            AgentError::Config(m) => ai_agent::AgentError::Config(m),
+            AgentError::Dns(m) => ai_agent::AgentError::Dns(m),
+            AgentError::Tcp(m) => ai_agent::AgentError::Tcp(m),
+            AgentError::Http(m) => ai_agent::AgentError::Http(m),
+            AgentError::Provider { status, message, .. } => ai_agent::AgentError::Provider(ai_agent::ProviderError { status, message }),
+            AgentError::Slack(m) => ai_agent::AgentError::Slack(m),
+            AgentError::Timeout(m) => ai_agent::AgentError::Timeout(m),
+            AgentError::Validation(m) => ai_agent::AgentError::Validation(m),
+            AgentError::Store(m) => ai_agent::AgentError::Store(m),
+            e @ (AgentError::RateLimited { .. } | AgentError::EgressDenied(_)) => ai_agent::AgentError::Http(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_errors_map_to_http_status_and_wit_variants() {
+        assert_eq!(AgentError::Config("OPENAI_API_KEY not set".into()).http_status(), 500);
+        assert_eq!(AgentError::Validation("missing query".into()).http_status(), 400);
+        assert_eq!(AgentError::Timeout("slow".into()).http_status(), 504);
+        assert_eq!(AgentError::Store("down".into()).http_status(), 503);
+        assert_eq!(AgentError::Provider { status: 401, message: "bad key".into(), retry_after: None }.http_status(), 502);
+
+        let wit: ai_agent::AgentError = AgentError::Provider { status: 429, message: "slow down".into(), retry_after: None }.into();
+        assert!(matches!(wit, ai_agent::AgentError::Provider(ai_agent::ProviderError { status: 429, .. })));
+        assert_eq!(AgentError::Dns("no IPs found".into()).context("dns").to_string(), "dns: no IPs found");
+
+        let throttled = AgentError::RateLimited { host: "api.openai.com".into(), retry_after: 12 };
+        assert_eq!(throttled.http_status(), 429);
+        assert!(matches!(ai_agent::AgentError::from(throttled), ai_agent::AgentError::Http(m) if m.ends_with("retry in 12s")));
+    }
+}
@@ -0,0 +1,318 @@
+//! Slack Events API callbacks (`/slack/events`).
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::idempotency::first_delivery;
+use crate::jobs::{DeferredTask, defer_task};
+use crate::payloads::{SlackEnvelope, SlackEvent, parse_json};
+use crate::prefs::user_prefs;
+use crate::messages::{set_locale, workspace_locale};
+use crate::residency::{set_region, workspace_region};
+use crate::slack::{slack_bot_token, slack_command_answer, slack_post_message_as, slack_post_reply_as, slack_thread_answer};
+use crate::usage::set_requester;
+use crate::workspaces::{CAPABILITIES, installation, set_workspace, workspace_config};
+
+/* ---- Event dispatch ---- */
+// Slack expects a 2xx within three seconds, so handlers only decide what to do and defer the
+// Slack calls until after the response. Events the app does not handle are acknowledged too,
+// otherwise Slack keeps retrying them. `retry` is set for redeliveries (X-Slack-Retry-Num); those
+// of events already handled are acknowledged and skipped.
+pub(crate) fn handle_slack_event(body: &str, retry: bool) -> (u16, String) {
+    let envelope = match parse_json::<SlackEnvelope>(body) {
+        Ok(envelope) => envelope,
+        Err(e) => return (400, e.to_string()),
+    };
+    if envelope.event_id.as_deref().is_some_and(|id| !first_delivery(&format!("event:{id}"), retry)) {
+        return (200, String::new());
+    }
+    let handled = match envelope.kind.as_str() {
+        // Sent once when the Request URL is saved in the app settings
+        "url_verification" => envelope.challenge().map(str::to_string),
+        "event_callback" => envelope.event().and_then(|event| match event.kind.as_str() {
+            "member_joined_channel" => on_member_joined(&envelope, event).map(|_| String::new()),
+            "app_mention" | "message" => on_message(&envelope, event).map(|_| String::new()),
+            "app_home_opened" => on_home_opened(&envelope, event).map(|_| String::new()),
+            _ => Ok(String::new()),
+        }),
+        _ => Ok(String::new()),
+    };
+    match handled {
+        Ok(text) => (200, text),
+        Err(e) => (400, e.to_string()),
+    }
+}
+
+/* ---- @mentions and direct messages (app_mention, message.im) ---- */
+// Both are answered like a slash command from the same user and channel. A mention is answered
+// in a thread under it, a DM in the conversation (or in its thread, if it was sent in one), and
+// an answer in a thread continues the thread's conversation (see `slack_thread_answer`). Only
+// plain posts by people count: bot messages, including the bot's own replies, and edits are
+// skipped so the bot never answers itself, and `message` events outside DMs are ignored.
+fn on_message(envelope: &SlackEnvelope, event: &SlackEvent) -> Result<(), AgentError> {
+    if event.bot_id.is_some() || event.subtype.is_some() {
+        return Ok(());
+    }
+    let is_dm = event.channel_type.as_deref() == Some("im");
+    if event.kind == "message" && !is_dm {
+        return Ok(());
+    }
+    let (user, channel, text, ts) = event.message()?;
+    let team_id = envelope.team_id.as_deref().or(event.team.as_deref()).unwrap_or_default();
+    let bot = bot_user_id(envelope, team_id);
+    if user == bot {
+        return Ok(());
+    }
+    let text = if bot.is_empty() { text.to_string() } else { text.replace(&format!("<@{bot}>"), "") };
+    let thread_ts = event.thread_ts.clone().or_else(|| (!is_dm).then(|| ts.to_string()));
+    defer_task(DeferredTask::AnswerMessage {
+        team_id: team_id.to_string(),
+        channel: channel.to_string(),
+        user: user.to_string(),
+        text: text.trim().to_string(),
+        thread_ts,
+    });
+    Ok(())
+}
+
+/// Answers a mention or DM with the workspace's region, locale and bot token.
+pub(crate) fn answer_message(team_id: &str, channel: &str, user: &str, text: &str, thread_ts: Option<&str>) -> Result<(), AgentError> {
+    set_region(workspace_region(team_id));
+    set_locale(workspace_locale(team_id));
+    set_requester(user, channel);
+    set_workspace(team_id);
+    let private = user_prefs(user).private;
+    let reply = match thread_ts {
+        _ if text.is_empty() => format!("Hi! Ask me anything here, or use one of my commands:\n{CAPABILITIES}"),
+        Some(thread_ts) => slack_thread_answer(text, user, channel, thread_ts, private).text,
+        None => slack_command_answer(text, user, channel, private).text,
+    };
+    slack_post_reply_as(&slack_bot_token()?, channel, &reply, thread_ts)
+}
+
+/// Publishes the Home tab after the ack; opening the Messages tab needs nothing.
+fn on_home_opened(envelope: &SlackEnvelope, event: &SlackEvent) -> Result<(), AgentError> {
+    if event.tab.as_deref().is_some_and(|tab| tab != "home") {
+        return Ok(());
+    }
+    let team_id = envelope.team_id.as_deref().or(event.team.as_deref()).unwrap_or_default();
+    defer_task(DeferredTask::PublishHome { team_id: team_id.to_string(), user: event.home_opened()?.to_string() });
+    Ok(())
+}
+
+/* ---- Channel-join greeting (CHANNEL_GREETING) ---- */
+// When the bot itself is added to a channel it introduces itself there. Workspaces installed via
+// OAuth turn this off with `greet_on_join: false` in their settings or replace the text with
+// `join_greeting`; CHANNEL_GREETING=off suppresses it everywhere.
+fn on_member_joined(envelope: &SlackEnvelope, event: &SlackEvent) -> Result<(), AgentError> {
+    let (user, channel) = event.member_joined()?;
+    let team_id = envelope.team_id.as_deref().or(event.team.as_deref()).unwrap_or_default();
+    if user == bot_user_id(envelope, team_id) && greeting_enabled(team_id) {
+        defer_task(DeferredTask::ChannelGreeting { team_id: team_id.to_string(), channel: channel.to_string() });
+    }
+    Ok(())
+}
+
+/// The bot's user ID: from the stored installation, the event's authorizations, or SLACK_BOT_USER_ID.
+fn bot_user_id(envelope: &SlackEnvelope, team_id: &str) -> String {
+    installation(team_id)
+        .ok()
+        .flatten()
+        .map(|i| i.bot_user_id)
+        .filter(|id| !id.is_empty())
+        .or_else(|| envelope.authorizations.first().map(|a| a.user_id.clone()))
+        .or_else(|| get_env_var("SLACK_BOT_USER_ID"))
+        .unwrap_or_default()
+}
+
+fn greeting_enabled(team_id: &str) -> bool {
+    if get_env_var("CHANNEL_GREETING").is_some_and(|v| matches!(v.trim(), "off" | "false" | "0")) {
+        return false;
+    }
+    workspace_config(team_id).ok().flatten().is_none_or(|c| c.greet_on_join)
+}
+
+/// Posts the greeting with the workspace's own bot token when it was installed via OAuth.
+pub(crate) fn greet_channel(team_id: &str, channel: &str) -> Result<(), AgentError> {
+    set_workspace(team_id);
+    let token = slack_bot_token()?;
+    let text = workspace_config(team_id)?
+        .and_then(|c| c.join_greeting)
+        .unwrap_or_else(|| format!("Hi! Thanks for adding me. Here is what I can do in this channel:\n{CAPABILITIES}"));
+    slack_post_message_as(&token, channel, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::jobs::run_deferred;
+    use crate::llm::completion;
+    use crate::store::{MockStore, store_set};
+
+    fn joined(user: &str) -> String {
+        serde_json::json!({
+            "type": "event_callback",
+            "team_id": "T0ACME",
+            "authorizations": [{"user_id": "U0BOT"}],
+            "event": {"type": "member_joined_channel", "user": user, "channel": "C0NEW"},
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn url_verification_echoes_the_challenge() {
+        assert_eq!(handle_slack_event(r#"{"type": "url_verification", "challenge": "3eZbrw1aB"}"#, false), (200, "3eZbrw1aB".to_string()));
+        assert_eq!(handle_slack_event("not json", false).0, 400);
+        assert_eq!(handle_slack_event(r#"{"type": "url_verification"}"#, false), (400, "missing field `challenge`".to_string()));
+    }
+
+    #[test]
+    fn bot_joining_a_channel_posts_the_workspace_greeting() {
+        with_openai_env();
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+
+        // Someone else joining is not greeted
+        assert_eq!(handle_slack_event(&joined("U0PERSON"), false), (200, String::new()));
+        run_deferred();
+        assert!(mock.requests.borrow().is_empty());
+
+        store_set("workspace-config:T0ACME", r#"{"intro_channel": null, "join_greeting": "Hello from the bot."}"#).unwrap();
+        handle_slack_event(&joined("U0BOT"), false);
+        run_deferred();
+        let sent: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        assert_eq!(sent, serde_json::json!({"channel": "C0NEW", "text": "Hello from the bot."}));
+    }
+
+    fn message_event(event: serde_json::Value) -> String {
+        serde_json::json!({"type": "event_callback", "team_id": "T0ACME", "authorizations": [{"user_id": "U0BOT"}], "event": event}).to_string()
+    }
+
+    #[test]
+    fn mentions_are_answered_in_a_thread_and_dms_in_the_conversation() {
+        with_openai_env();
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Paris"));
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+        mock.respond(200, "application/json", &completion("Four"));
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+
+        let mention = serde_json::json!({"type": "app_mention", "user": "U1", "channel": "C1", "text": "<@U0BOT> capital of France?", "ts": "1700000000.000100"});
+        assert_eq!(handle_slack_event(&message_event(mention), false), (200, String::new()));
+        let dm = serde_json::json!({"type": "message", "channel_type": "im", "user": "U1", "channel": "D1", "text": "2 + 2?", "ts": "1700000001.000100"});
+        handle_slack_event(&message_event(dm), false);
+        run_deferred();
+
+        let requests = mock.requests.borrow();
+        let question: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(question["messages"][0]["content"], "capital of France?");
+        let reply: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(reply, serde_json::json!({"channel": "C1", "text": "Paris", "thread_ts": "1700000000.000100"}));
+        let dm_reply: serde_json::Value = serde_json::from_slice(&requests[3].body).unwrap();
+        assert_eq!(dm_reply, serde_json::json!({"channel": "D1", "text": "Four"}));
+    }
+
+    #[test]
+    fn mentions_in_a_thread_continue_its_conversation() {
+        with_openai_env();
+        let clock = crate::clock::MockClock::install(1_700_000_000);
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        for answer in ["Paris", "About 2.1 million", "Hi!"] {
+            mock.respond(200, "application/json", &completion(answer));
+            mock.respond(200, "application/json", r#"{"ok": true}"#);
+        }
+
+        let mention = |text: &str, ts: &str, thread_ts: Option<&str>| {
+            let mut event = serde_json::json!({"type": "app_mention", "user": "U1", "channel": "C1", "text": text, "ts": ts});
+            if let Some(thread_ts) = thread_ts {
+                event["thread_ts"] = thread_ts.into();
+            }
+            handle_slack_event(&message_event(event), false);
+            run_deferred();
+            clock.advance(60);
+        };
+        mention("<@U0BOT> capital of France?", "1700000000.000100", None);
+        mention("<@U0BOT> and its population?", "1700000060.000100", Some("1700000000.000100"));
+        mention("<@U0BOT> hello", "1700000120.000100", None);
+
+        let requests = mock.requests.borrow();
+        let prompt = |i: usize| serde_json::from_slice::<serde_json::Value>(&requests[i].body).unwrap()["messages"].clone();
+        assert_eq!(
+            prompt(2),
+            serde_json::json!([
+                {"role": "user", "content": "capital of France?"},
+                {"role": "assistant", "content": "Paris"},
+                {"role": "user", "content": "and its population?"},
+            ])
+        );
+        let reply: serde_json::Value = serde_json::from_slice(&requests[3].body).unwrap();
+        assert_eq!(reply["thread_ts"], "1700000000.000100");
+        // A new thread starts a new conversation
+        assert_eq!(prompt(4).as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn bot_posts_edits_and_channel_messages_are_not_answered() {
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        for event in [
+            serde_json::json!({"type": "message", "channel_type": "im", "user": "U0BOT", "channel": "D1", "text": "Four", "ts": "1.2"}),
+            serde_json::json!({"type": "message", "channel_type": "im", "bot_id": "B1", "user": "U2", "channel": "D1", "text": "hi", "ts": "1.3"}),
+            serde_json::json!({"type": "message", "channel_type": "im", "subtype": "message_changed", "channel": "D1", "ts": "1.4"}),
+            serde_json::json!({"type": "message", "channel_type": "channel", "user": "U1", "channel": "C1", "text": "hello all", "ts": "1.5"}),
+        ] {
+            assert_eq!(handle_slack_event(&message_event(event), false), (200, String::new()));
+        }
+        run_deferred();
+        assert!(mock.requests.borrow().is_empty());
+        let missing_text = serde_json::json!({"type": "app_mention", "user": "U1", "channel": "C1", "ts": "1.6"});
+        assert_eq!(handle_slack_event(&message_event(missing_text), false), (400, "event: missing field `text`".to_string()));
+    }
+
+    #[test]
+    fn greeting_can_be_turned_off_per_workspace() {
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        store_set("workspace-config:T0ACME", r#"{"intro_channel": null, "greet_on_join": false}"#).unwrap();
+        handle_slack_event(&joined("U0BOT"), false);
+        run_deferred();
+        assert!(mock.requests.borrow().is_empty());
+    }
+
+    #[test]
+    fn opening_the_home_tab_publishes_it() {
+        with_openai_env();
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+        for tab in ["home", "messages"] {
+            let opened = serde_json::json!({"type": "app_home_opened", "user": "U1", "channel": "D1", "tab": tab});
+            assert_eq!(handle_slack_event(&message_event(opened), false), (200, String::new()));
+        }
+        run_deferred();
+        let requests = mock.requests.borrow();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "https://slack.com/api/views.publish");
+    }
+
+    #[test]
+    fn retried_events_are_answered_once() {
+        with_openai_env();
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Paris"));
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+
+        let mention = serde_json::json!({"type": "app_mention", "user": "U1", "channel": "C1", "text": "<@U0BOT> capital of France?", "ts": "1.2"});
+        let mut event: serde_json::Value = serde_json::from_str(&message_event(mention)).unwrap();
+        event["event_id"] = "Ev0MENTION".into();
+        assert_eq!(handle_slack_event(&event.to_string(), false), (200, String::new()));
+        assert_eq!(handle_slack_event(&event.to_string(), true), (200, String::new()));
+        run_deferred();
+        assert_eq!(mock.requests.borrow().len(), 2);
+    }
+}
@@ -0,0 +1,347 @@
+//! Versioned slash-command prompts, A/B buckets and thumbs-up/down feedback per version.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::messages::{Msg, message};
+use crate::payloads::BlockAction;
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::blocks::Button;
+use crate::slack::slack_post_message;
+use crate::store::{new_id, store_get, store_list_keys, store_set};
+use crate::tables::{render_markdown_table, table_from_json};
+use crate::usage::parse_window;
+
+/* ---- Prompt versions and buckets (PROMPT_VERSIONS, PROMPT_BUCKET_BY) ---- */
+// `PROMPT_VERSIONS` maps version names to system prompts, e.g. `{"v1": "...", "v2": "..."}`.
+// Each channel (or user, with PROMPT_BUCKET_BY=user) is hashed into one version, so the same
+// requester always sees the same prompt and the split does not depend on any stored state.
+// Without PROMPT_VERSIONS slash commands are answered as before and no feedback is collected.
+pub(crate) struct PromptVersion {
+    pub(crate) name: String,
+    pub(crate) system: String,
+}
+
+fn prompt_versions() -> Result<Vec<PromptVersion>, AgentError> {
+    let Some(raw) = get_env_var("PROMPT_VERSIONS") else { return Ok(Vec::new()) };
+    let map: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&raw).map_err(|e| AgentError::Config(format!("PROMPT_VERSIONS: {e}")))?;
+    let mut versions = map
+        .into_iter()
+        .map(|(name, system)| match system {
+            serde_json::Value::String(system) => Ok(PromptVersion { name, system }),
+            _ => Err(AgentError::Config(format!("PROMPT_VERSIONS: '{name}' must be a string"))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // Sorted so a bucket keeps its version when the object's key order changes
+    versions.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(versions)
+}
+
+fn bucket(key: &str, buckets: usize) -> usize {
+    let digest = Sha256::digest(key.as_bytes());
+    let n = u64::from_be_bytes(digest[..8].try_into().unwrap_or_default());
+    (n % buckets as u64) as usize
+}
+
+/// The prompt version for this requester, if an experiment is configured.
+pub(crate) fn assign_prompt_version(user_id: &str, channel_id: &str) -> Result<Option<PromptVersion>, AgentError> {
+    let mut versions = prompt_versions()?;
+    if versions.is_empty() {
+        return Ok(None);
+    }
+    let key = match get_env_var("PROMPT_BUCKET_BY").as_deref() {
+        Some("user") => user_id,
+        _ => channel_id,
+    };
+    let index = bucket(key, versions.len());
+    Ok(Some(versions.swap_remove(index)))
+}
+
+/* ---- Answers and feedback ---- */
+// Every experiment answer is recorded under `prompt-answer:{id}` with the version and model that
+// produced it. The feedback buttons under the answer carry the id; the latest vote per answer counts.
+pub(crate) const FEEDBACK_ACTION_PREFIX: &str = "prompt_feedback_";
+
+#[derive(Serialize, Deserialize)]
+struct AnswerRecord {
+    version: String,
+    /// Model that wrote the answer; empty for answers recorded before models were tracked
+    #[serde(default)]
+    model: String,
+    user_id: String,
+    channel_id: String,
+    created_at: u64,
+    #[serde(default)]
+    feedback: Option<Feedback>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Feedback {
+    Up,
+    Down,
+}
+
+pub(crate) fn record_answer(version: &str, model: &str, user_id: &str, channel_id: &str) -> Result<String, AgentError> {
+    let id = new_id();
+    let record = AnswerRecord {
+        version: version.to_string(),
+        model: model.to_string(),
+        user_id: user_id.to_string(),
+        channel_id: channel_id.to_string(),
+        created_at: now_secs(),
+        feedback: None,
+    };
+    store_set(&format!("prompt-answer:{id}"), &serde_json::to_string(&record).map_err(|e| AgentError::Store(e.to_string()))?)?;
+    Ok(id)
+}
+
+/// Thumbs-up/down buttons for an answer; votes report back to /slack/interactive.
+pub(crate) fn feedback_buttons(answer_id: &str) -> [Button; 2] {
+    let button = |emoji: &str, vote: &str| Button::new(emoji, &format!("{FEEDBACK_ACTION_PREFIX}{vote}")).value(&format!("{vote}:{answer_id}"));
+    [button(":thumbsup:", "up"), button(":thumbsdown:", "down")]
+}
+
+/// Records a vote from a `block_actions` payload; other interactions are ignored.
+pub(crate) fn handle_feedback_action(actions: &[BlockAction]) -> Result<(), AgentError> {
+    for action in actions {
+        let Some((vote, id)) = action.value().and_then(|v| v.split_once(':')) else { continue };
+        let feedback = match vote {
+            "up" => Feedback::Up,
+            "down" => Feedback::Down,
+            _ => continue,
+        };
+        let key = format!("prompt-answer:{id}");
+        let Some(raw) = store_get(&key)? else {
+            return Err(AgentError::Validation(format!("unknown answer {id}")));
+        };
+        let mut record: AnswerRecord = serde_json::from_str(&raw).map_err(|e| AgentError::Store(format!("answer {id}: {e}")))?;
+        record.feedback = Some(feedback);
+        store_set(&key, &serde_json::to_string(&record).map_err(|e| AgentError::Store(e.to_string()))?)?;
+    }
+    Ok(())
+}
+
+/// Clears the user and channel from answer records by `user_id` or in `channel_id`, keeping the version and vote for
+/// the report. Returns how many records match; they are only changed when `apply` is set.
+pub(crate) fn anonymize_answers(user_id: Option<&str>, channel_id: Option<&str>, apply: bool) -> Result<usize, AgentError> {
+    let mut matched = 0;
+    for key in store_list_keys("prompt-answer:")? {
+        let Some(mut record) = store_get(&key)?.and_then(|raw| serde_json::from_str::<AnswerRecord>(&raw).ok()) else { continue };
+        let user_matches = user_id.is_some_and(|u| !u.is_empty() && record.user_id == u);
+        let channel_matches = channel_id.is_some_and(|c| !c.is_empty() && record.channel_id == c);
+        if !user_matches && !channel_matches {
+            continue;
+        }
+        matched += 1;
+        if apply {
+            record.user_id.clear();
+            record.channel_id.clear();
+            store_set(&key, &serde_json::to_string(&record).map_err(|e| AgentError::Store(e.to_string()))?)?;
+        }
+    }
+    Ok(matched)
+}
+
+/* ---- /prompt-report ---- */
+#[derive(Default)]
+struct VersionStats {
+    answers: u32,
+    up: u32,
+    down: u32,
+}
+
+/// Answers, votes and thumbs-up rate (of rated answers) per prompt version.
+pub(crate) fn slack_prompt_report() -> String {
+    let keys = match store_list_keys("prompt-answer:") {
+        Ok(keys) => keys,
+        Err(e) => return with_ref(&message(Msg::PromptReportUnavailable, &[("error", &e.to_string())]), &current_request_id()),
+    };
+    let mut stats: std::collections::BTreeMap<String, VersionStats> = std::collections::BTreeMap::new();
+    for key in keys {
+        let Some(record) = store_get(&key).ok().flatten().and_then(|raw| serde_json::from_str::<AnswerRecord>(&raw).ok()) else {
+            continue;
+        };
+        let entry = stats.entry(record.version).or_default();
+        entry.answers += 1;
+        match record.feedback {
+            Some(Feedback::Up) => entry.up += 1,
+            Some(Feedback::Down) => entry.down += 1,
+            None => {}
+        }
+    }
+    if stats.is_empty() {
+        return "No prompt experiment answers recorded yet.".to_string();
+    }
+    let rows: Vec<serde_json::Value> = stats
+        .iter()
+        .map(|(version, s)| {
+            let rated = s.up + s.down;
+            let rate = if rated == 0 { "-".to_string() } else { format!("{:.0}%", s.up as f64 * 100.0 / rated as f64) };
+            serde_json::json!({"version": version, "answers": s.answers, "up": s.up, "down": s.down, "up rate": rate})
+        })
+        .collect();
+    let table = table_from_json(&serde_json::Value::Array(rows)).map(|t| render_markdown_table(&t)).unwrap_or_default();
+    format!("*Prompt versions*\n```\n{table}\n```")
+}
+
+/* ---- Quality report (/agent-quality, QUALITY_REPORT_CHANNEL) ---- */
+// Votes are grouped by prompt version and model over a window of whole UTC days, and the
+// combinations with the highest thumbs-down rate are called out. Combinations with fewer than
+// MIN_RATINGS votes are listed but not ranked, so one early vote cannot top the report.
+const MIN_RATINGS: u32 = 3;
+const WORST_N: usize = 3;
+const QUALITY_HELP: &str = "Usage: `/agent-quality [today|7d|30d|<N>d]` (default 7d, at most 90 days)";
+
+fn down_rate(s: &VersionStats) -> f64 {
+    s.down as f64 / (s.up + s.down).max(1) as f64
+}
+
+/// Answer quality per prompt version and model over the last `days` days.
+pub(crate) fn quality_report(days: u64) -> Result<String, AgentError> {
+    let since = (now_secs() / 86_400 + 1).saturating_sub(days) * 86_400;
+    let mut stats: std::collections::BTreeMap<(String, String), VersionStats> = std::collections::BTreeMap::new();
+    for key in store_list_keys("prompt-answer:")? {
+        let Some(record) = store_get(&key)?.and_then(|raw| serde_json::from_str::<AnswerRecord>(&raw).ok()) else { continue };
+        if record.created_at < since {
+            continue;
+        }
+        let model = if record.model.is_empty() { "unknown".to_string() } else { record.model };
+        let entry = stats.entry((record.version, model)).or_default();
+        entry.answers += 1;
+        match record.feedback {
+            Some(Feedback::Up) => entry.up += 1,
+            Some(Feedback::Down) => entry.down += 1,
+            None => {}
+        }
+    }
+    let window = if days == 1 { "today (UTC)".to_string() } else { format!("the last {days} days") };
+    if stats.is_empty() {
+        return Ok(format!("No prompt experiment answers in {window}."));
+    }
+
+    // Worst first: ranked combinations by thumbs-down rate, then the ones with too few votes
+    let mut combos: Vec<(&(String, String), &VersionStats)> = stats.iter().collect();
+    combos.sort_by(|(ka, a), (kb, b)| {
+        let ranked = |s: &VersionStats| s.up + s.down >= MIN_RATINGS;
+        ranked(b).cmp(&ranked(a)).then(down_rate(b).total_cmp(&down_rate(a))).then(ka.cmp(kb))
+    });
+    let worst: Vec<String> = combos
+        .iter()
+        .filter(|(_, s)| s.up + s.down >= MIN_RATINGS && s.down > 0)
+        .take(WORST_N)
+        .map(|((version, model), s)| {
+            format!("• `{version}` with `{model}`: {:.0}% thumbs-down ({} of {} rated)", down_rate(s) * 100.0, s.down, s.up + s.down)
+        })
+        .collect();
+    let headline = if worst.is_empty() {
+        format!("No combination has {MIN_RATINGS} or more votes with a thumbs-down yet.")
+    } else {
+        format!("*Worst-performing*\n{}", worst.join("\n"))
+    };
+    let rows: Vec<serde_json::Value> = combos
+        .iter()
+        .map(|((version, model), s)| {
+            let rated = s.up + s.down;
+            let rate = if rated == 0 { "-".to_string() } else { format!("{:.0}%", down_rate(s) * 100.0) };
+            serde_json::json!({"version": version, "model": model, "answers": s.answers, "up": s.up, "down": s.down, "down rate": rate})
+        })
+        .collect();
+    let table = table_from_json(&serde_json::Value::Array(rows)).map(|t| render_markdown_table(&t)).unwrap_or_default();
+    Ok(format!("*Answer quality in {window}*\n{headline}\n```\n{table}\n```"))
+}
+
+pub(crate) fn slack_quality_reply(text: &str) -> String {
+    let days = match parse_window(text) {
+        Ok(days) => days,
+        Err(e) => return format!("{e}\n{QUALITY_HELP}"),
+    };
+    quality_report(days).unwrap_or_else(|e| with_ref(&message(Msg::QualityReportUnavailable, &[("error", &e.to_string())]), &current_request_id()))
+}
+
+/// The weekly scheduled post; a no-op until QUALITY_REPORT_CHANNEL is set.
+pub(crate) fn post_quality_report() -> Result<String, AgentError> {
+    let Some(channel) = get_env_var("QUALITY_REPORT_CHANNEL").filter(|c| !c.trim().is_empty()) else {
+        return Ok("QUALITY_REPORT_CHANNEL not set".to_string());
+    };
+    slack_post_message(channel.trim(), &quality_report(7)?)?;
+    Ok(format!("posted to {}", channel.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::store::MockStore;
+
+    fn action(value: String) -> BlockAction {
+        BlockAction { action_id: "prompt_feedback_up".into(), value: Some(value), selected_option: None }
+    }
+
+    #[test]
+    fn buckets_are_deterministic_and_spread() {
+        assert_eq!(bucket("C012AB3CD", 2), bucket("C012AB3CD", 2));
+        let counts = (0..200).fold([0; 2], |mut counts, i| {
+            counts[bucket(&format!("C{i}"), 2)] += 1;
+            counts
+        });
+        assert!(counts.iter().all(|c| *c > 70), "{counts:?}");
+    }
+
+    #[test]
+    fn feedback_is_recorded_per_answer_and_reported_per_version() {
+        MockClock::install(1_700_000_000);
+        MockStore::install();
+        let a = record_answer("v1", "gpt-4o-mini", "U1", "C1").unwrap();
+        let b = record_answer("v1", "gpt-4o-mini", "U2", "C1").unwrap();
+        let c = record_answer("v2", "gpt-4o-mini", "U3", "C2").unwrap();
+        record_answer("v2", "gpt-4o-mini", "U3", "C2").unwrap();
+
+        let vote = |value: &str| handle_feedback_action(&[action(value.to_string())]);
+        vote(&format!("down:{a}")).unwrap();
+        vote(&format!("up:{a}")).unwrap();
+        vote(&format!("down:{b}")).unwrap();
+        vote(&format!("up:{c}")).unwrap();
+        assert_eq!(vote("up:missing").unwrap_err(), AgentError::Validation("unknown answer missing".into()));
+
+        let report = slack_prompt_report();
+        assert!(report.contains("| v1      | 2       | 1   | 1    | 50%     |"), "{report}");
+        assert!(report.contains("| v2      | 2       | 1   | 0    | 100%    |"), "{report}");
+    }
+
+    #[test]
+    fn quality_report_ranks_the_worst_version_and_model_in_the_window() {
+        let clock = MockClock::install(1_690_000_000);
+        MockStore::install();
+        let vote = |id: &str, value: &str| {
+            handle_feedback_action(&[action(format!("{value}:{id}"))]).unwrap();
+        };
+        // Outside the weekly window
+        vote(&record_answer("v1", "gpt-4o", "U1", "C1").unwrap(), "down");
+        clock.advance(10_000_000);
+        for value in ["down", "down", "up"] {
+            vote(&record_answer("v1", "gpt-4o-mini", "U1", "C1").unwrap(), value);
+        }
+        for value in ["up", "up", "down"] {
+            vote(&record_answer("v2", "gpt-4o-mini", "U1", "C1").unwrap(), value);
+        }
+        vote(&record_answer("v2", "gpt-4o", "U1", "C1").unwrap(), "down");
+
+        let report = slack_quality_reply("");
+        assert!(
+            report.starts_with(
+                "*Answer quality in the last 7 days*\n*Worst-performing*\n\
+                 • `v1` with `gpt-4o-mini`: 67% thumbs-down (2 of 3 rated)\n\
+                 • `v2` with `gpt-4o-mini`: 33% thumbs-down (1 of 3 rated)\n"
+            ),
+            "{report}"
+        );
+        // Too few votes to rank, so it is listed last despite its 100%
+        assert!(report.contains("| v2      | gpt-4o      | 1       | 0   | 1    | 100%      |\n```"), "{report}");
+        assert!(slack_quality_reply("soon").starts_with("unknown window 'soon'\nUsage: `/agent-quality"));
+    }
+}
@@ -0,0 +1,248 @@
+//! `/agent-forget`: deletes what the assistant stored about a thread, a channel or the requester.
+
+use crate::admins::admin_denial;
+use crate::clock::now_secs;
+use crate::error::AgentError;
+use crate::experiments::anonymize_answers;
+use crate::history::{forget_exchanges, history_prefix};
+use crate::requestid::{current_request_id, with_ref};
+use crate::profiles::profile_key;
+use crate::store::{store_delete, store_get, store_list_keys};
+use crate::usage::forget_usage;
+
+/* ---- Scopes ---- */
+// `me` covers the requester's data in every channel; `channel` covers everyone's data in the
+// current channel and is limited to admins; `thread` covers the exchanges in one thread, for
+// anyone who asked there. Feedback records on prompt experiments are anonymized rather than
+// deleted so the experiment totals stay correct; today's image quota counter is kept so
+// forgetting cannot be used to reset the quota.
+#[derive(Debug, PartialEq)]
+enum Scope {
+    Me,
+    Channel { channel_id: String },
+    Thread { channel_id: String, thread_ts: String },
+}
+
+const USAGE: &str = "Usage: `/agent-forget me`, `/agent-forget channel` or `/agent-forget thread <message link>`. \
+Add `confirm` at the end to delete; without it you get a summary of what would go.";
+
+/// A thread's timestamp (and channel, when given) from a message link such as
+/// `https://acme.slack.com/archives/C0123/p1700000000123456?thread_ts=1700000000.000100`, or a bare ts.
+fn parse_thread(target: &str, channel_id: &str) -> Option<(String, String)> {
+    let target = target.trim().trim_start_matches('<').trim_end_matches('>');
+    let target = target.split('|').next().unwrap_or_default();
+    if target.split_once('.').is_some_and(|(s, us)| !s.is_empty() && !us.is_empty() && target.chars().all(|c| c.is_ascii_digit() || c == '.')) {
+        return Some((channel_id.to_string(), target.to_string()));
+    }
+    let (_, rest) = target.split_once("/archives/")?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (channel, message) = path.split_once('/')?;
+    let digits = message.strip_prefix('p').filter(|d| d.len() > 6 && d.chars().all(|c| c.is_ascii_digit()))?;
+    let thread_ts = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("thread_ts="))
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.{}", &digits[..digits.len() - 6], &digits[digits.len() - 6..]));
+    Some((channel.to_string(), thread_ts))
+}
+
+fn parse_scope(text: &str, channel_id: &str) -> Result<(Scope, bool), AgentError> {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    let confirmed = words.last() == Some(&"confirm");
+    if confirmed {
+        words.pop();
+    }
+    let scope = match words.as_slice() {
+        ["me"] => Scope::Me,
+        ["channel"] if !channel_id.is_empty() => Scope::Channel { channel_id: channel_id.to_string() },
+        ["thread", target] => {
+            let (channel_id, thread_ts) =
+                parse_thread(target, channel_id).ok_or_else(|| AgentError::Validation(format!("'{target}' is not a message link")))?;
+            Scope::Thread { channel_id, thread_ts }
+        }
+        ["thread"] => return Err(AgentError::Validation("`thread` needs a link to a message in the thread".into())),
+        _ => return Err(AgentError::Validation(USAGE.into())),
+    };
+    Ok((scope, confirmed))
+}
+
+/* ---- Purge ---- */
+// Each step counts what it would remove, and removes it only when `apply` is set, so the
+// confirmation prompt and the purge itself list the same things.
+fn purge(scope: &Scope, user_id: &str, apply: bool) -> Result<Vec<(&'static str, usize)>, AgentError> {
+    let mut removed = Vec::new();
+    match scope {
+        Scope::Me => {
+            removed.push(("conversation exchanges", forget_exchanges("history:", |e| e.user_id == user_id, apply)?));
+            let prefs = format!("prefs:{user_id}");
+            let has_prefs = store_get(&prefs)?.is_some();
+            if has_prefs && apply {
+                store_delete(&prefs)?;
+            }
+            removed.push(("saved preferences", usize::from(has_prefs)));
+            let profile = profile_key(user_id);
+            let has_profile = store_get(&profile)?.is_some();
+            if has_profile && apply {
+                store_delete(&profile)?;
+            }
+            removed.push(("cached Slack profile", usize::from(has_profile)));
+            let today = format!("imagine-quota:{user_id}:{}", now_secs() / 86_400);
+            let counters: Vec<String> = store_list_keys(&format!("imagine-quota:{user_id}:"))?.into_iter().filter(|k| *k != today).collect();
+            if apply {
+                counters.iter().try_for_each(|k| store_delete(k))?;
+            }
+            removed.push(("past image quota counters", counters.len()));
+            removed.push(("rated answers (anonymized)", anonymize_answers(Some(user_id), None, apply)?));
+            removed.push(("days of usage statistics", forget_usage(Some(user_id), None, apply)?));
+        }
+        Scope::Channel { channel_id } => {
+            removed.push(("conversation exchanges", forget_exchanges(&history_prefix(channel_id), |_| true, apply)?));
+            removed.push(("rated answers (anonymized)", anonymize_answers(None, Some(channel_id), apply)?));
+            removed.push(("days of usage statistics", forget_usage(None, Some(channel_id), apply)?));
+        }
+        Scope::Thread { channel_id, thread_ts } => {
+            let in_thread = |e: &crate::history::Exchange| e.thread_ts.as_deref() == Some(thread_ts.as_str());
+            removed.push(("conversation exchanges", forget_exchanges(&history_prefix(channel_id), in_thread, apply)?));
+        }
+    }
+    removed.retain(|(_, n)| *n > 0);
+    Ok(removed)
+}
+
+fn describe(scope: &Scope) -> String {
+    match scope {
+        Scope::Me => "you, in every channel".to_string(),
+        Scope::Channel { channel_id } => format!("<#{channel_id}>, for everyone"),
+        Scope::Thread { channel_id, thread_ts } => format!("the thread {thread_ts} in <#{channel_id}>"),
+    }
+}
+
+fn forget(text: &str, user_id: &str, channel_id: &str) -> Result<String, AgentError> {
+    let (scope, confirmed) = parse_scope(text, channel_id)?;
+    match &scope {
+        Scope::Me => {}
+        Scope::Channel { .. } => {
+            if let Some(denial) = admin_denial("/agent-forget channel", user_id) {
+                return Ok(denial);
+            }
+        }
+        Scope::Thread { channel_id, thread_ts } => {
+            // Anyone who asked in the thread may clear it; anyone else needs to be an admin
+            let asked_there = forget_exchanges(
+                &history_prefix(channel_id),
+                |e| e.user_id == user_id && e.thread_ts.as_deref() == Some(thread_ts.as_str()),
+                false,
+            )? > 0;
+            if !asked_there {
+                if let Some(denial) = admin_denial("/agent-forget thread", user_id) {
+                    return Ok(format!("{denial} You can clear threads you asked in yourself."));
+                }
+            }
+        }
+    }
+
+    let found = purge(&scope, user_id, confirmed)?;
+    if found.is_empty() {
+        return Ok(format!("I have nothing stored about {}.", describe(&scope)));
+    }
+    let list: String = found.iter().map(|(what, n)| format!("\n• {what}: {n}")).collect();
+    if confirmed {
+        Ok(format!("Forgotten everything I stored about {}:{list}", describe(&scope)))
+    } else {
+        Ok(format!(
+            "This permanently deletes what I stored about {}:{list}\nRun `/agent-forget {} confirm` to go ahead.",
+            describe(&scope),
+            text.trim()
+        ))
+    }
+}
+
+pub(crate) fn slack_forget_reply(text: &str, user_id: &str, channel_id: &str) -> String {
+    if user_id.is_empty() {
+        return "Forgetting needs a Slack user.".to_string();
+    }
+    match forget(text, user_id, channel_id) {
+        Ok(reply) => reply,
+        Err(AgentError::Validation(message)) if message == USAGE => message,
+        Err(AgentError::Validation(message)) => format!("{message}\n{USAGE}"),
+        Err(e) => with_ref(&format!("Couldn't forget that: {e}"), &current_request_id()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::history::{Exchange, record_test_exchange, visible_history};
+    use crate::httpclient::MockHttpClient;
+    use crate::store::{MockStore, store_set};
+
+    fn exchange(user: &str, thread_ts: Option<&str>) -> Exchange {
+        Exchange {
+            user_id: user.into(),
+            question: "q".into(),
+            answer: "a".into(),
+            private: false,
+            thread_ts: thread_ts.map(str::to_string),
+            at: 1_700_000_000,
+            region: None,
+        }
+    }
+
+    #[test]
+    fn thread_links_and_timestamps_are_understood() {
+        assert_eq!(
+            parse_thread("<https://acme.slack.com/archives/C0ABC/p1700000000123456>", "C0HERE"),
+            Some(("C0ABC".to_string(), "1700000000.123456".to_string()))
+        );
+        assert_eq!(
+            parse_thread("https://acme.slack.com/archives/C0ABC/p1700000100000200?thread_ts=1700000000.123456&cid=C0ABC", "C0HERE"),
+            Some(("C0ABC".to_string(), "1700000000.123456".to_string()))
+        );
+        assert_eq!(parse_thread("1700000000.123456", "C0HERE"), Some(("C0HERE".to_string(), "1700000000.123456".to_string())));
+        assert_eq!(parse_thread("yesterday", "C0HERE"), None);
+    }
+
+    #[test]
+    fn forgetting_me_asks_for_confirmation_then_removes_only_my_data() {
+        let store = MockStore::install();
+        MockClock::install(1_700_000_000);
+        record_test_exchange("C1", &exchange("U1", None));
+        record_test_exchange("C2", &exchange("U1", None));
+        record_test_exchange("C1", &exchange("U2", None));
+        store_set("prefs:U1", r#"{"verbosity": "brief"}"#).unwrap();
+        store_set("imagine-quota:U1:19600", "3").unwrap();
+        store_set("imagine-quota:U1:19675", "1").unwrap();
+
+        let summary = slack_forget_reply("me", "U1", "C1");
+        assert_eq!(
+            summary,
+            "This permanently deletes what I stored about you, in every channel:\n• conversation exchanges: 2\n• saved preferences: 1\n\
+             • past image quota counters: 1\nRun `/agent-forget me confirm` to go ahead."
+        );
+        assert_eq!(store.keys().len(), 6);
+
+        assert!(slack_forget_reply("me confirm", "U1", "C1").starts_with("Forgotten everything I stored about you, in every channel:"));
+        assert_eq!(visible_history("C1", "U2").unwrap().len(), 1);
+        assert!(visible_history("C2", "U1").unwrap().is_empty());
+        assert!(store.keys().iter().all(|k| !k.starts_with("prefs:") && k != "imagine-quota:U1:19600"));
+        assert_eq!(slack_forget_reply("me", "U1", "C1"), "I have nothing stored about you, in every channel.");
+    }
+
+    #[test]
+    fn threads_can_be_cleared_by_people_who_asked_there() {
+        MockStore::install();
+        MockHttpClient::install();
+        record_test_exchange("C1", &exchange("U1", Some("1700000000.123456")));
+        record_test_exchange("C1", &exchange("U2", Some("1700000000.123456")));
+        record_test_exchange("C1", &exchange("U2", None));
+
+        let reply = slack_forget_reply("thread 1700000000.123456", "U3", "C1");
+        assert!(reply.ends_with("You can clear threads you asked in yourself."), "{reply}");
+        assert!(slack_forget_reply("thread 1700000000.123456 confirm", "U1", "C1").contains("• conversation exchanges: 2"));
+        assert_eq!(visible_history("C1", "U1").unwrap().len(), 1);
+
+        assert!(slack_forget_reply("everything", "U1", "C1").starts_with("Usage:"));
+        assert!(slack_forget_reply("thread", "U1", "C1").starts_with("`thread` needs a link"));
+    }
+}
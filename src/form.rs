@@ -0,0 +1,241 @@
+//! URL query strings and `application/x-www-form-urlencoded` bodies.
+
+/* ---- Path and query splitting ---- */
+pub(crate) fn split_path_and_query(path_q: &str) -> (String, Option<String>) {
+    if let Some(idx) = path_q.find('?') {
+        (path_q[..idx].to_string(), Some(path_q[idx+1..].to_string()))
+    } else {
+        (path_q.to_string(), None)
+    }
+}
+
+/* ---- Form / query-string parameters ---- */
+/// Decoded name/value pairs in their original order. Repeated names keep every value; `get`
+/// returns the first one, like `URLSearchParams`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FormParams {
+    pairs: Vec<(String, String)>,
+}
+
+impl FormParams {
+    pub(crate) fn get(&self, name: &str) -> Option<&String> {
+        self.pairs.iter().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+
+    pub(crate) fn get_all(&self, name: &str) -> Vec<&str> {
+        self.pairs.iter().filter(|(k, _)| k == name).map(|(_, v)| v.as_str()).collect()
+    }
+}
+
+/// Parses a query string or form body per the WHATWG urlencoded parser: `&`-separated pairs
+/// split at the first `=` (a missing `=` means an empty value), `+` decodes to a space, and
+/// percent-decoded bytes that are not valid UTF-8 become U+FFFD instead of failing the request.
+pub(crate) fn parse_query_params(qs: impl AsRef<str>) -> FormParams {
+    let pairs = qs
+        .as_ref()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (form_decode(k), form_decode(v))
+        })
+        .collect();
+    FormParams { pairs }
+}
+
+fn form_decode(s: &str) -> String {
+    String::from_utf8_lossy(&percent_decode_bytes(s.as_bytes(), true)).into_owned()
+}
+
+/* ---- Percent-encoding ---- */
+/// Decodes `%XX` escapes; malformed escapes (`%`, `%4`, `%zz`) are kept literally.
+pub(crate) fn percent_decode(s: &str) -> String {
+    String::from_utf8_lossy(&percent_decode_bytes(s.as_bytes(), false)).into_owned()
+}
+
+fn percent_decode_bytes(bytes: &[u8], plus_as_space: bool) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                if let (Some(a), Some(b)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    out.push((a << 4) | b);
+                    i += 3;
+                    continue;
+                }
+                out.push(b'%');
+            }
+            b'+' if plus_as_space => out.push(b' '),
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    out
+}
+
+pub(crate) fn percent_encode(s: &str) -> String {
+    // Encode everything except RFC 3986 unreserved characters
+    let mut out = String::with_capacity(s.len() * 3);
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+fn hex(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(10 + c - b'a'),
+        b'A'..=b'F' => Some(10 + c - b'A'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn pairs(qs: &str) -> Vec<(String, String)> {
+        parse_query_params(qs).pairs
+    }
+
+    fn pair(k: &str, v: &str) -> (String, String) {
+        (k.to_string(), v.to_string())
+    }
+
+    #[test]
+    fn plus_decodes_to_space_but_encoded_plus_stays() {
+        let form = parse_query_params("text=what+is+2%2B2%3F&command=%2Fask");
+        assert_eq!(form.get("text").unwrap(), "what is 2+2?");
+        assert_eq!(form.get("command").unwrap(), "/ask");
+        assert_eq!(parse_query_params("a+b=c+d").get("a b").unwrap(), "c d");
+    }
+
+    #[test]
+    fn slack_form_body_round_trips_user_prompt() {
+        let body = "token=x&team_id=T1&command=%2Fask&text=C%2B%2B+vs+Rust%3A+50%25+faster%3F+a%26b%3Dc\
+                    &response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1%2F2%2Fx";
+        let form = parse_query_params(body);
+        assert_eq!(form.get("text").unwrap(), "C++ vs Rust: 50% faster? a&b=c");
+        assert_eq!(form.get("response_url").unwrap(), "https://hooks.slack.com/commands/T1/2/x");
+    }
+
+    #[test]
+    fn repeated_keys_keep_every_value_in_order() {
+        let form = parse_query_params("tag=a&x=1&tag=b&tag=");
+        assert_eq!(form.get("tag").unwrap(), "a");
+        assert_eq!(form.get_all("tag"), ["a", "b", ""]);
+        assert_eq!(form.get_all("missing"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn empty_values_names_and_segments() {
+        assert_eq!(pairs("a=&b&=c&&d=="), [pair("a", ""), pair("b", ""), pair("", "c"), pair("d", "=")]);
+        assert_eq!(pairs(""), []);
+        assert_eq!(pairs("&&&"), []);
+        assert_eq!(parse_query_params("flag").get("flag").unwrap(), "");
+    }
+
+    #[test]
+    fn only_the_first_equals_sign_splits() {
+        assert_eq!(pairs("q=a=b=c"), [pair("q", "a=b=c")]);
+        assert_eq!(pairs("q=%3D"), [pair("q", "=")]);
+    }
+
+    #[test]
+    fn malformed_escapes_are_kept_literally() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%zz%41"), "%zzA");
+        assert_eq!(percent_decode("%%41"), "%A");
+        assert_eq!(percent_decode("%41"), "A");
+        assert_eq!(percent_decode("a%2fb%2Fc"), "a/b/c");
+    }
+
+    #[test]
+    fn percent_decode_leaves_plus_alone() {
+        assert_eq!(percent_decode("a+b%20c"), "a+b c");
+    }
+
+    #[test]
+    fn invalid_utf8_becomes_replacement_characters() {
+        assert_eq!(parse_query_params("q=%FF%FEok").get("q").unwrap(), "\u{FFFD}\u{FFFD}ok");
+        // A truncated multi-byte sequence does not swallow the following character
+        assert_eq!(percent_decode("%E2%82x"), "\u{FFFD}x");
+        assert_eq!(percent_decode("%E2%82%AC"), "€");
+    }
+
+    #[test]
+    fn raw_non_ascii_passes_through() {
+        assert_eq!(parse_query_params("q=Où+est+la+gare").get("q").unwrap(), "Où est la gare");
+    }
+
+    #[test]
+    fn percent_encode_round_trips_through_the_form_decoder() {
+        for s in ["", "a b+c", "50% & more=yes", "€/ü?#", "~-._"] {
+            let form = parse_query_params(format!("k={}", percent_encode(s)));
+            assert_eq!(form.get("k").unwrap(), s);
+        }
+        assert_eq!(percent_encode("a b/€"), "a%20b%2F%E2%82%AC");
+    }
+
+    proptest! {
+        #[test]
+        fn split_path_and_query_splits_at_the_first_question_mark(
+            path in "[^?]*",
+            query in proptest::option::of("\\PC*"),
+        ) {
+            let input = match &query {
+                Some(q) => format!("{path}?{q}"),
+                None => path.clone(),
+            };
+            let (p, q) = split_path_and_query(&input);
+            prop_assert_eq!(p, path);
+            prop_assert_eq!(q, query);
+        }
+
+        #[test]
+        fn percent_encoding_round_trips_any_string(s in any::<String>()) {
+            let encoded = percent_encode(&s);
+            prop_assert!(encoded.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.~%".contains(&b)));
+            prop_assert_eq!(percent_decode(&encoded), s);
+        }
+
+        #[test]
+        fn encoded_pairs_round_trip_through_the_form_parser(
+            pairs in proptest::collection::vec((any::<String>(), any::<String>()), 0..6),
+        ) {
+            let qs = pairs
+                .iter()
+                .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+                .collect::<Vec<_>>()
+                .join("&");
+            let form = parse_query_params(&qs);
+            prop_assert_eq!(&form.pairs[..], &pairs[..]);
+        }
+
+        #[test]
+        fn form_parsing_is_the_identity_without_escapes(s in "[^%+]*") {
+            let segments: Vec<&str> = s.split('&').filter(|p| !p.is_empty()).collect();
+            let form = parse_query_params(&s);
+            prop_assert_eq!(form.pairs.len(), segments.len());
+            for ((k, v), segment) in form.pairs.iter().zip(segments) {
+                prop_assert!(!k.contains('='));
+                let rebuilt = if segment.contains('=') { format!("{k}={v}") } else { k.clone() };
+                prop_assert_eq!(rebuilt, segment);
+            }
+        }
+
+        #[test]
+        fn decoding_never_panics_on_escape_heavy_input(s in "([%+&=]|[0-9a-fA-F]|\\PC){0,40}") {
+            let _ = parse_query_params(&s);
+            let decoded = percent_decode(&s);
+            prop_assert!(decoded.chars().count() <= s.chars().count());
+        }
+    }
+}
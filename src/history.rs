@@ -0,0 +1,234 @@
+//! Stored question/answer history per channel and `/agent-export`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::error::AgentError;
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::api::SlackApi;
+use crate::slackfiles::upload_slack_file;
+use crate::store::{new_id, store_delete, store_get, store_list_keys, store_set};
+
+/* ---- Exchanges (`history:{channel}:{time}:{id}`) ---- */
+// Every answered slash-command question is kept with the channel it was asked in, so it can be
+// exported or forgotten later. Keys sort by time within a channel. Answers the asker chose to
+// keep private are marked, and only that user ever sees them again.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct Exchange {
+    pub(crate) user_id: String,
+    pub(crate) question: String,
+    pub(crate) answer: String,
+    #[serde(default)]
+    pub(crate) private: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) thread_ts: Option<String>,
+    pub(crate) at: u64,
+    /// Region of the workspace the exchange came from, when it is pinned to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) region: Option<String>,
+}
+
+pub(crate) fn history_prefix(channel_id: &str) -> String {
+    format!("history:{channel_id}:")
+}
+
+/// A key for an exchange in `channel_id` recorded at `at`; `None` outside a channel, where nothing is kept.
+/// Taken before answering when the answer needs to refer to its own exchange.
+pub(crate) fn exchange_key(channel_id: &str, at: u64) -> Option<String> {
+    (!channel_id.is_empty()).then(|| format!("{}{at:010}:{}", history_prefix(channel_id), new_id()))
+}
+
+/// The channel an exchange key belongs to.
+pub(crate) fn exchange_channel(key: &str) -> Option<&str> {
+    key.strip_prefix("history:")?.split_once(':').map(|(channel, _)| channel).filter(|c| !c.is_empty())
+}
+
+/// Records an exchange under a key from `exchange_key`; history is best effort and never fails
+/// the answer it records.
+pub(crate) fn store_exchange(key: &str, exchange: &Exchange) {
+    if let Ok(raw) = serde_json::to_string(exchange) {
+        let _ = store_set(key, &raw);
+    }
+}
+
+/// Records an exchange in `channel_id` at its own time, as answering does.
+#[cfg(test)]
+pub(crate) fn record_test_exchange(channel_id: &str, exchange: &Exchange) {
+    store_exchange(&exchange_key(channel_id, exchange.at).expect("exchanges are kept per channel"), exchange);
+}
+
+/// The exchange under `key`, if it is still kept.
+pub(crate) fn load_exchange(key: &str) -> Result<Option<Exchange>, AgentError> {
+    if exchange_channel(key).is_none() {
+        return Ok(None);
+    }
+    Ok(store_get(key)?.and_then(|raw| serde_json::from_str(&raw).ok()))
+}
+
+/// The channel's exchanges `user_id` may see, oldest first: everything public plus their own.
+pub(crate) fn visible_history(channel_id: &str, user_id: &str) -> Result<Vec<Exchange>, AgentError> {
+    let mut keys = store_list_keys(&history_prefix(channel_id))?;
+    keys.sort();
+    Ok(keys
+        .iter()
+        .filter_map(|key| store_get(key).ok().flatten())
+        .filter_map(|raw| serde_json::from_str::<Exchange>(&raw).ok())
+        .filter(|e| !e.private || e.user_id == user_id)
+        .collect())
+}
+
+/// `user_id`'s latest exchanges in any channel, newest first, with their channel. Only the
+/// newest RECENT_SCAN exchanges are looked at, so an inactive user may get fewer than `limit`.
+pub(crate) fn recent_exchanges_by(user_id: &str, limit: usize) -> Result<Vec<(String, Exchange)>, AgentError> {
+    let mut keys = store_list_keys("history:")?;
+    // Keys are `history:{channel}:{time}:{id}` with a zero-padded time
+    keys.sort_by_cached_key(|key| std::cmp::Reverse(key.rsplitn(3, ':').nth(1).unwrap_or_default().to_string()));
+    Ok(keys
+        .iter()
+        .take(RECENT_SCAN)
+        .filter_map(|key| Some((exchange_channel(key)?.to_string(), load_exchange(key).ok().flatten()?)))
+        .filter(|(_, exchange)| exchange.user_id == user_id)
+        .take(limit)
+        .collect())
+}
+
+const RECENT_SCAN: usize = 200;
+
+/// The exchanges in the thread `thread_ts` of `channel_id` that `user_id` may see, oldest first.
+/// Only the channel's newest RECENT_SCAN exchanges are looked at, so a mention costs the same in a
+/// channel with a long history; a thread that has been quiet for that long starts over.
+pub(crate) fn thread_exchanges(channel_id: &str, thread_ts: &str, user_id: &str) -> Result<Vec<Exchange>, AgentError> {
+    let mut keys = store_list_keys(&history_prefix(channel_id))?;
+    keys.sort();
+    Ok(keys[keys.len().saturating_sub(RECENT_SCAN)..]
+        .iter()
+        .filter_map(|key| load_exchange(key).ok().flatten())
+        .filter(|e| (!e.private || e.user_id == user_id) && e.thread_ts.as_deref() == Some(thread_ts))
+        .collect())
+}
+
+/// Exchanges under `prefix` (one channel, or every channel with `history:`) that `matches` selects,
+/// deleted when `apply` is set. Returns how many there are.
+pub(crate) fn forget_exchanges(prefix: &str, matches: impl Fn(&Exchange) -> bool, apply: bool) -> Result<usize, AgentError> {
+    let mut matched = 0;
+    for key in store_list_keys(prefix)? {
+        let Some(exchange) = store_get(&key)?.and_then(|raw| serde_json::from_str::<Exchange>(&raw).ok()) else { continue };
+        if !matches(&exchange) {
+            continue;
+        }
+        matched += 1;
+        if apply {
+            store_delete(&key)?;
+        }
+    }
+    Ok(matched)
+}
+
+/* ---- /agent-export ---- */
+pub(crate) fn format_time(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0).map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()).unwrap_or_default()
+}
+
+fn history_markdown(channel_id: &str, user_id: &str, exchanges: &[Exchange]) -> String {
+    let mut out = format!(
+        "# Conversation history for <#{channel_id}>\n\nExported for <@{user_id}> on {}. {} exchanges.\n",
+        format_time(now_secs()),
+        exchanges.len()
+    );
+    for e in exchanges {
+        let private = if e.private { " (private)" } else { "" };
+        out.push_str(&format!("\n## {} · <@{}>{private}\n\n**Question:** {}\n\n{}\n", format_time(e.at), e.user_id, e.question, e.answer));
+    }
+    out
+}
+
+/// The requester's DM channel with the bot, where exports are delivered.
+fn open_dm(user_id: &str) -> Result<String, AgentError> {
+    SlackApi::from_env()?.open_dm(user_id)
+}
+
+// The file goes to the requester's DM rather than the channel, since it can hold their private answers
+fn export_history(user_id: &str, channel_id: &str) -> Result<String, AgentError> {
+    let exchanges = visible_history(channel_id, user_id)?;
+    if exchanges.is_empty() {
+        return Ok("There is no stored conversation history for this channel.".to_string());
+    }
+    let markdown = history_markdown(channel_id, user_id, &exchanges);
+    let dm = open_dm(user_id)?;
+    let filename = format!("agent-history-{channel_id}.md");
+    upload_slack_file(&dm, &filename, "text/markdown", markdown.as_bytes(), &format!("History of <#{channel_id}>"))?;
+    Ok(format!("_Exported {} exchanges; the file is in your DMs with me._", exchanges.len()))
+}
+
+pub(crate) fn slack_export_reply(user_id: &str, channel_id: &str) -> String {
+    if user_id.is_empty() || channel_id.is_empty() {
+        return "Export needs a Slack user and channel.".to_string();
+    }
+    export_history(user_id, channel_id).unwrap_or_else(|e| with_ref(&format!("Couldn't export the history: {e}"), &current_request_id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+
+    fn exchange(user: &str, question: &str, private: bool, at: u64) -> Exchange {
+        Exchange { user_id: user.into(), question: question.into(), answer: format!("Answer to {question}"), private, thread_ts: None, at, region: None }
+    }
+
+    #[test]
+    fn others_private_answers_are_left_out_in_time_order() {
+        MockStore::install();
+        record_test_exchange("C1", &exchange("U2", "second", false, 1_700_000_100));
+        record_test_exchange("C1", &exchange("U1", "first", false, 1_700_000_000));
+        record_test_exchange("C1", &exchange("U2", "secret", true, 1_700_000_200));
+        record_test_exchange("C2", &exchange("U1", "elsewhere", false, 1_700_000_000));
+        assert_eq!(exchange_key("", 1_700_000_000), None);
+
+        let questions = |user: &str| visible_history("C1", user).unwrap().into_iter().map(|e| e.question).collect::<Vec<_>>();
+        assert_eq!(questions("U1"), ["first", "second"]);
+        assert_eq!(questions("U2"), ["first", "second", "secret"]);
+    }
+
+    #[test]
+    fn thread_lookups_only_scan_the_newest_exchanges() {
+        MockStore::install();
+        let in_thread = |question: &str, at: u64| Exchange { thread_ts: Some("1.0".into()), ..exchange("U1", question, false, at) };
+        record_test_exchange("C1", &in_thread("long ago", 1_700_000_000));
+        for i in 0..RECENT_SCAN as u64 {
+            record_test_exchange("C1", &exchange("U2", "elsewhere", false, 1_700_000_001 + i));
+        }
+        assert!(thread_exchanges("C1", "1.0", "U1").unwrap().is_empty());
+        record_test_exchange("C1", &in_thread("lately", 1_700_001_000));
+        let questions: Vec<String> = thread_exchanges("C1", "1.0", "U1").unwrap().into_iter().map(|e| e.question).collect();
+        assert_eq!(questions, ["lately"]);
+    }
+
+    #[test]
+    fn export_uploads_markdown_to_the_requesters_dm() {
+        with_openai_env();
+        MockClock::install(1_700_000_500);
+        MockStore::install();
+        record_test_exchange("C1", &exchange("U1", "What is Rust?", false, 1_700_000_000));
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true, "channel": {"id": "D0DM"}}"#);
+        mock.respond(200, "application/json", r#"{"ok":true,"upload_url":"https://files.slack.com/upload/v1/x","file_id":"F0EXP"}"#);
+        mock.respond(200, "text/plain", "OK");
+        mock.respond(200, "application/json", r#"{"ok":true,"files":[{"id":"F0EXP"}]}"#);
+
+        assert_eq!(slack_export_reply("U1", "C1"), "_Exported 1 exchanges; the file is in your DMs with me._");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(String::from_utf8_lossy(&requests[0].body), r#"{"users":"U1"}"#);
+        assert_eq!(
+            String::from_utf8_lossy(&requests[2].body),
+            "# Conversation history for <#C1>\n\nExported for <@U1> on 2023-11-14 22:21 UTC. 1 exchanges.\n\n\
+             ## 2023-11-14 22:13 UTC · <@U1>\n\n**Question:** What is Rust?\n\nAnswer to What is Rust?\n"
+        );
+        let complete: serde_json::Value = serde_json::from_slice(&requests[3].body).unwrap();
+        assert_eq!(complete["channel_id"], "D0DM");
+    }
+}
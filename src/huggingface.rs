@@ -0,0 +1,109 @@
+//! Hugging Face Inference API as the chat provider (LLM_PROVIDER=huggingface).
+
+use crate::clock::{clock, now_secs};
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::httpclient::http_post_json_with_headers;
+use crate::providers::ChatEndpoint;
+use crate::requestid::log;
+
+/* ---- Endpoint and token (HF_TOKEN, HF_ENDPOINT_URL, HF_API_BASE) ---- */
+// Serverless models are addressed by ID in the path, e.g. `/models/meta-llama/Llama-3.1-8B-Instruct`,
+// and served through their OpenAI-compatible `/v1/chat/completions` route, so payloads and replies
+// are the same as with OpenAI. HF_ENDPOINT_URL points at a dedicated Inference Endpoint instead.
+pub(crate) const DEFAULT_HF_MODEL: &str = "meta-llama/Llama-3.1-8B-Instruct";
+
+pub(crate) fn hf_chat_url(model: &str) -> String {
+    if let Some(endpoint) = get_env_var("HF_ENDPOINT_URL") {
+        return format!("{}/v1/chat/completions", endpoint.trim_end_matches('/'));
+    }
+    let base = get_env_var("HF_API_BASE").unwrap_or_else(|| "https://api-inference.huggingface.co".to_string());
+    format!("{}/models/{model}/v1/chat/completions", base.trim_end_matches('/'))
+}
+
+pub(crate) fn hf_token() -> Result<String, AgentError> {
+    get_env_var("HF_TOKEN").ok_or_else(|| AgentError::Config("HF_TOKEN not set".into()))
+}
+
+/* ---- Cold starts (HF_WAIT_FOR_MODEL, HF_COLD_START_RETRIES) ---- */
+// A model that is not loaded answers 503 with `estimated_time` in seconds. By default the call
+// waits that long (at most MAX_COLD_START_WAIT_SECS) and retries, up to HF_COLD_START_RETRIES
+// times. With HF_WAIT_FOR_MODEL=true the API holds the request open until the model is ready.
+const DEFAULT_COLD_START_RETRIES: u32 = 3;
+const MAX_COLD_START_WAIT_SECS: u64 = 60;
+
+/// Seconds until a loading model should be ready, when `error` is a cold-start 503.
+fn cold_start_wait(error: &AgentError) -> Option<u64> {
+    let AgentError::Provider { status: 503, message, .. } = error else { return None };
+    let body = serde_json::from_str::<serde_json::Value>(message).ok()?;
+    let estimated = body["estimated_time"].as_f64()?;
+    Some((estimated.ceil() as u64).clamp(1, MAX_COLD_START_WAIT_SECS))
+}
+
+pub(crate) fn post_hf_chat(endpoint: &ChatEndpoint, payload: &serde_json::Value) -> Result<String, AgentError> {
+    let wait_for_model = get_env_var("HF_WAIT_FOR_MODEL").is_some_and(|v| matches!(v.trim(), "true" | "1" | "yes"));
+    let headers: Vec<(&str, String)> = if wait_for_model { vec![("x-wait-for-model", "true".to_string())] } else { Vec::new() };
+    let retries = get_env_var("HF_COLD_START_RETRIES").and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_COLD_START_RETRIES);
+    let body = payload.to_string();
+    let mut attempt = 0;
+    loop {
+        match http_post_json_with_headers(&endpoint.url, &body, &endpoint.api_key, &headers) {
+            Err(e) if attempt < retries => match cold_start_wait(&e) {
+                Some(wait) => {
+                    attempt += 1;
+                    log!("huggingface: model loading, retry {attempt}/{retries} in {wait}s");
+                    clock().sleep_until(now_secs() + wait);
+                }
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+    use crate::providers::chat_endpoint;
+    use std::env;
+
+    #[test]
+    fn loading_models_are_retried_after_the_estimated_time() {
+        env::set_var("HF_TOKEN", "hf_test");
+        MockClock::install(1_700_000_000);
+        let mock = MockHttpClient::install();
+        mock.respond(503, "application/json", r#"{"error": "Model is currently loading", "estimated_time": 12.4}"#);
+        mock.respond(200, "application/json", &completion("Hi from Llama"));
+
+        let endpoint = chat_endpoint("huggingface/meta-llama/Llama-3.1-8B-Instruct").unwrap();
+        let payload = serde_json::json!({"model": "meta-llama/Llama-3.1-8B-Instruct", "messages": []});
+        let body = post_hf_chat(&endpoint, &payload).unwrap();
+        assert!(body.contains("Hi from Llama"));
+        assert_eq!(now_secs(), 1_700_000_013);
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "https://api-inference.huggingface.co/models/meta-llama/Llama-3.1-8B-Instruct/v1/chat/completions");
+        assert!(requests[0].headers.contains(&("authorization".to_string(), "Bearer hf_test".to_string())));
+    }
+
+    #[test]
+    fn other_errors_and_exhausted_retries_are_returned() {
+        env::set_var("HF_TOKEN", "hf_test");
+        MockClock::install(1_700_000_000);
+        let mock = MockHttpClient::install();
+        mock.respond(401, "application/json", r#"{"error": "Invalid credentials"}"#);
+        let endpoint = chat_endpoint("huggingface/m").unwrap();
+        let payload = serde_json::json!({"model": "m", "messages": []});
+        assert!(matches!(post_hf_chat(&endpoint, &payload), Err(AgentError::Provider { status: 401, .. })));
+
+        for _ in 0..=DEFAULT_COLD_START_RETRIES {
+            mock.respond(503, "application/json", r#"{"error": "loading", "estimated_time": 500}"#);
+        }
+        assert!(matches!(post_hf_chat(&endpoint, &payload), Err(AgentError::Provider { status: 503, .. })));
+        assert_eq!(mock.requests.borrow().len(), 2 + DEFAULT_COLD_START_RETRIES as usize);
+    }
+}
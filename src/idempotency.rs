@@ -0,0 +1,48 @@
+//! Idempotency keys for Slack deliveries, so a retried event or command is handled once.
+
+use crate::clock::now_secs;
+use crate::store::{store_get, store_set};
+
+/* ---- Retry deduplication (X-Slack-Retry-Num) ---- */
+// Slack delivers an event again, up to three times, when it gets no 2xx in time or an error. A
+// retry carries X-Slack-Retry-Num, X-Slack-Retry-Reason and the same event_id; a slash command
+// keeps its trigger_id. Each first delivery records its key. A retry whose key was recorded in
+// the last hour is acknowledged without being handled again, so it makes no second model call and
+// posts no second message. A retry whose first attempt was never recorded is handled as usual,
+// and so is every delivery while the store is unavailable: a duplicate beats a lost message.
+const DEDUP_WINDOW_SECS: u64 = 3600;
+
+/// Whether the delivery with idempotency key `id` should be handled. First deliveries are recorded
+/// and handled; `retry` marks a redelivery (X-Slack-Retry-Num), skipped when `id` was seen recently.
+pub(crate) fn first_delivery(id: &str, retry: bool) -> bool {
+    let key = format!("slack-delivery:{id}");
+    let now = now_secs();
+    if retry {
+        let seen = store_get(&key).ok().flatten().and_then(|at| at.parse::<u64>().ok());
+        if seen.is_some_and(|at| now.saturating_sub(at) < DEDUP_WINDOW_SECS) {
+            return false;
+        }
+    }
+    let _ = store_set(&key, &now.to_string());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::store::MockStore;
+
+    #[test]
+    fn retries_of_recorded_deliveries_are_skipped_within_the_window() {
+        let clock = MockClock::install(1_700_000_000);
+        MockStore::install();
+        assert!(first_delivery("Ev1", false));
+        assert!(!first_delivery("Ev1", true));
+        // A retry of a delivery that was never recorded is handled
+        assert!(first_delivery("Ev2", true));
+        assert!(!first_delivery("Ev2", true));
+        clock.advance(DEDUP_WINDOW_SECS);
+        assert!(first_delivery("Ev1", true));
+    }
+}
@@ -0,0 +1,171 @@
+//! `/imagine`: image generation posted to the channel, with per-user daily quotas.
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::httpclient::http_get_bytes;
+use crate::llm::generate_image;
+use crate::messages::{Msg, message};
+use crate::requestid::{current_request_id, with_ref};
+use crate::slackfiles::upload_slack_file;
+use crate::store::{store_get, store_set};
+
+/* ---- Flags (--size, --style) ---- */
+const USAGE: &str = "Usage: `/imagine <prompt> [--size square|wide|tall] [--style vivid|natural]`";
+const MAX_PROMPT_CHARS: usize = 4_000;
+const MAX_GENERATED_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+struct ImagineRequest {
+    prompt: String,
+    size: &'static str,
+    style: &'static str,
+}
+
+fn parse_imagine(text: &str) -> Result<ImagineRequest, AgentError> {
+    let mut words = Vec::new();
+    let mut size = "1024x1024";
+    let mut style = "vivid";
+    let mut tokens = text.split_whitespace();
+    while let Some(token) = tokens.next() {
+        match token {
+            "--size" => {
+                size = match tokens.next() {
+                    Some("square" | "1024x1024") => "1024x1024",
+                    Some("wide" | "1792x1024") => "1792x1024",
+                    Some("tall" | "1024x1792") => "1024x1792",
+                    other => return Err(AgentError::Validation(format!("unknown size '{}'", other.unwrap_or_default()))),
+                }
+            }
+            "--style" => {
+                style = match tokens.next() {
+                    Some("vivid") => "vivid",
+                    Some("natural") => "natural",
+                    other => return Err(AgentError::Validation(format!("unknown style '{}'", other.unwrap_or_default()))),
+                }
+            }
+            word => words.push(word),
+        }
+    }
+    let prompt = words.join(" ");
+    if prompt.is_empty() {
+        return Err(AgentError::Validation("a prompt is required".into()));
+    }
+    if prompt.chars().count() > MAX_PROMPT_CHARS {
+        return Err(AgentError::Validation(format!("the prompt is longer than {MAX_PROMPT_CHARS} characters")));
+    }
+    Ok(ImagineRequest { prompt, size, style })
+}
+
+/* ---- Per-user daily quota (IMAGINE_DAILY_LIMIT) ---- */
+// Images are billed per generation, so each user gets N per UTC day (default 5). Counts live in
+// the key-value store under `imagine-quota:{user}:{day}`; only successful generations count.
+// As with outbound rate limits, an unavailable store lets the request through.
+const DEFAULT_IMAGINE_DAILY_LIMIT: u32 = 5;
+
+fn daily_limit() -> u32 {
+    get_env_var("IMAGINE_DAILY_LIMIT").and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_IMAGINE_DAILY_LIMIT)
+}
+
+fn quota_key(user: &str) -> String {
+    format!("imagine-quota:{user}:{}", now_secs() / 86_400)
+}
+
+fn images_used_today(user: &str) -> u32 {
+    store_get(&quota_key(user)).ok().flatten().and_then(|raw| raw.parse().ok()).unwrap_or(0)
+}
+
+/* ---- Generate, download and upload ---- */
+fn imagine(text: &str, user: &str, channel: &str) -> Result<String, AgentError> {
+    let request = parse_imagine(text)?;
+    let limit = daily_limit();
+    let used = images_used_today(user);
+    if used >= limit {
+        return Err(AgentError::Validation(message(Msg::ImageQuota, &[("limit", &limit.to_string())])));
+    }
+
+    let url = generate_image(&request.prompt, request.size, request.style)?;
+    // The generated image's URL is pre-signed; it must not be sent the API key
+    let (meta, bytes) = http_get_bytes(&url, &[], MAX_GENERATED_IMAGE_BYTES)?;
+    let content_type = meta.content_type.unwrap_or_else(|| "image/png".to_string());
+    let comment = format!("<@{user}> asked for: _{}_", request.prompt);
+    upload_slack_file(channel, "imagine.png", &content_type, &bytes, &comment)?;
+
+    let _ = store_set(&quota_key(user), &(used + 1).to_string());
+    Ok(format!("_Image posted ({} of {limit} today)._", used + 1))
+}
+
+pub(crate) fn slack_imagine_reply(text: &str, user: &str, channel: &str) -> String {
+    match imagine(text, user, channel) {
+        Ok(reply) => reply,
+        Err(AgentError::Validation(e)) => format!("{}\n{USAGE}", message(Msg::ImageFailed, &[("error", &e)])),
+        Err(e) => with_ref(&message(Msg::ImageFailed, &[("error", &e.to_string())]), &current_request_id()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+
+    #[test]
+    fn flags_set_size_and_style_anywhere_in_the_prompt() {
+        assert_eq!(
+            parse_imagine("a lighthouse --size wide at dusk --style natural").unwrap(),
+            ImagineRequest { prompt: "a lighthouse at dusk".into(), size: "1792x1024", style: "natural" }
+        );
+        assert_eq!(parse_imagine("a cat").unwrap(), ImagineRequest { prompt: "a cat".into(), size: "1024x1024", style: "vivid" });
+        assert_eq!(parse_imagine("a cat --size huge").unwrap_err(), AgentError::Validation("unknown size 'huge'".into()));
+        assert_eq!(parse_imagine("--style").unwrap_err(), AgentError::Validation("unknown style ''".into()));
+        assert_eq!(parse_imagine("--size tall").unwrap_err(), AgentError::Validation("a prompt is required".into()));
+    }
+
+    #[test]
+    fn generated_image_is_uploaded_to_the_channel_and_counted() {
+        with_openai_env();
+        MockClock::install(1_700_000_000);
+        let store = MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"data":[{"url":"https://images.example.net/gen/abc.png?sig=1"}]}"#);
+        mock.respond(200, "image/png", "PNG");
+        mock.respond(200, "application/json", r#"{"ok":true,"upload_url":"https://files.slack.com/upload/v1/abc","file_id":"F0GEN1"}"#);
+        mock.respond(200, "text/plain", "OK - 3");
+        mock.respond(200, "application/json", r#"{"ok":true,"files":[{"id":"F0GEN1"}]}"#);
+
+        assert_eq!(slack_imagine_reply("a red fox --style natural", "U1", "C1"), "_Image posted (1 of 5 today)._");
+        assert_eq!(store.keys(), ["imagine-quota:U1:19675"]);
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://api.openai.com/v1/images/generations");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!((&body["prompt"], &body["size"], &body["style"]), (&"a red fox".into(), &"1024x1024".into(), &"natural".into()));
+        assert!(requests[1].headers.is_empty());
+        assert_eq!(requests[2].url, "https://slack.com/api/files.getUploadURLExternal?filename=imagine.png&length=3");
+        assert_eq!((requests[3].url.as_str(), requests[3].body.as_slice()), ("https://files.slack.com/upload/v1/abc", b"PNG".as_slice()));
+        let body: serde_json::Value = serde_json::from_slice(&requests[4].body).unwrap();
+        assert_eq!(body, serde_json::json!({
+            "files": [{"id": "F0GEN1", "title": "imagine.png"}],
+            "channel_id": "C1",
+            "initial_comment": "<@U1> asked for: _a red fox_",
+        }));
+    }
+
+    #[test]
+    fn users_over_their_daily_quota_are_refused_until_the_next_day() {
+        with_openai_env();
+        let clock = MockClock::install(1_700_000_000);
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        store_set(&quota_key("U2"), "5").unwrap();
+
+        let reply = slack_imagine_reply("a red fox", "U2", "C1");
+        assert!(reply.starts_with("Couldn't create that image: you have used all 5 images for today"), "{reply}");
+        assert!(mock.requests.borrow().is_empty());
+
+        clock.advance(86_400);
+        assert_eq!(images_used_today("U2"), 0);
+    }
+}
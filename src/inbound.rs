@@ -0,0 +1,109 @@
+//! Limits on inbound request bodies: a size cap and a read deadline.
+
+use crate::bindings::wasi::clocks::monotonic_clock;
+use crate::bindings::wasi::http::types as http;
+use crate::bindings::wasi::io::poll;
+use crate::config::get_env_var;
+
+/* ---- Inbound limits (MAX_REQUEST_BODY_BYTES, REQUEST_READ_TIMEOUT_MS) ---- */
+// Bodies are read into memory before routing, so a client could otherwise hold an instance with
+// an endless or trickling upload. A body declared or found to be larger than the cap is refused
+// with 413 without reading the rest, and one that has not fully arrived when the read deadline
+// (counted from the first read) passes is refused with 408. The largest legitimate bodies are
+// job submissions and queries with pasted context, well under the default cap. How many
+// connections are open at once is up to the host (each instance handles one request).
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+const DEFAULT_READ_TIMEOUT_MS: u64 = 10_000;
+const READ_CHUNK_BYTES: u64 = 32 * 1024;
+
+/// Why a request body was not read, answered before the request is routed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BodyRejected {
+    pub(crate) status: u16,
+    pub(crate) detail: String,
+}
+
+struct BodyLimits {
+    max_bytes: usize,
+    read_timeout_ms: u64,
+}
+
+fn body_limits() -> BodyLimits {
+    let var = |name: &str| get_env_var(name).and_then(|v| v.trim().parse::<u64>().ok()).filter(|&v| v > 0);
+    BodyLimits {
+        max_bytes: var("MAX_REQUEST_BODY_BYTES").map_or(DEFAULT_MAX_BODY_BYTES, |v| v as usize),
+        read_timeout_ms: var("REQUEST_READ_TIMEOUT_MS").unwrap_or(DEFAULT_READ_TIMEOUT_MS),
+    }
+}
+
+fn too_large(max_bytes: usize) -> BodyRejected {
+    BodyRejected { status: 413, detail: format!("request body exceeds {max_bytes} bytes") }
+}
+
+/// Collects a body from `next_chunk` (`Ok(None)` at the end), refusing it once it passes `max_bytes`.
+fn collect_body(
+    declared_len: Option<&str>,
+    max_bytes: usize,
+    mut next_chunk: impl FnMut() -> Result<Option<Vec<u8>>, BodyRejected>,
+) -> Result<Vec<u8>, BodyRejected> {
+    if declared_len.and_then(|len| len.trim().parse::<u64>().ok()).is_some_and(|len| len > max_bytes as u64) {
+        return Err(too_large(max_bytes));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = next_chunk()? {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(too_large(max_bytes));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Reads the body of an incoming request within the configured limits.
+pub(crate) fn read_request_body(req: &http::IncomingRequest, declared_len: Option<&str>) -> Result<Vec<u8>, BodyRejected> {
+    let limits = body_limits();
+    let Ok(inc_body) = req.consume() else { return Ok(Vec::new()) };
+    let Ok(stream) = inc_body.stream() else { return Ok(Vec::new()) };
+    let until = monotonic_clock::now() + limits.read_timeout_ms * 1_000_000;
+    let body = collect_body(declared_len, limits.max_bytes, || {
+        let readable = stream.subscribe();
+        let timer = monotonic_clock::subscribe_instant(until);
+        let _ = poll::poll(&[&readable, &timer]);
+        if !readable.ready() {
+            return Err(BodyRejected { status: 408, detail: format!("request body not received within {} ms", limits.read_timeout_ms) });
+        }
+        // An error after readiness is the end of the stream
+        Ok(stream.read(READ_CHUNK_BYTES).ok())
+    });
+    // Drop the stream before finishing the body
+    drop(stream);
+    if body.is_ok() {
+        let _ = http::IncomingBody::finish(inc_body);
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(parts: &[&str]) -> impl FnMut() -> Result<Option<Vec<u8>>, BodyRejected> {
+        let mut parts: Vec<Vec<u8>> = parts.iter().rev().map(|p| p.as_bytes().to_vec()).collect();
+        move || Ok(parts.pop())
+    }
+
+    #[test]
+    fn bodies_past_the_cap_are_refused_without_reading_the_rest() {
+        assert_eq!(collect_body(Some("10"), 10, chunks(&["hello", "world"])), Ok(b"helloworld".to_vec()));
+        assert_eq!(collect_body(None, 10, chunks(&["hello", "world", "!"])).unwrap_err().status, 413);
+        let mut reads = 0;
+        let declared = collect_body(Some("4096"), 10, || {
+            reads += 1;
+            Ok(None)
+        });
+        assert_eq!(declared, Err(BodyRejected { status: 413, detail: "request body exceeds 10 bytes".into() }));
+        assert_eq!(reads, 0);
+        let stalled = BodyRejected { status: 408, detail: "request body not received within 10000 ms".into() };
+        assert_eq!(collect_body(None, 10, || Err(stalled.clone())), Err(stalled));
+    }
+}
@@ -0,0 +1,188 @@
+//! Slack interactivity (`/slack/interactive`): block actions routed by `action_id`, shortcuts and modals by `callback_id`.
+
+use crate::apphome::{HOME_PREFS_ACTION_PREFIX, handle_home_action};
+use crate::error::AgentError;
+use crate::experiments::{FEEDBACK_ACTION_PREFIX, handle_feedback_action};
+use crate::history::{exchange_channel, load_exchange};
+use crate::jobs::{DeferredTask, defer_task};
+use crate::messages::{Msg, message, set_locale, workspace_locale};
+use crate::modals::handle_view_submission;
+use crate::payloads::{BlockAction, SlackInteraction, parse_json};
+use crate::residency::{set_region, workspace_region};
+use crate::shortcuts::handle_shortcut;
+use crate::slack::blocks::Button;
+use crate::slack::{slack_follow_up_answer, slash_response_payload};
+use crate::usage::set_requester;
+use crate::workspaces::set_workspace;
+
+/* ---- Action routing ---- */
+// Slack posts each click or menu choice as a form whose `payload` field holds JSON. Actions in
+// `block_actions` are routed by `action_id`, and shortcuts and modal submissions by `callback_id`.
+// Other interaction types and unknown actions (say, buttons on messages from an older version) are
+// acknowledged and ignored, because Slack shows the user an error for anything but a 2xx. Slack
+// needs the ack within three seconds, so handlers that call the model defer that work and answer
+// through the payload's `response_url`.
+pub(crate) const EXPLAIN_ACTION: &str = "answer_explain";
+pub(crate) const REGENERATE_ACTION: &str = "answer_regenerate";
+
+/// Handles the `payload` form field of an interactivity request. Returns the JSON body to answer
+/// with, if any (`response_action` for a modal submission); otherwise the ack is empty.
+pub(crate) fn handle_interaction(payload: &str) -> Result<Option<serde_json::Value>, AgentError> {
+    let interaction = parse_json::<SlackInteraction>(payload)?;
+    match interaction.kind.as_str() {
+        "block_actions" => {}
+        "message_action" | "shortcut" => return handle_shortcut(&interaction).map(|_| None),
+        "view_submission" => return handle_view_submission(&interaction),
+        _ => return Ok(None),
+    }
+    for action in &interaction.actions {
+        match action.action_id.as_str() {
+            id if id.starts_with(FEEDBACK_ACTION_PREFIX) => handle_feedback_action(std::slice::from_ref(action))?,
+            EXPLAIN_ACTION | REGENERATE_ACTION => defer_follow_up(&interaction, action)?,
+            id if id.starts_with(HOME_PREFS_ACTION_PREFIX) => handle_home_action(&interaction, action)?,
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Posts `payload` (a message, with `replace_original` to edit the clicked one) to `response_url` after the ack.
+pub(crate) fn post_to_response_url(response_url: &str, payload: &serde_json::Value) {
+    defer_task(DeferredTask::SlackDelivery { response_url: response_url.to_string(), payload: payload.to_string() });
+}
+
+/* ---- "Explain more" and "Regenerate" ---- */
+// Both buttons carry the history key of the exchange they sit under. "Regenerate" asks the
+// question again and replaces the answer in place; "Explain more" posts a longer explanation as
+// a new message. Only the asker may follow up on a private answer. Exchanges removed by
+// /agent-forget cannot be followed up on. In either case the user gets a private notice instead.
+pub(crate) fn follow_up_buttons(exchange_key: &str) -> [Button; 2] {
+    [Button::new("Explain more", EXPLAIN_ACTION).value(exchange_key), Button::new("Regenerate", REGENERATE_ACTION).value(exchange_key)]
+}
+
+fn defer_follow_up(interaction: &SlackInteraction, action: &BlockAction) -> Result<(), AgentError> {
+    let missing = |field: &str| AgentError::Validation(format!("{}: missing {field}", action.action_id));
+    defer_task(DeferredTask::FollowUp {
+        team_id: interaction.team.as_ref().map(|t| t.id.clone()).unwrap_or_default(),
+        user: interaction.user.as_ref().ok_or_else(|| missing("user"))?.id.clone(),
+        action: action.action_id.clone(),
+        exchange_key: action.value().ok_or_else(|| missing("value"))?.to_string(),
+        response_url: interaction.response_url.clone().ok_or_else(|| missing("response_url"))?,
+    });
+    Ok(())
+}
+
+/// Answers a follow-up click with the workspace's region and locale.
+pub(crate) fn run_follow_up(team_id: &str, user: &str, action: &str, exchange_key: &str, response_url: &str) -> Result<(), AgentError> {
+    set_region(workspace_region(team_id));
+    set_locale(workspace_locale(team_id));
+    set_workspace(team_id);
+    let channel = exchange_channel(exchange_key).unwrap_or_default();
+    set_requester(user, channel);
+    let Some(earlier) = load_exchange(exchange_key)?.filter(|e| !e.private || e.user_id == user) else {
+        let notice = serde_json::json!({"response_type": "ephemeral", "replace_original": false, "text": message(Msg::AnswerGone, &[])});
+        post_to_response_url(response_url, &notice);
+        return Ok(());
+    };
+    let regenerate = action == REGENERATE_ACTION;
+    let reply = slack_follow_up_answer(&earlier, !regenerate, user, channel);
+    let mut payload = slash_response_payload(&reply.text);
+    payload["response_type"] = if earlier.private { "ephemeral" } else { "in_channel" }.into();
+    payload["replace_original"] = regenerate.into();
+    if let Some(blocks) = reply.blocks {
+        payload["blocks"] = blocks;
+    }
+    post_to_response_url(response_url, &payload);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::history::{Exchange, exchange_key, store_exchange};
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+
+    fn click(action_id: &str, value: &str, user: &str) -> String {
+        serde_json::json!({
+            "type": "block_actions",
+            "user": {"id": user},
+            "team": {"id": "T1"},
+            "response_url": "https://hooks.slack.com/actions/x",
+            "actions": [{"action_id": action_id, "value": value}],
+        })
+        .to_string()
+    }
+
+    fn earlier(private: bool) -> String {
+        let key = exchange_key("C1", 1_700_000_000).unwrap();
+        let exchange = Exchange {
+            user_id: "U1".into(),
+            question: "Capital of France?".into(),
+            answer: "Paris".into(),
+            private,
+            thread_ts: None,
+            at: 1_700_000_000,
+            region: None,
+        };
+        store_exchange(&key, &exchange);
+        key
+    }
+
+    #[test]
+    fn regenerate_replaces_the_answer_and_explain_posts_a_new_one() {
+        with_openai_env();
+        crate::clock::MockClock::install(1_700_000_100);
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        let key = earlier(false);
+        mock.respond(200, "application/json", &completion("Paris, again"));
+        mock.respond(200, "application/json", &completion("Paris has been the capital since 987."));
+        mock.respond(200, "text/plain", "ok");
+        mock.respond(200, "text/plain", "ok");
+
+        handle_interaction(&click(REGENERATE_ACTION, &key, "U2")).unwrap();
+        handle_interaction(&click(EXPLAIN_ACTION, &key, "U2")).unwrap();
+        crate::jobs::run_deferred();
+
+        // Both answers are generated before either is delivered
+        let requests = mock.requests.borrow();
+        let body = |i: usize| serde_json::from_slice::<serde_json::Value>(&requests[i].body).unwrap();
+        assert_eq!(body(1)["messages"][1]["content"], "Question: Capital of France?\n\nAnswer: Paris");
+        let (regenerated, explained) = (body(2), body(3));
+        assert_eq!(requests[2].url, "https://hooks.slack.com/actions/x");
+        assert_eq!((regenerated["replace_original"].as_bool(), regenerated["text"].as_str()), (Some(true), Some("Paris, again")));
+        assert_eq!((explained["replace_original"].as_bool(), explained["response_type"].as_str()), (Some(false), Some("in_channel")));
+        let buttons = &explained["blocks"][1]["elements"];
+        assert_eq!((buttons[0]["action_id"].as_str(), buttons[1]["action_id"].as_str()), (Some(EXPLAIN_ACTION), Some(REGENERATE_ACTION)));
+        let follow_up = load_exchange(buttons[0]["value"].as_str().unwrap()).unwrap().unwrap();
+        assert_eq!((follow_up.question.as_str(), follow_up.user_id.as_str()), ("Explain more: Capital of France?", "U2"));
+    }
+
+    #[test]
+    fn private_and_forgotten_answers_get_a_notice_and_unknown_actions_are_ignored() {
+        with_openai_env();
+        crate::clock::MockClock::install(1_700_000_100);
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "text/plain", "ok");
+        mock.respond(200, "text/plain", "ok");
+        let key = earlier(true);
+
+        handle_interaction(&click(REGENERATE_ACTION, &key, "U2")).unwrap();
+        handle_interaction(&click(EXPLAIN_ACTION, "history:C1:0000000001:gone", "U1")).unwrap();
+        handle_interaction(&click("some_old_button", "x", "U1")).unwrap();
+        handle_interaction(r#"{"type": "view_submission"}"#).unwrap();
+        assert!(handle_interaction(r#"{"type": "block_actions", "actions": [{"value": "x"}]}"#).is_err());
+        crate::jobs::run_deferred();
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests.len(), 2);
+        for request in requests.iter() {
+            let notice: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(notice["response_type"], "ephemeral");
+            assert_eq!(notice["text"], message(Msg::AnswerGone, &[]));
+        }
+    }
+}
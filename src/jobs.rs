@@ -0,0 +1,757 @@
+//! Deferred work, async jobs, dead letters and signed job callbacks.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+use crate::{fetch_and_process, multi_source_response};
+use crate::apphome::publish_home;
+use crate::broadcast::slack_broadcast_reply;
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::events::{answer_message, greet_channel};
+use crate::interactive::run_follow_up;
+use crate::router::run_slash_reply;
+use crate::shortcuts::{run_summarize_message, send_capabilities};
+use crate::history::slack_export_reply;
+use crate::httpclient::http_post_with_headers;
+use crate::imagine::slack_imagine_reply;
+use crate::llm::{answer_oversized_input, call_openai_with_system, condense_threshold};
+use crate::modals::run_modal_prompt;
+use crate::payloads::parse_json;
+use crate::prefs::answer_visibility;
+use crate::requestid::{current_request_id, log, with_ref};
+use crate::messages::{Msg, current_locale, message, set_locale};
+use crate::residency::{current_region, set_region};
+use crate::slack::{answer_blocks, slack_command_reply, slack_post_message, slash_response_payload};
+use crate::store::{new_id, store_delete, store_get, store_list_keys, store_set};
+use crate::urls::canonicalize_url;
+use crate::workspaces::{current_workspace, set_workspace};
+
+/* ---- Deferred work with in-flight tracking ---- */
+// Work that must outlive the response (e.g. response_url posts). Each task is persisted under
+// `inflight:{id}` before it runs and removed once it succeeds, so work interrupted by a host
+// shutdown can be flushed later via /admin/drain. Failed tasks stay in the store as a retry queue
+// and are re-attempted with exponential backoff by the `drain-inflight` scheduled task.
+// A task being run carries a lease (`claimed_until`), which every drain respects, forced or not,
+// so an instance flushing the queue never repeats work another instance is still doing. The store
+// has no compare-and-set: a drain takes a task by writing its own claim and reading it back.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum DeferredTask {
+    SlackDelivery { response_url: String, payload: String },
+    SlackPost { channel: String, text: String },
+    Webhook { url: String, payload: String },
+    RunJob { id: String },
+    ChannelGreeting { team_id: String, channel: String },
+    /// An @mention or direct message to answer, in `thread_ts` when set
+    AnswerMessage { team_id: String, channel: String, user: String, text: String, thread_ts: Option<String> },
+    /// A slash command acknowledged before it was answered
+    SlashReply { team_id: String, command: String, text: String, user_id: String, channel_id: String, response_url: String },
+    /// An "Explain more" or "Regenerate" click, answered through the interaction's `response_url`
+    FollowUp { team_id: String, user: String, action: String, exchange_key: String, response_url: String },
+    /// The user's App Home tab to rebuild and publish
+    PublishHome { team_id: String, user: String },
+    /// A message chosen with "Summarize this message", summarized in `thread_ts`
+    SummarizeMessage { team_id: String, user: String, channel: String, thread_ts: String, text: String, response_url: String },
+    /// The global shortcut that sends the list of commands to the user's DMs
+    ShortcutCapabilities { team_id: String, user: String },
+    /// A submitted prompt modal, answered in `channel` or the user's DMs
+    ModalPrompt { team_id: String, user: String, prompt: String, model: String, temperature: Option<f64>, channel: Option<String> },
+}
+
+#[derive(Serialize, Deserialize)]
+struct InflightRecord {
+    task: DeferredTask,
+    attempts: u32,
+    #[serde(default)]
+    next_attempt_at: u64,
+    #[serde(default)]
+    last_error: Option<String>,
+    /// Until when the instance running the task holds it; 0 when nobody does
+    #[serde(default)]
+    claimed_until: u64,
+    #[serde(default)]
+    claim: String,
+}
+
+thread_local! {
+    static DEFERRED: RefCell<Vec<(String, DeferredTask)>> = RefCell::new(Vec::new());
+}
+
+const INFLIGHT_PREFIX: &str = "inflight:";
+const RETRY_BASE_SECS: u64 = 30;
+const RETRY_MAX_SECS: u64 = 3600;
+/// Longer than any deferred task runs; a lease outliving its instance expires after this
+const LEASE_SECS: u64 = 900;
+
+/// 30s, 60s, 120s, ... capped at an hour.
+fn retry_delay(attempts: u32) -> u64 {
+    RETRY_BASE_SECS.saturating_mul(1u64 << attempts.saturating_sub(1).min(16)).min(RETRY_MAX_SECS)
+}
+
+fn save_record(key: &str, record: &InflightRecord) -> Result<(), AgentError> {
+    store_set(key, &serde_json::to_string(record).unwrap_or_default())
+}
+
+pub(crate) fn defer_task(task: DeferredTask) {
+    let id = new_id();
+    let record = InflightRecord {
+        task: task.clone(),
+        attempts: 0,
+        next_attempt_at: 0,
+        last_error: None,
+        claimed_until: now_secs() + LEASE_SECS,
+        claim: new_id(),
+    };
+    if let Err(e) = save_record(&format!("{INFLIGHT_PREFIX}{id}"), &record) {
+        log!("deferred task {id} not persisted: {e}");
+    }
+    DEFERRED.with(|q| q.borrow_mut().push((id, task)));
+}
+
+fn execute_task(task: &DeferredTask) -> Result<(), AgentError> {
+    match task {
+        DeferredTask::SlackDelivery { response_url, payload } => {
+            match http_post_with_headers(response_url, payload, "application/json", &[])? {
+                200..=299 => Ok(()),
+                code => Err(AgentError::Slack(format!("response_url returned HTTP {code}"))),
+            }
+        }
+        DeferredTask::SlackPost { channel, text } => slack_post_message(channel, text),
+        DeferredTask::Webhook { url, payload } => post_signed_callback(url, payload),
+        DeferredTask::RunJob { id } => run_async_job(id),
+        DeferredTask::ChannelGreeting { team_id, channel } => greet_channel(team_id, channel),
+        DeferredTask::AnswerMessage { team_id, channel, user, text, thread_ts } => {
+            answer_message(team_id, channel, user, text, thread_ts.as_deref())
+        }
+        DeferredTask::SlashReply { team_id, command, text, user_id, channel_id, response_url } => {
+            run_slash_reply(team_id, command, text, user_id, channel_id, response_url)
+        }
+        DeferredTask::FollowUp { team_id, user, action, exchange_key, response_url } => {
+            run_follow_up(team_id, user, action, exchange_key, response_url)
+        }
+        DeferredTask::PublishHome { team_id, user } => publish_home(team_id, user),
+        DeferredTask::SummarizeMessage { team_id, user, channel, thread_ts, text, response_url } => {
+            run_summarize_message(team_id, user, channel, thread_ts, text, response_url)
+        }
+        DeferredTask::ShortcutCapabilities { team_id, user } => send_capabilities(team_id, user),
+        DeferredTask::ModalPrompt { team_id, user, prompt, model, temperature, channel } => {
+            run_modal_prompt(team_id, user, prompt, model, *temperature, channel.as_deref())
+        }
+    }
+}
+
+/// Runs the tasks queued by the current request, including any queued by those tasks.
+pub(crate) fn run_deferred() -> DrainReport {
+    let mut report = DrainReport::default();
+    loop {
+        let tasks = DEFERRED.with(|q| std::mem::take(&mut *q.borrow_mut()));
+        if tasks.is_empty() {
+            return report;
+        }
+        for (id, task) in tasks {
+            finish_task(&id, &task, 0, &mut report);
+            report.attempted.push(id);
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct DrainReport {
+    pub(crate) flushed: u32,
+    pub(crate) failed: u32,
+    pub(crate) waiting: u32,
+    /// Tasks run in this pass, which a forced drain does not run again
+    attempted: Vec<String>,
+}
+
+fn finish_task(id: &str, task: &DeferredTask, attempts: u32, report: &mut DrainReport) {
+    let key = format!("{INFLIGHT_PREFIX}{id}");
+    match execute_task(task) {
+        Ok(()) => {
+            report.flushed += 1;
+            let _ = store_delete(&key);
+        }
+        Err(e) if attempts + 1 >= max_delivery_attempts() => {
+            report.failed += 1;
+            log!("deferred task {id} dead-lettered after {} attempts: {e}", attempts + 1);
+            let letter = DeadLetter { task: task.clone(), attempts: attempts + 1, reason: e.to_string(), failed_at: now_secs() };
+            match store_set(&format!("{DEADLETTER_PREFIX}{id}"), &serde_json::to_string(&letter).unwrap_or_default()) {
+                Ok(()) => {
+                    let _ = store_delete(&key);
+                }
+                Err(e) => log!("deferred task {id} could not be dead-lettered: {e}"),
+            }
+        }
+        Err(e) => {
+            report.failed += 1;
+            log!("deferred task {id} failed (attempt {}): {e}", attempts + 1);
+            // The backoff and the released lease are one write, so no drain sees one without the other
+            let record = InflightRecord {
+                task: task.clone(),
+                attempts: attempts + 1,
+                next_attempt_at: now_secs() + retry_delay(attempts + 1),
+                last_error: Some(e.to_string()),
+                claimed_until: 0,
+                claim: String::new(),
+            };
+            let _ = save_record(&key, &record);
+        }
+    }
+}
+
+/// Takes a stored task for this instance: claims it and reads the claim back, so of two drains
+/// that read the record at once only the last writer runs it.
+fn claim_task(key: &str, mut record: InflightRecord) -> Option<InflightRecord> {
+    let claim = new_id();
+    record.claimed_until = now_secs() + LEASE_SECS;
+    record.claim = claim.clone();
+    save_record(key, &record).ok()?;
+    let held = store_get(key).ok()??;
+    let held = serde_json::from_str::<InflightRecord>(&held).ok()?;
+    (held.claim == claim).then_some(held)
+}
+
+/// Flushes local work plus anything left in the store by interrupted instances or earlier
+/// failures. Unless `force` is set, failed tasks wait until their backoff has elapsed; tasks
+/// another instance holds a lease on, and tasks already run in this pass, always wait.
+pub(crate) fn drain_inflight(force: bool) -> DrainReport {
+    let mut report = run_deferred();
+    let keys = match store_list_keys(INFLIGHT_PREFIX) {
+        Ok(keys) => keys,
+        Err(e) => {
+            log!("drain: cannot list in-flight work: {e}");
+            return report;
+        }
+    };
+    for key in keys {
+        let Ok(Some(raw)) = store_get(&key) else { continue };
+        let Ok(record) = serde_json::from_str::<InflightRecord>(&raw) else { continue };
+        let id = &key[INFLIGHT_PREFIX.len()..];
+        if report.attempted.iter().any(|attempted| attempted == id) {
+            continue;
+        }
+        if record.claimed_until > now_secs() || (!force && record.next_attempt_at > now_secs()) {
+            report.waiting += 1;
+            continue;
+        }
+        let Some(record) = claim_task(&key, record) else { continue };
+        finish_task(id, &record.task, record.attempts, &mut report);
+        report.attempted.push(id.to_string());
+    }
+    report
+}
+
+/* ---- Dead letters (deliveries that exhausted their retries) ---- */
+const DEADLETTER_PREFIX: &str = "deadletter:";
+const DEFAULT_MAX_DELIVERY_ATTEMPTS: u32 = 8;
+
+#[derive(Serialize, Deserialize)]
+struct DeadLetter {
+    task: DeferredTask,
+    attempts: u32,
+    reason: String,
+    failed_at: u64,
+}
+
+fn max_delivery_attempts() -> u32 {
+    get_env_var("DELIVERY_MAX_ATTEMPTS")
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_DELIVERY_ATTEMPTS)
+}
+
+fn list_dead_letters() -> Result<Vec<(String, DeadLetter)>, AgentError> {
+    let mut letters = Vec::new();
+    for key in store_list_keys(DEADLETTER_PREFIX)? {
+        let Some(raw) = store_get(&key)? else { continue };
+        if let Ok(letter) = serde_json::from_str::<DeadLetter>(&raw) {
+            letters.push((key[DEADLETTER_PREFIX.len()..].to_string(), letter));
+        }
+    }
+    letters.sort_by_key(|(_, l)| l.failed_at);
+    Ok(letters)
+}
+
+/// `GET` lists dead letters; `POST ?id=<id>` (or without `id`, all of them) re-queues them as
+/// fresh deferred tasks that run once the response is sent.
+pub(crate) fn handle_admin_deadletters(redrive: bool, id: Option<&str>) -> (u16, String) {
+    let letters = match list_dead_letters() {
+        Ok(letters) => letters,
+        Err(e) => return (503, serde_json::json!({"error": format!("store unavailable: {e}")}).to_string()),
+    };
+    if !redrive {
+        let list: Vec<serde_json::Value> = letters
+            .iter()
+            .map(|(id, l)| serde_json::json!({"id": id, "task": l.task, "attempts": l.attempts, "reason": l.reason, "failed_at": l.failed_at}))
+            .collect();
+        return (200, serde_json::Value::Array(list).to_string());
+    }
+    let selected: Vec<_> = letters.into_iter().filter(|(lid, _)| id.map_or(true, |id| id == lid)).collect();
+    if selected.is_empty() && id.is_some() {
+        return (404, serde_json::json!({"error": "dead letter not found"}).to_string());
+    }
+    let mut redriven = Vec::new();
+    for (lid, letter) in selected {
+        defer_task(letter.task);
+        let _ = store_delete(&format!("{DEADLETTER_PREFIX}{lid}"));
+        redriven.push(lid);
+    }
+    (200, serde_json::json!({"redriven": redriven}).to_string())
+}
+
+/* ---- Async jobs (/api/v1/jobs) ---- */
+// Long-running work is recorded under `job:{id}`, acknowledged immediately and processed as a
+// deferred task once the response is sent. Results go to Slack and/or a callback URL.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum JobRequest {
+    Query { query: String, #[serde(default)] context: Option<String> },
+    Summarize { url: String },
+    Research { query: String, urls: Vec<String> },
+    SlashCommand { text: String, #[serde(default)] user_id: String, #[serde(default)] channel_id: String },
+    Imagine { text: String, user_id: String, channel_id: String },
+    Broadcast { text: String, user_id: String, channel_id: String },
+    Export { user_id: String, channel_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub(crate) struct JobDelivery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) response_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) callback_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JobRecord {
+    id: String,
+    request: JobRequest,
+    delivery: JobDelivery,
+    state: JobState,
+    result: Option<String>,
+    error: Option<String>,
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    started_at: Option<u64>,
+    #[serde(default)]
+    finished_at: Option<u64>,
+    /// The submitting request, quoted in failure messages
+    #[serde(default)]
+    request_id: String,
+    /// Region the submitting workspace is pinned to; the job runs there too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    region: Option<String>,
+    /// Locale of the submitting request, for messages delivered later
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    locale: Option<String>,
+    /// Slack workspace of the submitting request, whose bot token the job's Slack calls use
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    team_id: String,
+}
+
+#[derive(Deserialize)]
+struct JobSubmission {
+    #[serde(flatten)]
+    request: JobRequest,
+    #[serde(flatten)]
+    delivery: JobDelivery,
+}
+
+fn load_job(id: &str) -> Result<Option<JobRecord>, AgentError> {
+    match store_get(&format!("job:{id}"))? {
+        Some(raw) => serde_json::from_str(&raw).map(Some).map_err(|e| AgentError::Store(format!("job {id}: {e}"))),
+        None => Ok(None),
+    }
+}
+
+fn save_job(job: &JobRecord) -> Result<(), AgentError> {
+    store_set(&format!("job:{}", job.id), &serde_json::to_string(job).map_err(|e| AgentError::Store(e.to_string()))?)
+}
+
+/// Persists a queued job and schedules it to run after the current response.
+pub(crate) fn enqueue_job(request: JobRequest, delivery: JobDelivery) -> Result<String, AgentError> {
+    let job = JobRecord {
+        id: new_id(),
+        request,
+        delivery,
+        state: JobState::Queued,
+        result: None,
+        error: None,
+        created_at: now_secs(),
+        started_at: None,
+        finished_at: None,
+        request_id: current_request_id(),
+        region: current_region(),
+        locale: current_locale(),
+        team_id: current_workspace(),
+    };
+    save_job(&job)?;
+    defer_task(DeferredTask::RunJob { id: job.id.clone() });
+    Ok(job.id)
+}
+
+/// Where Slack's `response_url`s point; a job's `response_url` may not send results anywhere else.
+const SLACK_RESPONSE_URL_PREFIX: &str = "https://hooks.slack.com/";
+
+/// `admin` is set when the submission carries the admin token, which Slack deliveries need.
+pub(crate) fn handle_job_submit(body: &str, admin: bool) -> (u16, String) {
+    let mut submission: JobSubmission = match parse_json(body) {
+        Ok(s) => s,
+        Err(e) => return (400, serde_json::json!({"error": format!("invalid job: {e}")}).to_string()),
+    };
+    let invalid = |error: String| (400, serde_json::json!({"error": error}).to_string());
+    // Jobs that act for a Slack user (post or upload as the bot, count against their quota) need
+    // the signed identity of a slash command; the API has no Slack identity to check
+    let missing = match &submission.request {
+        JobRequest::Broadcast { .. } => return invalid("broadcast jobs can only be started with /agent-broadcast".into()),
+        JobRequest::Export { .. } => return invalid("export jobs can only be started with /agent-export".into()),
+        JobRequest::Imagine { .. } => return invalid("imagine jobs can only be started with /imagine".into()),
+        JobRequest::SlashCommand { user_id, channel_id, .. } if !user_id.is_empty() || !channel_id.is_empty() => {
+            return invalid("slash_command jobs cannot name a user_id or channel_id".into())
+        }
+        JobRequest::Query { query, .. } => query.trim().is_empty().then_some("query"),
+        JobRequest::Summarize { url } => url.trim().is_empty().then_some("url"),
+        JobRequest::Research { query, urls } => {
+            if query.trim().is_empty() { Some("query") } else if urls.is_empty() { Some("urls") } else { None }
+        }
+        JobRequest::SlashCommand { text, .. } => text.trim().is_empty().then_some("text"),
+    };
+    if let Some(field) = missing {
+        return invalid(format!("missing {field}"));
+    }
+    let urls = match &submission.request {
+        JobRequest::Summarize { url } => std::slice::from_ref(url),
+        JobRequest::Research { urls, .. } => urls.as_slice(),
+        _ => &[],
+    };
+    if let Some(e) = urls.iter().find_map(|url| canonicalize_url(url).err()) {
+        return invalid(e.to_string());
+    }
+    // Posting to a channel or a response_url speaks as the bot, so callers without the admin
+    // token cannot choose where it speaks
+    let delivery = &submission.delivery;
+    if !admin && (delivery.channel.is_some() || delivery.response_url.is_some()) {
+        return (401, serde_json::json!({"error": "channel and response_url deliveries need the admin token"}).to_string());
+    }
+    if let Some(url) = &submission.delivery.response_url {
+        match canonicalize_url(url) {
+            Ok(canonical) if canonical.starts_with(SLACK_RESPONSE_URL_PREFIX) => submission.delivery.response_url = Some(canonical),
+            Ok(_) => return invalid(format!("response_url must start with {SLACK_RESPONSE_URL_PREFIX}")),
+            Err(e) => return invalid(format!("response_url: {e}")),
+        }
+    }
+    if let Some(url) = &submission.delivery.callback_url {
+        match canonicalize_url(url) {
+            Ok(canonical) => submission.delivery.callback_url = Some(canonical),
+            Err(e) => return invalid(format!("callback_url: {e}")),
+        }
+    }
+    // Callbacks are always signed, so they need a shared secret
+    if submission.delivery.callback_url.is_some() && get_env_var("JOB_CALLBACK_SECRET").is_none() {
+        return invalid("callback_url requires JOB_CALLBACK_SECRET".into());
+    }
+    match enqueue_job(submission.request, submission.delivery) {
+        Ok(id) => (
+            202,
+            serde_json::json!({"id": id, "state": JobState::Queued, "status_url": format!("/api/v1/jobs/{id}")}).to_string(),
+        ),
+        Err(e) => (e.http_status(), serde_json::json!({"error": format!("job store unavailable: {e}")}).to_string()),
+    }
+}
+
+/// State, timing and (once finished) the result of a job.
+pub(crate) fn handle_job_status(id: &str) -> (u16, String) {
+    let job = match load_job(id) {
+        Ok(Some(job)) => job,
+        Ok(None) => return (404, serde_json::json!({"error": "job not found"}).to_string()),
+        Err(e) => return (503, serde_json::json!({"error": format!("job store unavailable: {e}")}).to_string()),
+    };
+    let mut body = serde_json::json!({
+        "id": job.id,
+        "type": serde_json::to_value(&job.request).ok().and_then(|v| v.get("type").cloned()),
+        "state": job.state,
+        "created_at": job.created_at,
+        "started_at": job.started_at,
+        "finished_at": job.finished_at,
+    });
+    if let (Some(start), Some(end)) = (job.started_at, job.finished_at) {
+        body["run_secs"] = serde_json::json!(end.saturating_sub(start));
+    }
+    match job.state {
+        JobState::Done => body["result"] = serde_json::json!(job.result),
+        JobState::Failed => body["error"] = serde_json::json!(job.error),
+        JobState::Queued | JobState::Running => {}
+    }
+    (200, body.to_string())
+}
+
+fn execute_job_request(request: &JobRequest) -> Result<String, AgentError> {
+    match request {
+        JobRequest::Query { query, context } => {
+            let user_text = match context {
+                Some(c) => format!("{query}\n\nContext:\n{c}"),
+                None => query.clone(),
+            };
+            if user_text.chars().count() > condense_threshold() {
+                answer_oversized_input(&user_text)
+            } else {
+                call_openai_with_system("You are a helpful assistant. Answer thoroughly but concisely.", &user_text, 800)
+            }
+        }
+        JobRequest::Summarize { url } => fetch_and_process(url),
+        JobRequest::Research { query, urls } => multi_source_response(query, urls),
+        JobRequest::SlashCommand { text, user_id, channel_id } => Ok(slack_command_reply(text, user_id, channel_id)),
+        JobRequest::Export { user_id, channel_id } => Ok(slack_export_reply(user_id, channel_id)),
+        JobRequest::Imagine { text, user_id, channel_id } => Ok(slack_imagine_reply(text, user_id, channel_id)),
+        JobRequest::Broadcast { text, user_id, channel_id } => Ok(slack_broadcast_reply(text, user_id, channel_id)),
+    }
+}
+
+/// Runs a queued job (or one interrupted mid-run) and queues its deliveries. Finished jobs are
+/// left alone, so a retried task never repeats the work.
+fn run_async_job(id: &str) -> Result<(), AgentError> {
+    let mut job = load_job(id)?.ok_or_else(|| AgentError::Validation(format!("job {id} not found")))?;
+    if matches!(job.state, JobState::Done | JobState::Failed) {
+        return Ok(());
+    }
+    set_region(job.region.clone());
+    set_locale(job.locale.clone());
+    set_workspace(&job.team_id);
+    job.state = JobState::Running;
+    job.started_at = Some(now_secs());
+    save_job(&job)?;
+
+    match execute_job_request(&job.request) {
+        Ok(result) => {
+            job.state = JobState::Done;
+            job.result = Some(result);
+        }
+        Err(e) => {
+            job.state = JobState::Failed;
+            job.error = Some(e.to_string());
+        }
+    }
+    job.finished_at = Some(now_secs());
+    save_job(&job)?;
+    deliver_job(&job);
+    Ok(())
+}
+
+fn deliver_job(job: &JobRecord) {
+    let text = match (&job.result, &job.error) {
+        (Some(result), _) => result.clone(),
+        (None, Some(e)) => with_ref(&message(Msg::RequestFailed, &[("error", e)]), &job.request_id),
+        (None, None) => return,
+    };
+    if let Some(response_url) = &job.delivery.response_url {
+        let mut payload = slash_response_payload(&text);
+        // Answers to users who prefer private replies stay private when they arrive later too
+        match &job.request {
+            JobRequest::SlashCommand { text, user_id, .. } => {
+                payload["response_type"] = if answer_visibility(text, user_id).1 { "ephemeral" } else { "in_channel" }.into();
+                // Queued answers are laid out like the ones answered right after the ack
+                if let Some(blocks) = job.result.as_ref().and_then(|_| answer_blocks(payload["text"].as_str().unwrap_or_default(), None, None)) {
+                    payload["blocks"] = blocks;
+                }
+            }
+            JobRequest::Export { .. } => payload["response_type"] = "ephemeral".into(),
+            _ => {}
+        }
+        defer_task(DeferredTask::SlackDelivery { response_url: response_url.clone(), payload: payload.to_string() });
+    }
+    if let Some(channel) = &job.delivery.channel {
+        defer_task(DeferredTask::SlackPost { channel: channel.clone(), text });
+    }
+    if let Some(url) = &job.delivery.callback_url {
+        let payload = serde_json::json!({"id": job.id, "state": job.state, "result": job.result, "error": job.error});
+        defer_task(DeferredTask::Webhook { url: url.clone(), payload: payload.to_string() });
+    }
+}
+
+/* ---- Signed job callbacks (HMAC-SHA256) ---- */
+// Receivers verify `X-Signature-256: sha256=<hex>` against HMAC(JOB_CALLBACK_SECRET, "{timestamp}.{body}")
+// and reject stale `X-Signature-Timestamp` values to prevent replays.
+fn sign_callback(secret: &str, timestamp: u64, body: &str) -> String {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", digest.iter().map(|b| format!("{b:02x}")).collect::<String>())
+}
+
+fn post_signed_callback(url: &str, payload: &str) -> Result<(), AgentError> {
+    let secret = get_env_var("JOB_CALLBACK_SECRET").ok_or(AgentError::Config("JOB_CALLBACK_SECRET not set".into()))?;
+    let timestamp = now_secs();
+    let headers = [
+        ("x-signature-timestamp", timestamp.to_string()),
+        ("x-signature-256", sign_callback(&secret, timestamp, payload)),
+    ];
+    match http_post_with_headers(url, payload, "application/json", &headers)? {
+        200..=299 => Ok(()),
+        code => Err(AgentError::Http(format!("callback returned HTTP {code}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use crate::clock::MockClock;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+
+    #[test]
+    fn retry_delay_doubles_up_to_an_hour() {
+        let delays: Vec<u64> = (1..=9).map(retry_delay).collect();
+        assert_eq!(delays, [30, 60, 120, 240, 480, 960, 1920, 3600, 3600]);
+        assert_eq!(retry_delay(u32::MAX), RETRY_MAX_SECS);
+    }
+
+    fn stored_task(id: &str, record: &InflightRecord) {
+        save_record(&format!("{INFLIGHT_PREFIX}{id}"), record).unwrap();
+    }
+
+    fn delivery(response_url: &str) -> DeferredTask {
+        DeferredTask::SlackDelivery { response_url: response_url.into(), payload: "{}".into() }
+    }
+
+    #[test]
+    fn drains_leave_leased_tasks_alone_even_when_forced() {
+        MockStore::install();
+        let clock = MockClock::install(1_700_000_000);
+        let mock = MockHttpClient::install();
+        let leased = |claimed_until| InflightRecord {
+            task: delivery("https://hooks.slack.com/x"),
+            attempts: 0,
+            next_attempt_at: 0,
+            last_error: None,
+            claimed_until,
+            claim: "other-instance".into(),
+        };
+        stored_task("t1", &leased(1_700_000_000 + LEASE_SECS));
+
+        let report = drain_inflight(true);
+        assert_eq!((report.flushed, report.waiting), (0, 1));
+        assert!(mock.requests.borrow().is_empty());
+
+        clock.advance(LEASE_SECS + 1);
+        mock.respond(200, "text/plain", "ok");
+        assert_eq!(drain_inflight(true).flushed, 1);
+        assert_eq!(mock.requests.borrow().len(), 1);
+        assert_eq!(store_get("inflight:t1"), Ok(None));
+    }
+
+    #[test]
+    fn a_task_in_flight_is_delivered_once_however_drains_interleave() {
+        MockStore::install();
+        let clock = MockClock::install(1_700_000_000);
+        let mock = MockHttpClient::install();
+        defer_task(delivery("https://hooks.slack.com/x"));
+
+        // Another instance drains while this one has yet to run the task after its response
+        let local = DEFERRED.with(|q| std::mem::take(&mut *q.borrow_mut()));
+        assert_eq!(drain_inflight(true).waiting, 1);
+        assert!(mock.requests.borrow().is_empty());
+
+        // The attempt fails; a forced drain in the same pass does not retry it at once
+        DEFERRED.with(|q| *q.borrow_mut() = local);
+        mock.respond(500, "text/plain", "boom");
+        let report = drain_inflight(true);
+        assert_eq!((report.flushed, report.failed), (0, 1));
+        assert_eq!(mock.requests.borrow().len(), 1);
+        let key = store_list_keys(INFLIGHT_PREFIX).unwrap().remove(0);
+        let record: InflightRecord = serde_json::from_str(&store_get(&key).unwrap().unwrap()).unwrap();
+        assert_eq!((record.attempts, record.next_attempt_at, record.claimed_until), (1, 1_700_000_000 + RETRY_BASE_SECS, 0));
+
+        assert_eq!(drain_inflight(false).waiting, 1);
+        clock.advance(RETRY_BASE_SECS);
+        mock.respond(200, "text/plain", "ok");
+        assert_eq!(drain_inflight(false).flushed, 1);
+        assert_eq!(drain_inflight(true).flushed, 0);
+        assert_eq!(mock.requests.borrow().len(), 2);
+        assert_eq!(store_get(&key), Ok(None));
+    }
+
+    #[test]
+    fn signed_callback_uses_clock_timestamp() {
+        env::set_var("JOB_CALLBACK_SECRET", "cb-secret");
+        let clock = MockClock::install(1_760_000_000);
+        let mock = MockHttpClient::install();
+        mock.respond(204, "text/plain", "");
+        mock.respond(500, "text/plain", "boom");
+
+        let payload = r#"{"id":"abc","state":"done"}"#;
+        post_signed_callback("https://hooks.example.com/done", payload).unwrap();
+        clock.advance(90);
+        let err = post_signed_callback("https://hooks.example.com/done", payload).unwrap_err();
+        assert_eq!(err, AgentError::Http("callback returned HTTP 500".into()));
+
+        let requests = mock.requests.borrow();
+        let header = |i: usize, name: &str| {
+            requests[i].headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()).unwrap()
+        };
+        assert_eq!(header(0, "x-signature-timestamp"), "1760000000");
+        assert_eq!(header(1, "x-signature-timestamp"), "1760000090");
+        assert_eq!(header(0, "x-signature-256"), sign_callback("cb-secret", 1_760_000_000, payload));
+        assert_ne!(header(0, "x-signature-256"), header(1, "x-signature-256"));
+    }
+
+    #[test]
+    fn job_submissions_with_ambiguous_urls_are_rejected_before_queueing() {
+        let (code, body) = handle_job_submit(r#"{"type":"research","query":"q","urls":["https://ok.example/","http://0x7f.1/"]}"#, false);
+        assert_eq!(code, 400);
+        assert!(body.contains("numeric hosts"), "{body}");
+        let (code, _) = handle_job_submit(r#"{"type":"summarize","url":"https://trusted.example@evil.example/"}"#, false);
+        assert_eq!(code, 400);
+        std::env::set_var("JOB_CALLBACK_SECRET", "cb-secret");
+        let (code, body) = handle_job_submit(r#"{"type":"query","query":"q","callback_url":"http://2130706433/hook"}"#, false);
+        assert_eq!(code, 400);
+        assert!(body.contains("callback_url"), "{body}");
+    }
+
+    #[test]
+    fn job_submissions_cannot_act_as_a_slack_user() {
+        for job in [
+            r#"{"type":"imagine","text":"a cat","user_id":"U1","channel_id":"C1"}"#,
+            r#"{"type":"slash_command","text":"hi","user_id":"U1"}"#,
+            r#"{"type":"slash_command","text":"hi","channel_id":"C1"}"#,
+            r#"{"type":"broadcast","text":"hi","user_id":"U1","channel_id":"C1"}"#,
+            r#"{"type":"export","user_id":"U1","channel_id":"C1"}"#,
+        ] {
+            let (code, body) = handle_job_submit(job, false);
+            assert_eq!(code, 400, "{job}: {body}");
+        }
+    }
+
+    #[test]
+    fn slack_deliveries_need_the_admin_token_and_a_slack_response_url() {
+        MockStore::install();
+        for job in [
+            r##"{"type":"query","query":"q","channel":"#general"}"##,
+            r#"{"type":"query","query":"q","response_url":"https://hooks.slack.com/commands/T1/2/x"}"#,
+        ] {
+            let (code, body) = handle_job_submit(job, false);
+            assert_eq!(code, 401, "{job}: {body}");
+        }
+        for url in ["http://169.254.169.254/latest/meta-data", "https://hooks.slack.com.evil.example/x", "https://hooks.slack.com@evil.example/x"] {
+            let (code, body) = handle_job_submit(&format!(r#"{{"type":"query","query":"q","response_url":"{url}"}}"#), true);
+            assert_eq!(code, 400, "{url}: {body}");
+        }
+        let (code, body) = handle_job_submit(r#"{"type":"query","query":"q","response_url":"https://hooks.slack.com/commands/T1/2/x"}"#, true);
+        assert_eq!(code, 202, "{body}");
+    }
+}
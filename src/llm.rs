@@ -0,0 +1,883 @@
+//! Chat completions (plain, JSON-mode, system-prompted, streaming) via the configured providers, and chunked condensing.
+
+use serde::Deserialize;
+
+use crate::config::get_env_var;
+use crate::documents::truncate_chars;
+use crate::error::AgentError;
+use crate::form::FormParams;
+use crate::huggingface::post_hf_chat;
+use crate::plugins::{MAX_TOOL_ROUNDS, invoke_tool, openai_tools, tool_registry};
+use crate::providers::{BEDROCK_SERVICE, Provider, chat_endpoint, default_chat_model, openai_only_model, parse_model, provider_payload};
+use crate::usage::record_completion_usage;
+use crate::httpclient::{FormPart, base64_encode, http_post_aws_json, http_post_aws_json_stream, http_post_json, http_post_json_stream, http_post_json_with_headers, http_post_multipart};
+
+/* ---- Chunked summarization of oversized input ---- */
+const DEFAULT_CONDENSE_THRESHOLD: usize = 8_000;
+const CONDENSE_CHUNK_CHARS: usize = 6_000;
+
+pub(crate) fn condense_threshold() -> usize {
+    get_env_var("INPUT_CONDENSE_THRESHOLD")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_CONDENSE_THRESHOLD)
+}
+
+/// A short first line followed by a long paste is treated as the question about the paste;
+/// otherwise the condensed text itself is the answer.
+pub(crate) fn answer_oversized_input(text: &str) -> Result<String, AgentError> {
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let question = first.trim();
+    let (question, material) = if !rest.trim().is_empty() && question.chars().count() <= 300 {
+        (Some(question), rest)
+    } else {
+        (None, text)
+    };
+
+    let condensed = condense_text(material, 0)?;
+    match question {
+        Some(q) => call_openai_with_system(
+            &format!("Answer the user's request using this condensed version of the material they pasted:\n\n{condensed}"),
+            q,
+            400,
+        ),
+        None => Ok(condensed),
+    }
+}
+
+/// Map step over chunks; re-condenses the joined summaries if they are still too long.
+pub(crate) fn condense_text(text: &str, depth: u32) -> Result<String, AgentError> {
+    let chunks = chunk_text(text, CONDENSE_CHUNK_CHARS);
+    let total = chunks.len();
+    let mut summaries = Vec::with_capacity(total);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let summary = call_openai_with_system(
+            &format!("You are condensing part {} of {} of a long text. Summarize it, preserving names, numbers, decisions and open questions.", i + 1, total),
+            chunk,
+            300,
+        )?;
+        summaries.push(summary);
+    }
+    let joined = summaries.join("\n\n");
+    if joined.chars().count() > CONDENSE_CHUNK_CHARS && depth < 2 {
+        condense_text(&joined, depth + 1)
+    } else {
+        Ok(joined)
+    }
+}
+
+/// Splits on paragraph, then line, then word boundaries so chunks stay under `max_chars`.
+fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let len = |s: &str| s.chars().count();
+    let mut pieces: Vec<&str> = Vec::new();
+    for para in text.split_inclusive("\n\n") {
+        if len(para) <= max_chars {
+            pieces.push(para);
+            continue;
+        }
+        for line in para.split_inclusive('\n') {
+            if len(line) <= max_chars {
+                pieces.push(line);
+            } else {
+                pieces.extend(line.split_inclusive(' '));
+            }
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for piece in pieces {
+        if !current.is_empty() && len(&current) + len(piece) > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(piece);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/* ---- Stop sequences, penalties, seed and logit bias (LLM_STOP, LLM_*_PENALTY, LLM_SEED, LLM_LOGIT_BIAS) ---- */
+// Defaults come from the environment and apply to every chat completion; API requests may
+// override them field by field. OpenAI accepts up to 4 stop sequences and penalties from -2 to 2.
+// A fixed seed makes supported models sample (mostly) deterministically; the response's
+// `system_fingerprint` identifies the backend configuration, so a changed fingerprint explains
+// a changed output for the same seed.
+const MAX_STOP_SEQUENCES: usize = 4;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SamplingOptions {
+    pub(crate) stop: Option<Vec<String>>,
+    pub(crate) frequency_penalty: Option<f64>,
+    pub(crate) presence_penalty: Option<f64>,
+    pub(crate) seed: Option<i64>,
+    /// Token ID to bias (-100 bans it); configuration only, token IDs are model-specific
+    pub(crate) logit_bias: Option<serde_json::Map<String, serde_json::Value>>,
+    /// Model string chosen by the request, e.g. `mistral/mistral-large-latest`; LLM_MODEL otherwise
+    pub(crate) model: Option<String>,
+    /// Sampling temperature from 0 to 2, chosen in the prompt modal; each call's own default otherwise
+    pub(crate) temperature: Option<f64>,
+}
+
+impl SamplingOptions {
+    /// `LLM_STOP` is a JSON array of strings or a single plain string.
+    fn from_env() -> Result<Self, AgentError> {
+        let stop = get_env_var("LLM_STOP").map(|raw| serde_json::from_str::<Vec<String>>(&raw).unwrap_or_else(|_| vec![raw]));
+        let penalty = |name: &str| {
+            get_env_var(name)
+                .map(|v| v.trim().parse::<f64>().map_err(|_| AgentError::Config(format!("{name}: '{v}' is not a number"))))
+                .transpose()
+        };
+        let seed = get_env_var("LLM_SEED")
+            .map(|v| v.trim().parse::<i64>().map_err(|_| AgentError::Config(format!("LLM_SEED: '{v}' is not an integer"))))
+            .transpose()?;
+        let logit_bias = get_env_var("LLM_LOGIT_BIAS")
+            .map(|raw| serde_json::from_str(&raw).map_err(|e| AgentError::Config(format!("LLM_LOGIT_BIAS: {e}"))))
+            .transpose()?;
+        let options = SamplingOptions {
+            stop,
+            frequency_penalty: penalty("LLM_FREQUENCY_PENALTY")?,
+            presence_penalty: penalty("LLM_PRESENCE_PENALTY")?,
+            seed,
+            logit_bias,
+            model: None,
+            temperature: None,
+        };
+        options.check().map_err(AgentError::Config)?;
+        Ok(options)
+    }
+
+    /// Overrides from a request: `stop` as a string or array, penalties as numbers, `model` as a model string.
+    pub(crate) fn from_json(json: &serde_json::Value) -> Result<Self, AgentError> {
+        let stop = match &json["stop"] {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(vec![s.clone()]),
+            serde_json::Value::Array(items) => Some(
+                items
+                    .iter()
+                    .map(|v| v.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| AgentError::Validation("stop: expected strings".into()))?,
+            ),
+            _ => return Err(AgentError::Validation("stop: expected a string or an array of strings".into())),
+        };
+        let penalty = |name: &str| match &json[name] {
+            serde_json::Value::Null => Ok(None),
+            v => v.as_f64().map(Some).ok_or_else(|| AgentError::Validation(format!("{name}: expected a number"))),
+        };
+        let seed = match &json["seed"] {
+            serde_json::Value::Null => None,
+            v => Some(v.as_i64().ok_or_else(|| AgentError::Validation("seed: expected an integer".into()))?),
+        };
+        let model = match &json["model"] {
+            serde_json::Value::Null => None,
+            v => Some(v.as_str().ok_or_else(|| AgentError::Validation("model: expected a string".into()))?.to_string()),
+        };
+        let options = SamplingOptions {
+            stop,
+            frequency_penalty: penalty("frequency_penalty")?,
+            presence_penalty: penalty("presence_penalty")?,
+            seed,
+            logit_bias: None,
+            model,
+            temperature: None,
+        };
+        options.check().map_err(AgentError::Validation)?;
+        Ok(options)
+    }
+
+    /// Overrides from query parameters: repeated `stop`, `frequency_penalty`, `presence_penalty`, `seed`, `model`.
+    pub(crate) fn from_params(params: &FormParams) -> Result<Self, AgentError> {
+        let stop: Vec<String> = params.get_all("stop").into_iter().map(str::to_string).collect();
+        let penalty = |name: &str| {
+            params
+                .get(name)
+                .map(|v| v.trim().parse::<f64>().map_err(|_| AgentError::Validation(format!("{name}: expected a number"))))
+                .transpose()
+        };
+        let seed = params
+            .get("seed")
+            .map(|v| v.trim().parse::<i64>().map_err(|_| AgentError::Validation("seed: expected an integer".into())))
+            .transpose()?;
+        let options = SamplingOptions {
+            stop: if stop.is_empty() { None } else { Some(stop) },
+            frequency_penalty: penalty("frequency_penalty")?,
+            presence_penalty: penalty("presence_penalty")?,
+            seed,
+            logit_bias: None,
+            model: params.get("model").cloned(),
+            temperature: None,
+        };
+        options.check().map_err(AgentError::Validation)?;
+        Ok(options)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if let Some(stop) = &self.stop {
+            if stop.len() > MAX_STOP_SEQUENCES {
+                return Err(format!("stop: at most {MAX_STOP_SEQUENCES} sequences"));
+            }
+            if stop.iter().any(String::is_empty) {
+                return Err("stop: sequences must not be empty".into());
+            }
+        }
+        if let Some(bias) = &self.logit_bias {
+            if bias.iter().any(|(token, v)| token.parse::<u64>().is_err() || !v.as_f64().is_some_and(|v| (-100.0..=100.0).contains(&v))) {
+                return Err("logit_bias: expected token IDs mapped to numbers between -100 and 100".into());
+            }
+        }
+        if self.model.as_ref().is_some_and(|m| parse_model(m).1.trim().is_empty()) {
+            return Err("model: must name a model".into());
+        }
+        for (name, value) in [("frequency_penalty", self.frequency_penalty), ("presence_penalty", self.presence_penalty)] {
+            if value.is_some_and(|v| !(-2.0..=2.0).contains(&v)) {
+                return Err(format!("{name}: must be between -2.0 and 2.0"));
+            }
+        }
+        if self.temperature.is_some_and(|t| !(0.0..=2.0).contains(&t)) {
+            return Err("temperature: must be between 0 and 2".into());
+        }
+        Ok(())
+    }
+
+    /// Fields set here win; the rest come from `fallback`.
+    pub(crate) fn or(self, fallback: SamplingOptions) -> SamplingOptions {
+        SamplingOptions {
+            stop: self.stop.or(fallback.stop),
+            frequency_penalty: self.frequency_penalty.or(fallback.frequency_penalty),
+            presence_penalty: self.presence_penalty.or(fallback.presence_penalty),
+            seed: self.seed.or(fallback.seed),
+            logit_bias: self.logit_bias.or(fallback.logit_bias),
+            model: self.model.or(fallback.model),
+            temperature: self.temperature.or(fallback.temperature),
+        }
+    }
+
+    /// Adds these overrides on top of the environment defaults to a chat completion payload.
+    fn apply(&self, payload: &mut serde_json::Value) -> Result<(), AgentError> {
+        let options = self.clone().or(SamplingOptions::from_env()?);
+        if let Some(stop) = options.stop {
+            payload["stop"] = serde_json::json!(stop);
+        }
+        if let Some(p) = options.frequency_penalty {
+            payload["frequency_penalty"] = serde_json::json!(p);
+        }
+        if let Some(p) = options.presence_penalty {
+            payload["presence_penalty"] = serde_json::json!(p);
+        }
+        if let Some(seed) = options.seed {
+            payload["seed"] = serde_json::json!(seed);
+        }
+        if let Some(bias) = options.logit_bias {
+            payload["logit_bias"] = serde_json::Value::Object(bias);
+        }
+        if let Some(t) = options.temperature {
+            payload["temperature"] = serde_json::json!(t);
+        }
+        Ok(())
+    }
+}
+
+/* ---- OpenAI API call ---- */
+/// The chat model answers come from (LLM_MODEL, default gpt-4o-mini or the provider's default).
+pub(crate) fn chat_model() -> String {
+    get_env_var("LLM_MODEL").unwrap_or_else(|| default_chat_model().to_string())
+}
+
+/// Posts a chat completion payload to its model's provider; Hugging Face adds cold-start retries.
+fn post_chat_completion(payload: &serde_json::Value) -> Result<String, AgentError> {
+    let endpoint = chat_endpoint(payload["model"].as_str().unwrap_or_default())?;
+    let payload = provider_payload(payload);
+    match endpoint.provider {
+        Provider::HuggingFace => post_hf_chat(&endpoint, &payload),
+        Provider::Bedrock => http_post_aws_json(&endpoint.url, &payload.to_string(), endpoint.aws_region.as_deref().unwrap_or_default(), BEDROCK_SERVICE),
+        _ => http_post_json_with_headers(&endpoint.url, &payload.to_string(), &endpoint.api_key, &endpoint.headers),
+    }
+}
+
+pub(crate) fn call_openai(user_text: &str) -> Result<String, AgentError> {
+    call_openai_completion(user_text, &SamplingOptions::default()).map(|c| c.content)
+}
+
+/// The reply together with the backend's `system_fingerprint`, when the provider sent one.
+pub(crate) struct Completion {
+    pub(crate) content: String,
+    pub(crate) system_fingerprint: Option<String>,
+}
+
+pub(crate) fn call_openai_completion(user_text: &str, options: &SamplingOptions) -> Result<Completion, AgentError> {
+    if mock_enabled() {
+        let content = mock_text_reply(get_env_var("LLM_MOCK_FIXTURES").as_deref(), user_text)?;
+        return Ok(Completion { content, system_fingerprint: None });
+    }
+
+    let model = options.model.clone().unwrap_or_else(chat_model);
+    // Fail on missing credentials before anything else
+    chat_endpoint(&model)?;
+
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": [{"role": "user", "content": user_text}],
+        "max_tokens": 150,
+        "temperature": 0.7,
+    });
+    options.apply(&mut payload)?;
+
+    let response_body = post_chat_completion_with_tools(&mut payload)?;
+    
+    parse_openai_completion(&response_body)
+}
+
+/// Posts `payload` with the plugin tools, answering the model's tool calls until it replies with text.
+fn post_chat_completion_with_tools(payload: &mut serde_json::Value) -> Result<String, AgentError> {
+    let registry = tool_registry();
+    if registry.is_empty() {
+        return post_chat_completion(payload);
+    }
+    payload["tools"] = openai_tools(&registry);
+    let mut response_body = post_chat_completion(payload)?;
+    for round in 1..=MAX_TOOL_ROUNDS {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&response_body) else { break };
+        let message = &json["choices"][0]["message"];
+        let Some(calls) = message["tool_calls"].as_array().filter(|calls| !calls.is_empty()) else { break };
+        record_completion_usage(&json);
+        let mut messages = vec![message.clone()];
+        for call in calls {
+            let (name, arguments) = (call["function"]["name"].as_str().unwrap_or_default(), call["function"]["arguments"].as_str().unwrap_or_default());
+            messages.push(serde_json::json!({"role": "tool", "tool_call_id": call["id"], "content": invoke_tool(&registry, name, arguments)}));
+        }
+        if let Some(history) = payload["messages"].as_array_mut() {
+            history.extend(messages);
+        }
+        // The last round has to end in an answer
+        if round == MAX_TOOL_ROUNDS {
+            payload["tool_choice"] = serde_json::json!("none");
+        }
+        response_body = post_chat_completion(payload)?;
+    }
+    Ok(response_body)
+}
+
+/* ---- Extract the assistant message from a chat completion response ---- */
+fn parse_openai_content(response_body: &str) -> Result<String, AgentError> {
+    parse_openai_completion(response_body).map(|c| c.content)
+}
+
+fn parse_openai_completion(response_body: &str) -> Result<Completion, AgentError> {
+    match serde_json::from_str::<serde_json::Value>(response_body) {
+        Ok(json) => {
+            if let Some(content) = json["choices"][0]["message"]["content"].as_str() {
+                record_completion_usage(&json);
+                Ok(Completion {
+                    content: content.trim().to_string(),
+                    system_fingerprint: json["system_fingerprint"].as_str().map(str::to_string),
+                })
+            } else if let Some(error) = json["error"]["message"].as_str() {
+                Err(AgentError::Provider { status: 200, message: format!("OpenAI error: {}", error), retry_after: None })
+            } else {
+                Err(AgentError::Provider { status: 200, message: "Unexpected OpenAI response format".into(), retry_after: None })
+            }
+        }
+        Err(e) => Err(AgentError::Provider { status: 200, message: format!("Failed to parse OpenAI response: {}", e), retry_after: None })
+    }
+}
+
+/* ---- OpenAI call in structured-output (JSON) mode ---- */
+pub(crate) fn call_openai_structured(
+    user_text: &str,
+    context: Option<&str>,
+    options: &SamplingOptions,
+) -> Result<serde_json::Value, AgentError> {
+    let mut system = String::from(
+        "Respond only with a JSON object. When the answer is a list, comparison or any other \
+         tabular data, use {\"rows\": [{\"<column>\": <value>, ...}, ...]} with the same keys in every row.",
+    );
+    if let Some(ctx) = context {
+        system.push_str(&format!("\n\nContext:\n{ctx}"));
+    }
+    json_completion(&system, user_text, options)
+}
+
+/* ---- OpenAI call constrained to a JSON object reply ---- */
+pub(crate) fn call_openai_json(system: &str, user_text: &str) -> Result<serde_json::Value, AgentError> {
+    json_completion(system, user_text, &SamplingOptions::default())
+}
+
+fn json_completion(system: &str, user_text: &str, options: &SamplingOptions) -> Result<serde_json::Value, AgentError> {
+    if mock_enabled() {
+        return mock_json_reply(get_env_var("LLM_MOCK_FIXTURES").as_deref(), system, user_text);
+    }
+
+    let model = options.model.clone().unwrap_or_else(chat_model);
+    chat_endpoint(&model)?;
+
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": [
+            {"role": "system", "content": system},
+            {"role": "user", "content": user_text},
+        ],
+        "response_format": {"type": "json_object"},
+        "temperature": 0.2,
+    });
+    options.apply(&mut payload)?;
+
+    let response_body = post_chat_completion(&payload)?;
+    let content = parse_openai_content(&response_body)?;
+    serde_json::from_str(&content)
+        .map_err(|e| AgentError::Provider { status: 200, message: format!("model returned invalid JSON: {e}"), retry_after: None })
+}
+
+/* ---- OpenAI call with a system prompt ---- */
+pub(crate) fn call_openai_with_system(system: &str, user_text: &str, max_tokens: u32) -> Result<String, AgentError> {
+    call_openai_conversation(Some(system), &[], user_text, max_tokens)
+}
+
+/// Like `call_openai_with_system`, with earlier `(question, answer)` turns of the conversation
+/// sent before `user_text`, oldest first.
+pub(crate) fn call_openai_conversation(system: Option<&str>, turns: &[(&str, &str)], user_text: &str, max_tokens: u32) -> Result<String, AgentError> {
+    if mock_enabled() {
+        return mock_text_reply(get_env_var("LLM_MOCK_FIXTURES").as_deref(), user_text);
+    }
+
+    let model = chat_model();
+    chat_endpoint(&model)?;
+
+    let mut messages: Vec<serde_json::Value> = system.map(|s| serde_json::json!({"role": "system", "content": s})).into_iter().collect();
+    for (question, answer) in turns {
+        messages.push(serde_json::json!({"role": "user", "content": question}));
+        messages.push(serde_json::json!({"role": "assistant", "content": answer}));
+    }
+    messages.push(serde_json::json!({"role": "user", "content": user_text}));
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "max_tokens": max_tokens,
+        "temperature": 0.3,
+    });
+    SamplingOptions::default().apply(&mut payload)?;
+
+    let response_body = post_chat_completion(&payload)?;
+    parse_openai_content(&response_body)
+}
+
+/* ---- Multimodal chat completion (images) ---- */
+/// Image bytes, sent inline as a `data:` URL since the images are behind authentication.
+pub(crate) struct ImageInput {
+    pub(crate) media_type: String,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl ImageInput {
+    fn url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, base64_encode(&self.bytes))
+    }
+}
+
+/// Asks a vision-capable model (VISION_MODEL, else LLM_MODEL) about one or more images.
+pub(crate) fn call_openai_vision(question: &str, images: &[ImageInput], max_tokens: u32) -> Result<String, AgentError> {
+    if mock_enabled() {
+        return mock_text_reply(get_env_var("LLM_MOCK_FIXTURES").as_deref(), question);
+    }
+
+    let model = get_env_var("VISION_MODEL").unwrap_or_else(chat_model);
+    chat_endpoint(&model)?;
+
+    let mut content = vec![serde_json::json!({"type": "text", "text": question})];
+    content.extend(images.iter().map(|image| serde_json::json!({"type": "image_url", "image_url": {"url": image.url()}})));
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": [{"role": "user", "content": content}],
+        "max_tokens": max_tokens,
+        "temperature": 0.3,
+    });
+    SamplingOptions::default().apply(&mut payload)?;
+
+    let response_body = post_chat_completion(&payload)?;
+    parse_openai_content(&response_body)
+}
+
+/* ---- Audio transcription (multipart upload) ---- */
+/// Transcribes an audio or video clip with TRANSCRIPTION_MODEL (default `whisper-1`).
+pub(crate) fn transcribe_audio(filename: &str, content_type: &str, bytes: &[u8]) -> Result<String, AgentError> {
+    if mock_enabled() {
+        return Ok(format!("Mock transcript of {filename} ({} bytes).", bytes.len()));
+    }
+
+    let api_key = get_env_var("OPENAI_API_KEY").unwrap_or_default();
+    let model = get_env_var("TRANSCRIPTION_MODEL").unwrap_or_else(|| "whisper-1".to_string());
+    let model = openai_only_model("TRANSCRIPTION_MODEL", &model)?;
+
+    if api_key.is_empty() {
+        return Err(AgentError::Config("OPENAI_API_KEY not set".into()));
+    }
+
+    let parts = [
+        FormPart::Text { name: "model", value: model },
+        FormPart::Text { name: "response_format", value: "json" },
+        FormPart::File { name: "file", filename, content_type, bytes },
+    ];
+    let response_body = http_post_multipart("https://api.openai.com/v1/audio/transcriptions", &parts, &api_key)?;
+    let json: serde_json::Value = serde_json::from_str(&response_body)
+        .map_err(|e| AgentError::Provider { status: 200, message: format!("Failed to parse transcription: {e}"), retry_after: None })?;
+    json["text"]
+        .as_str()
+        .map(|text| text.trim().to_string())
+        .ok_or_else(|| AgentError::Provider { status: 200, message: "transcription missing from response".into(), retry_after: None })
+}
+
+/* ---- Image generation (Images API) ---- */
+/// Generates one image with IMAGE_MODEL (default `dall-e-3`) and returns the URL it can be
+/// downloaded from. `size` and `style` are passed through as the API expects them.
+pub(crate) fn generate_image(prompt: &str, size: &str, style: &str) -> Result<String, AgentError> {
+    if mock_enabled() {
+        return Err(AgentError::Config("image generation is not available with LLM_PROVIDER=mock".into()));
+    }
+
+    let api_key = get_env_var("OPENAI_API_KEY").unwrap_or_default();
+    let model = get_env_var("IMAGE_MODEL").unwrap_or_else(|| "dall-e-3".to_string());
+    let model = openai_only_model("IMAGE_MODEL", &model)?;
+
+    if api_key.is_empty() {
+        return Err(AgentError::Config("OPENAI_API_KEY not set".into()));
+    }
+
+    let payload = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "n": 1,
+        "size": size,
+        "style": style,
+        "response_format": "url",
+    });
+    let response_body = http_post_json("https://api.openai.com/v1/images/generations", &payload.to_string(), &api_key)?;
+    let json: serde_json::Value = serde_json::from_str(&response_body)
+        .map_err(|e| AgentError::Provider { status: 200, message: format!("Failed to parse image response: {e}"), retry_after: None })?;
+    json["data"][0]["url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| AgentError::Provider { status: 200, message: "image URL missing from response".into(), retry_after: None })
+}
+
+/* ---- OpenAI streaming chat completion ---- */
+pub(crate) enum StreamEvent {
+    Token(String),
+    ToolCall { index: u64, id: Option<String>, name: Option<String>, arguments: String },
+}
+
+pub(crate) fn call_openai_stream(
+    user_text: &str,
+    context: Option<&str>,
+    options: &SamplingOptions,
+    on_event: &mut dyn FnMut(StreamEvent),
+) -> Result<Option<String>, AgentError> {
+    if mock_enabled() {
+        let reply = mock_text_reply(get_env_var("LLM_MOCK_FIXTURES").as_deref(), user_text)?;
+        for word in reply.split_inclusive(' ') {
+            on_event(StreamEvent::Token(word.to_string()));
+        }
+        return Ok(Some("stop".to_string()));
+    }
+
+    let model = options.model.clone().unwrap_or_else(chat_model);
+    let endpoint = chat_endpoint(&model)?;
+
+    let mut messages = Vec::new();
+    if let Some(ctx) = context {
+        messages.push(serde_json::json!({"role": "system", "content": format!("Context:\n{ctx}")}));
+    }
+    messages.push(serde_json::json!({"role": "user", "content": user_text}));
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": true,
+    });
+    options.apply(&mut payload)?;
+    let payload = provider_payload(&payload);
+
+    // Reassemble SSE lines across chunk boundaries
+    let mut pending: Vec<u8> = Vec::new();
+    let mut finish_reason: Option<String> = None;
+    let mut done = false;
+    let mut on_chunk = |chunk: &[u8]| {
+        pending.extend_from_slice(chunk);
+        while let Some(pos) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else { continue };
+            let data = data.trim();
+            if data == "[DONE]" {
+                done = true;
+                continue;
+            }
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(data) else { continue };
+            let choice = &json["choices"][0];
+            if let Some(text) = choice["delta"]["content"].as_str() {
+                if !text.is_empty() {
+                    on_event(StreamEvent::Token(text.to_string()));
+                }
+            }
+            if let Some(calls) = choice["delta"]["tool_calls"].as_array() {
+                for call in calls {
+                    on_event(StreamEvent::ToolCall {
+                        index: call["index"].as_u64().unwrap_or(0),
+                        id: call["id"].as_str().map(str::to_string),
+                        name: call["function"]["name"].as_str().map(str::to_string),
+                        arguments: call["function"]["arguments"].as_str().unwrap_or("").to_string(),
+                    });
+                }
+            }
+            if let Some(reason) = choice["finish_reason"].as_str() {
+                finish_reason = Some(reason.to_string());
+            }
+        }
+    };
+    match &endpoint.aws_region {
+        Some(region) => http_post_aws_json_stream(&endpoint.url, &payload.to_string(), region, BEDROCK_SERVICE, &mut on_chunk)?,
+        None => http_post_json_stream(&endpoint.url, &payload.to_string(), &endpoint.api_key, &endpoint.headers, &mut on_chunk)?,
+    }
+
+    if !done && finish_reason.is_none() {
+        return Err(AgentError::Provider { status: 200, message: "stream ended unexpectedly".into(), retry_after: None });
+    }
+    Ok(finish_reason)
+}
+
+/* ---- Deterministic mock provider (LLM_PROVIDER=mock) ---- */
+// Lets staging and demos run without an API key. Replies come from the first matching rule in the
+// LLM_MOCK_FIXTURES file, otherwise from fixed rules keyed on the prompt, so the same input always
+// produces the same output.
+pub(crate) fn mock_enabled() -> bool {
+    get_env_var("LLM_PROVIDER").is_some_and(|p| p.eq_ignore_ascii_case("mock"))
+}
+
+/// One fixture rule: `match` is a case-insensitive substring of the user text (`*` or empty matches
+/// anything); `reply` is a string, or a JSON value for JSON-mode calls.
+#[derive(Deserialize)]
+struct MockRule {
+    #[serde(default, rename = "match")]
+    pattern: String,
+    reply: serde_json::Value,
+}
+
+fn mock_fixture(fixtures: Option<&str>, user_text: &str) -> Result<Option<serde_json::Value>, AgentError> {
+    let Some(path) = fixtures else { return Ok(None) };
+    let raw = std::fs::read_to_string(path).map_err(|e| AgentError::Config(format!("LLM_MOCK_FIXTURES {path}: {e}")))?;
+    let rules: Vec<MockRule> =
+        serde_json::from_str(&raw).map_err(|e| AgentError::Config(format!("LLM_MOCK_FIXTURES {path}: {e}")))?;
+    let text = user_text.to_lowercase();
+    Ok(rules
+        .into_iter()
+        .find(|r| r.pattern.is_empty() || r.pattern == "*" || text.contains(&r.pattern.to_lowercase()))
+        .map(|r| r.reply))
+}
+
+fn mock_text_reply(fixtures: Option<&str>, user_text: &str) -> Result<String, AgentError> {
+    Ok(match mock_fixture(fixtures, user_text)? {
+        Some(serde_json::Value::String(reply)) => reply,
+        Some(reply) => reply.to_string(),
+        None => {
+            let text = user_text.split_whitespace().collect::<Vec<_>>().join(" ");
+            format!("Mock reply: {}", truncate_chars(&text, 200))
+        }
+    })
+}
+
+fn mock_json_reply(fixtures: Option<&str>, system: &str, user_text: &str) -> Result<serde_json::Value, AgentError> {
+    match mock_fixture(fixtures, user_text)? {
+        Some(serde_json::Value::String(reply)) => {
+            return serde_json::from_str(&reply)
+                .map_err(|e| AgentError::Provider { status: 200, message: format!("model returned invalid JSON: {e}"), retry_after: None });
+        }
+        Some(reply) => return Ok(reply),
+        None => {}
+    }
+
+    // Built-in rules recognise the JSON shapes our own prompts ask for
+    let text = user_text.trim();
+    if system.contains("\"translation\"") {
+        let target = system
+            .split_once("into ")
+            .and_then(|(_, rest)| rest.split('.').next())
+            .unwrap_or("the target language");
+        return Ok(serde_json::json!({"detected_language": "English", "translation": format!("[{target}] {text}")}));
+    }
+    if system.contains("\"category\"") {
+        let categories: Vec<&str> = system
+            .split_once("categories: ")
+            .and_then(|(_, rest)| rest.split_once(". "))
+            .map(|(list, _)| list.split(", ").collect())
+            .unwrap_or_default();
+        let lower = text.to_lowercase();
+        let category = categories
+            .iter()
+            .find(|c| lower.contains(&c.replace('_', " ")))
+            .or_else(|| categories.iter().find(|c| **c == "other"))
+            .or_else(|| categories.first())
+            .copied()
+            .unwrap_or("other");
+        return Ok(serde_json::json!({
+            "category": category,
+            "confidence": 0.5,
+            "sentiment": "neutral",
+            "rationale": "Mock classification (LLM_PROVIDER=mock).",
+        }));
+    }
+    let answer = mock_text_reply(None, text)?;
+    if system.contains("\"rows\"") {
+        return Ok(serde_json::json!({"rows": [{"question": text, "answer": answer}]}));
+    }
+    Ok(serde_json::json!({"answer": answer}))
+}
+
+#[cfg(test)]
+pub(crate) fn completion(content: &str) -> String {
+    serde_json::json!({"choices": [{"message": {"role": "assistant", "content": content}}]}).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::plugins::MockTools;
+
+    #[test]
+    fn call_openai_posts_chat_completion_and_returns_content() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("  Hello there  "));
+
+        assert_eq!(call_openai("Say hi").unwrap(), "Hello there");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests.len(), 1);
+        let req = &requests[0];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.url, "https://api.openai.com/v1/chat/completions");
+        assert!(req.headers.contains(&("authorization".to_string(), "Bearer sk-test".to_string())));
+        let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+        assert_eq!(body["messages"][0]["content"], "Say hi");
+    }
+
+    #[test]
+    fn call_openai_reports_http_errors_with_status() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(429, "application/json", r#"{"error":{"message":"rate limited"}}"#);
+
+        let err = call_openai("hello").unwrap_err();
+        assert_eq!(err, AgentError::Provider { status: 429, message: r#"{"error":{"message":"rate limited"}}"#.into(), retry_after: None });
+        assert_eq!(err.http_status(), 429);
+    }
+
+    #[test]
+    fn call_openai_surfaces_transport_failures() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.fail("connection refused");
+
+        assert_eq!(call_openai("hello").unwrap_err(), AgentError::Http("connection refused".into()));
+    }
+
+    #[test]
+    fn model_strings_select_the_provider_per_request() {
+        with_openai_env();
+        std::env::set_var("MISTRAL_API_KEY", "mistral-test");
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Bonjour"));
+
+        let options = SamplingOptions::from_json(&serde_json::json!({"model": "mistral/mistral-large-latest", "seed": 42})).unwrap();
+        assert_eq!(call_openai_completion("Say hi", &options).unwrap().content, "Bonjour");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://api.mistral.ai/v1/chat/completions");
+        assert!(requests[0].headers.contains(&("authorization".to_string(), "Bearer mistral-test".to_string())));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!((&body["model"], &body["random_seed"], &body["seed"]), (&serde_json::json!("mistral-large-latest"), &serde_json::json!(42), &serde_json::Value::Null));
+
+        assert!(SamplingOptions::from_json(&serde_json::json!({"model": "mistral/"})).is_err());
+    }
+
+    #[test]
+    fn bedrock_models_are_signed_for_the_aws_region() {
+        with_openai_env();
+        std::env::set_var("AWS_REGION", "us-west-2");
+        std::env::set_var("AWS_ACCESS_KEY_ID", "AKIDEXAMPLE");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY");
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Hello from Bedrock"));
+
+        let options = SamplingOptions::from_json(&serde_json::json!({"model": "bedrock/openai.gpt-oss-120b-1:0"})).unwrap();
+        assert_eq!(call_openai_completion("Say hi", &options).unwrap().content, "Hello from Bedrock");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://bedrock-runtime.us-west-2.amazonaws.com/openai/v1/chat/completions");
+        let authorization = &requests[0].headers.iter().find(|(name, _)| name == "authorization").unwrap().1;
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/19700101/us-west-2/bedrock/aws4_request"));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "openai.gpt-oss-120b-1:0");
+    }
+
+    #[test]
+    fn plugin_tool_calls_are_answered_before_the_reply() {
+        with_openai_env();
+        let tools = MockTools::install(&[("lookup_order", r#"{"type": "object", "properties": {"id": {"type": "string"}}}"#)]);
+        let mock = MockHttpClient::install();
+        let call = serde_json::json!({"choices": [{"message": {"role": "assistant", "content": null, "tool_calls": [
+            {"id": "call_1", "type": "function", "function": {"name": "lookup_order", "arguments": "{\"id\": \"A7\"}"}},
+        ]}}]});
+        mock.respond(200, "application/json", &call.to_string());
+        mock.respond(200, "application/json", &completion("Order A7 has shipped."));
+
+        assert_eq!(call_openai("Where is order A7?").unwrap(), "Order A7 has shipped.");
+        assert_eq!(*tools.calls.borrow(), [("lookup_order".to_string(), r#"{"id": "A7"}"#.to_string())]);
+        let requests = mock.requests.borrow();
+        let first: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(first["tools"][0]["function"]["name"], "lookup_order");
+        let second: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(second["messages"][2], serde_json::json!({"role": "tool", "tool_call_id": "call_1", "content": r#"lookup_order called with {"id": "A7"}"#}));
+    }
+
+    #[test]
+    fn mock_text_reply_is_deterministic_without_fixtures() {
+        let first = mock_text_reply(None, "  What is   the capital\nof France?  ").unwrap();
+        assert_eq!(first, "Mock reply: What is the capital of France?");
+        assert_eq!(mock_text_reply(None, "  What is   the capital\nof France?  ").unwrap(), first);
+    }
+
+    #[test]
+    fn mock_json_reply_recognises_translate_and_triage_prompts() {
+        let translate = "Detect the language of the user's text and translate it into es. Preserve meaning. \
+            Respond only with a JSON object: {\"detected_language\": \"...\", \"translation\": \"...\"}";
+        assert_eq!(
+            mock_json_reply(None, translate, "Where is the station?").unwrap(),
+            serde_json::json!({"detected_language": "English", "translation": "[es] Where is the station?"})
+        );
+
+        let triage = "Classify the user's text into exactly one of these categories: bug, billing, other. \
+            Respond only with a JSON object: {\"category\": \"<one of the categories>\"}";
+        assert_eq!(mock_json_reply(None, triage, "I was charged twice, billing is broken").unwrap()["category"], "billing");
+        assert_eq!(mock_json_reply(None, triage, "Love the product").unwrap()["category"], "other");
+    }
+
+    #[test]
+    fn mock_fixture_rules_match_case_insensitively_in_order() {
+        let path = std::env::temp_dir().join(format!("llm-mock-fixtures-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[{"match": "weather", "reply": "Sunny, 21°C."},
+                {"match": "rows", "reply": {"rows": [{"city": "Oslo"}]}},
+                {"match": "*", "reply": "Fallback."}]"#,
+        )
+        .unwrap();
+        let fixtures = path.to_str();
+
+        assert_eq!(mock_text_reply(fixtures, "What's the WEATHER like?").unwrap(), "Sunny, 21°C.");
+        assert_eq!(mock_json_reply(fixtures, "", "give me rows").unwrap(), serde_json::json!({"rows": [{"city": "Oslo"}]}));
+        assert_eq!(mock_text_reply(fixtures, "anything else").unwrap(), "Fallback.");
+        let err = mock_json_reply(fixtures, "", "anything else").unwrap_err();
+        assert!(matches!(err, AgentError::Provider { status: 200, .. }), "{err:?}");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
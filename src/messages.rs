@@ -0,0 +1,259 @@
+//! Catalog of user-facing status and error messages, in the locale of the workspace or caller.
+
+use std::cell::RefCell;
+
+use crate::workspaces::workspace_config;
+
+/* ---- Message catalog (`locale` in workspace-config:{team_id}, Accept-Language) ---- */
+// Replies that the agent writes itself (timeouts, fallbacks when the model is unavailable,
+// admin-only refusals, quotas) are looked up here rather than formatted in place. Slash commands
+// use the workspace's `locale` setting, such as `es` or `pt-BR`; other routes use the caller's
+// Accept-Language. Queued jobs keep the locale they were submitted with. Only the language
+// subtag selects a translation, and anything without one is answered in English. Error details
+// from upstreams (`{error}`) are passed through as they are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Msg {
+    TookTooLong,
+    WorkingOnIt,
+    RequestFailed,
+    AiUnavailable,
+    InputTooLong,
+    TranslationUnavailable,
+    TriageUnavailable,
+    AdminsOnly,
+    ImageQuota,
+    ImageFailed,
+    TcpFetchOk,
+    TcpFetchFailed,
+    UsageUnavailable,
+    PromptReportUnavailable,
+    QualityReportUnavailable,
+    AnswerGone,
+}
+
+fn english(msg: Msg) -> &'static str {
+    match msg {
+        Msg::TookTooLong => "Sorry, that took too long to answer. Please try again in a moment.",
+        Msg::WorkingOnIt => "Working on it, this may take a minute (job `{id}`).",
+        Msg::RequestFailed => "Sorry, that request failed: {error}",
+        Msg::AiUnavailable => "You said: {text} (AI unavailable: {error})",
+        Msg::InputTooLong => "Your input was too long to process ({chars} characters; AI unavailable: {error})",
+        Msg::TranslationUnavailable => "Translation unavailable: {error}",
+        Msg::TriageUnavailable => "Triage unavailable: {error}",
+        Msg::AdminsOnly => "Sorry, `{action}` is limited to workspace admins. Ask an admin to run it for you.",
+        Msg::ImageQuota => "you have used all {limit} images for today; the quota resets at midnight UTC",
+        Msg::ImageFailed => "Couldn't create that image: {error}",
+        Msg::TcpFetchOk => "✅ TCP fetch successful!\n\nTarget: {target}\n\n{body}\n",
+        Msg::TcpFetchFailed => "⚠️  TCP fetch failed: {error}\n\n🔧 This is expected in some environments.\n📡 Server is running.\n\nTry: /?host=127.0.0.1&port=8082 after starting a local server, or /debug/tcpscan to test several targets.\n",
+        Msg::UsageUnavailable => "Usage statistics unavailable: {error}",
+        Msg::PromptReportUnavailable => "Prompt report unavailable: {error}",
+        Msg::QualityReportUnavailable => "Quality report unavailable: {error}",
+        Msg::AnswerGone => "That answer is no longer available, so there is nothing to follow up on.",
+    }
+}
+
+const TRANSLATIONS: &[(&str, &[(Msg, &str)])] = &[
+    ("es", &[
+        (Msg::TookTooLong, "Lo siento, la respuesta tardó demasiado. Inténtalo de nuevo en un momento."),
+        (Msg::WorkingOnIt, "Trabajando en ello, puede tardar un minuto (tarea `{id}`)."),
+        (Msg::RequestFailed, "Lo siento, la solicitud falló: {error}"),
+        (Msg::AiUnavailable, "Dijiste: {text} (IA no disponible: {error})"),
+        (Msg::InputTooLong, "Tu mensaje era demasiado largo para procesarlo ({chars} caracteres; IA no disponible: {error})"),
+        (Msg::TranslationUnavailable, "Traducción no disponible: {error}"),
+        (Msg::TriageUnavailable, "Clasificación no disponible: {error}"),
+        (Msg::AdminsOnly, "Lo siento, `{action}` está reservado a los administradores del espacio de trabajo. Pide a un administrador que lo ejecute por ti."),
+        (Msg::ImageQuota, "ya usaste las {limit} imágenes de hoy; el cupo se renueva a medianoche UTC"),
+        (Msg::ImageFailed, "No se pudo crear esa imagen: {error}"),
+        (Msg::TcpFetchOk, "✅ ¡Conexión TCP correcta!\n\nDestino: {target}\n\n{body}\n"),
+        (Msg::TcpFetchFailed, "⚠️  Falló la conexión TCP: {error}\n\n🔧 Es normal en algunos entornos.\n📡 El servidor está en marcha.\n\nPrueba /?host=127.0.0.1&port=8082 después de iniciar un servidor local, o /debug/tcpscan para probar varios destinos.\n"),
+        (Msg::UsageUnavailable, "Estadísticas de uso no disponibles: {error}"),
+        (Msg::PromptReportUnavailable, "Informe de prompts no disponible: {error}"),
+        (Msg::QualityReportUnavailable, "Informe de calidad no disponible: {error}"),
+        (Msg::AnswerGone, "Esa respuesta ya no está disponible, así que no se puede continuar."),
+    ]),
+    ("fr", &[
+        (Msg::TookTooLong, "Désolé, la réponse a pris trop de temps. Réessayez dans un instant."),
+        (Msg::WorkingOnIt, "C'est en cours, cela peut prendre une minute (tâche `{id}`)."),
+        (Msg::RequestFailed, "Désolé, la demande a échoué : {error}"),
+        (Msg::AiUnavailable, "Vous avez dit : {text} (IA indisponible : {error})"),
+        (Msg::InputTooLong, "Votre message était trop long pour être traité ({chars} caractères ; IA indisponible : {error})"),
+        (Msg::TranslationUnavailable, "Traduction indisponible : {error}"),
+        (Msg::TriageUnavailable, "Classement indisponible : {error}"),
+        (Msg::AdminsOnly, "Désolé, `{action}` est réservé aux administrateurs de l'espace de travail. Demandez à un administrateur de le lancer pour vous."),
+        (Msg::ImageQuota, "vous avez utilisé vos {limit} images du jour ; le quota est réinitialisé à minuit UTC"),
+        (Msg::ImageFailed, "Impossible de créer cette image : {error}"),
+        (Msg::TcpFetchOk, "✅ Connexion TCP réussie !\n\nCible : {target}\n\n{body}\n"),
+        (Msg::TcpFetchFailed, "⚠️  Échec de la connexion TCP : {error}\n\n🔧 C'est normal dans certains environnements.\n📡 Le serveur fonctionne.\n\nEssayez /?host=127.0.0.1&port=8082 après avoir démarré un serveur local, ou /debug/tcpscan pour tester plusieurs cibles.\n"),
+        (Msg::UsageUnavailable, "Statistiques d'utilisation indisponibles : {error}"),
+        (Msg::PromptReportUnavailable, "Rapport sur les prompts indisponible : {error}"),
+        (Msg::QualityReportUnavailable, "Rapport de qualité indisponible : {error}"),
+        (Msg::AnswerGone, "Cette réponse n'est plus disponible, il n'est donc pas possible d'y donner suite."),
+    ]),
+    ("de", &[
+        (Msg::TookTooLong, "Die Antwort hat leider zu lange gedauert. Bitte versuche es gleich noch einmal."),
+        (Msg::WorkingOnIt, "Wird bearbeitet, das kann eine Minute dauern (Job `{id}`)."),
+        (Msg::RequestFailed, "Die Anfrage ist leider fehlgeschlagen: {error}"),
+        (Msg::AiUnavailable, "Du hast geschrieben: {text} (KI nicht verfügbar: {error})"),
+        (Msg::InputTooLong, "Deine Eingabe war zu lang für die Verarbeitung ({chars} Zeichen; KI nicht verfügbar: {error})"),
+        (Msg::TranslationUnavailable, "Übersetzung nicht verfügbar: {error}"),
+        (Msg::TriageUnavailable, "Einordnung nicht verfügbar: {error}"),
+        (Msg::AdminsOnly, "`{action}` ist leider den Workspace-Admins vorbehalten. Bitte einen Admin, es für dich auszuführen."),
+        (Msg::ImageQuota, "du hast heute schon alle {limit} Bilder verwendet; das Kontingent wird um Mitternacht UTC zurückgesetzt"),
+        (Msg::ImageFailed, "Das Bild konnte nicht erstellt werden: {error}"),
+        (Msg::TcpFetchOk, "✅ TCP-Abruf erfolgreich!\n\nZiel: {target}\n\n{body}\n"),
+        (Msg::TcpFetchFailed, "⚠️  TCP-Abruf fehlgeschlagen: {error}\n\n🔧 In manchen Umgebungen ist das zu erwarten.\n📡 Der Server läuft.\n\nVersuche /?host=127.0.0.1&port=8082, nachdem du einen lokalen Server gestartet hast, oder /debug/tcpscan, um mehrere Ziele zu testen.\n"),
+        (Msg::UsageUnavailable, "Nutzungsstatistik nicht verfügbar: {error}"),
+        (Msg::PromptReportUnavailable, "Prompt-Bericht nicht verfügbar: {error}"),
+        (Msg::QualityReportUnavailable, "Qualitätsbericht nicht verfügbar: {error}"),
+        (Msg::AnswerGone, "Diese Antwort ist nicht mehr verfügbar, daher ist keine Nachfrage möglich."),
+    ]),
+    ("pt", &[
+        (Msg::TookTooLong, "Desculpe, a resposta demorou demais. Tente novamente em instantes."),
+        (Msg::WorkingOnIt, "Trabalhando nisso, pode levar um minuto (tarefa `{id}`)."),
+        (Msg::RequestFailed, "Desculpe, a solicitação falhou: {error}"),
+        (Msg::AiUnavailable, "Você disse: {text} (IA indisponível: {error})"),
+        (Msg::InputTooLong, "Sua mensagem era longa demais para ser processada ({chars} caracteres; IA indisponível: {error})"),
+        (Msg::TranslationUnavailable, "Tradução indisponível: {error}"),
+        (Msg::TriageUnavailable, "Classificação indisponível: {error}"),
+        (Msg::AdminsOnly, "Desculpe, `{action}` é restrito aos administradores do workspace. Peça a um administrador para executá-lo por você."),
+        (Msg::ImageQuota, "você já usou todas as {limit} imagens de hoje; a cota é renovada à meia-noite UTC"),
+        (Msg::ImageFailed, "Não foi possível criar essa imagem: {error}"),
+        (Msg::TcpFetchOk, "✅ Busca TCP concluída!\n\nDestino: {target}\n\n{body}\n"),
+        (Msg::TcpFetchFailed, "⚠️  Falha na busca TCP: {error}\n\n🔧 Isso é esperado em alguns ambientes.\n📡 O servidor está em execução.\n\nTente /?host=127.0.0.1&port=8082 depois de iniciar um servidor local, ou /debug/tcpscan para testar vários destinos.\n"),
+        (Msg::UsageUnavailable, "Estatísticas de uso indisponíveis: {error}"),
+        (Msg::PromptReportUnavailable, "Relatório de prompts indisponível: {error}"),
+        (Msg::QualityReportUnavailable, "Relatório de qualidade indisponível: {error}"),
+        (Msg::AnswerGone, "Essa resposta não está mais disponível, então não é possível dar continuidade."),
+    ]),
+    ("ja", &[
+        (Msg::TookTooLong, "申し訳ありません。回答に時間がかかりすぎました。しばらくしてからもう一度お試しください。"),
+        (Msg::WorkingOnIt, "処理中です。1分ほどかかる場合があります (ジョブ `{id}`)。"),
+        (Msg::RequestFailed, "申し訳ありません。リクエストに失敗しました: {error}"),
+        (Msg::AiUnavailable, "入力内容: {text} (AI を利用できません: {error})"),
+        (Msg::InputTooLong, "入力が長すぎるため処理できませんでした ({chars} 文字、AI を利用できません: {error})"),
+        (Msg::TranslationUnavailable, "翻訳を利用できません: {error}"),
+        (Msg::TriageUnavailable, "分類を利用できません: {error}"),
+        (Msg::AdminsOnly, "申し訳ありません。`{action}` はワークスペース管理者のみ実行できます。管理者に実行を依頼してください。"),
+        (Msg::ImageQuota, "本日の画像 {limit} 枚をすべて使用しました。上限は UTC の午前 0 時にリセットされます"),
+        (Msg::ImageFailed, "画像を作成できませんでした: {error}"),
+        (Msg::TcpFetchOk, "✅ TCP 取得に成功しました!\n\n宛先: {target}\n\n{body}\n"),
+        (Msg::TcpFetchFailed, "⚠️  TCP 取得に失敗しました: {error}\n\n🔧 環境によってはこれは想定どおりです。\n📡 サーバーは稼働中です。\n\nローカルサーバーを起動してから /?host=127.0.0.1&port=8082 を試すか、/debug/tcpscan で複数の宛先をテストしてください。\n"),
+        (Msg::UsageUnavailable, "使用状況の統計を利用できません: {error}"),
+        (Msg::PromptReportUnavailable, "プロンプトレポートを利用できません: {error}"),
+        (Msg::QualityReportUnavailable, "品質レポートを利用できません: {error}"),
+        (Msg::AnswerGone, "その回答はもう利用できないため、続けて質問できません。"),
+    ]),
+];
+
+thread_local! {
+    static LOCALE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Sets the locale of the messages for the current request (or job); `None` is English.
+pub(crate) fn set_locale(locale: Option<String>) {
+    LOCALE.with(|current| *current.borrow_mut() = locale);
+}
+
+pub(crate) fn current_locale() -> Option<String> {
+    LOCALE.with(|current| current.borrow().clone())
+}
+
+/// The language subtag of `locale` when the catalog has it: `pt-BR` is `pt`.
+fn catalog_language(locale: &str) -> Option<&'static str> {
+    let language = locale.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    TRANSLATIONS.iter().map(|(lang, _)| *lang).chain(["en"]).find(|lang| *lang == language)
+}
+
+/// The locale configured for `team_id`; `None` when it has none.
+pub(crate) fn workspace_locale(team_id: &str) -> Option<String> {
+    if team_id.is_empty() {
+        return None;
+    }
+    workspace_config(team_id).ok().flatten()?.locale.filter(|l| !l.trim().is_empty())
+}
+
+/// The first language in an Accept-Language header, by weight, that the catalog has.
+pub(crate) fn accept_language_locale(header: &str) -> Option<String> {
+    let mut ranges: Vec<(&str, f32)> = header
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let q = parts.find_map(|p| p.trim().strip_prefix("q=")).map_or(Some(1.0), |q| q.trim().parse().ok())?;
+            (q > 0.0).then_some((tag, q))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter().find(|(tag, _)| catalog_language(tag).is_some()).map(|(tag, _)| tag.to_string())
+}
+
+fn template(msg: Msg, locale: Option<&str>) -> &'static str {
+    let translations = locale.and_then(catalog_language).and_then(|lang| TRANSLATIONS.iter().find(|(l, _)| *l == lang));
+    translations.and_then(|(_, table)| table.iter().find(|(m, _)| *m == msg)).map_or_else(|| english(msg), |(_, text)| *text)
+}
+
+/// Fills the `{name}` placeholders of `template` in one pass, so values are never re-expanded.
+fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').and_then(|end| args.iter().find(|(name, _)| *name == &after[..end]).map(|(_, value)| (end, value))) {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `msg` in the current locale with its placeholders filled from `args`.
+pub(crate) fn message(msg: Msg, args: &[(&str, &str)]) -> String {
+    fill(template(msg, current_locale().as_deref()), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: &[Msg] = &[
+        Msg::TookTooLong, Msg::WorkingOnIt, Msg::RequestFailed, Msg::AiUnavailable, Msg::InputTooLong,
+        Msg::TranslationUnavailable, Msg::TriageUnavailable, Msg::AdminsOnly, Msg::ImageQuota, Msg::ImageFailed,
+        Msg::TcpFetchOk, Msg::TcpFetchFailed, Msg::UsageUnavailable, Msg::PromptReportUnavailable, Msg::QualityReportUnavailable,
+        Msg::AnswerGone,
+    ];
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text.split('{').skip(1).filter_map(|s| s.split_once('}').map(|(name, _)| name)).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn every_language_translates_every_message_with_the_same_placeholders() {
+        for (lang, table) in TRANSLATIONS {
+            for msg in ALL {
+                let text = table.iter().find(|(m, _)| m == msg).map(|(_, t)| *t).unwrap_or_else(|| panic!("{lang} has no {msg:?}"));
+                assert_eq!(placeholders(text), placeholders(english(*msg)), "{lang} {msg:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn messages_follow_the_locale_and_fall_back_to_english() {
+        assert_eq!(message(Msg::TranslationUnavailable, &[("error", "timeout")]), "Translation unavailable: timeout");
+        set_locale(Some("pt-BR".into()));
+        assert_eq!(message(Msg::TranslationUnavailable, &[("error", "timeout")]), "Tradução indisponível: timeout");
+        set_locale(Some("sv".into()));
+        assert_eq!(message(Msg::AiUnavailable, &[("text", "{error}"), ("error", "down")]), "You said: {error} (AI unavailable: down)");
+        assert_eq!(accept_language_locale("sv, de-CH;q=0.8, fr;q=0.9, es;q=0").as_deref(), Some("fr"));
+        assert_eq!(accept_language_locale("sv, *;q=0.5"), None);
+    }
+}
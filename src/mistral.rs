@@ -0,0 +1,51 @@
+//! Mistral (La Plateforme) as a chat provider (LLM_PROVIDER=mistral or a `mistral/` model string).
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+
+/* ---- Endpoint and key (MISTRAL_API_KEY, MISTRAL_API_BASE) ---- */
+// La Plateforme's `/v1/chat/completions` takes the OpenAI message format, so replies are parsed
+// the same way; only a few request fields differ (see `mistral_payload`).
+pub(crate) const DEFAULT_MISTRAL_MODEL: &str = "mistral-small-latest";
+
+pub(crate) fn mistral_chat_url() -> String {
+    let base = get_env_var("MISTRAL_API_BASE").unwrap_or_else(|| "https://api.mistral.ai".to_string());
+    format!("{}/v1/chat/completions", base.trim_end_matches('/'))
+}
+
+pub(crate) fn mistral_api_key() -> Result<String, AgentError> {
+    get_env_var("MISTRAL_API_KEY").ok_or_else(|| AgentError::Config("MISTRAL_API_KEY not set".into()))
+}
+
+/* ---- Safety prompt and request fields (MISTRAL_SAFE_PROMPT) ---- */
+// `safe_prompt` makes Mistral prepend its guardrail system prompt to the conversation. The seed
+// is called `random_seed` there, and logit bias is not supported, so it is dropped rather than
+// failing the request.
+pub(crate) fn mistral_payload(payload: &mut serde_json::Value) {
+    let Some(fields) = payload.as_object_mut() else { return };
+    if let Some(seed) = fields.remove("seed") {
+        fields.insert("random_seed".to_string(), seed);
+    }
+    fields.remove("logit_bias");
+    if get_env_var("MISTRAL_SAFE_PROMPT").is_some_and(|v| matches!(v.trim(), "true" | "1" | "yes")) {
+        fields.insert("safe_prompt".to_string(), serde_json::Value::Bool(true));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn payloads_are_adapted_to_mistral_fields() {
+        env::set_var("MISTRAL_SAFE_PROMPT", "true");
+        let mut payload = serde_json::json!({"model": "mistral-large-latest", "seed": 7, "logit_bias": {"50256": -100}, "stop": ["END"]});
+        mistral_payload(&mut payload);
+        assert_eq!(
+            payload,
+            serde_json::json!({"model": "mistral-large-latest", "random_seed": 7, "stop": ["END"], "safe_prompt": true})
+        );
+        env::remove_var("MISTRAL_SAFE_PROMPT");
+    }
+}
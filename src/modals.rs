@@ -0,0 +1,204 @@
+//! The prompt modal: a global shortcut opens a form (prompt, model, temperature, channel) with views.open.
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::jobs::{DeferredTask, defer_task};
+use crate::llm::{SamplingOptions, call_openai_completion, chat_model};
+use crate::messages::{Msg, message, set_locale, workspace_locale};
+use crate::payloads::{SlackInteraction, SubmittedView};
+use crate::requestid::{current_request_id, log, with_ref};
+use crate::residency::{set_region, workspace_region};
+use crate::slack::api::{OutgoingMessage, SlackApi};
+use crate::slack::blocks::{Block, InputElement, SelectOption, View};
+use crate::usage::set_requester;
+use crate::workspaces::set_workspace;
+
+/* ---- Prompt modal (views.open, view_submission, PROMPT_MODAL_MODELS) ---- */
+// The "Ask with options" global shortcut opens a modal at once, because its trigger_id expires
+// three seconds after the click. The modal asks for the prompt, a model from PROMPT_MODAL_MODELS
+// (LLM_MODEL when unset), a temperature from 0 to 2 and where to post the answer. Slack enforces
+// required fields and the number range; the fields are checked again on `view_submission`, and a
+// problem is returned as `response_action: errors`, which Slack shows under the field while the
+// modal stays open. A valid submission closes the modal and is answered after the ack, in the
+// chosen conversation or in the user's DMs when none was chosen or the bot cannot post there.
+pub(crate) const PROMPT_SHORTCUT_CALLBACK: &str = "ask_with_options";
+pub(crate) const PROMPT_MODAL_CALLBACK: &str = "prompt_modal";
+const DEFAULT_TEMPERATURE: f64 = 0.7;
+const MAX_TEMPERATURE: f64 = 2.0;
+
+/// The models offered in the modal, LLM_MODEL first when PROMPT_MODAL_MODELS is unset.
+fn modal_models() -> Vec<String> {
+    let models: Vec<String> = get_env_var("PROMPT_MODAL_MODELS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    if models.is_empty() { vec![chat_model()] } else { models }
+}
+
+fn prompt_modal() -> View {
+    let models = modal_models();
+    let options = models.iter().map(|m| SelectOption::new(m, m)).collect();
+    View::modal("Ask the assistant", "Ask", PROMPT_MODAL_CALLBACK, [
+        Block::input("prompt", "Prompt", InputElement::text("prompt", true), false),
+        Block::input("model", "Model", InputElement::select("model", options, models.first().map(String::as_str)), false),
+        Block::input("temperature", "Temperature (0 to 2)", InputElement::decimal("temperature", 0.0, MAX_TEMPERATURE, Some(DEFAULT_TEMPERATURE)), true),
+        Block::input("channel", "Post the answer in (your DMs if empty)", InputElement::conversation("channel"), true),
+    ])
+}
+
+/// Opens the prompt modal for the shortcut's `trigger_id`, before the ack.
+pub(crate) fn open_prompt_modal(interaction: &SlackInteraction) -> Result<(), AgentError> {
+    let trigger_id = interaction.trigger_id.as_deref().ok_or_else(|| AgentError::Validation(format!("{PROMPT_SHORTCUT_CALLBACK}: missing trigger_id")))?;
+    set_workspace(interaction.team.as_ref().map_or("", |t| t.id.as_str()));
+    SlackApi::from_env()?.open_view(trigger_id, &prompt_modal())
+}
+
+/// The submitted fields, or the errors to show by block ID.
+fn read_submission(view: &SubmittedView) -> Result<(String, String, Option<f64>, Option<String>), serde_json::Value> {
+    let mut errors = serde_json::Map::new();
+    let prompt = view.input("prompt", "prompt").and_then(|v| v.value.as_deref()).unwrap_or_default().trim().to_string();
+    if prompt.is_empty() {
+        errors.insert("prompt".into(), "Enter a prompt.".into());
+    }
+    let model = view.input("model", "model").and_then(|v| v.selected_option.as_ref()).map(|o| o.value.clone()).unwrap_or_default();
+    if !modal_models().contains(&model) {
+        errors.insert("model".into(), "Choose one of the listed models.".into());
+    }
+    let temperature = match view.input("temperature", "temperature").and_then(|v| v.value.as_deref()).map(str::trim).filter(|t| !t.is_empty()) {
+        None => None,
+        Some(t) => match t.parse::<f64>() {
+            Ok(t) if (0.0..=MAX_TEMPERATURE).contains(&t) => Some(t),
+            _ => {
+                errors.insert("temperature".into(), "Enter a number from 0 to 2.".into());
+                None
+            }
+        },
+    };
+    let channel = view.input("channel", "channel").and_then(|v| v.selected_conversation.clone());
+    if errors.is_empty() { Ok((prompt, model, temperature, channel)) } else { Err(serde_json::json!({"response_action": "errors", "errors": errors})) }
+}
+
+/// Handles `view_submission`: queues the answer and returns nothing, which closes the modal, or
+/// returns the `response_action: errors` body.
+pub(crate) fn handle_view_submission(interaction: &SlackInteraction) -> Result<Option<serde_json::Value>, AgentError> {
+    let Some(view) = interaction.view.as_ref().filter(|v| v.callback_id == PROMPT_MODAL_CALLBACK) else {
+        return Ok(None);
+    };
+    let user = interaction.user.as_ref().ok_or_else(|| AgentError::Validation(format!("{PROMPT_MODAL_CALLBACK}: missing user")))?.id.clone();
+    match read_submission(view) {
+        Ok((prompt, model, temperature, channel)) => {
+            let team_id = interaction.team.as_ref().map(|t| t.id.clone()).unwrap_or_default();
+            defer_task(DeferredTask::ModalPrompt { team_id, user, prompt, model, temperature, channel });
+            Ok(None)
+        }
+        Err(errors) => Ok(Some(errors)),
+    }
+}
+
+/// Answers a submitted prompt modal with the chosen model and temperature.
+pub(crate) fn run_modal_prompt(
+    team_id: &str,
+    user: &str,
+    prompt: &str,
+    model: &str,
+    temperature: Option<f64>,
+    channel: Option<&str>,
+) -> Result<(), AgentError> {
+    set_region(workspace_region(team_id));
+    set_locale(workspace_locale(team_id));
+    set_workspace(team_id);
+    set_requester(user, channel.unwrap_or_default());
+    let options = SamplingOptions { model: Some(model.to_string()), temperature, ..Default::default() };
+    let answer = match call_openai_completion(prompt, &options) {
+        Ok(completion) => completion.content,
+        Err(e) => with_ref(&message(Msg::RequestFailed, &[("error", &e.to_string())]), &current_request_id()),
+    };
+    let settings = match temperature {
+        Some(t) => format!("{model}, temperature {t}"),
+        None => model.to_string(),
+    };
+    let text = format!("<@{user}> asked: {prompt}\n\n{answer}\n\n_{settings}_");
+    let api = SlackApi::from_env()?;
+    if let Some(channel) = channel {
+        match api.post_message(&OutgoingMessage { channel, text: &text, ..Default::default() }) {
+            Ok(_) => return Ok(()),
+            Err(e) => log!("modal answer not posted in {channel}: {e}"),
+        }
+    }
+    let dm = api.open_dm(user)?;
+    api.post_message(&OutgoingMessage { channel: &dm, text: &text, ..Default::default() }).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::interactive::handle_interaction;
+    use crate::jobs::run_deferred;
+    use crate::llm::completion;
+
+    fn submission(prompt: &str, temperature: Option<&str>, channel: Option<&str>) -> String {
+        serde_json::json!({
+            "type": "view_submission",
+            "team": {"id": "T1"},
+            "user": {"id": "U1"},
+            "view": {"callback_id": PROMPT_MODAL_CALLBACK, "state": {"values": {
+                "prompt": {"prompt": {"type": "plain_text_input", "value": prompt}},
+                "model": {"model": {"type": "static_select", "selected_option": {"value": "gpt-4o-mini"}}},
+                "temperature": {"temperature": {"type": "number_input", "value": temperature}},
+                "channel": {"channel": {"type": "conversations_select", "selected_conversation": channel}},
+            }}},
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn the_shortcut_opens_the_modal_with_its_trigger_id() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+        let shortcut = serde_json::json!({"type": "shortcut", "callback_id": PROMPT_SHORTCUT_CALLBACK, "trigger_id": "1.2.3", "team": {"id": "T1"}, "user": {"id": "U1"}});
+        assert_eq!(handle_interaction(&shortcut.to_string()).unwrap(), None);
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/views.open");
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!((body["trigger_id"].as_str(), body["view"]["callback_id"].as_str()), (Some("1.2.3"), Some(PROMPT_MODAL_CALLBACK)));
+        let blocks: Vec<&str> = body["view"]["blocks"].as_array().unwrap().iter().filter_map(|b| b["block_id"].as_str()).collect();
+        assert_eq!(blocks, ["prompt", "model", "temperature", "channel"]);
+        assert_eq!(body["view"]["blocks"][1]["element"]["initial_option"]["value"], "gpt-4o-mini");
+    }
+
+    #[test]
+    fn submissions_are_answered_with_the_chosen_settings() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("A haiku."));
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "1"}"#);
+
+        assert_eq!(handle_interaction(&submission("Write a haiku", Some("1.2"), Some("C1"))).unwrap(), None);
+        run_deferred();
+
+        let requests = mock.requests.borrow();
+        let prompt: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!((prompt["model"].as_str(), prompt["temperature"].as_f64()), (Some("gpt-4o-mini"), Some(1.2)));
+        let post: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(post["channel"], "C1");
+        assert_eq!(post["text"], "<@U1> asked: Write a haiku\n\nA haiku.\n\n_gpt-4o-mini, temperature 1.2_");
+    }
+
+    #[test]
+    fn invalid_fields_keep_the_modal_open_with_errors() {
+        with_openai_env();
+        let errors = handle_interaction(&submission("  ", Some("3"), None)).unwrap().unwrap();
+        assert_eq!(errors["response_action"], "errors");
+        assert_eq!(errors["errors"]["prompt"], "Enter a prompt.");
+        assert_eq!(errors["errors"]["temperature"], "Enter a number from 0 to 2.");
+        assert!(errors["errors"].get("model").is_none());
+    }
+}
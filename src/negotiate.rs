@@ -0,0 +1,88 @@
+//! Content negotiation (`Accept`) for JSON API responses.
+
+use crate::router::Response;
+use crate::tables::{cell_text, render_markdown_table, table_from_json};
+
+/* ---- Content negotiation (Accept) ---- */
+// Handlers build JSON; callers that ask for `text/plain` or `text/markdown` get the same response
+// rendered for reading. A query answer becomes its text, `data` rows become a table and other
+// objects become one `key: value` line per field. JSON stays the default, including for Accept
+// headers naming nothing offered here, and `?format=` (which picks a non-JSON type) wins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Representation {
+    Json,
+    Text,
+    Markdown,
+}
+
+/// The best offered representation by q-value, ties going to the earlier entry.
+fn preferred(accept: &str) -> Representation {
+    let mut best = (Representation::Json, 0.0);
+    for range in accept.split(',') {
+        let mut parts = range.split(';').map(str::trim);
+        let media = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let q = parts.find_map(|p| p.strip_prefix("q=")).and_then(|q| q.parse::<f32>().ok()).unwrap_or(1.0);
+        let representation = match media.as_str() {
+            "application/json" | "application/*" | "*/*" => Representation::Json,
+            "text/plain" | "text/*" => Representation::Text,
+            "text/markdown" => Representation::Markdown,
+            _ => continue,
+        };
+        if q > best.1 {
+            best = (representation, q);
+        }
+    }
+    best.0
+}
+
+fn render(value: &serde_json::Value, markdown: bool) -> String {
+    match value {
+        serde_json::Value::Object(fields) => {
+            if let Some(answer) = fields.get("answer").and_then(|a| a.as_str()) {
+                return answer.to_string();
+            }
+            if let Some(data) = fields.get("data") {
+                return render(data, markdown);
+            }
+            let line = |(key, value): (&String, &serde_json::Value)| {
+                if markdown { format!("- **{key}**: {}", cell_text(value)) } else { format!("{key}: {}", cell_text(value)) }
+            };
+            fields.iter().map(line).collect::<Vec<_>>().join("\n")
+        }
+        serde_json::Value::Array(_) => match table_from_json(value) {
+            Some(table) => render_markdown_table(&table),
+            None => value.as_array().into_iter().flatten().map(cell_text).collect::<Vec<_>>().join("\n"),
+        },
+        other => cell_text(other),
+    }
+}
+
+/// `response` in the representation `accept` prefers; only successful JSON responses are converted.
+pub(crate) fn negotiate(response: Response, accept: Option<&str>) -> Response {
+    let representation = accept.map_or(Representation::Json, preferred);
+    if representation == Representation::Json || response.status() != 200 || response.content_type() != Some("application/json") {
+        return response;
+    }
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(response.body()) else { return response };
+    let (content_type, markdown) = match representation {
+        Representation::Markdown => ("text/markdown", true),
+        _ => ("text/plain", false),
+    };
+    response.replace_body(content_type, render(&value, markdown)).header("vary", "accept")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_headers_pick_the_best_offered_type() {
+        assert_eq!(preferred("text/markdown"), Representation::Markdown);
+        assert_eq!(preferred("application/json;q=0.5, text/plain"), Representation::Text);
+        assert_eq!(preferred("text/html, */*;q=0.8"), Representation::Json);
+        assert_eq!(preferred("image/png"), Representation::Json);
+        let job = serde_json::json!({"id": "j1", "state": "done", "run_secs": 12});
+        assert_eq!(render(&job, false), "id: j1\nstate: done\nrun_secs: 12");
+        assert_eq!(render(&job, true), "- **id**: j1\n- **state**: done\n- **run_secs**: 12");
+    }
+}
@@ -0,0 +1,220 @@
+//! Typed inbound JSON payloads (Slack events and interactivity, API bodies) with field-level errors.
+
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
+use crate::error::AgentError;
+use crate::slack::api::SlackMessage;
+
+/* ---- Parsing with field-level errors ---- */
+// Inbound JSON is deserialized into the types below instead of being read field by field with
+// defaults, so a missing or mistyped field is a 400 that names it, e.g. `actions[0].value:
+// invalid type: integer, expected a string`, rather than a request that carries on with an
+// empty string.
+pub(crate) fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T, AgentError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        let message = inner.to_string();
+        // serde_json appends the position, which means little to the sender of a form field
+        let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(m, _)| m);
+        AgentError::Validation(match (inner.is_syntax() || inner.is_eof(), path.as_str()) {
+            (true, _) => format!("invalid JSON: {message}"),
+            (false, ".") => message.to_string(),
+            (false, path) => format!("{path}: {message}"),
+        })
+    })
+}
+
+/// A field the payload's `type` makes mandatory, or the error serde gives for a missing field.
+fn required<'a, T: ?Sized>(value: Option<&'a T>, parent: Option<&str>, name: &str) -> Result<&'a T, AgentError> {
+    value.ok_or_else(|| {
+        AgentError::Validation(match parent {
+            Some(parent) => format!("{parent}: missing field `{name}`"),
+            None => format!("missing field `{name}`"),
+        })
+    })
+}
+
+/* ---- Slack Events API (/slack/events) ---- */
+// Envelopes and events are structs keyed by `type` rather than tagged enums, which would lose
+// the path of a mistyped field; the fields a type requires are checked by its accessor.
+#[derive(Deserialize)]
+pub(crate) struct SlackEnvelope {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    challenge: Option<String>,
+    pub(crate) team_id: Option<String>,
+    /// Unique per event and kept across retries
+    pub(crate) event_id: Option<String>,
+    event: Option<SlackEvent>,
+    #[serde(default)]
+    pub(crate) authorizations: Vec<SlackAuthorization>,
+}
+
+impl SlackEnvelope {
+    /// `url_verification`: the challenge to echo.
+    pub(crate) fn challenge(&self) -> Result<&str, AgentError> {
+        required(self.challenge.as_deref(), None, "challenge")
+    }
+
+    /// `event_callback`: the wrapped event.
+    pub(crate) fn event(&self) -> Result<&SlackEvent, AgentError> {
+        required(self.event.as_ref(), None, "event")
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SlackEvent {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    user: Option<String>,
+    channel: Option<String>,
+    pub(crate) team: Option<String>,
+    text: Option<String>,
+    ts: Option<String>,
+    pub(crate) thread_ts: Option<String>,
+    /// `im` for direct messages
+    pub(crate) channel_type: Option<String>,
+    /// Set on messages posted by bots, including this one
+    pub(crate) bot_id: Option<String>,
+    /// Set on edits, deletions, joins and other messages that are not plain posts
+    pub(crate) subtype: Option<String>,
+    /// `app_home_opened`: `home` or `messages`
+    pub(crate) tab: Option<String>,
+}
+
+impl SlackEvent {
+    /// `member_joined_channel`: who joined which channel.
+    pub(crate) fn member_joined(&self) -> Result<(&str, &str), AgentError> {
+        Ok((required(self.user.as_deref(), Some("event"), "user")?, required(self.channel.as_deref(), Some("event"), "channel")?))
+    }
+
+    /// `app_home_opened`: whose App Home was opened.
+    pub(crate) fn home_opened(&self) -> Result<&str, AgentError> {
+        required(self.user.as_deref(), Some("event"), "user")
+    }
+
+    /// `app_mention` and `message`: who wrote what, where, and the message's `ts`.
+    pub(crate) fn message(&self) -> Result<(&str, &str, &str, &str), AgentError> {
+        let (user, channel) = self.member_joined()?;
+        Ok((user, channel, required(self.text.as_deref(), Some("event"), "text")?, required(self.ts.as_deref(), Some("event"), "ts")?))
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SlackAuthorization {
+    pub(crate) user_id: String,
+}
+
+/* ---- Slack interactivity (/slack/interactive) ---- */
+#[derive(Deserialize)]
+pub(crate) struct SlackInteraction {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    /// Present on `block_actions`
+    #[serde(default)]
+    pub(crate) actions: Vec<BlockAction>,
+    /// Where follow-up messages about the clicked message go, for about 30 minutes
+    pub(crate) response_url: Option<String>,
+    pub(crate) user: Option<SlackRef>,
+    pub(crate) team: Option<SlackRef>,
+    /// Which shortcut was chosen, on `message_action` and `shortcut`
+    pub(crate) callback_id: Option<String>,
+    /// Opens a modal within three seconds of the interaction
+    pub(crate) trigger_id: Option<String>,
+    /// The channel of the message a message shortcut was chosen on
+    pub(crate) channel: Option<SlackRef>,
+    /// The message a message shortcut was chosen on
+    pub(crate) message: Option<SlackMessage>,
+    /// The submitted modal, on `view_submission`
+    pub(crate) view: Option<SubmittedView>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SubmittedView {
+    #[serde(default)]
+    pub(crate) callback_id: String,
+    pub(crate) state: ViewState,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ViewState {
+    /// Input values by block ID, then by the element's action ID
+    pub(crate) values: HashMap<String, HashMap<String, InputValue>>,
+}
+
+/// A submitted input element; which field is set depends on the element type.
+#[derive(Deserialize)]
+pub(crate) struct InputValue {
+    /// Text and number inputs; absent or null when left empty
+    pub(crate) value: Option<String>,
+    pub(crate) selected_option: Option<SelectedOption>,
+    pub(crate) selected_conversation: Option<String>,
+}
+
+impl SubmittedView {
+    /// The input element `block_id`/`action_id`, when it was submitted.
+    pub(crate) fn input(&self, block_id: &str, action_id: &str) -> Option<&InputValue> {
+        self.state.values.get(block_id).and_then(|block| block.get(action_id))
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SlackRef {
+    pub(crate) id: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BlockAction {
+    pub(crate) action_id: String,
+    /// Absent on link buttons and other elements without a value
+    pub(crate) value: Option<String>,
+    /// The chosen option of a select menu
+    pub(crate) selected_option: Option<SelectedOption>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SelectedOption {
+    pub(crate) value: String,
+}
+
+impl BlockAction {
+    /// The button's value or the selected menu option's.
+    pub(crate) fn value(&self) -> Option<&str> {
+        self.value.as_deref().or(self.selected_option.as_ref().map(|o| o.value.as_str()))
+    }
+}
+
+/* ---- API bodies (/api/v1/query, /api/v1/query/stream) ---- */
+#[derive(Deserialize, Default)]
+pub(crate) struct QueryBody {
+    pub(crate) query: Option<String>,
+    pub(crate) context: Option<String>,
+    pub(crate) format: Option<String>,
+    pub(crate) structured: Option<bool>,
+    /// Sampling overrides (`stop`, `seed`, `model`, ...), checked by `SamplingOptions::from_json`
+    #[serde(flatten)]
+    pub(crate) sampling: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_name_the_missing_or_mistyped_field() {
+        let event = |body: &str| parse_json::<SlackEnvelope>(body).and_then(|e| e.event()?.member_joined().map(|_| ())).err().map(|e| e.to_string());
+        assert_eq!(event(r#"{"type": "event_callback", "event": {"type": "member_joined_channel", "user": "U1"}}"#).as_deref(), Some("event: missing field `channel`"));
+        assert_eq!(event(r#"{"type": "event_callback", "event": {"type": "member_joined_channel", "user": 5}}"#).as_deref(), Some("event.user: invalid type: integer `5`, expected a string"));
+        assert_eq!(event(r#"{"type": "event_callback"}"#).as_deref(), Some("missing field `event`"));
+        assert_eq!(event(r#"{"type": "event_callback", "event": {}}"#).as_deref(), Some("event: missing field `type`"));
+        assert!(event("{").unwrap().starts_with("invalid JSON: "));
+        let err = parse_json::<SlackInteraction>(r#"{"type": "block_actions", "actions": [{"value": 7}]}"#).err().unwrap();
+        assert_eq!(err.to_string(), "actions[0].value: invalid type: integer `7`, expected a string");
+        let err = parse_json::<QueryBody>(r#"{"query": "hi", "structured": "yes"}"#).err().unwrap();
+        assert_eq!(err.to_string(), "structured: invalid type: string \"yes\", expected a boolean");
+    }
+}
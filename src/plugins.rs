@@ -0,0 +1,197 @@
+//! Tools from composed plugin components, offered to the model for function calling.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::requestid::log;
+
+/* ---- Plugin tools (`plugins` feature) ---- */
+// A component built with the `plugins` feature imports `component:ai-agent/tools`, which another
+// component provides once the two are composed (`wac plug`). Every tool it describes is sent with
+// chat completions; when the model calls one, the arguments go to the plugin and its result goes
+// back to the model, for at most MAX_TOOL_ROUNDS rounds. Descriptions with a name the providers
+// would reject, a repeated name or parameters that are not a JSON object are skipped and logged.
+// A failed invocation is reported to the model as the tool's result instead of failing the answer.
+pub(crate) const MAX_TOOL_ROUNDS: usize = 4;
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// A tool as a plugin describes it; `parameters` is a JSON Schema in text form.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ToolDescriptor {
+    pub(crate) name: String,
+    pub(crate) description: String,
+    pub(crate) parameters: String,
+}
+
+/// A tool in the function-calling registry.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ToolSpec {
+    pub(crate) name: String,
+    pub(crate) description: String,
+    pub(crate) parameters: serde_json::Value,
+}
+
+pub(crate) trait ToolHost {
+    fn describe(&self) -> Vec<ToolDescriptor>;
+    fn invoke(&self, name: &str, arguments: &str) -> Result<String, String>;
+}
+
+/// The host when no plugin is composed in.
+struct NoTools;
+
+impl ToolHost for NoTools {
+    fn describe(&self) -> Vec<ToolDescriptor> {
+        Vec::new()
+    }
+
+    fn invoke(&self, name: &str, _arguments: &str) -> Result<String, String> {
+        Err(format!("no plugin provides '{name}'"))
+    }
+}
+
+#[cfg(feature = "plugins")]
+struct ComposedTools;
+
+#[cfg(feature = "plugins")]
+impl ToolHost for ComposedTools {
+    fn describe(&self) -> Vec<ToolDescriptor> {
+        use crate::bindings::component::ai_agent::tools;
+        tools::describe()
+            .into_iter()
+            .map(|tool| ToolDescriptor { name: tool.name, description: tool.description, parameters: tool.parameters })
+            .collect()
+    }
+
+    fn invoke(&self, name: &str, arguments: &str) -> Result<String, String> {
+        crate::bindings::component::ai_agent::tools::invoke(name, arguments)
+    }
+}
+
+#[cfg(all(feature = "plugins", not(test)))]
+fn default_host() -> Rc<dyn ToolHost> {
+    Rc::new(ComposedTools)
+}
+
+// Native tests have no composed plugin unless a test installs MockTools
+#[cfg(any(not(feature = "plugins"), test))]
+fn default_host() -> Rc<dyn ToolHost> {
+    Rc::new(NoTools)
+}
+
+thread_local! {
+    static HOST: RefCell<Rc<dyn ToolHost>> = RefCell::new(default_host());
+}
+
+fn host() -> Rc<dyn ToolHost> {
+    HOST.with(|h| h.borrow().clone())
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_TOOL_NAME_LEN && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn registry_from(descriptors: Vec<ToolDescriptor>) -> Vec<ToolSpec> {
+    let mut registry: Vec<ToolSpec> = Vec::new();
+    for tool in descriptors {
+        let spec = match serde_json::from_str::<serde_json::Value>(&tool.parameters) {
+            _ if !valid_name(&tool.name) => Err("name must be 1-64 letters, digits, '_' or '-'"),
+            _ if registry.iter().any(|t| t.name == tool.name) => Err("name is already taken"),
+            Ok(parameters) if parameters.is_object() => {
+                Ok(ToolSpec { name: tool.name.clone(), description: tool.description, parameters })
+            }
+            _ => Err("parameters are not a JSON Schema object"),
+        };
+        match spec {
+            Ok(spec) => registry.push(spec),
+            Err(problem) => log!("skipping plugin tool '{}': {problem}", tool.name),
+        }
+    }
+    registry
+}
+
+/// The tools the composed plugins provide.
+pub(crate) fn tool_registry() -> Vec<ToolSpec> {
+    registry_from(host().describe())
+}
+
+/// The registry as a chat completion `tools` array.
+pub(crate) fn openai_tools(registry: &[ToolSpec]) -> serde_json::Value {
+    registry
+        .iter()
+        .map(|tool| serde_json::json!({
+            "type": "function",
+            "function": {"name": tool.name, "description": tool.description, "parameters": tool.parameters},
+        }))
+        .collect()
+}
+
+/// Runs one tool call from the model; the text returned is what the model sees as its result.
+pub(crate) fn invoke_tool(registry: &[ToolSpec], name: &str, arguments: &str) -> String {
+    if !registry.iter().any(|tool| tool.name == name) {
+        return format!("error: unknown tool '{name}'");
+    }
+    // Models sometimes send an empty string for a call without arguments
+    let arguments = if arguments.trim().is_empty() { "{}" } else { arguments };
+    match host().invoke(name, arguments) {
+        Ok(result) => result,
+        Err(e) => {
+            log!("plugin tool '{name}' failed: {e}");
+            format!("error: {e}")
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) struct MockTools {
+    pub(crate) tools: Vec<ToolDescriptor>,
+    pub(crate) calls: RefCell<Vec<(String, String)>>,
+}
+
+#[cfg(test)]
+impl ToolHost for MockTools {
+    fn describe(&self) -> Vec<ToolDescriptor> {
+        self.tools.clone()
+    }
+
+    /// Echoes the call, or fails for a tool named `broken`.
+    fn invoke(&self, name: &str, arguments: &str) -> Result<String, String> {
+        self.calls.borrow_mut().push((name.to_string(), arguments.to_string()));
+        match name {
+            "broken" => Err("plugin trapped".into()),
+            _ => Ok(format!("{name} called with {arguments}")),
+        }
+    }
+}
+
+#[cfg(test)]
+impl MockTools {
+    /// Installs a plugin providing `tools` (name, parameters) as this thread's tool host.
+    pub(crate) fn install(tools: &[(&str, &str)]) -> Rc<MockTools> {
+        let tools = tools
+            .iter()
+            .map(|(name, parameters)| ToolDescriptor { name: name.to_string(), description: format!("The {name} tool"), parameters: parameters.to_string() })
+            .collect();
+        let mock = Rc::new(MockTools { tools, calls: RefCell::new(Vec::new()) });
+        HOST.with(|h| *h.borrow_mut() = mock.clone());
+        mock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_and_duplicate_tools_are_left_out_of_the_registry() {
+        let object = r#"{"type": "object", "properties": {"city": {"type": "string"}}}"#;
+        let mock = MockTools::install(&[("weather", object), ("weather", "{}"), ("bad name", "{}"), ("list", "[]"), ("broken", "{}")]);
+        let registry = tool_registry();
+        assert_eq!(registry.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["weather", "broken"]);
+        assert_eq!(openai_tools(&registry)[0]["function"]["parameters"]["properties"]["city"]["type"], "string");
+
+        assert_eq!(invoke_tool(&registry, "weather", ""), "weather called with {}");
+        assert_eq!(invoke_tool(&registry, "broken", "{}"), "error: plugin trapped");
+        assert_eq!(invoke_tool(&registry, "list", "{}"), "error: unknown tool 'list'");
+        assert_eq!(mock.calls.borrow().len(), 2);
+    }
+}
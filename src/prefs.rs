@@ -0,0 +1,216 @@
+//! Per-user preferences (`/agent-prefs`), applied to that user's questions in every channel.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AgentError;
+use crate::requestid::{current_request_id, with_ref};
+use crate::store::{store_delete, store_get, store_set};
+
+/* ---- Stored preferences (`prefs:{user}`) ---- */
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Verbosity {
+    Brief,
+    #[default]
+    Normal,
+    Detailed,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug)]
+pub(crate) struct UserPrefs {
+    #[serde(default)]
+    pub(crate) verbosity: Verbosity,
+    /// Language answers are written in, whatever the question's language
+    #[serde(default)]
+    pub(crate) language: Option<String>,
+    /// Who the assistant should answer as, e.g. "a patient teacher"
+    #[serde(default)]
+    pub(crate) persona: Option<String>,
+    /// Answers only the user can see (ephemeral) instead of posting in the channel
+    #[serde(default)]
+    pub(crate) private: bool,
+}
+
+const MAX_LANGUAGE_CHARS: usize = 40;
+const MAX_PERSONA_CHARS: usize = 200;
+
+/// The user's preferences; defaults when none are stored or the store is unavailable.
+pub(crate) fn user_prefs(user_id: &str) -> UserPrefs {
+    if user_id.is_empty() {
+        return UserPrefs::default();
+    }
+    store_get(&format!("prefs:{user_id}")).ok().flatten().and_then(|raw| serde_json::from_str(&raw).ok()).unwrap_or_default()
+}
+
+impl UserPrefs {
+    /// `base` (e.g. an experiment prompt) with the preferences added, or `None` when neither applies.
+    pub(crate) fn system_prompt(&self, base: Option<&str>) -> Option<String> {
+        let mut parts: Vec<String> = base.map(str::to_string).into_iter().collect();
+        if let Some(persona) = &self.persona {
+            parts.push(format!("Answer as {persona}."));
+        }
+        match self.verbosity {
+            Verbosity::Brief => parts.push("Keep answers to one or two sentences.".to_string()),
+            Verbosity::Normal => {}
+            Verbosity::Detailed => parts.push("Give thorough answers with explanation and examples where useful.".to_string()),
+        }
+        if let Some(language) = &self.language {
+            parts.push(format!("Always answer in {language}."));
+        }
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+
+    pub(crate) fn max_tokens(&self) -> u32 {
+        match self.verbosity {
+            Verbosity::Brief => 100,
+            Verbosity::Normal => 150,
+            Verbosity::Detailed => 600,
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        let verbosity = match self.verbosity {
+            Verbosity::Brief => "brief",
+            Verbosity::Normal => "normal",
+            Verbosity::Detailed => "detailed",
+        };
+        format!(
+            "*Your preferences*\n• verbosity: {verbosity}\n• language: {}\n• persona: {}\n• visibility: {}",
+            self.language.as_deref().unwrap_or("same as the question"),
+            self.persona.as_deref().unwrap_or("default"),
+            if self.private { "private (only you see answers)" } else { "channel" },
+        )
+    }
+}
+
+/* ---- --private / --public (one command) ---- */
+// Either flag anywhere in a command's text overrides the user's visibility preference for that
+// command alone: `--private` answers only the asker (ephemeral) and keeps the exchange private in
+// history; `--public` posts in the channel. The flag is taken out of the text before it is
+// answered. It stays in the text that is queued or deferred, so a job answered later still sees it.
+const PRIVATE_FLAG: &str = "--private";
+const PUBLIC_FLAG: &str = "--public";
+
+/// `text` without visibility flags, and whether its answer is private: as the last flag says, else as the user prefers.
+pub(crate) fn answer_visibility(text: &str, user_id: &str) -> (String, bool) {
+    let mut flag = None;
+    let words: Vec<&str> = text
+        .split(' ')
+        .filter(|word| match word.trim() {
+            PRIVATE_FLAG => {
+                flag = Some(true);
+                false
+            }
+            PUBLIC_FLAG => {
+                flag = Some(false);
+                false
+            }
+            _ => true,
+        })
+        .collect();
+    let text = if flag.is_some() { words.join(" ").trim().to_string() } else { text.to_string() };
+    (text, flag.unwrap_or_else(|| user_prefs(user_id).private))
+}
+
+/* ---- /agent-prefs [<setting> <value>|reset] ---- */
+const USAGE: &str = "Usage: `/agent-prefs` to show, `/agent-prefs <setting> <value>` to change, `/agent-prefs reset` to clear.\n\
+Settings: `verbosity brief|normal|detailed`, `language <name>|clear`, `persona <description>|clear`, `visibility private|channel`";
+
+/// Applies `<setting> <value>` to the user's stored preferences.
+pub(crate) fn update_prefs(user_id: &str, text: &str) -> Result<UserPrefs, AgentError> {
+    let mut prefs = user_prefs(user_id);
+    let (setting, value) = text.trim().split_once(char::is_whitespace).unwrap_or((text.trim(), ""));
+    let value = value.trim();
+    let cleared = |max: usize, what: &str| -> Result<Option<String>, AgentError> {
+        match value {
+            "" => Err(AgentError::Validation(format!("a {what} is required"))),
+            "clear" | "none" => Ok(None),
+            v if v.chars().count() > max => Err(AgentError::Validation(format!("the {what} is longer than {max} characters"))),
+            v => Ok(Some(v.to_string())),
+        }
+    };
+    match setting {
+        "verbosity" => {
+            prefs.verbosity = match value {
+                "brief" => Verbosity::Brief,
+                "normal" => Verbosity::Normal,
+                "detailed" => Verbosity::Detailed,
+                other => return Err(AgentError::Validation(format!("unknown verbosity '{other}'"))),
+            }
+        }
+        "language" => prefs.language = cleared(MAX_LANGUAGE_CHARS, "language")?,
+        "persona" => prefs.persona = cleared(MAX_PERSONA_CHARS, "persona")?,
+        "visibility" => {
+            prefs.private = match value {
+                "private" | "ephemeral" => true,
+                "channel" | "public" | "in_channel" => false,
+                other => return Err(AgentError::Validation(format!("unknown visibility '{other}'"))),
+            }
+        }
+        other => return Err(AgentError::Validation(format!("unknown setting '{other}'"))),
+    }
+    let raw = serde_json::to_string(&prefs).map_err(|e| AgentError::Store(e.to_string()))?;
+    store_set(&format!("prefs:{user_id}"), &raw)?;
+    Ok(prefs)
+}
+
+pub(crate) fn slack_prefs_reply(text: &str, user_id: &str) -> String {
+    if user_id.is_empty() {
+        return "Preferences need a Slack user.".to_string();
+    }
+    match text.trim() {
+        "" => format!("{}\n\n{USAGE}", user_prefs(user_id).describe()),
+        "reset" => match store_delete(&format!("prefs:{user_id}")) {
+            Ok(()) => format!("Preferences reset.\n{}", UserPrefs::default().describe()),
+            Err(e) => with_ref(&format!("Couldn't reset your preferences: {e}"), &current_request_id()),
+        },
+        text => match update_prefs(user_id, text) {
+            Ok(prefs) => format!("Saved.\n{}", prefs.describe()),
+            Err(AgentError::Validation(message)) => format!("Couldn't save that: {message}\n{USAGE}"),
+            Err(e) => with_ref(&format!("Couldn't save that: {e}"), &current_request_id()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MockStore;
+
+    #[test]
+    fn settings_are_saved_one_at_a_time_and_shape_the_prompt() {
+        MockStore::install();
+        assert!(slack_prefs_reply("verbosity brief", "U1").starts_with("Saved."));
+        slack_prefs_reply("language Brazilian Portuguese", "U1");
+        slack_prefs_reply("persona a patient teacher", "U1");
+        slack_prefs_reply("visibility private", "U1");
+
+        let prefs = user_prefs("U1");
+        assert_eq!(prefs.max_tokens(), 100);
+        assert!(prefs.private);
+        assert_eq!(
+            prefs.system_prompt(Some("Experiment prompt.")).unwrap(),
+            "Experiment prompt.\n\nAnswer as a patient teacher.\n\nKeep answers to one or two sentences.\n\nAlways answer in Brazilian Portuguese."
+        );
+
+        slack_prefs_reply("persona clear", "U1");
+        assert_eq!(user_prefs("U1").persona, None);
+        assert!(slack_prefs_reply("reset", "U1").starts_with("Preferences reset."));
+        assert_eq!(user_prefs("U1"), UserPrefs::default());
+        assert_eq!(UserPrefs::default().system_prompt(None), None);
+
+        slack_prefs_reply("visibility private", "U1");
+        assert_eq!(answer_visibility("Is this ok? --public", "U1"), ("Is this ok?".to_string(), false));
+        assert_eq!(answer_visibility("--private my salary question", "U2"), ("my salary question".to_string(), true));
+        assert_eq!(answer_visibility("what does --privately mean", "U1"), ("what does --privately mean".to_string(), true));
+    }
+
+    #[test]
+    fn invalid_settings_are_explained() {
+        MockStore::install();
+        assert!(slack_prefs_reply("verbosity chatty", "U1").starts_with("Couldn't save that: unknown verbosity 'chatty'\nUsage:"));
+        assert!(slack_prefs_reply("mood happy", "U1").starts_with("Couldn't save that: unknown setting 'mood'"));
+        assert!(slack_prefs_reply("language", "U1").starts_with("Couldn't save that: a language is required"));
+        assert!(slack_prefs_reply("", "U1").starts_with("*Your preferences*\n• verbosity: normal"));
+    }
+}
@@ -0,0 +1,151 @@
+//! The asker's Slack profile (users.info), added to the system prompt to personalize answers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::requestid::log;
+use crate::slack::api::SlackApi;
+use crate::store::{store_get, store_set};
+use crate::workspaces::{current_workspace, workspace_config};
+
+/* ---- Requester context (PERSONALIZE_ANSWERS) ---- */
+// Answers can be written for the person asking: their display name, time zone and Slack locale
+// come from users.info (`users:read`) and go into the system prompt with the current time in
+// their zone, so "tomorrow at 9" or "in three hours" is worked out in their local time. This is
+// off unless PERSONALIZE_ANSWERS=on, because it sends the name to the model provider; a
+// workspace's `personalize` setting overrides the variable either way. Profiles are cached for a
+// day under `slack-profile:{user}`, and /agent-forget me removes the copy. When the lookup fails,
+// the answer is written without it.
+const PROFILE_CACHE_SECS: u64 = 86_400;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct CachedProfile {
+    name: String,
+    /// IANA time zone, e.g. `Europe/Paris`
+    tz: Option<String>,
+    /// Seconds east of UTC, daylight saving included, as of the lookup
+    tz_offset: i64,
+    /// Slack's locale for the user, e.g. `en-US`
+    locale: Option<String>,
+    fetched_at: u64,
+}
+
+pub(crate) fn profile_key(user_id: &str) -> String {
+    format!("slack-profile:{user_id}")
+}
+
+fn personalization_enabled() -> bool {
+    if let Some(setting) = workspace_config(&current_workspace()).ok().flatten().and_then(|c| c.personalize) {
+        return setting;
+    }
+    get_env_var("PERSONALIZE_ANSWERS").is_some_and(|v| matches!(v.trim(), "on" | "true" | "1"))
+}
+
+fn cached_profile(user_id: &str) -> Result<CachedProfile, AgentError> {
+    let key = profile_key(user_id);
+    let cached = store_get(&key).ok().flatten().and_then(|raw| serde_json::from_str::<CachedProfile>(&raw).ok());
+    if let Some(profile) = cached.filter(|p| now_secs().saturating_sub(p.fetched_at) < PROFILE_CACHE_SECS) {
+        return Ok(profile);
+    }
+    let user = SlackApi::from_env()?.user_info(user_id)?;
+    let profile = CachedProfile {
+        name: user.display_name().to_string(),
+        tz: user.tz.clone(),
+        tz_offset: user.tz_offset,
+        locale: user.locale.clone(),
+        fetched_at: now_secs(),
+    };
+    if let Err(e) = store_set(&key, &serde_json::to_string(&profile).unwrap_or_default()) {
+        log!("profile of {user_id} not cached: {e}");
+    }
+    Ok(profile)
+}
+
+impl CachedProfile {
+    fn describe(&self, now: u64) -> String {
+        let mut parts = Vec::new();
+        if !self.name.is_empty() {
+            parts.push(format!("You are talking to {}; address them by name where it is natural.", self.name));
+        }
+        let offset = i32::try_from(self.tz_offset).ok().and_then(chrono::FixedOffset::east_opt);
+        let local = offset.and_then(|offset| Some(chrono::DateTime::from_timestamp(now as i64, 0)?.with_timezone(&offset)));
+        if let (Some(tz), Some(local)) = (&self.tz, local) {
+            parts.push(format!(
+                "Their time zone is {tz} (UTC{}), where it is now {}. Use it for dates and times unless they name another zone.",
+                local.format("%:z"),
+                local.format("%A %Y-%m-%d %H:%M")
+            ));
+        }
+        if let Some(locale) = &self.locale {
+            parts.push(format!("Their Slack locale is {locale}; follow its conventions for dates, numbers and units."));
+        }
+        parts.join(" ")
+    }
+}
+
+/// What the system prompt should know about `user_id`, or `None` when personalization is off or
+/// the profile is unavailable.
+pub(crate) fn requester_context(user_id: &str) -> Option<String> {
+    if user_id.is_empty() || !personalization_enabled() {
+        return None;
+    }
+    match cached_profile(user_id) {
+        Ok(profile) => Some(profile.describe(now_secs())).filter(|c| !c.is_empty()),
+        Err(e) => {
+            log!("users.info for {user_id} failed, answering without the profile: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+    use crate::workspaces::set_workspace;
+
+    #[test]
+    fn profiles_are_looked_up_once_and_described_in_local_time() {
+        with_openai_env();
+        let clock = MockClock::install(1_700_000_000);
+        MockStore::install();
+        set_workspace("T1");
+        store_set("workspace-config:T1", r#"{"personalize": true}"#).unwrap();
+        let mock = MockHttpClient::install();
+        let user = r#"{"ok": true, "user": {"id": "U1", "name": "ada", "tz": "Europe/Paris", "tz_offset": 3600, "locale": "fr-FR",
+            "profile": {"display_name": "Ada"}}}"#;
+        mock.respond(200, "application/json", user);
+        mock.respond(200, "application/json", user);
+
+        let context = requester_context("U1").unwrap();
+        assert_eq!(
+            context,
+            "You are talking to Ada; address them by name where it is natural. Their time zone is Europe/Paris (UTC+01:00), where it is now \
+             Tuesday 2023-11-14 23:13. Use it for dates and times unless they name another zone. Their Slack locale is fr-FR; follow its \
+             conventions for dates, numbers and units."
+        );
+        assert_eq!(requester_context("U1").unwrap(), context);
+        assert_eq!(mock.requests.borrow().len(), 1);
+        assert_eq!(mock.requests.borrow()[0].url, "https://slack.com/api/users.info?user=U1&include_locale=true");
+        clock.advance(PROFILE_CACHE_SECS);
+        requester_context("U1").unwrap();
+        assert_eq!(mock.requests.borrow().len(), 2);
+    }
+
+    #[test]
+    fn personalization_is_off_unless_enabled() {
+        with_openai_env();
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        set_workspace("T2");
+        assert_eq!(requester_context("U1"), None);
+        store_set("workspace-config:T2", r#"{"personalize": false}"#).unwrap();
+        assert_eq!(requester_context("U1"), None);
+        assert!(mock.requests.borrow().is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+//! Model strings (`provider/model`) and the chat completions endpoint of each provider.
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::huggingface::{DEFAULT_HF_MODEL, hf_chat_url, hf_token};
+use crate::mistral::{DEFAULT_MISTRAL_MODEL, mistral_api_key, mistral_chat_url, mistral_payload};
+use crate::residency::{current_region, regional_var};
+use crate::sigv4::AwsCredentials;
+
+/* ---- Model strings ---- */
+// Anywhere a model is configured it may start with the provider that serves it:
+// `anthropic/claude-3-5-sonnet-latest`, `azure/my-deployment`, `mistral/mistral-large-latest`,
+// `huggingface/meta-llama/Llama-3.1-8B-Instruct`, `bedrock/openai.gpt-oss-120b-1:0`. `provider:model` is accepted too. Only the
+// provider names below count as a prefix, so Hugging Face IDs like `meta-llama/...` and OpenAI
+// fine-tunes like `ft:gpt-4o-mini:acme::abc123` go to LLM_PROVIDER unchanged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Provider {
+    OpenAi,
+    Azure,
+    Anthropic,
+    HuggingFace,
+    Mistral,
+    Bedrock,
+}
+
+impl Provider {
+    fn from_prefix(prefix: &str) -> Option<Provider> {
+        match prefix.to_ascii_lowercase().as_str() {
+            "openai" => Some(Provider::OpenAi),
+            "azure" => Some(Provider::Azure),
+            "anthropic" => Some(Provider::Anthropic),
+            "huggingface" | "hf" => Some(Provider::HuggingFace),
+            "mistral" => Some(Provider::Mistral),
+            "bedrock" => Some(Provider::Bedrock),
+            _ => None,
+        }
+    }
+}
+
+/// LLM_PROVIDER, for models without a provider prefix.
+pub(crate) fn configured_provider() -> Provider {
+    get_env_var("LLM_PROVIDER").and_then(|p| Provider::from_prefix(p.trim())).unwrap_or(Provider::OpenAi)
+}
+
+/// The provider a model string names explicitly, and the rest of the string.
+pub(crate) fn split_provider(model: &str) -> Option<(Provider, &str)> {
+    let at = model.find(['/', ':'])?;
+    Provider::from_prefix(&model[..at]).map(|provider| (provider, &model[at + 1..]))
+}
+
+/// The provider that serves `model`, and the model name to send to it.
+pub(crate) fn parse_model(model: &str) -> (Provider, &str) {
+    split_provider(model).unwrap_or((configured_provider(), model))
+}
+
+/// The model for a call only OpenAI offers (transcription, images): unprefixed or `openai/`.
+pub(crate) fn openai_only_model<'a>(var: &str, model: &'a str) -> Result<&'a str, AgentError> {
+    if let Some(region) = current_region() {
+        return Err(AgentError::Config(format!("{var}: this workspace's data must stay in region '{region}', which OpenAI-only calls cannot")));
+    }
+    match split_provider(model) {
+        None => Ok(model),
+        Some((Provider::OpenAi, name)) => Ok(name),
+        Some(_) => Err(AgentError::Config(format!("{var}: '{model}' is not an OpenAI model; only OpenAI serves this"))),
+    }
+}
+
+pub(crate) fn default_chat_model() -> &'static str {
+    match configured_provider() {
+        Provider::HuggingFace => DEFAULT_HF_MODEL,
+        Provider::Mistral => DEFAULT_MISTRAL_MODEL,
+        Provider::Anthropic => DEFAULT_ANTHROPIC_MODEL,
+        Provider::Bedrock => DEFAULT_BEDROCK_MODEL,
+        Provider::OpenAi | Provider::Azure => "gpt-4o-mini",
+    }
+}
+
+/* ---- Endpoints (OPENAI_API_KEY, ANTHROPIC_API_KEY, AZURE_OPENAI_*) ---- */
+// Every provider here speaks the OpenAI chat completions format. Anthropic through its OpenAI
+// SDK compatibility endpoint, which ignores `response_format`, `seed` and `logit_bias`. Azure
+// addresses a deployment rather than a model and authenticates with an `api-key` header.
+const OPENAI_CHAT_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_ANTHROPIC_MODEL: &str = "claude-3-5-sonnet-latest";
+const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+/* ---- Amazon Bedrock (AWS_REGION, AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY) ---- */
+// Bedrock's OpenAI-compatible chat completions endpoint takes no key: every request is signed
+// with the AWS credentials (SigV4, see sigv4.rs) for the region in the endpoint's host, under
+// the `bedrock` signing name that the control-plane model list used by the probe shares.
+const DEFAULT_BEDROCK_MODEL: &str = "openai.gpt-oss-20b-1:0";
+pub(crate) const BEDROCK_SERVICE: &str = "bedrock";
+
+fn bedrock_endpoint(region: String) -> Result<ChatEndpoint, AgentError> {
+    // Fail on missing credentials here, as the other providers do for their keys
+    AwsCredentials::from_env()?;
+    Ok(ChatEndpoint {
+        provider: Provider::Bedrock,
+        url: format!("https://bedrock-runtime.{region}.amazonaws.com/openai/v1/chat/completions"),
+        api_key: String::new(),
+        headers: Vec::new(),
+        aws_region: Some(region),
+    })
+}
+
+pub(crate) struct ChatEndpoint {
+    pub(crate) provider: Provider,
+    pub(crate) url: String,
+    /// Sent as a bearer token; empty when `headers` carry the credentials
+    pub(crate) api_key: String,
+    pub(crate) headers: Vec<(&'static str, String)>,
+    /// Set for Bedrock, whose requests are signed for this AWS region instead of carrying a key
+    pub(crate) aws_region: Option<String>,
+}
+
+fn required(var: &str) -> Result<String, AgentError> {
+    get_env_var(var).ok_or_else(|| AgentError::Config(format!("{var} not set")))
+}
+
+fn azure_endpoint(endpoint: &str, deployment: &str, api_key: String) -> ChatEndpoint {
+    let version = get_env_var("AZURE_OPENAI_API_VERSION").unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string());
+    ChatEndpoint {
+        provider: Provider::Azure,
+        url: format!("{}/openai/deployments/{deployment}/chat/completions?api-version={version}", endpoint.trim_end_matches('/')),
+        api_key: String::new(),
+        headers: vec![("api-key", api_key)],
+        aws_region: None,
+    }
+}
+
+/// Where to post a chat completion for `model`, with the credentials it needs.
+pub(crate) fn chat_endpoint(model: &str) -> Result<ChatEndpoint, AgentError> {
+    let (provider, name) = parse_model(model);
+    if let Some(region) = current_region() {
+        return regional_chat_endpoint(provider, name, &region);
+    }
+    let bearer = |url: String, api_key: String| ChatEndpoint { provider, url, api_key, headers: Vec::new(), aws_region: None };
+    match provider {
+        Provider::OpenAi => Ok(bearer(OPENAI_CHAT_URL.to_string(), required("OPENAI_API_KEY")?)),
+        Provider::HuggingFace => Ok(bearer(hf_chat_url(name), hf_token()?)),
+        Provider::Mistral => Ok(bearer(mistral_chat_url(), mistral_api_key()?)),
+        Provider::Anthropic => {
+            let base = get_env_var("ANTHROPIC_API_BASE").unwrap_or_else(|| "https://api.anthropic.com".to_string());
+            Ok(bearer(format!("{}/v1/chat/completions", base.trim_end_matches('/')), required("ANTHROPIC_API_KEY")?))
+        }
+        Provider::Azure => Ok(azure_endpoint(&required("AZURE_OPENAI_ENDPOINT")?, name, required("AZURE_OPENAI_API_KEY")?)),
+        Provider::Bedrock => bedrock_endpoint(required("AWS_REGION")?),
+    }
+}
+
+/* ---- Regional endpoints (AZURE_OPENAI_ENDPOINT_{REGION}, OPENAI_API_BASE_{REGION}, ...) ---- */
+// A workspace pinned to a region (see residency.rs) is served only by endpoints configured for
+// it under the region-suffixed variable; when the provider has none, the call fails instead of
+// going to the default endpoint. Keys may be shared across regions, so `{KEY}_{REGION}` falls
+// back to the plain key.
+fn regional_chat_endpoint(provider: Provider, name: &str, region: &str) -> Result<ChatEndpoint, AgentError> {
+    let pinned = |var: &str| {
+        let var = regional_var(var, region);
+        get_env_var(&var).ok_or_else(|| AgentError::Config(format!("{var} not set; this workspace's data must stay in region '{region}'")))
+    };
+    let key = |var: &str| get_env_var(&regional_var(var, region)).map_or_else(|| required(var), Ok);
+    let bearer = |base: String, api_key: String| ChatEndpoint {
+        provider,
+        url: format!("{}/v1/chat/completions", base.trim_end_matches('/')),
+        api_key,
+        headers: Vec::new(),
+        aws_region: None,
+    };
+    match provider {
+        Provider::OpenAi => Ok(bearer(pinned("OPENAI_API_BASE")?, key("OPENAI_API_KEY")?)),
+        Provider::Anthropic => Ok(bearer(pinned("ANTHROPIC_API_BASE")?, key("ANTHROPIC_API_KEY")?)),
+        Provider::Mistral => Ok(bearer(pinned("MISTRAL_API_BASE")?, key("MISTRAL_API_KEY")?)),
+        // Only a dedicated Inference Endpoint has a known region
+        Provider::HuggingFace => Ok(bearer(pinned("HF_ENDPOINT_URL")?, key("HF_TOKEN")?)),
+        Provider::Azure => Ok(azure_endpoint(&pinned("AZURE_OPENAI_ENDPOINT")?, name, key("AZURE_OPENAI_API_KEY")?)),
+        // AWS_REGION_{REGION} names the AWS region that serves the workspace's region
+        Provider::Bedrock => bedrock_endpoint(pinned("AWS_REGION")?),
+    }
+}
+
+/// A cheap authenticated GET showing the provider of `model` is reachable and takes the
+/// credentials, without running a completion: the model list, or the token's owner on Hugging Face.
+/// On Bedrock the GET carries no headers and is signed for the returned AWS region instead.
+pub(crate) fn credentials_probe(model: &str) -> Result<(String, Vec<(&'static str, String)>, Option<String>), AgentError> {
+    let endpoint = chat_endpoint(model)?;
+    let aws_region = endpoint.aws_region.clone();
+    let bearer = vec![("authorization", format!("Bearer {}", endpoint.api_key))];
+    let (url, headers) = match endpoint.provider {
+        Provider::OpenAi | Provider::Mistral => (endpoint.url.replace("/chat/completions", "/models"), bearer),
+        Provider::HuggingFace => ("https://huggingface.co/api/whoami-v2".to_string(), bearer),
+        // The native model list takes the key as `x-api-key` rather than a bearer token
+        Provider::Anthropic => (
+            endpoint.url.replace("/chat/completions", "/models"),
+            vec![("x-api-key", endpoint.api_key), ("anthropic-version", "2023-06-01".to_string())],
+        ),
+        Provider::Azure => {
+            let (base, query) = endpoint.url.split_once("/openai/deployments/").unwrap_or((&endpoint.url, ""));
+            let version = query.split_once("api-version=").map(|(_, v)| v).unwrap_or(DEFAULT_AZURE_API_VERSION);
+            (format!("{base}/openai/models?api-version={version}"), endpoint.headers)
+        }
+        Provider::Bedrock => (format!("https://bedrock.{}.amazonaws.com/foundation-models", aws_region.as_deref().unwrap_or_default()), Vec::new()),
+    };
+    Ok((url, headers, aws_region))
+}
+
+/// The payload as its model's provider expects it: the bare model name plus provider-specific fields.
+pub(crate) fn provider_payload(payload: &serde_json::Value) -> serde_json::Value {
+    let mut payload = payload.clone();
+    let model = payload["model"].as_str().unwrap_or_default().to_string();
+    let (provider, name) = parse_model(&model);
+    payload["model"] = serde_json::json!(name);
+    if provider == Provider::Mistral {
+        mistral_payload(&mut payload);
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::residency::set_region;
+    use std::env;
+
+    #[test]
+    fn provider_prefixes_are_parsed_with_either_separator() {
+        assert_eq!(parse_model("anthropic/claude-3-5-sonnet"), (Provider::Anthropic, "claude-3-5-sonnet"));
+        assert_eq!(parse_model("azure/my-deployment"), (Provider::Azure, "my-deployment"));
+        assert_eq!(parse_model("mistral:mistral-large-latest"), (Provider::Mistral, "mistral-large-latest"));
+        assert_eq!(parse_model("HF/meta-llama/Llama-3.1-8B-Instruct"), (Provider::HuggingFace, "meta-llama/Llama-3.1-8B-Instruct"));
+        assert_eq!(split_provider("meta-llama/Llama-3.1-8B-Instruct"), None);
+        assert_eq!(split_provider("ft:gpt-4o-mini:acme::abc123"), None);
+        assert_eq!(openai_only_model("IMAGE_MODEL", "openai/dall-e-3"), Ok("dall-e-3"));
+        assert!(openai_only_model("IMAGE_MODEL", "mistral/pixtral").is_err());
+    }
+
+    #[test]
+    fn azure_deployments_use_an_api_key_header() {
+        env::set_var("AZURE_OPENAI_ENDPOINT", "https://acme.openai.azure.com/");
+        env::set_var("AZURE_OPENAI_API_KEY", "azure-test");
+        let endpoint = chat_endpoint("azure/gpt4o-prod").unwrap();
+        assert_eq!(endpoint.url, "https://acme.openai.azure.com/openai/deployments/gpt4o-prod/chat/completions?api-version=2024-10-21");
+        assert_eq!((endpoint.api_key.as_str(), endpoint.headers), ("", vec![("api-key", "azure-test".to_string())]));
+    }
+
+    #[test]
+    fn pinned_workspaces_use_only_their_regions_endpoints() {
+        env::set_var("AZURE_OPENAI_ENDPOINT_NORDICS", "https://acme-se.openai.azure.com");
+        env::set_var("AZURE_OPENAI_API_KEY", "azure-test");
+        set_region(Some("nordics".to_string()));
+        let endpoint = chat_endpoint("azure/gpt4o-prod").unwrap();
+        assert_eq!(endpoint.url, "https://acme-se.openai.azure.com/openai/deployments/gpt4o-prod/chat/completions?api-version=2024-10-21");
+        let err = chat_endpoint("anthropic/claude-3-5-sonnet-latest").err().unwrap();
+        assert_eq!(err.to_string(), "ANTHROPIC_API_BASE_NORDICS not set; this workspace's data must stay in region 'nordics'");
+        assert!(openai_only_model("IMAGE_MODEL", "dall-e-3").is_err());
+        set_region(None);
+    }
+}
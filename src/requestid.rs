@@ -0,0 +1,63 @@
+//! Per-request IDs, echoed in responses, log lines and the errors Slack users see.
+
+use crate::store::new_id;
+use std::cell::RefCell;
+
+/* ---- Request IDs (X-Request-Id) ---- */
+// A caller (or a proxy in front of the component) may pass its own ID so one value follows the
+// request through every hop; anything that is not a short token is replaced with a fresh one,
+// since the ID is echoed into headers and logs. Each instance handles one request at a time, so
+// the ID of the request in flight is kept per thread for log lines and user-facing errors.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+thread_local! {
+    static CURRENT: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+fn valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Starts handling a request: the caller's `X-Request-Id` if usable, else a new ID.
+pub(crate) fn begin_request(header: Option<&str>) -> String {
+    let id = header.map(str::trim).filter(|id| valid_request_id(id)).map(str::to_string).unwrap_or_else(new_id);
+    CURRENT.with(|current| *current.borrow_mut() = id.clone());
+    id
+}
+
+/// The ID of the request being handled; empty outside a request.
+pub(crate) fn current_request_id() -> String {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// An error message for a Slack user, with the reference support can look up in the logs.
+pub(crate) fn with_ref(message: &str, request_id: &str) -> String {
+    if request_id.is_empty() {
+        message.to_string()
+    } else {
+        format!("{message} (ref: {request_id})")
+    }
+}
+
+/// `println!` prefixed with the ID of the request being handled.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        println!("[{}] {}", $crate::requestid::current_request_id(), format_args!($($arg)*))
+    };
+}
+pub(crate) use log;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caller_ids_are_kept_only_when_they_are_short_tokens() {
+        assert_eq!(begin_request(Some(" req-42.a_b ")), "req-42.a_b");
+        assert_eq!(current_request_id(), "req-42.a_b");
+        let replaced = begin_request(Some("bad id\r\nx-evil: 1"));
+        assert!(valid_request_id(&replaced) && replaced != "bad id\r\nx-evil: 1");
+        assert_ne!(begin_request(Some(&"a".repeat(65))).len(), 65);
+        assert_eq!(with_ref("Translation unavailable: timeout", "abc123"), "Translation unavailable: timeout (ref: abc123)");
+    }
+}
@@ -0,0 +1,119 @@
+//! Relevance scoring of fetched sources before a multi-source answer is written.
+
+use crate::config::get_env_var;
+use crate::documents::truncate_chars;
+use crate::error::AgentError;
+use crate::llm::call_openai_json;
+
+/* ---- LLM rerank (RERANK_MIN_SCORE) ---- */
+// One JSON-mode call rates an excerpt of every source from 0 to 1. Sources scoring below
+// RERANK_MIN_SCORE (default 0.3) are left out, and the document budget is shared among the rest
+// in proportion to their scores. Reranking is best effort: if the call fails every source keeps
+// an equal share, as before.
+const RERANK_EXCERPT_CHARS: usize = 1_500;
+const DEFAULT_RERANK_MIN_SCORE: f64 = 0.3;
+// A source the model did not rate is kept rather than silently dropped
+const UNRATED_SCORE: f64 = 0.5;
+
+const RERANK_PROMPT: &str = "You judge search results. Rate how useful each numbered source is for answering the \
+user's question, from 0.0 (unrelated) to 1.0 (directly answers it). Respond only with a JSON object: \
+{\"scores\": [{\"source\": <number>, \"score\": <0.0-1.0>}]}";
+
+fn min_score() -> f64 {
+    get_env_var("RERANK_MIN_SCORE").and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_RERANK_MIN_SCORE)
+}
+
+/// Relevance of each source to `query`; sources without text score 0 and are not sent.
+fn score_sources(query: &str, texts: &[Option<&str>]) -> Result<Vec<f64>, AgentError> {
+    let excerpts: Vec<String> = texts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, text)| text.map(|t| format!("[{}]\n{}", i + 1, truncate_chars(t, RERANK_EXCERPT_CHARS))))
+        .collect();
+    let json = call_openai_json(RERANK_PROMPT, &format!("Question: {query}\n\nSources:\n\n{}", excerpts.join("\n\n")))?;
+    let rated: Vec<(usize, f64)> = json["scores"]
+        .as_array()
+        .map(|scores| {
+            scores
+                .iter()
+                .filter_map(|s| Some((s["source"].as_u64()? as usize, s["score"].as_f64()?.clamp(0.0, 1.0))))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(texts
+        .iter()
+        .enumerate()
+        .map(|(i, text)| match text {
+            Some(_) => rated.iter().find(|(n, _)| *n == i + 1).map_or(UNRATED_SCORE, |(_, score)| *score),
+            None => 0.0,
+        })
+        .collect())
+}
+
+/// Share of the document budget for each source, summing to 1 over the sources worth using.
+/// Unavailable and off-topic sources get 0; the best source is always kept if any has text.
+pub(crate) fn source_weights(query: &str, texts: &[Option<&str>]) -> Vec<f64> {
+    let available = texts.iter().filter(|t| t.is_some()).count();
+    let equal = || texts.iter().map(|t| if t.is_some() { 1.0 } else { 0.0 }).collect::<Vec<f64>>();
+    // With a single source there is nothing to weigh it against
+    let scores = if available > 1 { score_sources(query, texts).unwrap_or_else(|_| equal()) } else { equal() };
+    let threshold = min_score();
+    let best = scores.iter().cloned().enumerate().filter(|(_, s)| *s > 0.0).max_by(|a, b| a.1.total_cmp(&b.1)).map(|(i, _)| i);
+    let kept: Vec<f64> = scores
+        .iter()
+        .enumerate()
+        .map(|(i, s)| if (*s > 0.0 && *s >= threshold) || Some(i) == best { *s } else { 0.0 })
+        .collect();
+    let total: f64 = kept.iter().sum();
+    if total <= 0.0 {
+        return kept;
+    }
+    kept.iter().map(|s| s / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+
+    #[test]
+    fn off_topic_sources_are_dropped_and_the_rest_weighted_by_score() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(
+            200,
+            "application/json",
+            &completion(r#"{"scores": [{"source": 1, "score": 0.9}, {"source": 2, "score": 0.05}, {"source": 4, "score": 0.3}]}"#),
+        );
+
+        let texts = [Some("Rust 1.80 stabilized LazyLock."), Some("Cookie recipes"), None, Some("Release notes index")];
+        let weights = source_weights("what did Rust 1.80 stabilize?", &texts);
+        assert_eq!(weights, [0.75, 0.0, 0.0, 0.25]);
+
+        let body: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        let sent = body["messages"][1]["content"].as_str().unwrap();
+        assert!(sent.starts_with("Question: what did Rust 1.80 stabilize?"), "{sent}");
+        assert!(sent.contains("[2]\nCookie recipes") && !sent.contains("[3]"), "{sent}");
+    }
+
+    #[test]
+    fn failed_rerank_keeps_available_sources_equally() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(500, "application/json", r#"{"error": "overloaded"}"#);
+        assert_eq!(source_weights("q", &[Some("a"), None, Some("b")]), [0.5, 0.0, 0.5]);
+        // A lone source is used without asking the model
+        assert_eq!(source_weights("q", &[None, Some("a")]), [0.0, 1.0]);
+        assert_eq!(mock.requests.borrow().len(), 1);
+    }
+
+    #[test]
+    fn best_source_is_kept_when_all_score_low() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion(r#"{"scores": [{"source": 1, "score": 0.1}, {"source": 2, "score": 0.2}]}"#));
+        assert_eq!(source_weights("q", &[Some("a"), Some("b")]), [0.0, 1.0]);
+    }
+}
@@ -0,0 +1,56 @@
+//! Data residency: the provider region a workspace's requests are pinned to.
+
+use std::cell::RefCell;
+
+use crate::workspaces::workspace_config;
+
+/* ---- Workspace regions (`region` in workspace-config:{team_id}) ---- */
+// A workspace with `"region": "eu"` in its settings has its model calls sent only to endpoints
+// configured for that region (`AZURE_OPENAI_ENDPOINT_EU` and so on, see `chat_endpoint`), never
+// to the default ones, and the history and jobs it stores are tagged with the region. Queued
+// jobs carry the tag, so work finished later stays in the region too. Requests that do not come
+// from a workspace (the API, scheduled fetches) are not pinned.
+thread_local! {
+    static REGION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// The region configured for `team_id`, lowercased; `None` when it has none.
+pub(crate) fn workspace_region(team_id: &str) -> Option<String> {
+    if team_id.is_empty() {
+        return None;
+    }
+    let region = workspace_config(team_id).ok().flatten()?.region?;
+    Some(region.trim().to_ascii_lowercase()).filter(|r| !r.is_empty())
+}
+
+/// Pins the calls made for the current request (or job) to `region`.
+pub(crate) fn set_region(region: Option<String>) {
+    REGION.with(|current| *current.borrow_mut() = region);
+}
+
+pub(crate) fn current_region() -> Option<String> {
+    REGION.with(|current| current.borrow().clone())
+}
+
+/// The name of `var` for `region`: `AZURE_OPENAI_ENDPOINT` in `eu-west` is `AZURE_OPENAI_ENDPOINT_EU_WEST`.
+pub(crate) fn regional_var(var: &str, region: &str) -> String {
+    let suffix: String = region.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+    format!("{var}_{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{MockStore, store_set};
+
+    #[test]
+    fn workspaces_are_pinned_to_their_configured_region() {
+        MockStore::install();
+        store_set("workspace-config:T0EU", r#"{"intro_channel": null, "region": " EU-West "}"#).unwrap();
+        store_set("workspace-config:T0US", r#"{"intro_channel": null}"#).unwrap();
+        assert_eq!(workspace_region("T0EU").as_deref(), Some("eu-west"));
+        assert_eq!(workspace_region("T0US"), None);
+        assert_eq!(workspace_region(""), None);
+        assert_eq!(regional_var("AZURE_OPENAI_ENDPOINT", "eu-west"), "AZURE_OPENAI_ENDPOINT_EU_WEST");
+    }
+}
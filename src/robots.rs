@@ -0,0 +1,224 @@
+//! robots.txt rules for fetches the agent makes on its own.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::error::AgentError;
+use crate::httpclient::{fetch_response, redact_userinfo};
+use crate::store::{store_get, store_set};
+
+/* ---- robots.txt (RFC 9309) ---- */
+// Scheduled jobs fetch pages nobody asked for at that moment, so they honour the site's
+// robots.txt. URLs a user explicitly asked for (summaries, research queries, debug probes) are
+// fetched on that user's behalf and skip the check, as does a job configured with
+// `ignore_robots`. The parsed rules for each origin are cached in the store for a day.
+pub(crate) const ROBOTS_USER_AGENT: &str = "ai-agent";
+const ROBOTS_CACHE_SECS: u64 = 24 * 3600;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+struct RobotsRules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedRobots {
+    fetched_at: u64,
+    rules: RobotsRules,
+}
+
+/// The rules of the groups naming `agent`, or of the `*` groups when none does.
+fn parse_robots(txt: &str, agent: &str) -> RobotsRules {
+    let agent = agent.to_ascii_lowercase();
+    let (mut named, mut wildcard) = (Vec::new(), Vec::new());
+    let mut group_agents: Vec<String> = Vec::new();
+    let mut in_rules = false;
+    let mut named_group = false;
+    for line in txt.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                // A user-agent line after rules starts a new group
+                if in_rules {
+                    group_agents.clear();
+                    in_rules = false;
+                }
+                named_group |= value.eq_ignore_ascii_case(&agent);
+                group_agents.push(value.to_ascii_lowercase());
+            }
+            field @ ("allow" | "disallow") => {
+                in_rules = true;
+                if value.is_empty() {
+                    continue;
+                }
+                let rule = Rule { allow: field == "allow", pattern: value.to_string() };
+                if group_agents.iter().any(|a| *a == agent) {
+                    named.push(rule.clone());
+                }
+                if group_agents.iter().any(|a| a == "*") {
+                    wildcard.push(rule);
+                }
+            }
+            _ => {}
+        }
+    }
+    RobotsRules { rules: if named_group { named } else { wildcard } }
+}
+
+impl RobotsRules {
+    /// The longest matching pattern decides; on a tie `Allow` wins. No match means allowed.
+    fn allows(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+}
+
+/// Prefix match where `*` matches any run of characters and a trailing `$` anchors the end.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut pieces = pattern.split('*');
+    let first = pieces.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else { return false };
+    let pieces: Vec<&str> = pieces.collect();
+    for (i, piece) in pieces.iter().enumerate() {
+        // The last piece of an anchored pattern must sit at the very end
+        if anchored && i == pieces.len() - 1 {
+            return rest.ends_with(piece);
+        }
+        match rest.find(piece) {
+            Some(at) => rest = &rest[at + piece.len()..],
+            None => return false,
+        }
+    }
+    !anchored || (pieces.is_empty() && rest.is_empty())
+}
+
+/// (`scheme://authority`, path and query) of `url`.
+fn split_origin(url: &str) -> Result<(String, String), AgentError> {
+    let (scheme, rest) = url.split_once("://").ok_or_else(|| AgentError::Validation(format!("invalid URL: {url}")))?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let tail = &rest[end..];
+    let tail = &tail[..tail.find('#').unwrap_or(tail.len())];
+    let path = if tail.starts_with('/') { tail.to_string() } else { format!("/{tail}") };
+    Ok((format!("{}://{}", scheme.to_ascii_lowercase(), &rest[..end]), path))
+}
+
+/// Rules for `origin`, from the store when fetched within the last day.
+fn rules_for(origin: &str) -> Result<RobotsRules, AgentError> {
+    let key = format!("robots:{}", redact_userinfo(origin).to_ascii_lowercase());
+    let now = now_secs();
+    let cached: Option<CachedRobots> = store_get(&key).ok().flatten().and_then(|raw| serde_json::from_str(&raw).ok());
+    if let Some(cached) = cached.filter(|c| now.saturating_sub(c.fetched_at) < ROBOTS_CACHE_SECS) {
+        return Ok(cached.rules);
+    }
+    let resp = fetch_response(&format!("{origin}/robots.txt"), &[], None)
+        .map_err(|e| e.context(&format!("robots.txt for {}", redact_userinfo(origin))))?;
+    let rules = match resp.status {
+        200..=299 => parse_robots(&resp.text(), ROBOTS_USER_AGENT),
+        // No robots.txt (or no access to it) means no restrictions
+        400..=499 => RobotsRules::default(),
+        // A server error is treated as a temporary full disallow and is not cached
+        status => {
+            return Err(AgentError::Http(format!("robots.txt for {} unavailable: HTTP {status}", redact_userinfo(origin))));
+        }
+    };
+    let entry = CachedRobots { fetched_at: now, rules };
+    let _ = store_set(&key, &serde_json::to_string(&entry).unwrap_or_default());
+    Ok(entry.rules)
+}
+
+/// Fails with a validation error when the site's robots.txt disallows `url` for this agent.
+pub(crate) fn ensure_allowed(url: &str) -> Result<(), AgentError> {
+    let (origin, path) = split_origin(url)?;
+    if rules_for(&origin)?.allows(&path) {
+        Ok(())
+    } else {
+        Err(AgentError::Validation(format!("{} is disallowed by robots.txt for {ROBOTS_USER_AGENT}", redact_userinfo(url))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+
+    const ROBOTS: &str = "\
+# example
+User-agent: *
+Disallow: /private/
+Allow: /private/press/
+Disallow: /*.pdf$
+
+User-agent: ai-agent
+User-agent: other-bot
+Disallow: /drafts   # unpublished
+Disallow:
+";
+
+    #[test]
+    fn groups_for_this_agent_replace_the_wildcard_group() {
+        let ours = parse_robots(ROBOTS, "AI-Agent");
+        assert!(!ours.allows("/drafts/q3"));
+        assert!(ours.allows("/private/x"));
+
+        let others = parse_robots(ROBOTS, "somebody-else");
+        assert!(others.allows("/drafts/q3"));
+        assert!(!others.allows("/private/x"));
+        assert!(others.allows("/private/press/release"));
+        assert!(!others.allows("/files/report.pdf"));
+        assert!(others.allows("/files/report.pdf?download=1"));
+        assert!(others.allows("/robots.txt"));
+    }
+
+    #[test]
+    fn wildcards_and_anchors_match_like_rfc_9309() {
+        assert!(pattern_matches("/", "/anything"));
+        assert!(pattern_matches("/a*c", "/abbbc/d"));
+        assert!(pattern_matches("/*.php$", "/x/index.php"));
+        assert!(!pattern_matches("/*.php$", "/x/index.php5"));
+        assert!(pattern_matches("/exact$", "/exact"));
+        assert!(!pattern_matches("/exact$", "/exact/"));
+        assert!(!pattern_matches("/a*c", "/ab"));
+    }
+
+    #[test]
+    fn rules_are_fetched_once_per_origin_and_cached() {
+        MockStore::install();
+        let clock = MockClock::install(1_700_000_000);
+        let mock = MockHttpClient::install();
+        mock.respond(200, "text/plain", "User-agent: *\nDisallow: /admin\n");
+
+        assert!(ensure_allowed("https://example.com/news?page=2").is_ok());
+        let err = ensure_allowed("https://example.com/admin/users").err().unwrap();
+        assert!(matches!(err, AgentError::Validation(ref m) if m.contains("disallowed by robots.txt")), "{err}");
+        assert_eq!(mock.requests.borrow().len(), 1);
+        assert_eq!(mock.requests.borrow()[0].url, "https://example.com/robots.txt");
+
+        // A missing robots.txt allows everything; server errors block without being cached
+        clock.advance(ROBOTS_CACHE_SECS);
+        mock.respond(503, "text/plain", "down");
+        assert!(matches!(ensure_allowed("https://example.com/admin"), Err(AgentError::Http(_))));
+        mock.respond(404, "text/plain", "not found");
+        assert!(ensure_allowed("https://example.com/admin").is_ok());
+        assert!(ensure_allowed("https://example.com/admin").is_ok());
+        assert_eq!(mock.requests.borrow().len(), 3);
+    }
+}
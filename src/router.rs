@@ -0,0 +1,1872 @@
+//! Plain request/response types, the route registry and the handlers that sit directly on it.
+
+use std::env;
+
+use crate::admins::admin_command_denial;
+use crate::bindings::wasi::http::types as http;
+use crate::bindings::wasi::http::types::Method;
+use crate::bindings::wasi::io::streams;
+use crate::broadcast::slack_broadcast_reply;
+use crate::buildinfo::build_info;
+use crate::config::get_env_var;
+use crate::cookies::CookieJar;
+use crate::error::AgentError;
+use crate::events::handle_slack_event;
+use crate::experiments::{slack_prompt_report, slack_quality_reply};
+use crate::form::{parse_query_params, split_path_and_query};
+use crate::forget::slack_forget_reply;
+use crate::health_status;
+use crate::history::slack_export_reply;
+use crate::inbound::{BodyRejected, read_request_body};
+use crate::idempotency::first_delivery;
+use crate::interactive::handle_interaction;
+use crate::httpclient::{
+    fetch_response,
+    http_post_json,
+    http_probe,
+    parse_header_specs,
+    redact_userinfo,
+    write_all,
+};
+use crate::imagine::slack_imagine_reply;
+use crate::jobs::{
+    DeferredTask,
+    JobDelivery,
+    JobRequest,
+    defer_task,
+    drain_inflight,
+    enqueue_job,
+    handle_admin_deadletters,
+    handle_job_status,
+    handle_job_submit,
+};
+use crate::llm::{
+    SamplingOptions,
+    StreamEvent,
+    call_openai_completion,
+    call_openai_stream,
+    call_openai_structured,
+    chat_model,
+    condense_threshold,
+};
+use crate::negotiate::negotiate;
+use crate::payloads::{QueryBody, parse_json};
+use crate::prefs::{answer_visibility, slack_prefs_reply};
+use crate::rpc::handle_rpc;
+use crate::requestid::{begin_request, log, with_ref};
+use crate::residency::{set_region, workspace_region};
+use crate::messages::{Msg, accept_language_locale, message, set_locale, workspace_locale};
+use crate::connect::{CONNECT_SERVICE_PATH, handle_connect};
+use crate::deadline::{begin_deadline, end_deadline, route_deadline_ms};
+use crate::egress::{begin_egress, egress_scope, end_egress};
+use crate::scheduler::{handle_admin_jobs, scheduler_tick};
+use crate::shortlinks::handle_shortlink_redirect;
+use crate::slack::{SlashAnswer, slack_command_answer, slack_translate_reply, slack_triage_reply, slash_response_payload};
+use crate::slack::signature::verify_slack_signature;
+use crate::slackfiles::slack_file_ids;
+use crate::subcommands::{SUBCOMMAND_COMMAND, slack_subcommand_reply};
+use crate::tables::{render_csv, render_markdown_table, table_from_json};
+use crate::tcpnet::{dns_diagnostics, tcp_get_host_port, tcp_scan, tcp_send_message};
+use crate::urls::canonicalize_url;
+use crate::usage::{set_requester, slack_usage_report};
+use crate::warmup::{deep_health, run_warmup};
+use crate::workspaces::{complete_install, install_url, set_workspace};
+
+/* ---- Plain request/response types with wasi:http adapters ---- */
+pub(crate) struct Request {
+    method: String,
+    pub(crate) path: String,
+    query: Option<String>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// Set when the body broke the inbound limits; the request is refused before routing
+    body_rejected: Option<BodyRejected>,
+}
+
+impl Request {
+    /// Reads the whole incoming request, body included.
+    pub(crate) fn from_incoming(req: &http::IncomingRequest) -> Request {
+        let method = match req.method() {
+            Method::Get => "GET".to_string(),
+            Method::Head => "HEAD".to_string(),
+            Method::Post => "POST".to_string(),
+            Method::Put => "PUT".to_string(),
+            Method::Delete => "DELETE".to_string(),
+            Method::Connect => "CONNECT".to_string(),
+            Method::Options => "OPTIONS".to_string(),
+            Method::Trace => "TRACE".to_string(),
+            Method::Patch => "PATCH".to_string(),
+            Method::Other(m) => m,
+        };
+        let (path, query) = split_path_and_query(&req.path_with_query().unwrap_or_default());
+        let headers: Vec<(String, String)> = req
+            .headers()
+            .entries()
+            .into_iter()
+            .map(|(k, v)| (k, String::from_utf8_lossy(&v).into_owned()))
+            .collect();
+        let declared_len = headers.iter().find(|(k, _)| k.eq_ignore_ascii_case("content-length")).map(|(_, v)| v.as_str());
+        let (body, body_rejected) = match read_request_body(req, declared_len) {
+            Ok(body) => (body, None),
+            Err(rejected) => (Vec::new(), Some(rejected)),
+        };
+        Request { method, path, query, headers, body, body_rejected }
+    }
+
+    pub(crate) fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub(crate) fn body_rejected(&self) -> bool {
+        self.body_rejected.is_some()
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+pub(crate) struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    pub(crate) fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Response {
+        Response { status, headers: vec![("content-type".to_string(), content_type.to_string())], body: body.into() }
+    }
+
+    pub(crate) fn header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub(crate) fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub(crate) fn content_type(&self) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k == "content-type").map(|(_, v)| v.as_str())
+    }
+
+    pub(crate) fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// The same response (status, other headers) with a new body of another type.
+    pub(crate) fn replace_body(mut self, content_type: &str, body: impl Into<Vec<u8>>) -> Response {
+        self.headers.retain(|(k, _)| k != "content-type");
+        self.headers.insert(0, ("content-type".to_string(), content_type.to_string()));
+        self.body = body.into();
+        self
+    }
+}
+
+pub(crate) fn send_response(out: http::ResponseOutparam, response: Response) {
+    let headers = http::Fields::new();
+    for (name, value) in &response.headers {
+        let _ = headers.append(name, value.as_bytes());
+    }
+    let resp = http::OutgoingResponse::new(headers);
+    let _ = resp.set_status_code(response.status);
+    let body = resp.body().expect("response body");
+    http::ResponseOutparam::set(out, Ok(resp));
+    let writer = body.write().expect("writer");
+    write_all(&writer, &response.body);
+    drop(writer);
+    let _ = http::OutgoingBody::finish(body, None);
+}
+
+/* ---- Request routing (plain Request/Response, independent of wasi types) ---- */
+pub(crate) fn route(req: &Request) -> Response {
+    let request_id = begin_request(req.header("x-request-id"));
+    // Only Slack commands name a workspace whose region and locale apply (see /slack/command)
+    set_region(None);
+    set_workspace("");
+    set_locale(req.header("accept-language").and_then(accept_language_locale));
+    let route_path = find_route(&req.path).map(|r| r.path).unwrap_or("/");
+    begin_deadline(route_path, route_deadline_ms(route_path));
+    begin_egress(route_path);
+    let mut response = match (&req.body_rejected, req.method.as_str()) {
+        (Some(rejected), _) => {
+            // The rest of the body was never read, so the connection cannot be reused
+            let problem = Problem::from_status(rejected.status, rejected.detail.clone()).response(&request_id);
+            problem.header("x-request-id", &request_id).header("connection", "close")
+        }
+        (None, "OPTIONS") => allowed_methods(req).header("x-request-id", &request_id),
+        (None, "HEAD") => head(req, &request_id),
+        (None, _) => respond(req, &request_id),
+    };
+    if let Some(breakdown) = end_deadline() {
+        log!("deadline exceeded: {breakdown}");
+        // Slack shows its own generic failure for non-200 replies, so say what happened instead
+        if route_path.starts_with("/slack/") && response.status >= 500 {
+            let text = with_ref(&message(Msg::TookTooLong, &[]), &request_id);
+            response = Response::new(200, "text/plain", text).header("x-request-id", &request_id);
+        }
+    }
+    end_egress();
+    log!("{} {} -> {}", req.method, req.path, response.status);
+    response
+}
+
+/* ---- HEAD and OPTIONS for every registered path ---- */
+// OPTIONS lists the route's registered methods in `Allow`. HEAD on a route whose GET only reads
+// stored state runs the GET handler and sends its headers with the body's `Content-Length` and no
+// body. Every other GET bills the provider or reaches an upstream (streams have no length known up
+// front either), so HEAD there answers from the route table without running the handler.
+fn allow_header(route: &Route) -> String {
+    let mut methods: Vec<&str> = route.methods.to_vec();
+    if methods.contains(&"GET") {
+        methods.push("HEAD");
+    }
+    methods.push("OPTIONS");
+    methods.join(", ")
+}
+
+fn allowed_methods(req: &Request) -> Response {
+    // Unregistered paths are served by the fallback route
+    let route = find_route(&req.path).or_else(|| find_route("/")).expect("fallback route is registered");
+    Response { status: 204, headers: vec![("allow".to_string(), allow_header(route))], body: Vec::new() }
+}
+
+fn head(req: &Request, request_id: &str) -> Response {
+    let route = find_route(&req.path).or_else(|| find_route("/")).expect("fallback route is registered");
+    if !route.methods.contains(&"GET") {
+        return Response { status: 405, headers: vec![("allow".to_string(), allow_header(route))], body: Vec::new() }
+            .header("x-request-id", request_id);
+    }
+    if !route.head_runs_get {
+        if admin_only(route.path) && !admin_authorized(req) {
+            return Problem::from_status(401, "unauthorized".into()).response(request_id).header("x-request-id", request_id);
+        }
+        let response = Response::new(200, route.content_type, "");
+        let response = if route.content_type == "text/event-stream" { response.header("cache-control", "no-cache") } else { response };
+        return response.header("x-request-id", request_id);
+    }
+    let get = Request { method: "GET".to_string(), path: req.path.clone(), query: req.query.clone(), headers: req.headers.clone(), body: Vec::new(), body_rejected: None };
+    let mut response = respond(&get, request_id);
+    let length = response.body.len();
+    response.body.clear();
+    response.header("content-length", &length.to_string())
+}
+
+fn respond(req: &Request, request_id: &str) -> Response {
+    let mut response = dispatch(req, request_id);
+    if req.path.starts_with("/api/") {
+        response = negotiate(response, req.header("accept"));
+    }
+    if find_route(&req.path).is_some_and(|r| r.etag) && req.method == "GET" {
+        response = with_etag(response, req.header("if-none-match"));
+    }
+    response.header("x-request-id", request_id)
+}
+
+fn admin_only(route_path: &str) -> bool {
+    route_path.starts_with("/admin/") || ADMIN_DEBUG_ROUTES.contains(&route_path)
+}
+
+fn dispatch(req: &Request, request_id: &str) -> Response {
+    let route = find_route(&req.path);
+    let route_path = route.map(|r| r.path).unwrap_or("/");
+    let query = req.query.clone();
+
+    // The probes can reach any host and port from inside the deployment, so they are admin-only
+    if admin_only(route_path) && !admin_authorized(req) {
+        return Problem::from_status(401, "unauthorized".into()).response(request_id);
+    }
+
+    // Everything a Slack request says about its user, channel and team rests on Slack's signature
+    if SLACK_SIGNED_ROUTES.contains(&route_path) {
+        if let Err(e) = verify_slack_signature(req.header("x-slack-request-timestamp"), req.header("x-slack-signature"), &req.body) {
+            log!("{route_path} refused: {e}");
+            let status = if matches!(e, AgentError::Config(_)) { 500 } else { 401 };
+            return Problem::from_status(status, e.to_string()).response(request_id);
+        }
+    }
+
+    // Redirects need a non-200 status and a Location header
+    if route_path == "/r/" {
+        return handle_shortlink_redirect(&req.path["/r/".len()..]);
+    }
+
+    // "Add to Slack" sends the user on to Slack's consent page
+    if route_path == "/slack/oauth/start" {
+        return match install_url() {
+            Ok(url) => Response::new(302, "text/plain", format!("Redirecting to {url}")).header("location", &url),
+            Err(e) => Response::new(e.http_status(), "text/plain", format!("Installation is not available: {e}")),
+        };
+    }
+
+    // API errors caused by the provider forward its Retry-After header
+    if route_path == "/api/v1/query" {
+        return handle_api_query(req, request_id);
+    }
+
+    // Connect errors set their own status and content type
+    if route_path == CONNECT_SERVICE_PATH {
+        return handle_connect(&req.path[CONNECT_SERVICE_PATH.len()..], &req.method, req.header("content-type"), &req.body_text());
+    }
+
+    // Routes may override the registered content type (e.g. CSV on request) and status
+    let mut content_type = route.map(|r| r.content_type).unwrap_or("text/plain");
+    let mut status: u16 = 200;
+
+    // Routing + body content
+    let response_text = if route_path == "/health" {
+        health_status()
+    } else if route_path == "/health/deep" {
+        let (code, report) = deep_health();
+        status = code;
+        report.to_string()
+    } else if route_path == "/openapi.json" {
+        openapi_document().to_string()
+    } else if route_path == "/version" {
+        build_info().to_string()
+    } else if route_path == "/slack/command" {
+        // Slack slash command: body is x-www-form-urlencoded
+        let body_text = req.body_text();
+        let form = parse_query_params(body_text);
+        let text = form.get("text").cloned().unwrap_or_default();
+        let response_url = form.get("response_url").cloned().unwrap_or_default();
+
+        let command = form.get("command").cloned().unwrap_or_default();
+        let user_id = form.get("user_id").cloned().unwrap_or_default();
+        let channel_id = form.get("channel_id").cloned().unwrap_or_default();
+        set_requester(&user_id, &channel_id);
+        let team_id = form.get("team_id").map(String::as_str).unwrap_or_default();
+        set_region(workspace_region(team_id));
+        set_locale(workspace_locale(team_id));
+        set_workspace(team_id);
+
+        // A retry of a command already handled (same trigger_id) gets an empty ack and nothing else
+        let trigger_id = form.get("trigger_id").map(String::as_str).unwrap_or_default();
+        let retry = req.header("x-slack-retry-num").is_some();
+        let duplicate = !trigger_id.is_empty() && !first_delivery(&format!("command:{trigger_id}"), retry);
+
+        // Admin commands answer everyone else with a denial instead, before any work is queued
+        let denial = admin_command_denial(&command, &user_id);
+
+        // Slow work (oversized input, linked files, image generation, broadcasts) goes through the job queue and is answered later
+        let slow = match command.as_str() {
+            _ if denial.is_some() => false,
+            "/translate" | "/triage" | "/prompt-report" | "/agent-quality" | "/agent-usage" | "/agent-prefs" | "/agent-forget" => {
+                false
+            }
+            "/imagine" | "/agent-broadcast" | "/agent-export" => true,
+            _ => text.chars().count() > condense_threshold() || !slack_file_ids(&text).is_empty(),
+        };
+        let queued = if slow && !duplicate && !response_url.is_empty() {
+            let delivery = JobDelivery { response_url: Some(response_url.clone()), ..Default::default() };
+            let request = match command.as_str() {
+                "/imagine" => JobRequest::Imagine { text: text.clone(), user_id: user_id.clone(), channel_id: channel_id.clone() },
+                "/agent-broadcast" => JobRequest::Broadcast { text: text.clone(), user_id: user_id.clone(), channel_id: channel_id.clone() },
+                "/agent-export" => JobRequest::Export { user_id: user_id.clone(), channel_id: channel_id.clone() },
+                _ => JobRequest::SlashCommand { text: text.clone(), user_id: user_id.clone(), channel_id: channel_id.clone() },
+            };
+            enqueue_job(request, delivery).ok()
+        } else {
+            None
+        };
+
+        if duplicate {
+            String::new()
+        } else if let Some(id) = queued {
+            message(Msg::WorkingOnIt, &[("id", &id)])
+        } else {
+            let reply = match denial {
+                Some(denial) => Some(SlashAnswer::from(denial)),
+                None if response_url.is_empty() => Some(slash_reply(&command, &text, &user_id, &channel_id)),
+                None => None,
+            };
+            match reply {
+                // Without a response_url (not a request from Slack) there is nowhere to answer later
+                Some(reply) if response_url.is_empty() => reply.text,
+                Some(reply) => {
+                    deliver_slash_reply(&command, &text, &user_id, &response_url, reply);
+                    "ack".to_string()
+                }
+                // Slack gets its ack before the model is called; the answer follows via response_url
+                None => {
+                    let team_id = team_id.to_string();
+                    defer_task(DeferredTask::SlashReply { team_id, command, text, user_id, channel_id, response_url });
+                    "ack".to_string()
+                }
+            }
+        }
+    } else if route_path == "/slack/events" {
+        let (code, text) = handle_slack_event(&req.body_text(), req.header("x-slack-retry-num").is_some());
+        status = code;
+        text
+    } else if route_path == "/slack/oauth/callback" {
+        // Slack's OAuth redirect after "Add to Slack": ?code=... on approval, ?error=... otherwise
+        let params = parse_query_params(query.clone().unwrap_or_default());
+        match (params.get("code"), params.get("error")) {
+            (Some(code), _) => match complete_install(code, params.get("state").map(String::as_str).unwrap_or_default()) {
+                Ok(text) => text,
+                Err(e) => {
+                    status = e.http_status();
+                    format!("Installation failed: {e}")
+                }
+            },
+            (None, error) => {
+                status = 400;
+                format!("Installation was not completed: {}", error.map(String::as_str).unwrap_or("missing code"))
+            }
+        }
+    } else if route_path == "/slack/interactive" {
+        // Slack interactivity: the form field `payload` holds the action as JSON
+        let form = parse_query_params(req.body_text());
+        let payload = form.get("payload").ok_or_else(|| AgentError::Validation("missing form field `payload`".into()));
+        match payload.and_then(|p| handle_interaction(p)) {
+            Ok(None) => String::new(),
+            Ok(Some(json)) => {
+                content_type = "application/json";
+                json.to_string()
+            }
+            Err(e) => {
+                status = e.http_status();
+                e.to_string()
+            }
+        }
+    } else if route_path == "/admin/warmup" {
+        run_warmup().to_string()
+    } else if route_path == "/admin/tick" {
+        serde_json::Value::Array(scheduler_tick()).to_string()
+    } else if route_path == "/admin/jobs" || route_path == "/admin/jobs/preview" {
+        let name = query.clone().map(parse_query_params).and_then(|p| p.get("name").cloned());
+        handle_admin_jobs(route_path == "/admin/jobs/preview", name.as_deref())
+    } else if route_path == "/admin/deadletters" {
+        let id = query.clone().map(parse_query_params).and_then(|p| p.get("id").cloned());
+        let (code, text) = handle_admin_deadletters(req.method == "POST", id.as_deref());
+        status = code;
+        text
+    } else if route_path == "/admin/drain" {
+        let report = drain_inflight(true);
+        serde_json::json!({"flushed": report.flushed, "failed": report.failed}).to_string()
+    } else if route_path == "/api/v1/jobs" {
+        let body_text = req.body_text();
+        let (code, text) = handle_job_submit(&body_text, admin_authorized(req));
+        status = code;
+        text
+    } else if route_path == "/api/v1/jobs/" {
+        let (code, text) = handle_job_status(req.path["/api/v1/jobs/".len()..].trim_end_matches('/'));
+        status = code;
+        text
+    } else if route_path == "/rpc" {
+        // JSON-RPC 2.0 over POST; notifications-only batches yield an empty body
+        let body_text = req.body_text();
+        handle_rpc(&body_text).unwrap_or_default()
+    } else if route_path == "/tcp/send" {
+        // Send a custom message over TCP and return the response
+        let mut host = "127.0.0.1".to_string();
+        let mut port: u16 = 9090;
+        let mut msg = "hello from wasi".to_string();
+
+        if let Some(qs) = query.clone() {
+            let params = parse_query_params(qs);
+            if let Some(h) = params.get("host") { host = h.to_string(); }
+            if let Some(p) = params.get("port") { if let Ok(v) = p.parse::<u16>() { port = v; } }
+            if let Some(m) = params.get("msg") { msg = m.to_string(); }
+        }
+
+        match tcp_send_message(&host, port, &msg) {
+            Ok(reply) => format!("✅ Sent to {host}:{port}\n\n> {msg}\n\n< {reply}\n"),
+            Err(e) => format!("⚠️  Send failed: {e}\nTarget: {host}:{port}\n"),
+        }
+    } else if route_path == "/debug/httpget" {
+        // Example: /debug/httpget?url=https://httpbin.org/get
+        // Extra headers: &header=Accept:%20application/json (repeatable; taken literally)
+        let mut url = "https://httpbin.org/get".to_string();
+        let params = query.clone().map(parse_query_params).unwrap_or_default();
+        if let Some(u) = params.get("url") { url = u.to_string(); }
+        // &cookies=1 keeps a cookie jar across redirects, for pages behind session gates
+        let mut jar = CookieJar::default();
+        let jar = matches!(params.get("cookies").map(String::as_str), Some("1" | "true")).then_some(&mut jar);
+        let fetched = canonicalize_url(&url).and_then(|canonical| {
+            url = canonical;
+            parse_header_specs(&params.get_all("header")).and_then(|headers| fetch_response(&url, &headers, jar))
+        });
+        match fetched {
+            Ok(resp) => format!("GET {}\n{}\n\n{}", redact_userinfo(&url), resp.meta().head(), resp.text()),
+            Err(e) => format!("GET {} failed: {}", redact_userinfo(&url), e),
+        }
+    } else if route_path == "/debug/httpreq" {
+        // Example: curl -X POST --data '{"a":1}' '/debug/httpreq?method=PUT&url=https://httpbin.org/put'
+        let params = query.clone().map(parse_query_params).unwrap_or_default();
+        let method = params.get("method").map(|m| m.to_uppercase()).unwrap_or_else(|| "GET".to_string());
+        match params.get("url") {
+            None => {
+                status = 400;
+                "missing url".to_string()
+            }
+            Some(raw_url) => match canonicalize_url(raw_url).and_then(|url| {
+                let headers = parse_header_specs(&params.get_all("header"))?;
+                http_probe(&method, &url, &headers, req.header("content-type"), &req.body)
+            }) {
+                Ok(resp) => format!("{method} {}\n{}\n\n{}", redact_userinfo(raw_url), resp.meta().head(), resp.text()),
+                Err(e) => {
+                    status = e.http_status();
+                    format!("{method} {} failed: {e}", redact_userinfo(raw_url))
+                }
+            },
+        }
+    } else if route_path == "/debug/dns" {
+        // Example: /debug/dns?host=api.openai.com
+        let params = query.clone().map(parse_query_params).unwrap_or_default();
+        let timeout_ms = params.get("timeout_ms").and_then(|ms| ms.parse().ok());
+        match params.get("host").map(|h| h.trim()).filter(|h| !h.is_empty()) {
+            None => {
+                status = 400;
+                serde_json::json!({"error": "missing host"}).to_string()
+            }
+            Some(host) => {
+                let (code, report) = dns_diagnostics(host, timeout_ms);
+                status = code;
+                report.to_string()
+            }
+        }
+    } else if route_path == "/debug/tcpscan" {
+        // Example: /debug/tcpscan?targets=redis.internal:6379,api.openai.com:443
+        let params = query.clone().map(parse_query_params).unwrap_or_default();
+        let timeout_ms = params.get("timeout_ms").and_then(|ms| ms.parse().ok());
+        match tcp_scan(params.get("targets").map(String::as_str).unwrap_or_default(), timeout_ms) {
+            Ok(report) => report.to_string(),
+            Err(e) => {
+                status = e.http_status();
+                serde_json::json!({"error": e.to_string()}).to_string()
+            }
+        }
+    } else if route_path == "/debug/openai" {
+        let api_key = get_env_var("OPENAI_API_KEY").unwrap_or_else(|| "MISSING".to_string());
+        let model = chat_model();
+        
+        // Test with a simple request
+        let test_payload = format!(r#"{{"model":"{}","messages":[{{"role":"user","content":"Hello"}}],"max_tokens":10}}"#, model);
+        
+        match http_post_json("https://api.openai.com/v1/chat/completions", &test_payload, &api_key) {
+            Ok(response_body) => {
+                format!("OpenAI API Test Success:\nModel: {}\nAPI Key: {}...\nResponse: {}", 
+                    model, 
+                    if api_key.len() > 10 { &api_key[..10] } else { &api_key },
+                    response_body)
+            }
+            Err(e) => {
+                format!("OpenAI API Test Failed:\nModel: {}\nAPI Key: {}...\nError: {}", 
+                    model,
+                    if api_key.len() > 10 { &api_key[..10] } else { &api_key },
+                    e)
+            }
+        }
+    } else {
+        // Defaults
+        let mut host = "example.com".to_string();
+        let mut port: u16 = 80;
+
+        if let Some(qs) = query {
+            let params = parse_query_params(qs);
+            if let Some(h) = params.get("host") {
+                host = h.to_string();
+            }
+            if let Some(p) = params.get("port") {
+                if let Ok(parsed) = p.parse::<u16>() { port = parsed; }
+            }
+        }
+
+        match tcp_get_host_port(&host, port) {
+            Ok(s) => message(Msg::TcpFetchOk, &[("target", &format!("{host}:{port}")), ("body", &s)]),
+            Err(e) => message(Msg::TcpFetchFailed, &[("error", &e.to_string())]),
+        }
+    };
+
+    // Handlers that report errors as `{"error": ...}` answer API callers with problem details
+    if status >= 400 && content_type == "application/json" && (route_path.starts_with("/api/") || route_path.starts_with("/admin/")) {
+        let body = serde_json::from_str::<serde_json::Value>(&response_text).unwrap_or_default();
+        let detail = body["error"].as_str().map(str::to_string).unwrap_or(response_text);
+        return Problem::from_status(status, detail).response(request_id);
+    }
+
+    Response::new(status, content_type, response_text)
+}
+
+/* ---- Slash command replies (acked first, answered via response_url) ---- */
+// Slack fails a command that is not acknowledged within 3 seconds, and a model call alone can
+// take longer. So /slack/command only decides what to do and answers `ack`; the reply is worked
+// out once the response has been sent and is posted to the command's response_url. Slow work
+// (images, broadcasts, exports, oversized input, linked files) still goes through the job queue.
+fn slash_reply(command: &str, text: &str, user_id: &str, channel_id: &str) -> SlashAnswer {
+    let (text, private) = answer_visibility(text, user_id);
+    let text = text.as_str();
+    match command {
+        "/translate" => slack_translate_reply(text).into(),
+        "/triage" => slack_triage_reply(text).into(),
+        "/imagine" => slack_imagine_reply(text, user_id, channel_id).into(),
+        "/agent-broadcast" => slack_broadcast_reply(text, user_id, channel_id).into(),
+        "/prompt-report" => slack_prompt_report().into(),
+        "/agent-quality" => slack_quality_reply(text).into(),
+        "/agent-prefs" => slack_prefs_reply(text, user_id).into(),
+        "/agent-export" => slack_export_reply(user_id, channel_id).into(),
+        "/agent-forget" => slack_forget_reply(text, user_id, channel_id).into(),
+        SUBCOMMAND_COMMAND => slack_subcommand_reply(text, user_id, channel_id, private),
+        "/agent-usage" => {
+            let (text, blocks) = slack_usage_report(text);
+            SlashAnswer { text, blocks, full_text: None }
+        }
+        _ => slack_command_answer(text, user_id, channel_id, private),
+    }
+}
+
+fn deliver_slash_reply(command: &str, text: &str, user_id: &str, response_url: &str, reply: SlashAnswer) {
+    let mut json = slash_response_payload(&reply.text);
+    // Preferences and purges are private to the user; other replies follow --private/--public, else the user's preference
+    let private = matches!(command, "/agent-prefs" | "/agent-forget") || answer_visibility(text, user_id).1;
+    json["response_type"] = if private { "ephemeral" } else { "in_channel" }.into();
+    // Answers and reports laid out in Block Kit keep `text` as the notification fallback
+    if let Some(blocks) = reply.blocks {
+        json["blocks"] = blocks;
+    }
+    defer_task(DeferredTask::SlackDelivery { response_url: response_url.to_string(), payload: json.to_string() });
+}
+
+/// Answers an acknowledged slash command with the workspace's region and locale.
+pub(crate) fn run_slash_reply(team_id: &str, command: &str, text: &str, user_id: &str, channel_id: &str, response_url: &str) -> Result<(), AgentError> {
+    set_requester(user_id, channel_id);
+    set_region(workspace_region(team_id));
+    set_locale(workspace_locale(team_id));
+    set_workspace(team_id);
+    deliver_slash_reply(command, text, user_id, response_url, slash_reply(command, text, user_id, channel_id));
+    Ok(())
+}
+
+/* ---- Problem details (RFC 7807) for API errors ---- */
+// API routes (/api/v1/*, /admin/*) report errors as `application/problem+json`. `type` is a
+// relative URI naming the error class and `title` its fixed summary; `detail` describes this
+// occurrence and `request_id` identifies the request. Provider failures add `upstream_status`
+// and `retry_after` as extension members.
+struct Problem {
+    status: u16,
+    kind: &'static str,
+    title: &'static str,
+    detail: String,
+    extensions: serde_json::Map<String, serde_json::Value>,
+    retry_after: Option<String>,
+}
+
+impl Problem {
+    fn from_error(e: &AgentError) -> Problem {
+        let (kind, title) = e.problem_type();
+        let mut problem = Problem { status: e.http_status(), kind, title, detail: e.to_string(), extensions: Default::default(), retry_after: None };
+        if let AgentError::Provider { status, retry_after, .. } = e {
+            problem.extensions.insert("upstream_status".into(), serde_json::json!(status));
+            problem.retry_after = retry_after.clone();
+        } else if let AgentError::RateLimited { retry_after, .. } = e {
+            problem.retry_after = Some(retry_after.to_string());
+        }
+        if let Some(r) = &problem.retry_after {
+            problem.extensions.insert("retry_after".into(), serde_json::json!(r));
+        }
+        problem
+    }
+
+    /// For handlers that only report a status and a message.
+    fn from_status(status: u16, detail: String) -> Problem {
+        let (kind, title) = match status {
+            400 => ("validation", "Invalid request"),
+            401 => ("unauthorized", "Unauthorized"),
+            404 => ("not-found", "Not found"),
+            408 => ("request-timeout", "Request body not received in time"),
+            413 => ("payload-too-large", "Request body too large"),
+            429 => ("rate-limited", "Rate limit reached"),
+            503 => ("store", "Store unavailable"),
+            _ => ("internal", "Request failed"),
+        };
+        Problem { status, kind, title, detail, extensions: Default::default(), retry_after: None }
+    }
+
+    fn response(self, request_id: &str) -> Response {
+        let mut body = serde_json::json!({
+            "type": format!("/problems/{}", self.kind),
+            "title": self.title,
+            "status": self.status,
+            "detail": self.detail,
+            "request_id": request_id,
+        });
+        if let Some(members) = body.as_object_mut() {
+            members.extend(self.extensions);
+        }
+        let resp = Response::new(self.status, "application/problem+json", body.to_string());
+        match self.retry_after {
+            Some(r) => resp.header("retry-after", &r),
+            None => resp,
+        }
+    }
+}
+
+/* ---- Conditional GETs (ETag / If-None-Match) ---- */
+// The ETag is a digest of the body, so it changes exactly when the representation does. Polling
+// clients send it back as If-None-Match and get an empty 304 until then.
+fn etag_of(body: &[u8]) -> String {
+    use sha2::Digest;
+    let digest = sha2::Sha256::digest(body);
+    format!("\"{}\"", digest[..16].iter().map(|b| format!("{b:02x}")).collect::<String>())
+}
+
+/// Whether an If-None-Match header lists `etag` (weak comparison, as RFC 9110 requires for it).
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+fn with_etag(response: Response, if_none_match: Option<&str>) -> Response {
+    if response.status != 200 {
+        return response;
+    }
+    let etag = etag_of(&response.body);
+    if if_none_match.is_some_and(|tags| etag_matches(tags, &etag)) {
+        return Response { status: 304, headers: vec![("etag".to_string(), etag)], body: Vec::new() };
+    }
+    response.header("etag", &etag)
+}
+
+/* ---- Route registry (drives dispatch and /openapi.json) ---- */
+pub(crate) struct Route {
+    pub(crate) path: &'static str,
+    methods: &'static [&'static str],
+    summary: &'static str,
+    params: &'static [Param],
+    request_body: Option<&'static str>,
+    content_type: &'static str,
+    // Match any path starting with `path` (legacy debug routes)
+    prefix: bool,
+    // GET responses depend only on stored state, so they carry an ETag and honor If-None-Match
+    etag: bool,
+    // GET only reads stored state, so HEAD may run it; other GETs bill the provider or reach upstreams
+    head_runs_get: bool,
+}
+
+struct Param {
+    name: &'static str,
+    kind: &'static str,
+    required: bool,
+    description: &'static str,
+}
+
+const ROUTES: &[Route] = &[
+    Route {
+        path: "/health",
+        methods: &["GET"],
+        summary: "Liveness check",
+        params: &[],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/health/deep",
+        methods: &["GET"],
+        summary: "Probe each dependency (LLM provider, Slack, DNS, store) and report status and latency; 503 when one fails",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/version",
+        methods: &["GET"],
+        summary: "Crate version, git commit, build time and WIT world of the running build",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/openapi.json",
+        methods: &["GET"],
+        summary: "OpenAPI description of this service",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: true,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/slack/command",
+        methods: &["POST"],
+        summary: "Slack slash command; answer is posted to response_url",
+        params: &[],
+        request_body: Some("application/x-www-form-urlencoded"),
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/slack/events",
+        methods: &["POST"],
+        summary: "Slack Events API: url_verification and event callbacks (e.g. the bot joining a channel)",
+        params: &[],
+        request_body: Some("application/json"),
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/slack/oauth/start",
+        methods: &["GET"],
+        summary: "\"Add to Slack\": redirects to Slack's consent page with a one-time state for the callback",
+        params: &[],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/slack/oauth/callback",
+        methods: &["GET"],
+        summary: "Slack OAuth redirect: stores the workspace's bot token and sends onboarding messages",
+        params: &[
+            Param { name: "code", kind: "string", required: false, description: "Authorization code from Slack" },
+            Param { name: "state", kind: "string", required: false, description: "One-time state issued by /slack/oauth/start" },
+            Param { name: "error", kind: "string", required: false, description: "Set by Slack when the install was cancelled" },
+        ],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/slack/interactive",
+        methods: &["POST"],
+        summary: "Slack interactivity (answer buttons, shortcuts and modal submissions)",
+        params: &[],
+        request_body: Some("application/x-www-form-urlencoded"),
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/api/v1/query/stream",
+        methods: &["GET", "POST"],
+        summary: "Stream a model answer as Server-Sent Events",
+        params: &[
+            Param { name: "q", kind: "string", required: false, description: "Question (or `query` in the JSON body)" },
+            Param { name: "context", kind: "string", required: false, description: "Optional context for the model" },
+            Param { name: "stop", kind: "string", required: false, description: "Stop sequence, repeatable up to 4 (or `stop` string/array in the JSON body)" },
+            Param { name: "frequency_penalty", kind: "number", required: false, description: "-2.0 to 2.0; overrides LLM_FREQUENCY_PENALTY" },
+            Param { name: "presence_penalty", kind: "number", required: false, description: "-2.0 to 2.0; overrides LLM_PRESENCE_PENALTY" },
+            Param { name: "seed", kind: "integer", required: false, description: "Sampling seed for reproducible output; overrides LLM_SEED" },
+        ],
+        request_body: Some("application/json"),
+        content_type: "text/event-stream",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/api/v1/query",
+        methods: &["GET", "POST"],
+        summary: "Answer a query as JSON, text, or (structured mode) CSV/Markdown table",
+        params: &[
+            Param { name: "q", kind: "string", required: false, description: "Question (or `query` in the JSON body)" },
+            Param { name: "context", kind: "string", required: false, description: "Optional context for the model" },
+            Param { name: "format", kind: "string", required: false, description: "json (default), text, csv or markdown" },
+            Param { name: "structured", kind: "boolean", required: false, description: "Request structured JSON output" },
+            Param { name: "stop", kind: "string", required: false, description: "Stop sequence, repeatable up to 4 (or `stop` string/array in the JSON body)" },
+            Param { name: "frequency_penalty", kind: "number", required: false, description: "-2.0 to 2.0; overrides LLM_FREQUENCY_PENALTY" },
+            Param { name: "presence_penalty", kind: "number", required: false, description: "-2.0 to 2.0; overrides LLM_PRESENCE_PENALTY" },
+            Param { name: "seed", kind: "integer", required: false, description: "Sampling seed for reproducible output; overrides LLM_SEED" },
+        ],
+        request_body: Some("application/json"),
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/api/v1/jobs",
+        methods: &["POST"],
+        summary: "Queue a long-running query, summarization or research job; results go to Slack or a callback URL",
+        params: &[],
+        request_body: Some("application/json"),
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/api/v1/jobs/",
+        methods: &["GET"],
+        summary: "Job state, timing and result: /api/v1/jobs/{id}",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: true,
+        etag: true,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/admin/warmup",
+        methods: &["GET", "POST"],
+        summary: "Pre-resolve critical hosts and validate OpenAI/Slack credentials; requires the admin token",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/admin/tick",
+        methods: &["POST"],
+        summary: "Run due scheduled tasks now (for external cron drivers); requires the admin token",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/admin/jobs",
+        methods: &["GET"],
+        summary: "List configured jobs with their schedules and last results; requires the admin token",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/admin/jobs/preview",
+        methods: &["GET", "POST"],
+        summary: "Dry-run a configured job and return what it would post; requires the admin token",
+        params: &[Param { name: "name", kind: "string", required: true, description: "Job name" }],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/admin/deadletters",
+        methods: &["GET", "POST"],
+        summary: "List undeliverable responses (GET) or re-drive them (POST); requires the admin token",
+        params: &[Param { name: "id", kind: "string", required: false, description: "Re-drive only this dead letter" }],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/admin/drain",
+        methods: &["POST"],
+        summary: "Flush pending deferred work (call before shutting the host down); requires the admin token",
+        params: &[],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/r/",
+        methods: &["GET"],
+        summary: "Redirect a short citation link (/r/{id}) to its source URL",
+        params: &[],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: true,
+        etag: false,
+        head_runs_get: true,
+    },
+    Route {
+        path: "/rpc",
+        methods: &["POST"],
+        summary: "JSON-RPC 2.0 access to the agent operations",
+        params: &[],
+        request_body: Some("application/json"),
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: CONNECT_SERVICE_PATH,
+        methods: &["POST"],
+        summary: "Connect protocol (unary, JSON) access to the agent operations: ProcessQuery, FetchAndProcess, MultiSourceResponse, HealthCheck",
+        params: &[],
+        request_body: Some("application/json"),
+        content_type: "application/json",
+        prefix: true,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/tcp/send",
+        methods: &["GET"],
+        summary: "Send a message over TCP and return the reply",
+        params: &[
+            Param { name: "host", kind: "string", required: false, description: "Target host (default 127.0.0.1)" },
+            Param { name: "port", kind: "integer", required: false, description: "Target port (default 9090)" },
+            Param { name: "msg", kind: "string", required: false, description: "Message to send" },
+        ],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/debug/httpget",
+        methods: &["GET"],
+        summary: "Outbound HTTP GET probe",
+        params: &[
+            Param { name: "url", kind: "string", required: false, description: "URL to fetch (default https://httpbin.org/get)" },
+            Param { name: "header", kind: "string", required: false, description: "Extra 'Name: value' header, repeatable; secret:<name> is refused" },
+            Param { name: "cookies", kind: "boolean", required: false, description: "Keep cookies across redirects" },
+        ],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: true,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/debug/httpreq",
+        methods: &["GET", "POST", "PUT", "PATCH", "DELETE"],
+        summary: "Outbound HTTP probe with any method; the request body, if any, is forwarded",
+        params: &[
+            Param { name: "method", kind: "string", required: false, description: "GET, POST, PUT, DELETE, HEAD or PATCH (default GET)" },
+            Param { name: "url", kind: "string", required: true, description: "URL to call" },
+            Param { name: "header", kind: "string", required: false, description: "Extra 'Name: value' header, repeatable; secret:<name> is refused" },
+        ],
+        request_body: Some("application/octet-stream"),
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/debug/dns",
+        methods: &["GET"],
+        summary: "Every A/AAAA record of a host with its resolution time, and the address a connection would use",
+        params: &[
+            Param { name: "host", kind: "string", required: true, description: "Host name (or IP literal) to resolve" },
+            Param { name: "timeout_ms", kind: "integer", required: false, description: "Lookup timeout (default 5000)" },
+        ],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/debug/tcpscan",
+        methods: &["GET"],
+        summary: "Concurrent TCP connect test: reachability and connect latency per target (admin token required)",
+        params: &[
+            Param { name: "targets", kind: "string", required: true, description: "Comma-separated host:port pairs, IPv6 in brackets (at most 32)" },
+            Param { name: "timeout_ms", kind: "integer", required: false, description: "Time allowed for the whole scan (default 2000)" },
+        ],
+        request_body: None,
+        content_type: "application/json",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/debug/openai",
+        methods: &["GET"],
+        summary: "OpenAI connectivity probe",
+        params: &[],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: true,
+        etag: false,
+        head_runs_get: false,
+    },
+    Route {
+        path: "/",
+        methods: &["GET"],
+        summary: "Raw TCP HTTP fetch (fallback for unknown paths; requires the admin token)",
+        params: &[
+            Param { name: "host", kind: "string", required: false, description: "Target host (default example.com)" },
+            Param { name: "port", kind: "integer", required: false, description: "Target port (default 80)" },
+        ],
+        request_body: None,
+        content_type: "text/plain",
+        prefix: false,
+        etag: false,
+        head_runs_get: false,
+    },
+];
+
+pub(crate) fn find_route(path: &str) -> Option<&'static Route> {
+    ROUTES
+        .iter()
+        .find(|r| r.path == path || (r.prefix && path.starts_with(r.path)))
+}
+
+/* ---- OpenAPI 3.1 document generated from ROUTES ---- */
+fn openapi_document() -> serde_json::Value {
+    let mut paths = serde_json::Map::new();
+    for route in ROUTES {
+        let mut ops = serde_json::Map::new();
+        for method in route.methods {
+            let params: Vec<serde_json::Value> = route
+                .params
+                .iter()
+                .map(|p| serde_json::json!({
+                    "name": p.name,
+                    "in": "query",
+                    "required": p.required,
+                    "description": p.description,
+                    "schema": {"type": p.kind},
+                }))
+                .collect();
+            let mut op = serde_json::json!({
+                "summary": route.summary,
+                "parameters": params,
+                "responses": {
+                    "200": {"description": "OK", "content": {route.content_type: {}}}
+                },
+            });
+            if let (Some(media), true) = (route.request_body, *method != "GET") {
+                op["requestBody"] = serde_json::json!({"content": {media: {}}});
+            }
+            ops.insert(method.to_lowercase(), op);
+        }
+        paths.insert(route.path.to_string(), serde_json::Value::Object(ops));
+    }
+    serde_json::json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "AI Agent Rust Slack",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+    })
+}
+
+/// The JSON body of a POST to a query endpoint; `None` when there is none.
+fn query_body(req: &Request) -> Option<Result<QueryBody, AgentError>> {
+    (req.method == "POST" && !req.body.iter().all(u8::is_ascii_whitespace)).then(|| parse_json(&req.body_text()))
+}
+
+/* ---- SSE streaming query endpoint ---- */
+pub(crate) fn handle_query_stream(req: &Request, out: http::ResponseOutparam) {
+    let request_id = begin_request(req.header("x-request-id"));
+    set_region(None);
+    set_locale(req.header("accept-language").and_then(accept_language_locale));
+
+    let headers = http::Fields::new();
+    let _ = headers.set("content-type", &[b"text/event-stream".to_vec()]);
+    let _ = headers.set("cache-control", &[b"no-cache".to_vec()]);
+    let _ = headers.set("x-request-id", &[request_id.as_bytes().to_vec()]);
+    let resp = http::OutgoingResponse::new(headers);
+    let body = resp.body().expect("response body");
+    http::ResponseOutparam::set(out, Ok(resp));
+    let writer = body.write().expect("writer");
+
+    stream_query(req, &request_id, &mut |event, data| write_sse_event(&writer, event, data));
+
+    drop(writer);
+    let _ = http::OutgoingBody::finish(body, None);
+}
+
+/// Answers a streaming query as SSE events passed to `emit`, ending with `done`. The route's
+/// deadline covers the whole stream; an upstream that stalls past it gets a `timeout` event.
+fn stream_query(req: &Request, request_id: &str, emit: &mut dyn FnMut(&str, &serde_json::Value)) {
+    begin_deadline("/api/v1/query/stream", route_deadline_ms("/api/v1/query/stream"));
+    let _egress = egress_scope("/api/v1/query/stream");
+    // Accept the prompt from the query string (?q=...&context=...) or a JSON body
+    let mut user_query = String::new();
+    let mut context: Option<String> = None;
+    let mut options = Ok(SamplingOptions::default());
+    if let Some(qs) = req.query.clone() {
+        let params = parse_query_params(qs);
+        if let Some(q) = params.get("q") { user_query = q.to_string(); }
+        if let Some(c) = params.get("context") { context = Some(c.to_string()); }
+        options = SamplingOptions::from_params(&params);
+    }
+    match query_body(req) {
+        Some(Ok(body)) => {
+            if let Some(q) = body.query { user_query = q; }
+            if body.context.is_some() { context = body.context; }
+            options = options.and_then(|o| Ok(SamplingOptions::from_json(&serde_json::Value::Object(body.sampling))?.or(o)));
+        }
+        Some(Err(e)) => options = Err(e),
+        None => {}
+    }
+
+    if let Err(e) = &options {
+        emit("done", &serde_json::json!({"error": e.to_string(), "request_id": request_id}));
+    } else if user_query.is_empty() {
+        emit("done", &serde_json::json!({"error": "missing query", "request_id": request_id}));
+    } else {
+        let options = options.unwrap_or_default();
+        let result = call_openai_stream(&user_query, context.as_deref(), &options, &mut |event| match event {
+            StreamEvent::Token(text) => {
+                emit("token", &serde_json::json!({"text": text}));
+            }
+            StreamEvent::ToolCall { index, id, name, arguments } => {
+                emit("tool_call", &serde_json::json!({
+                    "index": index, "id": id, "name": name, "arguments": arguments
+                }));
+            }
+        });
+        if let Err(AgentError::Timeout(detail)) = &result {
+            emit("timeout", &serde_json::json!({"error": detail, "request_id": request_id}));
+        }
+        let done = match result {
+            Ok(finish_reason) => serde_json::json!({"finish_reason": finish_reason}),
+            Err(e) => serde_json::json!({"error": e.to_string(), "request_id": request_id}),
+        };
+        emit("done", &done);
+    }
+    if let Some(breakdown) = end_deadline() {
+        log!("deadline exceeded: {breakdown}");
+    }
+}
+
+/* ---- SSE framing: one event per write, flushed immediately ---- */
+fn write_sse_event(writer: &streams::OutputStream, event: &str, data: &serde_json::Value) {
+    let frame = format!("event: {event}\ndata: {data}\n\n");
+    write_all(writer, frame.as_bytes());
+}
+
+/* ---- Non-streaming query API (JSON, CSV or Markdown) ---- */
+fn handle_api_query(req: &Request, request_id: &str) -> Response {
+    let mut user_query = String::new();
+    let mut context: Option<String> = None;
+    let mut format = String::from("json");
+    let mut structured = false;
+    // Stop sequences and penalties override the configured defaults for this request
+    let mut options = Ok(SamplingOptions::default());
+    if let Some(qs) = req.query.clone() {
+        let params = parse_query_params(qs);
+        if let Some(q) = params.get("q") { user_query = q.to_string(); }
+        if let Some(c) = params.get("context") { context = Some(c.to_string()); }
+        if let Some(f) = params.get("format") { format = f.to_lowercase(); }
+        if let Some(st) = params.get("structured") { structured = st == "true" || st == "1"; }
+        options = SamplingOptions::from_params(&params);
+    }
+    let error = |e: AgentError| Problem::from_error(&e).response(request_id);
+    if let Some(body) = query_body(req) {
+        let body = match body {
+            Ok(body) => body,
+            Err(e) => return error(e),
+        };
+        if let Some(q) = body.query { user_query = q; }
+        if body.context.is_some() { context = body.context; }
+        if let Some(f) = body.format { format = f.to_lowercase(); }
+        if let Some(st) = body.structured { structured = st; }
+        options = options.and_then(|o| Ok(SamplingOptions::from_json(&serde_json::Value::Object(body.sampling))?.or(o)));
+    }
+
+    if user_query.is_empty() {
+        return error(AgentError::Validation("missing query".into()));
+    }
+    let options = match options {
+        Ok(options) => options,
+        Err(e) => return error(e),
+    };
+
+    // Tabular formats imply structured-output mode
+    if !structured && format != "csv" && format != "markdown" {
+        let completion = match call_openai_completion(&user_query, &options) {
+            Ok(completion) => completion,
+            Err(e) => return error(e),
+        };
+        // The fingerprint lets callers that pin a `seed` tell a backend change from nondeterminism
+        let resp = if format == "text" {
+            Response::new(200, "text/plain", completion.content)
+        } else {
+            let mut body = serde_json::json!({"answer": completion.content});
+            if let Some(fingerprint) = &completion.system_fingerprint {
+                body["system_fingerprint"] = serde_json::json!(fingerprint);
+            }
+            Response::new(200, "application/json", body.to_string())
+        };
+        return match &completion.system_fingerprint {
+            Some(fingerprint) => resp.header("x-system-fingerprint", fingerprint),
+            None => resp,
+        };
+    }
+
+    let data = match call_openai_structured(&user_query, context.as_deref(), &options) {
+        Ok(v) => v,
+        Err(e) => return error(e),
+    };
+    match (format.as_str(), table_from_json(&data)) {
+        ("csv", Some(table)) => Response::new(200, "text/csv", render_csv(&table)),
+        ("markdown", Some(table)) => Response::new(200, "text/markdown", render_markdown_table(&table)),
+        ("csv", None) | ("markdown", None) => {
+            error(AgentError::Provider { status: 200, message: "model output is not tabular".into(), retry_after: None })
+        }
+        _ => Response::new(200, "application/json", serde_json::json!({"data": data}).to_string()),
+    }
+}
+
+/* ---- Slack request signatures ---- */
+// Slack calls these routes; anyone else could claim to be any user or workspace (see slack::signature)
+const SLACK_SIGNED_ROUTES: &[&str] = &["/slack/command", "/slack/events", "/slack/interactive"];
+
+/* ---- Admin authorization (Bearer ADMIN_TOKEN) ---- */
+// Debug probes that send requests to caller-chosen hosts need the token as well, including the
+// raw TCP fetch behind `/` and every unregistered path
+const ADMIN_DEBUG_ROUTES: &[&str] = &["/debug/httpget", "/debug/httpreq", "/debug/tcpscan", "/"];
+
+fn admin_authorized(req: &Request) -> bool {
+    let Some(token) = get_env_var("ADMIN_TOKEN") else { return false };
+    let expected = format!("Bearer {token}");
+    req.headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+        .any(|(_, v)| constant_time_eq(v.as_bytes(), expected.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+    use crate::slack::signature::sign_slack_request;
+
+    const SIGNING_SECRET: &str = "signing-test";
+
+    /// Requests to the Slack routes are signed as Slack would, unless the test sets a signature.
+    fn request(method: &str, path_with_query: &str, headers: &[(&str, &str)], body: &str) -> Request {
+        let (path, query) = split_path_and_query(path_with_query);
+        let mut headers: Vec<(String, String)> = headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        if SLACK_SIGNED_ROUTES.contains(&path.as_str()) && !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("x-slack-signature")) {
+            env::set_var("SLACK_SIGNING_SECRET", SIGNING_SECRET);
+            let timestamp = crate::clock::now_secs();
+            headers.push(("x-slack-request-timestamp".to_string(), timestamp.to_string()));
+            headers.push(("x-slack-signature".to_string(), sign_slack_request(SIGNING_SECRET, timestamp, body.as_bytes())));
+        }
+        Request { method: method.to_string(), path, query, headers, body: body.as_bytes().to_vec(), body_rejected: None }
+    }
+
+    fn body_json(resp: &Response) -> serde_json::Value {
+        serde_json::from_slice(&resp.body).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "keyvalue")]
+    fn route_health_returns_plain_ok() {
+        let resp = route(&request("GET", "/health", &[], ""));
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body, b"ok");
+        assert!(resp.headers.contains(&("content-type".to_string(), "text/plain".to_string())));
+    }
+
+    #[test]
+    #[cfg(not(feature = "keyvalue"))]
+    fn route_health_reports_the_in_memory_store_as_degraded() {
+        let resp = route(&request("GET", "/health", &[], ""));
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body, b"ok (degraded: in-memory store, data is lost when the instance exits)");
+    }
+
+    #[test]
+    fn route_version_reports_the_build() {
+        let resp = route(&request("GET", "/version", &[], ""));
+        assert_eq!(resp.status, 200);
+        let info = body_json(&resp);
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(info["git_sha"].is_string() && info["built_at"].is_string());
+    }
+
+    #[test]
+    fn route_openapi_lists_registered_paths() {
+        let doc = body_json(&route(&request("GET", "/openapi.json", &[], "")));
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert!(doc["paths"]["/api/v1/query"]["post"].is_object());
+    }
+
+    #[test]
+    fn route_admin_requires_bearer_token() {
+        env::set_var("ADMIN_TOKEN", "admin-secret");
+        let denied = route(&request("GET", "/admin/jobs", &[("Authorization", "Bearer wrong")], ""));
+        assert_eq!(denied.status, 401);
+        assert_eq!(body_json(&denied)["type"], "/problems/unauthorized");
+
+        let allowed = route(&request("GET", "/admin/jobs", &[("authorization", "Bearer admin-secret")], ""));
+        assert_eq!(allowed.status, 200);
+
+        let scan = route(&request("GET", "/debug/tcpscan?targets=10.0.0.5:22", &[], ""));
+        assert_eq!(scan.status, 401);
+        let invalid = route(&request("GET", "/debug/tcpscan?targets=10.0.0.5", &[("authorization", "Bearer admin-secret")], ""));
+        assert_eq!((invalid.status, body_json(&invalid)["error"].as_str()), (400, Some("target '10.0.0.5': expected host:port")));
+
+        // The fallback's raw TCP fetch is a probe as well, for `/` and any unregistered path
+        for path in ["/?host=10.0.0.5&port=22", "/no-such-route?host=10.0.0.5"] {
+            assert_eq!(route(&request("GET", path, &[], "")).status, 401, "{path}");
+        }
+    }
+
+    #[test]
+    fn route_slack_requests_need_slacks_signature() {
+        with_openai_env();
+        env::set_var("SLACK_SIGNING_SECRET", SIGNING_SECRET);
+        env::set_var("SLACK_ADMIN_USERS", "UADMIN");
+        let mock = MockHttpClient::install();
+        let body = "command=%2Fagent-broadcast&text=hello&user_id=UADMIN&channel_id=C1";
+        let timestamp = crate::clock::now_secs();
+        // A forged admin command: unsigned, or signed for other content
+        let other = sign_slack_request(SIGNING_SECRET, timestamp, b"command=%2Fask&text=hi&user_id=U1");
+        let headers = [("X-Slack-Request-Timestamp", timestamp.to_string()), ("X-Slack-Signature", other)];
+        let headers: Vec<(&str, &str)> = headers.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let forged = route(&request("POST", "/slack/command", &headers, body));
+        assert_eq!((forged.status, body_json(&forged)["type"].as_str()), (401, Some("/problems/unauthorized")));
+        let unsigned = route(&request("POST", "/slack/interactive", &[("x-slack-signature", "v0=")], "payload=%7B%7D"));
+        assert_eq!(unsigned.status, 401);
+        assert!(mock.requests.borrow().is_empty());
+    }
+
+    #[test]
+    fn route_slack_command_answers_with_the_bucketed_prompt_and_takes_feedback() {
+        with_openai_env();
+        env::set_var("PROMPT_VERSIONS", r#"{"v1": "Answer tersely.", "v2": "Answer tersely."}"#);
+        crate::clock::MockClock::install(1_700_000_000);
+        let store = crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Paris"));
+        mock.respond(200, "text/plain", "ok");
+
+        let body = "command=%2Fask&text=Capital%20of%20France&user_id=U1&channel_id=C1&response_url=https%3A%2F%2Fhooks.slack.com%2Fx";
+        assert_eq!(route(&request("POST", "/slack/command", &[], body)).body, b"ack");
+        // The model is only called once the ack has been sent
+        assert!(mock.requests.borrow().is_empty());
+        crate::jobs::run_deferred();
+
+        let requests = mock.requests.borrow();
+        let prompt: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(prompt["messages"][0]["content"], "Answer tersely.");
+        let delivered: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(delivered["blocks"][0]["text"]["text"], "Paris");
+        let vote = delivered["blocks"][1]["elements"][0]["value"].as_str().unwrap().to_string();
+        drop(requests);
+
+        let payload = serde_json::json!({"type": "block_actions", "actions": [{"action_id": "prompt_feedback_up", "value": vote}]}).to_string();
+        let form = format!("payload={}", crate::form::percent_encode(&payload));
+        assert_eq!(route(&request("POST", "/slack/interactive", &[], &form)).status, 200);
+        let key = store.keys().into_iter().find(|k| k.starts_with("prompt-answer:")).unwrap();
+        assert!(crate::store::store_get(&key).unwrap().unwrap().contains(r#""feedback":"up""#));
+        assert_eq!(route(&request("POST", "/slack/interactive", &[], "payload=nope")).status, 400);
+    }
+
+    #[test]
+    fn route_api_query_answers_from_query_string_or_body() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Paris"));
+        mock.respond(200, "application/json", &completion("Berlin"));
+
+        let resp = route(&request("GET", "/api/v1/query?q=Capital%20of%20France&format=text", &[], ""));
+        assert_eq!(resp.body, b"Paris");
+        assert!(resp.headers.contains(&("content-type".to_string(), "text/plain".to_string())));
+
+        let resp = route(&request("POST", "/api/v1/query", &[], r#"{"query": "Capital of Germany"}"#));
+        assert_eq!(body_json(&resp), serde_json::json!({"answer": "Berlin"}));
+    }
+
+    #[test]
+    fn route_api_query_forwards_provider_retry_after() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond_with_headers(429, &[("content-type", "application/json"), ("retry-after", "20")], "slow down");
+
+        let resp = route(&request("GET", "/api/v1/query?q=hi", &[], ""));
+        assert_eq!(resp.status, 429);
+        assert!(resp.headers.contains(&("retry-after".to_string(), "20".to_string())));
+        let body = body_json(&resp);
+        assert_eq!((body["upstream_status"].as_u64(), body["retry_after"].as_str()), (Some(429), Some("20")));
+    }
+
+    #[test]
+    fn route_api_query_applies_stop_and_penalty_overrides() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("1. Paris"));
+
+        let body = r#"{"query": "List capitals", "stop": ["---", "END"], "frequency_penalty": 1}"#;
+        let resp = route(&request("POST", "/api/v1/query?stop=%0A%0A&presence_penalty=0.5", &[], body));
+        assert_eq!(resp.status, 200);
+        let body: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        assert_eq!(body["stop"], serde_json::json!(["---", "END"]));
+        assert_eq!((body["frequency_penalty"].as_f64(), body["presence_penalty"].as_f64()), (Some(1.0), Some(0.5)));
+
+        let resp = route(&request("GET", "/api/v1/query?q=hi&frequency_penalty=3", &[], ""));
+        assert_eq!(resp.status, 400);
+        assert_eq!(body_json(&resp)["detail"], "frequency_penalty: must be between -2.0 and 2.0");
+    }
+
+    #[test]
+    fn route_api_query_sends_seed_and_returns_system_fingerprint() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        let reply = serde_json::json!({"system_fingerprint": "fp_44709d6fcb", "choices": [{"message": {"content": "Paris"}}]});
+        mock.respond(200, "application/json", &reply.to_string());
+
+        let resp = route(&request("GET", "/api/v1/query?q=Capital%20of%20France&seed=42", &[], ""));
+        assert_eq!(body_json(&resp), serde_json::json!({"answer": "Paris", "system_fingerprint": "fp_44709d6fcb"}));
+        assert!(resp.headers.contains(&("x-system-fingerprint".to_string(), "fp_44709d6fcb".to_string())));
+        let body: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        assert_eq!(body["seed"], 42);
+
+        let resp = route(&request("POST", "/api/v1/query", &[], r#"{"query": "hi", "seed": "abc"}"#));
+        assert_eq!(body_json(&resp)["detail"], "seed: expected an integer");
+    }
+
+    #[test]
+    fn route_api_query_rejects_missing_query() {
+        let resp = route(&request("GET", "/api/v1/query", &[], ""));
+        assert_eq!(resp.status, 400);
+        assert!(resp.headers.contains(&("content-type".to_string(), "application/problem+json".to_string())));
+        let body = body_json(&resp);
+        assert_eq!((&body["type"], &body["title"], &body["status"]), (&"/problems/validation".into(), &"Invalid request".into(), &400.into()));
+        assert_eq!((body["detail"].as_str(), body["request_id"].as_str().map(str::len)), (Some("missing query"), Some(16)));
+    }
+
+    #[test]
+    fn route_echoes_or_generates_the_request_id() {
+        let resp = route(&request("GET", "/api/v1/query", &[("X-Request-Id", "support-7f3a")], ""));
+        assert!(resp.headers.contains(&("x-request-id".to_string(), "support-7f3a".to_string())));
+        assert_eq!(body_json(&resp)["request_id"], "support-7f3a");
+        let resp = route(&request("GET", "/health", &[], ""));
+        let generated = resp.headers.iter().find(|(name, _)| name == "x-request-id").map(|(_, v)| v.len());
+        assert_eq!(generated, Some(16));
+    }
+
+    #[test]
+    fn route_api_responses_follow_the_accept_header() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("**Paris**"));
+
+        let resp = route(&request("GET", "/api/v1/query?q=Capital%20of%20France", &[("Accept", "text/markdown, application/json;q=0.9")], ""));
+        assert_eq!(resp.body, b"**Paris**");
+        assert_eq!((resp.content_type(), resp.headers.contains(&("vary".to_string(), "accept".to_string()))), (Some("text/markdown"), true));
+
+        let missing = route(&request("GET", "/api/v1/query", &[("Accept", "text/plain")], ""));
+        assert_eq!(missing.content_type(), Some("application/problem+json"));
+    }
+
+    #[test]
+    fn route_answers_head_and_options_for_registered_paths() {
+        let options = route(&request("OPTIONS", "/api/v1/query", &[], ""));
+        assert_eq!(options.status, 204);
+        assert!(options.headers.contains(&("allow".to_string(), "GET, POST, HEAD, OPTIONS".to_string())));
+
+        let get = route(&request("GET", "/openapi.json", &[], ""));
+        let head = route(&request("HEAD", "/openapi.json", &[], ""));
+        assert_eq!((head.status, head.body.len(), head.content_type()), (200, 0, Some("application/json")));
+        assert!(head.headers.contains(&("content-length".to_string(), get.body.len().to_string())));
+
+        let post_only = route(&request("HEAD", "/api/v1/jobs", &[], ""));
+        assert_eq!(post_only.status, 405);
+        assert!(post_only.headers.contains(&("allow".to_string(), "POST, OPTIONS".to_string())));
+    }
+
+    #[test]
+    fn head_on_billed_or_upstream_routes_runs_no_handler() {
+        with_openai_env();
+        env::set_var("ADMIN_TOKEN", "admin-secret");
+        let mock = MockHttpClient::install();
+
+        let query = route(&request("HEAD", "/api/v1/query?q=hi", &[], ""));
+        assert_eq!((query.status, query.body.len(), query.content_type()), (200, 0, Some("application/json")));
+        let stream = route(&request("HEAD", "/api/v1/query/stream?q=hi", &[], ""));
+        assert_eq!(stream.content_type(), Some("text/event-stream"));
+        let fetch = route(&request("HEAD", "/debug/httpget?url=https://example.com/", &[ADMIN], ""));
+        assert_eq!((fetch.status, fetch.content_type()), (200, Some("text/plain")));
+        assert!(mock.requests.borrow().is_empty());
+
+        // HEAD says no more about an admin route than GET would
+        assert_eq!(route(&request("HEAD", "/debug/httpget", &[], "")).status, 401);
+    }
+
+    #[test]
+    fn route_slack_command_without_response_url_answers_in_the_body() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Madrid"));
+
+        let resp = route(&request("POST", "/slack/command", &[], "command=%2Fask&text=Capital%20of%20Spain&user_id=U1&channel_id=C1"));
+        assert_eq!(resp.body, b"Madrid");
+        crate::jobs::run_deferred();
+        assert_eq!(mock.requests.borrow().len(), 1);
+
+        // The streaming route is served outside route() and ends its exception too
+        crate::egress::set_test_policy("api.example.com:443", "/api/v1/query/stream=*");
+        mock.respond(200, "text/event-stream", "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"},\"finish_reason\":\"stop\"}]}\n\ndata: [DONE]\n\n");
+        stream_query(&request("GET", "/api/v1/query/stream?q=hi", &[], ""), "req-1", &mut |_, _| {});
+        assert_eq!(mock.requests.borrow().len(), 2);
+        crate::jobs::defer_task(crate::jobs::DeferredTask::SlackPost { channel: "C1".into(), text: "digest".into() });
+        crate::jobs::run_deferred();
+        assert_eq!(mock.requests.borrow().len(), 2);
+    }
+
+    #[test]
+    fn route_slack_command_private_flag_answers_only_the_asker() {
+        with_openai_env();
+        crate::clock::MockClock::install(1_700_000_000);
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Ask HR."));
+        mock.respond(200, "text/plain", "ok");
+
+        let body = "command=%2Fask&text=--private%20How%20do%20raises%20work%3F&user_id=U1&channel_id=C1&response_url=https%3A%2F%2Fhooks.slack.com%2Fx";
+        assert_eq!(route(&request("POST", "/slack/command", &[], body)).body, b"ack");
+        crate::jobs::run_deferred();
+
+        let requests = mock.requests.borrow();
+        let prompt: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(prompt["messages"].as_array().unwrap().last().unwrap()["content"], "How do raises work?");
+        let delivered: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(delivered["response_type"], "ephemeral");
+        let key = crate::store::store_list_keys("history:C1:").unwrap().remove(0);
+        assert!(crate::history::load_exchange(&key).unwrap().unwrap().private);
+    }
+
+    #[test]
+    fn route_slack_command_retries_are_acked_without_a_second_answer() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Madrid"));
+        mock.respond(200, "text/plain", "ok");
+
+        let body = "command=%2Fask&text=Capital%20of%20Spain&user_id=U1&channel_id=C1&trigger_id=13345.42&response_url=https%3A%2F%2Fhooks.slack.com%2Fx";
+        assert_eq!(route(&request("POST", "/slack/command", &[], body)).body, b"ack");
+        let retry = [("X-Slack-Retry-Num", "1"), ("X-Slack-Retry-Reason", "http_timeout")];
+        let resp = route(&request("POST", "/slack/command", &retry, body));
+        assert_eq!((resp.status, resp.body.as_slice()), (200, b"".as_slice()));
+        crate::jobs::run_deferred();
+        assert_eq!(mock.requests.borrow().len(), 2);
+    }
+
+    #[test]
+    fn route_slack_command_replies_in_the_workspace_locale() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        crate::store::store_set("workspace-config:T0ES", r#"{"intro_channel": null, "locale": "es-MX"}"#).unwrap();
+        mock.respond(500, "application/json", r#"{"error":{"message":"boom"}}"#);
+        mock.respond(200, "text/plain", "ok");
+
+        let body = "command=%2Fimagine&text=un%20zorro&team_id=T0ES&user_id=U1&channel_id=C1&response_url=https%3A%2F%2Fhooks.slack.com%2Fx";
+        let ack = String::from_utf8(route(&request("POST", "/slack/command", &[], body)).body).unwrap();
+        assert!(ack.starts_with("Trabajando en ello, puede tardar un minuto (tarea `"), "{ack}");
+        crate::jobs::run_deferred();
+        let delivered: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[1].body).unwrap();
+        assert!(delivered["text"].as_str().unwrap().starts_with("No se pudo crear esa imagen: "), "{delivered}");
+    }
+
+    #[test]
+    fn route_refuses_bodies_that_broke_the_inbound_limits() {
+        let mut req = request("POST", "/slack/command", &[], "");
+        req.body_rejected = Some(BodyRejected { status: 413, detail: "request body exceeds 1048576 bytes".into() });
+        let resp = route(&req);
+        assert_eq!((resp.status, resp.content_type()), (413, Some("application/problem+json")));
+        assert_eq!(body_json(&resp)["type"], "/problems/payload-too-large");
+        assert!(resp.headers.contains(&("connection".to_string(), "close".to_string())));
+    }
+
+    #[test]
+    fn route_openapi_is_revalidated_with_its_etag() {
+        let first = route(&request("GET", "/openapi.json", &[], ""));
+        let etag = first.headers.iter().find(|(name, _)| name == "etag").map(|(_, v)| v.clone()).unwrap();
+        let unchanged = route(&request("GET", "/openapi.json", &[("If-None-Match", &format!("\"other\", W/{etag}"))], ""));
+        assert_eq!((unchanged.status, unchanged.body.len()), (304, 0));
+        assert!(unchanged.headers.contains(&("etag".to_string(), etag.clone())));
+        let stale = route(&request("GET", "/openapi.json", &[("If-None-Match", "\"other\"")], ""));
+        assert_eq!((stale.status, stale.body), (200, first.body));
+        assert!(!route(&request("GET", "/health", &[], "")).headers.iter().any(|(name, _)| name == "etag"));
+    }
+
+    #[test]
+    fn route_rejects_malformed_json_bodies_naming_the_field() {
+        let resp = route(&request("POST", "/api/v1/query", &[], r#"{"query": "hi", "context": ["a"]}"#));
+        assert_eq!((resp.status, body_json(&resp)["detail"].as_str()), (400, Some("context: invalid type: sequence, expected a string")));
+        let resp = route(&request("POST", "/api/v1/query", &[], "query=hi"));
+        assert_eq!(resp.status, 400);
+        assert!(body_json(&resp)["detail"].as_str().unwrap().starts_with("invalid JSON: "));
+        let resp = route(&request("POST", "/slack/interactive", &[], "text=hi"));
+        assert_eq!((resp.status, resp.body), (400, b"missing form field `payload`".to_vec()));
+    }
+
+    #[test]
+    fn route_interactive_returns_modal_errors_as_json() {
+        with_openai_env();
+        let payload = serde_json::json!({
+            "type": "view_submission",
+            "user": {"id": "U1"},
+            "view": {"callback_id": "prompt_modal", "state": {"values": {"prompt": {"prompt": {"value": ""}}}}},
+        });
+        let resp = route(&request("POST", "/slack/interactive", &[], &format!("payload={}", crate::form::percent_encode(&payload.to_string()))));
+        assert_eq!((resp.status, resp.content_type()), (200, Some("application/json")));
+        assert_eq!(body_json(&resp)["errors"]["prompt"], "Enter a prompt.");
+    }
+
+    #[test]
+    fn route_job_errors_are_problem_details() {
+        crate::store::MockStore::install();
+        let resp = route(&request("GET", "/api/v1/jobs/unknown", &[], ""));
+        assert_eq!((resp.status, body_json(&resp)["type"].as_str()), (404, Some("/problems/not-found")));
+        assert_eq!(body_json(&resp)["detail"], "job not found");
+    }
+
+    #[test]
+    #[cfg(feature = "keyvalue")]
+    fn route_rpc_dispatches_health_check() {
+        let resp = route(&request("POST", "/rpc", &[], r#"{"jsonrpc":"2.0","id":7,"method":"health-check"}"#));
+        assert_eq!(body_json(&resp), serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": "ok"}));
+    }
+
+    #[test]
+    fn route_connect_calls_agent_operations_and_reports_errors() {
+        let json = [("Content-Type", "application/json"), ("Connect-Protocol-Version", "1")];
+        let resp = route(&request("POST", "/component.ai_agent.v1.AgentService/ProcessQuery", &json, r#"{"query": "hi"}"#));
+        assert_eq!((resp.status, body_json(&resp)), (200, serde_json::json!({"text": "query=hi, context=None"})));
+
+        let resp = route(&request("POST", "/component.ai_agent.v1.AgentService/FetchAndProcess", &json, "{}"));
+        assert_eq!((resp.status, body_json(&resp)), (400, serde_json::json!({"code": "invalid_argument", "message": "missing field: url"})));
+
+        let resp = route(&request("POST", "/component.ai_agent.v1.AgentService/Summon", &json, "{}"));
+        assert_eq!((resp.status, &body_json(&resp)["code"]), (404, &serde_json::json!("unimplemented")));
+        let resp = route(&request("POST", "/component.ai_agent.v1.AgentService/HealthCheck", &[("Content-Type", "application/proto")], ""));
+        assert_eq!(resp.status, 415);
+    }
+
+    const ADMIN: (&str, &str) = ("authorization", "Bearer admin-secret");
+
+    #[test]
+    fn route_debug_httpreq_forwards_method_and_body() {
+        env::set_var("ADMIN_TOKEN", "admin-secret");
+        let mock = MockHttpClient::install();
+        mock.respond(201, "application/json", r#"{"created":true}"#);
+
+        let resp = route(&request(
+            "POST",
+            "/debug/httpreq?method=put&url=https%3A%2F%2Fapi.example.com%2Fitems%2F1",
+            &[("Content-Type", "application/json"), ADMIN],
+            r#"{"name":"widget"}"#,
+        ));
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body, b"PUT https://api.example.com/items/1\nHTTP 201\ncontent-type: application/json\n\n{\"created\":true}");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].body, br#"{"name":"widget"}"#);
+        assert!(requests[0].headers.contains(&("content-type".to_string(), "application/json".to_string())));
+
+        let resp = route(&request("GET", "/debug/httpreq?method=TRACE&url=https://api.example.com/", &[ADMIN], ""));
+        assert_eq!(resp.status, 400);
+        let resp = route(&request("GET", "/debug/httpreq?url=https://api.example.com/", &[], ""));
+        assert_eq!(resp.status, 401);
+    }
+
+    #[test]
+    fn route_debug_httpget_sends_header_params_without_echoing_them() {
+        env::set_var("ADMIN_TOKEN", "admin-secret");
+        std::env::set_var("FETCH_SECRET_ROUTER_TOKEN", "Bearer r0uter");
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok":true}"#);
+
+        let resp = route(&request("GET", "/debug/httpget?url=https://api.example.com/v1&header=Accept:+application/json&header=X-Trace:+abc", &[ADMIN], ""));
+        assert_eq!(resp.body, b"GET https://api.example.com/v1\nHTTP 200\ncontent-type: application/json\n\n{\"ok\":true}");
+        {
+            let requests = mock.requests.borrow();
+            assert!(requests[0].headers.contains(&("accept".to_string(), "application/json".to_string())));
+            assert!(requests[0].headers.contains(&("x-trace".to_string(), "abc".to_string())));
+        }
+
+        // A secret must never go to a host the caller picked, and the probe needs the admin token
+        let path = "/debug/httpget?url=https://attacker.example/&header=Authorization:secret:router_token";
+        let refused = route(&request("GET", path, &[ADMIN], ""));
+        assert!(String::from_utf8(refused.body).unwrap().contains("can only be referenced in FETCH_HEADERS"));
+        assert_eq!(route(&request("GET", path, &[], "")).status, 401);
+        assert_eq!(mock.requests.borrow().len(), 1);
+    }
+
+    #[test]
+    fn route_egress_exceptions_end_with_the_response() {
+        with_openai_env();
+        env::set_var("ADMIN_TOKEN", "admin-secret");
+        crate::store::MockStore::install();
+        crate::egress::set_test_policy("api.example.com:443", "/debug/httpget=*");
+        let mock = MockHttpClient::install();
+        mock.respond(200, "text/plain", "anywhere");
+
+        let resp = route(&request("GET", "/debug/httpget?url=https://anywhere.example/", &[ADMIN], ""));
+        assert!(String::from_utf8(resp.body).unwrap().ends_with("anywhere"));
+        // Queued work runs after the response, outside the route's exception
+        crate::jobs::defer_task(crate::jobs::DeferredTask::SlackPost { channel: "C1".into(), text: "digest".into() });
+        crate::jobs::run_deferred();
+        assert_eq!(mock.requests.borrow().len(), 1);
+
+        // The streaming route is served outside route() and ends its exception too
+        crate::egress::set_test_policy("api.example.com:443", "/api/v1/query/stream=*");
+        mock.respond(200, "text/event-stream", "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"},\"finish_reason\":\"stop\"}]}\n\ndata: [DONE]\n\n");
+        stream_query(&request("GET", "/api/v1/query/stream?q=hi", &[], ""), "req-1", &mut |_, _| {});
+        assert_eq!(mock.requests.borrow().len(), 2);
+        crate::jobs::defer_task(crate::jobs::DeferredTask::SlackPost { channel: "C1".into(), text: "digest".into() });
+        crate::jobs::run_deferred();
+        assert_eq!(mock.requests.borrow().len(), 2);
+    }
+
+    #[test]
+    fn streamed_queries_run_under_the_route_deadline() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.fail_with(AgentError::Timeout("response body timed out".into()));
+
+        let mut events = Vec::new();
+        stream_query(&request("GET", "/api/v1/query/stream?q=hi", &[], ""), "req-1", &mut |event, data| {
+            events.push((event.to_string(), data.clone()));
+        });
+        assert_eq!(mock.requests.borrow()[0].timeout_ms, Some(route_deadline_ms("/api/v1/query/stream")));
+        let names: Vec<&str> = events.iter().map(|(event, _)| event.as_str()).collect();
+        assert_eq!(names, ["timeout", "done"]);
+        assert_eq!(events[0].1, serde_json::json!({"error": "response body timed out", "request_id": "req-1"}));
+    }
+
+    #[test]
+    fn route_debug_httpget_shows_status_and_headers_for_any_status() {
+        let mock = MockHttpClient::install();
+        mock.respond_with_headers(429, &[("content-type", "text/plain"), ("content-length", "4"), ("retry-after", "30")], "wait");
+
+        env::set_var("ADMIN_TOKEN", "admin-secret");
+        let resp = route(&request("GET", "/debug/httpget?url=https://api.example.com/limited", &[ADMIN], ""));
+        assert_eq!(
+            String::from_utf8(resp.body).unwrap(),
+            "GET https://api.example.com/limited\nHTTP 429\ncontent-type: text/plain\ncontent-length: 4\nretry-after: 30\n\nwait"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! JSON-RPC 2.0 endpoint.
+
+use crate::bindings::exports::component::ai_agent::ai_agent;
+use crate::{Component, fetch_and_process, multi_source_response, process_query};
+
+/* ---- JSON-RPC 2.0 endpoint ---- */
+const RPC_PARSE_ERROR: i64 = -32700;
+const RPC_INVALID_REQUEST: i64 = -32600;
+const RPC_METHOD_NOT_FOUND: i64 = -32601;
+const RPC_INVALID_PARAMS: i64 = -32602;
+const RPC_SERVER_ERROR: i64 = -32000;
+
+pub(crate) fn handle_rpc(body: &str) -> Option<String> {
+    let parsed = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(v) => v,
+        Err(e) => {
+            return Some(rpc_error(serde_json::Value::Null, RPC_PARSE_ERROR, &format!("Parse error: {e}")).to_string());
+        }
+    };
+
+    match parsed {
+        serde_json::Value::Array(calls) => {
+            if calls.is_empty() {
+                return Some(rpc_error(serde_json::Value::Null, RPC_INVALID_REQUEST, "Invalid Request: empty batch").to_string());
+            }
+            let replies: Vec<serde_json::Value> = calls.iter().filter_map(rpc_dispatch).collect();
+            if replies.is_empty() { None } else { Some(serde_json::Value::Array(replies).to_string()) }
+        }
+        single => rpc_dispatch(&single).map(|r| r.to_string()),
+    }
+}
+
+/// Executes one call; returns None for notifications (requests without an id).
+fn rpc_dispatch(call: &serde_json::Value) -> Option<serde_json::Value> {
+    if !call.is_object() || call["jsonrpc"] != "2.0" || !call["method"].is_string() {
+        return Some(rpc_error(call.get("id").cloned().unwrap_or_default(), RPC_INVALID_REQUEST, "Invalid Request"));
+    }
+    let id = call.get("id").cloned();
+    let method = call["method"].as_str().unwrap_or_default();
+    let params = call.get("params").cloned().unwrap_or(serde_json::Value::Null);
+
+    let result = match method {
+        "process-query" => match (rpc_param(&params, 0, "query"), rpc_param(&params, 1, "context")) {
+            (Some(q), ctx) => match q.as_str() {
+                Some(q) => Ok(process_query(q, ctx.as_ref().and_then(|c| c.as_str()))),
+                None => Err((RPC_INVALID_PARAMS, "query must be a string".to_string())),
+            },
+            (None, _) => Err((RPC_INVALID_PARAMS, "missing param: query".to_string())),
+        },
+        "fetch-and-process" => match rpc_param(&params, 0, "url").and_then(|u| u.as_str().map(str::to_string)) {
+            Some(url) => Ok(fetch_and_process(&url)),
+            None => Err((RPC_INVALID_PARAMS, "missing param: url".to_string())),
+        },
+        "multi-source-response" => {
+            let query = rpc_param(&params, 0, "query").and_then(|q| q.as_str().map(str::to_string));
+            let urls = rpc_param(&params, 1, "urls").and_then(|u| {
+                u.as_array().map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect::<Vec<_>>())
+            });
+            match (query, urls) {
+                (Some(q), Some(u)) => Ok(multi_source_response(&q, &u)),
+                _ => Err((RPC_INVALID_PARAMS, "expected params: query (string), urls (string[])".to_string())),
+            }
+        }
+        "health-check" => Ok(Ok(<Component as ai_agent::Guest>::health_check())),
+        "version" => Ok(Ok(<Component as ai_agent::Guest>::version())),
+        other => Err((RPC_METHOD_NOT_FOUND, format!("Method not found: {other}"))),
+    };
+
+    // Notifications get no reply, even on error
+    let id = id?;
+    Some(match result {
+        Ok(Ok(value)) => serde_json::json!({"jsonrpc": "2.0", "id": id, "result": value}),
+        Ok(Err(e)) => rpc_error(id, RPC_SERVER_ERROR, &e.to_string()),
+        Err((code, msg)) => rpc_error(id, code, &msg),
+    })
+}
+
+/// Looks up a parameter by name (object params) or position (array params).
+fn rpc_param(params: &serde_json::Value, index: usize, name: &str) -> Option<serde_json::Value> {
+    match params {
+        serde_json::Value::Object(map) => map.get(name).cloned(),
+        serde_json::Value::Array(list) => list.get(index).cloned(),
+        _ => None,
+    }
+}
+
+fn rpc_error(id: serde_json::Value, code: i64, message: &str) -> serde_json::Value {
+    serde_json::json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
@@ -0,0 +1,436 @@
+//! Background scheduler, configured jobs and cron schedules.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{clock, now_secs};
+use crate::config::get_env_var;
+use crate::cookies::CookieJar;
+use crate::documents::extract_document_text;
+use crate::error::AgentError;
+use crate::experiments::post_quality_report;
+use crate::httpclient::{fetch_document, redact_userinfo};
+use crate::jobs::drain_inflight;
+use crate::requestid::log;
+use crate::robots::ensure_allowed;
+use crate::shortlinks::citation_footer;
+use crate::slack::slack_post_message;
+use crate::store::{store_get, store_set};
+use crate::summarize::summarize_document;
+use crate::urls::canonicalize_url;
+use crate::warmup::run_warmup;
+
+/* ---- Background scheduler ---- */
+// The component only runs while serving requests, so periodic tasks execute in the idle tail
+// after a response is finished (or via /admin/tick). Next-run timestamps live in the store under
+// `schedule:{name}`, so schedules survive restarts and are shared across instances.
+struct ScheduledTask {
+    name: &'static str,
+    interval_secs: u64,
+    run: fn() -> Result<String, AgentError>,
+}
+
+const SCHEDULED_TASKS: &[ScheduledTask] = &[
+    ScheduledTask { name: "drain-inflight", interval_secs: 60, run: task_drain_inflight },
+    ScheduledTask { name: "warmup-refresh", interval_secs: 1800, run: task_warmup_refresh },
+    ScheduledTask { name: "quality-report", interval_secs: 7 * 86_400, run: post_quality_report },
+];
+
+enum Schedule {
+    Every(u64),
+    Cron(CronSchedule),
+}
+
+impl Schedule {
+    fn next_after(&self, now: u64) -> u64 {
+        match self {
+            Schedule::Every(secs) => now + secs,
+            Schedule::Cron(cron) => cron.next_after(now),
+        }
+    }
+}
+
+enum TaskAction {
+    Builtin(fn() -> Result<String, AgentError>),
+    Job(JobDefinition),
+}
+
+struct TaskEntry {
+    name: String,
+    schedule: Schedule,
+    action: TaskAction,
+}
+
+/// Built-in interval tasks plus the jobs configured in `JOBS`.
+fn scheduled_tasks() -> Vec<TaskEntry> {
+    let mut tasks: Vec<TaskEntry> = SCHEDULED_TASKS
+        .iter()
+        .map(|t| TaskEntry { name: t.name.to_string(), schedule: Schedule::Every(t.interval_secs), action: TaskAction::Builtin(t.run) })
+        .collect();
+    for job in configured_jobs() {
+        match parse_schedule(&job.schedule) {
+            Ok(cron) => tasks.push(TaskEntry { name: format!("job:{}", job.name), schedule: Schedule::Cron(cron), action: TaskAction::Job(job) }),
+            Err(e) => log!("scheduler: job '{}' skipped: {e}", job.name),
+        }
+    }
+    tasks
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct ScheduleState {
+    next_run: u64,
+    last_run: Option<u64>,
+    last_result: Option<String>,
+}
+
+fn task_drain_inflight() -> Result<String, AgentError> {
+    let report = drain_inflight(false);
+    Ok(format!("flushed {}, failed {}, waiting {}", report.flushed, report.failed, report.waiting))
+}
+
+fn task_warmup_refresh() -> Result<String, AgentError> {
+    let report = run_warmup();
+    Ok(format!("openai ok={}, slack ok={}", report["openai"]["ok"], report["slack"]["ok"]))
+}
+
+fn load_schedule(name: &str) -> Option<ScheduleState> {
+    store_get(&format!("schedule:{name}"))
+        .ok()
+        .flatten()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+}
+
+fn save_schedule(name: &str, state: &ScheduleState) {
+    if let Err(e) = store_set(&format!("schedule:{name}"), &serde_json::to_string(state).unwrap_or_default()) {
+        log!("scheduler: cannot persist {name}: {e}");
+    }
+}
+
+/// Runs every task whose next-run time has passed; returns a summary per executed task.
+pub(crate) fn scheduler_tick() -> Vec<serde_json::Value> {
+    let mut ran = Vec::new();
+    for task in scheduled_tasks() {
+        let now = now_secs();
+        let mut state = match load_schedule(&task.name) {
+            Some(state) => state,
+            // Interval tasks run on first sight; cron jobs wait for their first slot
+            None if matches!(task.schedule, Schedule::Every(_)) => ScheduleState::default(),
+            None => {
+                let state = ScheduleState { next_run: task.schedule.next_after(now), ..Default::default() };
+                save_schedule(&task.name, &state);
+                continue;
+            }
+        };
+        if state.next_run > now {
+            continue;
+        }
+        // Claim the slot before running so concurrent instances skip it
+        state.next_run = task.schedule.next_after(now);
+        save_schedule(&task.name, &state);
+
+        let result = match &task.action {
+            TaskAction::Builtin(run) => run(),
+            TaskAction::Job(job) => run_job(job, false).map(|p| format!("posted to {}", p.channel)),
+        };
+        state.last_run = Some(now);
+        state.last_result = Some(match &result {
+            Ok(msg) => msg.clone(),
+            Err(e) => format!("error: {e}"),
+        });
+        save_schedule(&task.name, &state);
+        ran.push(serde_json::json!({"task": task.name, "result": state.last_result, "next_run": state.next_run}));
+    }
+    ran
+}
+
+/// Ticks once, then optionally waits (on a monotonic-clock pollable) for tasks that fall due
+/// within `SCHEDULER_IDLE_WAIT_SECS`.
+pub(crate) fn scheduler_idle() {
+    let _ = scheduler_tick();
+    let wait = get_env_var("SCHEDULER_IDLE_WAIT_SECS").and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    if wait == 0 {
+        return;
+    }
+    let deadline = now_secs() + wait;
+    loop {
+        let now = now_secs();
+        let next_due = scheduled_tasks()
+            .iter()
+            .map(|t| load_schedule(&t.name).map_or(0, |s| s.next_run))
+            .min()
+            .unwrap_or(u64::MAX);
+        // Still due right after a tick means the schedule could not be persisted
+        if next_due > deadline || now >= deadline || next_due <= now {
+            return;
+        }
+        clock().sleep_until(next_due);
+        let _ = scheduler_tick();
+    }
+}
+
+/* ---- Configured jobs ("every weekday 09:00 UTC: fetch URL, summarize, post to #news") ---- */
+#[derive(Serialize, Deserialize, Clone)]
+struct JobDefinition {
+    name: String,
+    schedule: String,
+    url: String,
+    channel: String,
+    #[serde(default)]
+    prompt: Option<String>,
+    /// Fetch even where robots.txt disallows it, e.g. for the team's own pages
+    #[serde(default)]
+    ignore_robots: bool,
+}
+
+struct JobPost {
+    channel: String,
+    text: String,
+}
+
+/// `JOBS` is either a JSON array of job objects or one job per line (or `;`-separated):
+/// `[name =] <schedule>: fetch <url>, summarize, post to <channel>[, ignore robots.txt]`
+fn configured_jobs() -> Vec<JobDefinition> {
+    let Some(raw) = get_env_var("JOBS") else { return Vec::new() };
+    if raw.trim_start().starts_with('[') {
+        return serde_json::from_str(&raw).unwrap_or_else(|e| {
+            log!("JOBS: invalid JSON: {e}");
+            Vec::new()
+        });
+    }
+    raw.split(['\n', ';'])
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .enumerate()
+        .filter_map(|(i, line)| match parse_job_line(line, i + 1) {
+            Ok(job) => Some(job),
+            Err(e) => {
+                log!("JOBS: line {} ignored: {e}", i + 1);
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_job_line(line: &str, index: usize) -> Result<JobDefinition, AgentError> {
+    let (name, rest) = match line.split_once('=') {
+        Some((n, r)) if !n.contains(':') => (n.trim().to_string(), r.trim()),
+        _ => (format!("job-{index}"), line),
+    };
+    // The schedule ends at the first ": " (times like 09:00 contain a bare colon)
+    let (schedule, action) = rest.split_once(": ").ok_or(AgentError::Validation("expected '<schedule>: <action>'".into()))?;
+    let mut url = None;
+    let mut channel = None;
+    let mut ignore_robots = false;
+    for step in action.split(',').map(str::trim) {
+        let lower = step.to_lowercase();
+        if lower.starts_with("fetch ") {
+            url = Some(step[6..].trim().to_string());
+        } else if lower.starts_with("post to ") {
+            channel = Some(step[8..].trim().to_string());
+        } else if lower == "ignore robots.txt" {
+            ignore_robots = true;
+        } else if lower != "summarize" {
+            return Err(AgentError::Validation(format!("unknown step '{step}'")));
+        }
+    }
+    Ok(JobDefinition {
+        name,
+        schedule: schedule.trim().to_string(),
+        url: url.ok_or(AgentError::Validation("missing 'fetch <url>'".into()))?,
+        channel: channel.ok_or(AgentError::Validation("missing 'post to <channel>'".into()))?,
+        prompt: None,
+        ignore_robots,
+    })
+}
+
+/// Fetches and summarizes the job's URL; posts to the channel unless `dry_run`.
+fn run_job(job: &JobDefinition, dry_run: bool) -> Result<JobPost, AgentError> {
+    let url = canonicalize_url(&job.url)?;
+    if !job.ignore_robots {
+        ensure_allowed(&url)?;
+    }
+    let doc = fetch_document(&url, &[], Some(&mut CookieJar::default())).map_err(|e| e.context(&format!("fetch {}", redact_userinfo(&url))))?;
+    let text = extract_document_text(doc.meta.content_type.as_deref(), &url, &doc.body);
+    if text.is_empty() {
+        return Err(AgentError::Validation(format!("no text content at {}", redact_userinfo(&url))));
+    }
+    let system = job.prompt.clone().unwrap_or_else(|| {
+        "Summarize the following page as a short digest for a Slack channel: a one-line headline, then 3-5 bullet points.".to_string()
+    });
+    let summary = summarize_document(&text, &system, 400)?;
+    let post = JobPost {
+        channel: job.channel.clone(),
+        text: format!("*{}*\n{}{}", job.name, summary, citation_footer(&[url])),
+    };
+    if !dry_run {
+        slack_post_message(&post.channel, &post.text)?;
+    }
+    Ok(post)
+}
+
+pub(crate) fn handle_admin_jobs(preview: bool, name: Option<&str>) -> String {
+    let jobs = configured_jobs();
+    if !preview {
+        let list: Vec<serde_json::Value> = jobs
+            .iter()
+            .map(|job| {
+                let state = load_schedule(&format!("job:{}", job.name));
+                serde_json::json!({
+                    "name": job.name,
+                    "schedule": job.schedule,
+                    "schedule_error": parse_schedule(&job.schedule).err().map(|e| e.to_string()),
+                    "url": redact_userinfo(&job.url),
+                    "channel": job.channel,
+                    "ignore_robots": job.ignore_robots,
+                    "next_run": state.as_ref().map(|s| s.next_run),
+                    "last_run": state.as_ref().and_then(|s| s.last_run),
+                    "last_result": state.and_then(|s| s.last_result),
+                })
+            })
+            .collect();
+        return serde_json::Value::Array(list).to_string();
+    }
+    let Some(job) = jobs.iter().find(|j| Some(j.name.as_str()) == name) else {
+        return serde_json::json!({"error": format!("unknown job {:?}", name.unwrap_or_default())}).to_string();
+    };
+    match run_job(job, true) {
+        Ok(post) => serde_json::json!({"dry_run": true, "channel": post.channel, "text": post.text}).to_string(),
+        Err(e) => serde_json::json!({"dry_run": true, "error": e.to_string()}).to_string(),
+    }
+}
+
+/* ---- Cron schedules (UTC) ---- */
+struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+}
+
+/// Accepts 5-field cron (`0 9 * * 1-5`) or phrases: `every day 09:00`, `every weekday 09:00 UTC`,
+/// `every weekend 10:30`, `every monday 08:00`, `every hour`, `every 15 minutes`.
+fn parse_schedule(spec: &str) -> Result<CronSchedule, AgentError> {
+    let spec = spec.trim();
+    let lower = spec.to_lowercase();
+    let lower = lower.strip_suffix(" utc").unwrap_or(&lower).trim().to_string();
+    let Some(phrase) = lower.strip_prefix("every ") else {
+        return parse_cron(spec);
+    };
+
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    let cron = match words.as_slice() {
+        ["hour"] => "0 * * * *".to_string(),
+        [n, "minutes"] => format!("*/{n} * * * *"),
+        [day, time] => {
+            let (h, m) = time.split_once(':').ok_or_else(|| AgentError::Validation(format!("bad time '{time}'")))?;
+            let dow = match *day {
+                "day" => "*".to_string(),
+                "weekday" => "1-5".to_string(),
+                "weekend" => "0,6".to_string(),
+                other => weekday_number(other).ok_or_else(|| AgentError::Validation(format!("unknown day '{other}'")))?.to_string(),
+            };
+            let hour: u32 = h.parse().map_err(|_| AgentError::Validation(format!("bad time '{time}'")))?;
+            let minute: u32 = m.parse().map_err(|_| AgentError::Validation(format!("bad time '{time}'")))?;
+            format!("{minute} {hour} * * {dow}")
+        }
+        _ => return Err(AgentError::Validation(format!("unsupported schedule '{spec}'"))),
+    };
+    parse_cron(&cron)
+}
+
+fn weekday_number(name: &str) -> Option<u32> {
+    let days = ["sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday"];
+    days.iter().position(|d| *d == name || d[..3] == *name).map(|i| i as u32)
+}
+
+fn parse_cron(expr: &str) -> Result<CronSchedule, AgentError> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Err(AgentError::Validation(format!("expected 5 cron fields, got {}", fields.len())));
+    }
+    Ok(CronSchedule {
+        minutes: parse_cron_field(fields[0], 0, 59)?,
+        hours: parse_cron_field(fields[1], 0, 23)?,
+        days_of_month: parse_cron_field(fields[2], 1, 31)?,
+        months: parse_cron_field(fields[3], 1, 12)?,
+        // 7 is an alias for Sunday
+        days_of_week: {
+            let mask = parse_cron_field(fields[4], 0, 7)?;
+            (mask | (mask >> 7)) & 0x7f
+        },
+    })
+}
+
+/// Bitmask of allowed values for one field: `*`, `n`, `a-b`, lists and `/step`.
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64, AgentError> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => (r, s.parse::<u32>().map_err(|_| AgentError::Validation(format!("bad step in '{part}'")))?),
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (a.parse().map_err(|_| AgentError::Validation(format!("bad range '{range}'")))?, b.parse().map_err(|_| AgentError::Validation(format!("bad range '{range}'")))?)
+        } else {
+            let v: u32 = range.parse().map_err(|_| AgentError::Validation(format!("bad value '{range}'")))?;
+            (v, if step > 1 { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi || step == 0 {
+            return Err(AgentError::Validation(format!("'{part}' out of range {min}-{max}")));
+        }
+        let mut v = lo;
+        while v <= hi {
+            mask |= 1 << v;
+            v += step;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronSchedule {
+    /// First matching minute strictly after `now` (searches up to ~4 years ahead).
+    fn next_after(&self, now: u64) -> u64 {
+        use chrono::{Datelike, Timelike};
+        let mut t = (now / 60 + 1) * 60;
+        let limit = now + 4 * 366 * 86_400;
+        while t < limit {
+            let Some(dt) = chrono::DateTime::from_timestamp(t as i64, 0) else { break };
+            let day_ok = self.months & (1 << dt.month()) != 0
+                && self.days_of_month & (1 << dt.day()) != 0
+                && self.days_of_week & (1 << dt.weekday().num_days_from_sunday()) != 0;
+            if !day_ok {
+                t = (t / 86_400 + 1) * 86_400;
+            } else if self.hours & (1 << dt.hour()) == 0 {
+                t = (t / 3_600 + 1) * 3_600;
+            } else if self.minutes & (1 << dt.minute()) == 0 {
+                t += 60;
+            } else {
+                return t;
+            }
+        }
+        u64::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cron_next_after_is_deterministic() {
+        // Friday 2026-10-16 10:00:00 UTC
+        let friday = 1_792_144_800;
+        let cron = parse_schedule("every weekday 09:00 UTC").unwrap();
+        assert_eq!(cron.next_after(friday), friday + 3 * 86_400 - 3_600);
+        let cron = parse_schedule("*/15 * * * *").unwrap();
+        assert_eq!(cron.next_after(friday), friday + 900);
+    }
+    #[test]
+    fn job_lines_can_opt_out_of_robots_txt() {
+        let job = parse_job_line("intranet = every day 08:00: fetch https://wiki.internal/status, summarize, post to #ops, ignore robots.txt", 1).unwrap();
+        assert_eq!((job.name.as_str(), job.url.as_str(), job.ignore_robots), ("intranet", "https://wiki.internal/status", true));
+        assert!(!parse_job_line("every hour: fetch https://example.com/, post to #news", 2).unwrap().ignore_robots);
+    }
+}
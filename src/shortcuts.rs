@@ -0,0 +1,182 @@
+//! Slack shortcuts: "Summarize this message" on a message's menu and the global shortcuts menu.
+
+use crate::error::AgentError;
+use crate::interactive::post_to_response_url;
+use crate::jobs::{DeferredTask, defer_task};
+use crate::messages::{set_locale, workspace_locale};
+use crate::modals::{PROMPT_SHORTCUT_CALLBACK, open_prompt_modal};
+use crate::payloads::SlackInteraction;
+use crate::requestid::log;
+use crate::residency::{set_region, workspace_region};
+use crate::slack::api::{OutgoingMessage, SlackApi};
+use crate::summarize::summarize_document;
+use crate::usage::set_requester;
+use crate::workspaces::{CAPABILITIES, set_workspace};
+
+/* ---- Message and global shortcuts (message_action, shortcut) ---- */
+// Shortcuts are registered in the Slack app with a callback ID, which is how they are routed
+// here. A message shortcut (`message_action`) carries the message it was chosen on; "Summarize
+// this message" summarizes it after the ack and posts the summary in the message's thread, or
+// starts a thread under it. When the bot cannot post there (say, it is not in the channel), the
+// summary goes to the user privately through `response_url` instead. A global shortcut
+// (`shortcut`) has no message or channel; "What can the assistant do?" answers in the user's DMs
+// and "Ask with options" opens the prompt modal (see `modals`).
+// Unknown callback IDs are acknowledged and ignored like unknown actions.
+pub(crate) const SUMMARIZE_MESSAGE_CALLBACK: &str = "summarize_message";
+pub(crate) const CAPABILITIES_CALLBACK: &str = "assistant_capabilities";
+const SUMMARY_MAX_TOKENS: u32 = 300;
+const SUMMARY_INSTRUCTION: &str = "Summarize the following Slack message in a few short bullet points. Keep names, figures, \
+     decisions, requests and deadlines; do not add anything that is not in the message.";
+
+/// Handles a `message_action` or `shortcut` interaction by its callback ID.
+pub(crate) fn handle_shortcut(interaction: &SlackInteraction) -> Result<(), AgentError> {
+    let callback_id = interaction.callback_id.as_deref().unwrap_or_default();
+    let missing = |field: &str| AgentError::Validation(format!("{callback_id}: missing {field}"));
+    let team_id = interaction.team.as_ref().map(|t| t.id.clone()).unwrap_or_default();
+    let user = interaction.user.as_ref().ok_or_else(|| missing("user"))?.id.clone();
+    match (interaction.kind.as_str(), callback_id) {
+        ("message_action", SUMMARIZE_MESSAGE_CALLBACK) => {
+            let message = interaction.message.as_ref().ok_or_else(|| missing("message"))?;
+            defer_task(DeferredTask::SummarizeMessage {
+                team_id,
+                user,
+                channel: interaction.channel.as_ref().ok_or_else(|| missing("channel"))?.id.clone(),
+                // A reply is summarized in its own thread; a top-level message gets a new thread
+                thread_ts: message.thread_ts.clone().unwrap_or_else(|| message.ts.clone()),
+                text: message.text.clone(),
+                response_url: interaction.response_url.clone().ok_or_else(|| missing("response_url"))?,
+            });
+        }
+        ("shortcut", CAPABILITIES_CALLBACK) => defer_task(DeferredTask::ShortcutCapabilities { team_id, user }),
+        ("shortcut", PROMPT_SHORTCUT_CALLBACK) => open_prompt_modal(interaction)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Summarizes a message chosen with "Summarize this message" and posts the summary in its thread.
+pub(crate) fn run_summarize_message(
+    team_id: &str,
+    user: &str,
+    channel: &str,
+    thread_ts: &str,
+    text: &str,
+    response_url: &str,
+) -> Result<(), AgentError> {
+    set_region(workspace_region(team_id));
+    set_locale(workspace_locale(team_id));
+    set_workspace(team_id);
+    set_requester(user, channel);
+    let private = |text: String| post_to_response_url(response_url, &serde_json::json!({"response_type": "ephemeral", "text": text}));
+    if text.trim().is_empty() {
+        private("That message has no text to summarize.".to_string());
+        return Ok(());
+    }
+    let summary = format!("*Summary for <@{user}>*\n{}", summarize_document(text, SUMMARY_INSTRUCTION, SUMMARY_MAX_TOKENS)?);
+    let posted = SlackApi::from_env()?.post_message(&OutgoingMessage { channel, text: &summary, thread_ts: Some(thread_ts), ..Default::default() });
+    if let Err(e) = posted {
+        log!("message summary not posted in {channel}: {e}");
+        private(format!("I couldn't post in that thread ({e}), so here is the summary just for you:\n{summary}"));
+    }
+    Ok(())
+}
+
+/// Sends the list of commands to `user`'s DMs.
+pub(crate) fn send_capabilities(team_id: &str, user: &str) -> Result<(), AgentError> {
+    set_workspace(team_id);
+    let api = SlackApi::from_env()?;
+    let channel = api.open_dm(user)?;
+    let text = format!("Here is what I can do:\n{CAPABILITIES}");
+    api.post_message(&OutgoingMessage { channel: &channel, text: &text, ..Default::default() }).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::interactive::handle_interaction;
+    use crate::jobs::run_deferred;
+    use crate::llm::completion;
+
+    fn message_action(message: serde_json::Value) -> String {
+        serde_json::json!({
+            "type": "message_action",
+            "callback_id": SUMMARIZE_MESSAGE_CALLBACK,
+            "trigger_id": "13345224609.8534564800.6f8ab1f53e13d0cd15f96106292d5536",
+            "team": {"id": "T1"},
+            "user": {"id": "U1"},
+            "channel": {"id": "C1"},
+            "message": message,
+            "response_url": "https://hooks.slack.com/app/x",
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn summaries_are_posted_in_the_messages_thread() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("• Launch moves to Friday"));
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "1700000000.000200"}"#);
+
+        let text = "Heads up: the launch moves to Friday because QA found a blocker.";
+        handle_interaction(&message_action(serde_json::json!({"ts": "1700000000.000100", "text": text, "user": "U2"}))).unwrap();
+        run_deferred();
+
+        let requests = mock.requests.borrow();
+        let prompt: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(prompt["messages"][1]["content"], text);
+        assert_eq!(requests[1].url, "https://slack.com/api/chat.postMessage");
+        let post: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!((post["channel"].as_str(), post["thread_ts"].as_str()), (Some("C1"), Some("1700000000.000100")));
+        assert_eq!(post["text"], "*Summary for <@U1>*\n• Launch moves to Friday");
+    }
+
+    #[test]
+    fn summaries_fall_back_to_the_user_when_the_thread_is_closed() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("• Short"));
+        mock.respond(200, "application/json", r#"{"ok": false, "error": "not_in_channel"}"#);
+        mock.respond(200, "text/plain", "ok");
+
+        let reply = serde_json::json!({"ts": "1700000000.000300", "thread_ts": "1700000000.000100", "text": "A reply"});
+        handle_interaction(&message_action(reply)).unwrap();
+        run_deferred();
+
+        let requests = mock.requests.borrow();
+        let post: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(post["thread_ts"], "1700000000.000100");
+        assert_eq!(requests[2].url, "https://hooks.slack.com/app/x");
+        let notice: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(notice["response_type"], "ephemeral");
+        assert!(notice["text"].as_str().unwrap().starts_with("I couldn't post in that thread (chat.postMessage: not_in_channel)"), "{notice}");
+    }
+
+    #[test]
+    fn the_global_shortcut_answers_in_dms_and_unknown_ones_are_ignored() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true, "channel": {"id": "D1"}}"#);
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "1"}"#);
+
+        let shortcut = |callback_id: &str| {
+            serde_json::json!({"type": "shortcut", "callback_id": callback_id, "trigger_id": "1.2.3", "team": {"id": "T1"}, "user": {"id": "U1"}})
+                .to_string()
+        };
+        handle_interaction(&shortcut("something_else")).unwrap();
+        handle_interaction(&shortcut(CAPABILITIES_CALLBACK)).unwrap();
+        run_deferred();
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "https://slack.com/api/conversations.open");
+        let post: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(post["channel"], "D1");
+        assert!(post["text"].as_str().unwrap().starts_with("Here is what I can do:\n"));
+    }
+}
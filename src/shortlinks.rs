@@ -0,0 +1,170 @@
+//! Short links for citation footers.
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::httpclient::{http_get_text, redact_userinfo};
+use crate::form::percent_encode;
+use crate::requestid::log;
+use crate::router::Response;
+use crate::store::{store_get, store_set};
+
+/* ---- Short links for citation footers ---- */
+// URLs at or below this length are already compact enough for Slack
+const SHORTEN_MIN_LEN: usize = 40;
+
+/// `URL_SHORTENER=internal` stores the link and serves it from `/r/{id}` under `PUBLIC_BASE_URL`;
+/// any other value is treated as an external endpoint that takes the URL appended (percent-encoded)
+/// and returns the short link as plain text. Falls back to the original URL on any failure.
+fn shorten_url(url: &str) -> String {
+    if url.len() <= SHORTEN_MIN_LEN {
+        return url.to_string();
+    }
+    let shortened = match get_env_var("URL_SHORTENER").as_deref() {
+        None => return url.to_string(),
+        Some("internal") => store_shortlink(url),
+        Some(endpoint) => http_get_text(&format!("{endpoint}{}", percent_encode(url)))
+            .map(|s| s.trim().to_string())
+            .and_then(|s| if s.starts_with("http") { Ok(s) } else { Err(AgentError::Http(format!("unexpected shortener reply: {s}"))) }),
+    };
+    match shortened {
+        Ok(short) => short,
+        Err(e) => {
+            log!("URL shortening failed for {url}: {e}");
+            url.to_string()
+        }
+    }
+}
+
+fn store_shortlink(url: &str) -> Result<String, AgentError> {
+    let base = get_env_var("PUBLIC_BASE_URL").ok_or(AgentError::Config("PUBLIC_BASE_URL not set".into()))?;
+    // Deterministic ids: the same URL always maps to the same short link
+    let mut id = base62(fnv1a64(url.as_bytes()));
+    id.truncate(8);
+    match store_get(&format!("shortlink:{id}"))? {
+        Some(existing) if existing != url => {
+            id = base62(fnv1a64(format!("{url}#{id}").as_bytes()));
+            store_set(&format!("shortlink:{id}"), url)?;
+        }
+        Some(_) => {}
+        None => store_set(&format!("shortlink:{id}"), url)?,
+    }
+    Ok(format!("{}/r/{id}", base.trim_end_matches('/')))
+}
+
+pub(crate) fn handle_shortlink_redirect(id: &str) -> Response {
+    let target = if id.is_empty() { Ok(None) } else { store_get(&format!("shortlink:{id}")) };
+    match target {
+        Ok(Some(url)) => Response::new(302, "text/plain", format!("Redirecting to {url}")).header("location", &url),
+        Ok(None) => Response::new(404, "text/plain", "unknown link"),
+        Err(e) => Response::new(503, "text/plain", format!("store unavailable: {e}")),
+    }
+}
+
+/// Slack mrkdwn footer listing sources as numbered, shortened links.
+pub(crate) fn citation_footer(urls: &[String]) -> String {
+    if urls.is_empty() {
+        return String::new();
+    }
+    let links: Vec<String> = urls
+        .iter()
+        .enumerate()
+        .map(|(i, url)| format!("<{}|[{}]>", shorten_url(&redact_userinfo(url)), i + 1))
+        .collect();
+    format!("\n\n*Sources:* {}", links.join(" · "))
+}
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+fn base62(mut n: u64) -> String {
+    const ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut out = Vec::new();
+    loop {
+        out.push(ALPHABET[(n % 62) as usize]);
+        n /= 62;
+        if n == 0 { break; }
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/* ---- Inline citations with footnotes ---- */
+// Citations above this are not source numbers but bracketed figures such as `[2024]`
+const MAX_CITATION: usize = 99;
+
+/// Parses `[1]` or `[1, 3]` at the start of `text`: the marker's length and its numbers.
+fn citation_marker(text: &str) -> Option<(usize, Vec<usize>)> {
+    let end = text.find(']')?;
+    let inner = text.get(1..end)?;
+    let numbers = inner
+        .split(',')
+        .map(|n| n.trim().parse::<usize>().ok().filter(|n| (1..=MAX_CITATION).contains(n)))
+        .collect::<Option<Vec<_>>>()?;
+    Some((end + 1, numbers))
+}
+
+/// Checks the answer's `[n]` citations against `urls` and appends footnotes for the cited
+/// sources. Citations of sources that do not exist are removed; an answer citing nothing gets
+/// every source listed, as with `citation_footer`.
+pub(crate) fn footnote_citations(answer: &str, urls: &[String]) -> String {
+    let mut text = String::with_capacity(answer.len());
+    let mut cited: Vec<usize> = Vec::new();
+    let mut rest = answer;
+    while let Some(i) = rest.find('[') {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let Some((len, numbers)) = citation_marker(rest) else {
+            text.push('[');
+            rest = &rest[1..];
+            continue;
+        };
+        let valid: Vec<String> = numbers.iter().filter(|n| **n <= urls.len()).map(|n| n.to_string()).collect();
+        if valid.is_empty() {
+            text.truncate(text.trim_end_matches(' ').len());
+        } else {
+            text.push_str(&format!("[{}]", valid.join(", ")));
+        }
+        cited.extend(numbers.into_iter().filter(|n| *n <= urls.len()));
+        rest = &rest[len..];
+    }
+    text.push_str(rest);
+
+    if cited.is_empty() {
+        return format!("{text}{}", citation_footer(urls));
+    }
+    cited.sort_unstable();
+    cited.dedup();
+    let notes: Vec<String> = cited
+        .iter()
+        .map(|n| {
+            let url = redact_userinfo(&urls[n - 1]);
+            let label = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest).trim_end_matches('/');
+            let label = if label.chars().count() > 60 { format!("{}…", label.chars().take(59).collect::<String>()) } else { label.to_string() };
+            format!("[{n}] <{}|{label}>", shorten_url(&url))
+        })
+        .collect();
+    format!("{text}\n\n*Sources*\n{}", notes.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn citations_are_checked_and_footnoted() {
+        let urls = vec!["https://example.com/a".to_string(), "https://docs.example.org/guide/".to_string()];
+        let answer = "Rust 1.80 stabilized LazyLock [2]. It shipped in July [1, 4] and was planned since [2019]. Unsupported claim [3].";
+        assert_eq!(
+            footnote_citations(answer, &urls),
+            "Rust 1.80 stabilized LazyLock [2]. It shipped in July [1] and was planned since [2019]. Unsupported claim.\n\n\
+             *Sources*\n[1] <https://example.com/a|example.com/a>\n[2] <https://docs.example.org/guide/|docs.example.org/guide>"
+        );
+    }
+
+    #[test]
+    fn answers_without_citations_list_every_source() {
+        let urls = vec!["https://example.com/a".to_string()];
+        assert_eq!(footnote_citations("No markers [here].", &urls), format!("No markers [here].{}", citation_footer(&urls)));
+    }
+}
@@ -0,0 +1,188 @@
+//! AWS Signature Version 4 for outgoing requests to AWS APIs.
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::form::{percent_decode, percent_encode};
+use crate::httpclient::{HttpRequest, split_url};
+
+/* ---- Credentials (AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN) ---- */
+pub(crate) struct AwsCredentials {
+    pub(crate) access_key_id: String,
+    pub(crate) secret_access_key: String,
+    /// Set for temporary credentials, sent as `x-amz-security-token`
+    pub(crate) session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    pub(crate) fn from_env() -> Result<AwsCredentials, AgentError> {
+        let (Some(access_key_id), Some(secret_access_key)) = (get_env_var("AWS_ACCESS_KEY_ID"), get_env_var("AWS_SECRET_ACCESS_KEY")) else {
+            return Err(AgentError::Config("AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set".into()));
+        };
+        Ok(AwsCredentials { access_key_id, secret_access_key, session_token: get_env_var("AWS_SESSION_TOKEN") })
+    }
+}
+
+/* ---- Canonical request ---- */
+// https://docs.aws.amazon.com/IAM/latest/UserGuide/create-signed-request.html. Every header on
+// the request is signed, plus `host`, which wasi:http derives from the URL rather than taking
+// it from the header list. Path segments are encoded again on top of the encoding they were
+// sent with, except for S3, which signs the path as sent.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn canonical_uri(path: &str, service: &str) -> String {
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| if service == "s3" { percent_encode(&percent_decode(segment)) } else { percent_encode(segment) })
+        .collect();
+    segments.join("/")
+}
+
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_encode(&percent_decode(name)), percent_encode(&percent_decode(value)))
+        })
+        .collect();
+    pairs.sort();
+    pairs.iter().map(|(name, value)| format!("{name}={value}")).collect::<Vec<_>>().join("&")
+}
+
+/// Lowercased names with their trimmed, space-collapsed values, sorted by name.
+fn canonical_headers(headers: &[(String, String)]) -> Vec<(String, String)> {
+    let mut canonical: Vec<(String, String)> = headers
+        .iter()
+        .filter(|(name, _)| !name.eq_ignore_ascii_case("authorization"))
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .collect();
+    canonical.sort();
+    // Repeated headers are signed once, with their values comma-separated
+    canonical.dedup_by(|later, earlier| {
+        if later.0 != earlier.0 {
+            return false;
+        }
+        earlier.1 = format!("{},{}", earlier.1, later.1);
+        true
+    });
+    canonical
+}
+
+/// Adds `x-amz-date` (plus `x-amz-security-token`, and `x-amz-content-sha256` for S3) and the
+/// `Authorization` header for `service` in `region`, signed at `now` (Unix seconds).
+pub(crate) fn sign_request(req: &mut HttpRequest, credentials: &AwsCredentials, region: &str, service: &str, now: u64) -> Result<(), AgentError> {
+    let time = chrono::DateTime::from_timestamp(now as i64, 0).ok_or_else(|| AgentError::Validation("invalid signing time".into()))?;
+    let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = time.format("%Y%m%d").to_string();
+    let payload_hash = sha256_hex(&req.body);
+
+    req.headers.retain(|(name, _)| {
+        !["authorization", "x-amz-date", "x-amz-security-token", "x-amz-content-sha256"].iter().any(|h| name.eq_ignore_ascii_case(h))
+    });
+    req.headers.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        req.headers.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    if service == "s3" {
+        req.headers.push(("x-amz-content-sha256".to_string(), payload_hash.clone()));
+    }
+
+    let (_, authority, path_and_query) = split_url(&req.url)?;
+    let (path, query) = path_and_query.split_once('?').unwrap_or((&path_and_query, ""));
+    let host = authority.strip_suffix(":443").or_else(|| authority.strip_suffix(":80")).unwrap_or(&authority);
+    let mut signed = req.headers.clone();
+    signed.push(("host".to_string(), host.to_string()));
+    let headers = canonical_headers(&signed);
+    let signed_headers = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n\n{signed_headers}\n{payload_hash}",
+        req.method,
+        canonical_uri(path, service),
+        canonical_query(query),
+        headers.iter().map(|(name, value)| format!("{name}:{value}")).collect::<Vec<_>>().join("\n"),
+    );
+
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}", sha256_hex(canonical_request.as_bytes()));
+    let key = [date.as_str(), region, service, "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", credentials.secret_access_key).into_bytes(), |key, part| hmac(&key, part));
+    let signature = hex(&hmac(&key, &string_to_sign));
+    req.headers.push((
+        "authorization".to_string(),
+        format!("AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}", credentials.access_key_id),
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // From the AWS Signature Version 4 test suite
+    const EXAMPLE_TIME: u64 = 1_440_938_160; // 2015-08-30T12:36:00Z
+
+    fn example_credentials(session_token: Option<&str>) -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: session_token.map(str::to_string),
+        }
+    }
+
+    fn authorization(req: &HttpRequest) -> &str {
+        req.headers.iter().find(|(name, _)| name == "authorization").map(|(_, v)| v.as_str()).unwrap()
+    }
+
+    #[test]
+    fn get_vanilla_matches_the_aws_test_suite() {
+        let mut req = HttpRequest { method: "GET", url: "https://example.amazonaws.com/".into(), headers: Vec::new(), body: Vec::new(), timeout_ms: None };
+        sign_request(&mut req, &example_credentials(None), "us-east-1", "service", EXAMPLE_TIME).unwrap();
+        assert_eq!(
+            authorization(&req),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert!(req.headers.contains(&("x-amz-date".to_string(), "20150830T123600Z".to_string())));
+    }
+
+    #[test]
+    fn query_and_path_are_canonicalized() {
+        assert_eq!(canonical_query("Version=2010-05-08&Action=ListUsers&b=a%20b&a"), "Action=ListUsers&Version=2010-05-08&a=&b=a%20b");
+        assert_eq!(canonical_uri("/model/anthropic.claude-v2%3A1/invoke", "bedrock"), "/model/anthropic.claude-v2%253A1/invoke");
+        assert_eq!(canonical_uri("/my%20bucket/key", "s3"), "/my%20bucket/key");
+    }
+
+    #[test]
+    fn session_tokens_and_existing_headers_are_signed() {
+        let mut req = HttpRequest {
+            method: "POST",
+            url: "https://bedrock-runtime.us-west-2.amazonaws.com:443/model/m/invoke".into(),
+            headers: vec![("Content-Type".into(), "application/json".into()), ("authorization".into(), "Bearer stale".into())],
+            body: b"{}".to_vec(),
+            timeout_ms: None,
+        };
+        sign_request(&mut req, &example_credentials(Some("token")), "us-west-2", "bedrock", EXAMPLE_TIME).unwrap();
+        assert!(
+            authorization(&req).contains("/20150830/us-west-2/bedrock/aws4_request, SignedHeaders=content-type;host;x-amz-date;x-amz-security-token,")
+        );
+        assert_eq!(req.headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("authorization")).count(), 1);
+        assert!(req.headers.contains(&("x-amz-security-token".to_string(), "token".to_string())));
+    }
+}
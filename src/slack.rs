@@ -0,0 +1,705 @@
+//! Slack slash commands, message formatting and chat.postMessage.
+
+pub(crate) mod api;
+pub(crate) mod blocks;
+pub(crate) mod signature;
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::experiments::{assign_prompt_version, feedback_buttons, record_answer};
+use crate::history::{Exchange, exchange_key, store_exchange, thread_exchanges};
+use crate::interactive::follow_up_buttons;
+use crate::llm::{
+    SamplingOptions,
+    answer_oversized_input,
+    call_openai,
+    call_openai_conversation,
+    call_openai_json,
+    call_openai_structured,
+    call_openai_with_system,
+    chat_model,
+    condense_threshold,
+};
+use crate::messages::{Msg, message};
+use crate::prefs::{answer_visibility, user_prefs};
+use crate::profiles::requester_context;
+use crate::requestid::{current_request_id, log, with_ref};
+use crate::residency::current_region;
+use crate::slackfiles::{slack_file_ids, slack_file_reply, upload_slack_file};
+use crate::tables::{render_markdown_table, table_from_json};
+use crate::workspaces::{current_workspace, installation};
+
+use self::api::{OutgoingMessage, SlackApi};
+use self::blocks::{Block, Button, MAX_BLOCKS, MAX_SECTION_TEXT, blocks_json};
+
+/* ---- Slash command reply (flags, oversized input, OpenAI or fallback) ---- */
+pub(crate) fn slack_command_reply(text: &str, user_id: &str, channel_id: &str) -> String {
+    let (text, private) = answer_visibility(text, user_id);
+    slack_command_answer(&text, user_id, channel_id, private).text
+}
+
+/// A slash command answer. `blocks` replaces the plain-text rendering with Block Kit (including the
+/// feedback buttons of prompt-experiment answers); `text` is then the notification fallback.
+pub(crate) struct SlashAnswer {
+    pub(crate) text: String,
+    pub(crate) blocks: Option<serde_json::Value>,
+    /// The whole answer when `text` only begins it and the rest was uploaded as a file
+    pub(crate) full_text: Option<String>,
+}
+
+impl From<String> for SlashAnswer {
+    fn from(text: String) -> Self {
+        SlashAnswer { text, blocks: None, full_text: None }
+    }
+}
+
+/// Answers a question and keeps the exchange in the channel's history; `private` answers only the asker.
+pub(crate) fn slack_command_answer(text: &str, user_id: &str, channel_id: &str, private: bool) -> SlashAnswer {
+    let key = exchange_key(channel_id, now_secs());
+    let answer = answer_command(text, user_id, channel_id, key.as_deref(), private, &[]);
+    remember_answer(text, user_id, key, private, None, answer)
+}
+
+/* ---- Thread conversations (THREAD_MEMORY_TURNS) ---- */
+// A question asked in a thread (an @mention, or a DM sent in a thread) is answered like a slash
+// command, but with the thread's earlier exchanges sent to the model as the conversation so far,
+// and the exchange is recorded with the thread, so the next question there sees this one too.
+// Only the latest THREAD_MEMORY_TURNS exchanges are sent, and older ones are dropped first until
+// they fit THREAD_MEMORY_CHARS, so a long thread keeps a rolling window of its recent turns.
+// /agent-forget thread clears a thread's memory along with its history.
+const DEFAULT_THREAD_MEMORY_TURNS: usize = 10;
+const THREAD_MEMORY_CHARS: usize = 12_000;
+
+/// The latest exchanges of the thread that fit the memory, oldest first; private ones only for their asker.
+fn thread_memory(channel_id: &str, thread_ts: &str, user_id: &str) -> Vec<Exchange> {
+    let turns = get_env_var("THREAD_MEMORY_TURNS").and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_THREAD_MEMORY_TURNS);
+    let mut earlier = thread_exchanges(channel_id, thread_ts, user_id).unwrap_or_else(|e| {
+        log!("thread {thread_ts} in {channel_id} answered without memory: {e}");
+        Vec::new()
+    });
+    earlier.drain(..earlier.len().saturating_sub(turns));
+    let size = |e: &[Exchange]| e.iter().map(|e| e.question.chars().count() + e.answer.chars().count()).sum::<usize>();
+    while size(&earlier) > THREAD_MEMORY_CHARS {
+        earlier.remove(0);
+    }
+    earlier
+}
+
+/// Answers a question asked in the thread `thread_ts`, continuing the thread's conversation.
+pub(crate) fn slack_thread_answer(text: &str, user_id: &str, channel_id: &str, thread_ts: &str, private: bool) -> SlashAnswer {
+    let earlier = thread_memory(channel_id, thread_ts, user_id);
+    let key = exchange_key(channel_id, now_secs());
+    let answer = answer_command(text, user_id, channel_id, key.as_deref(), private, &earlier);
+    remember_answer(text, user_id, key, private, Some(thread_ts), answer)
+}
+
+/// Records the exchange under `key` (from `exchange_key`, so `None` outside a channel).
+fn remember_answer(text: &str, user_id: &str, key: Option<String>, private: bool, thread_ts: Option<&str>, answer: SlashAnswer) -> SlashAnswer {
+    if let Some(key) = key {
+        let exchange = Exchange {
+            user_id: user_id.to_string(),
+            question: text.to_string(),
+            answer: answer.full_text.clone().unwrap_or_else(|| answer.text.clone()),
+            private,
+            thread_ts: thread_ts.map(str::to_string),
+            at: now_secs(),
+            region: current_region(),
+        };
+        store_exchange(&key, &exchange);
+    }
+    answer
+}
+
+/// `exchange_key` is where the answer will be recorded, for the follow-up buttons under it.
+/// `earlier` is the conversation so far, for questions asked in a thread.
+fn answer_command(text: &str, user_id: &str, channel_id: &str, exchange_key: Option<&str>, private: bool, earlier: &[Exchange]) -> SlashAnswer {
+    // Linked Slack files turn the command into a question about them (images) or a transcript (clips)
+    let file_ids = slack_file_ids(text);
+    if !file_ids.is_empty() {
+        return slack_file_reply(text, &file_ids).into();
+    }
+
+    // `--table` switches to structured-output mode
+    let (text, structured) = match text.strip_suffix("--table").or_else(|| text.strip_prefix("--table")) {
+        Some(rest) => (rest.trim().to_string(), true),
+        None => (text.to_string(), false),
+    };
+
+    // Oversized pastes are condensed first so they fit the model's context
+    if text.chars().count() > condense_threshold() {
+        let notice = format!(
+            "_Your input was {} characters, so it was condensed before answering._",
+            text.chars().count()
+        );
+        return match answer_oversized_input(&text) {
+            Ok(answer) => format!("{notice}\n\n{}", render_for_slack(&answer)),
+            Err(e) => with_ref(
+                &message(Msg::InputTooLong, &[("chars", &text.chars().count().to_string()), ("error", &e.to_string())]),
+                &current_request_id(),
+            ),
+        }
+        .into();
+    }
+
+    // Plain questions from a known channel take part in the prompt experiment, if one is configured
+    let version = if structured || channel_id.is_empty() {
+        None
+    } else {
+        match assign_prompt_version(user_id, channel_id) {
+            Ok(version) => version,
+            Err(e) => return with_ref(&message(Msg::AiUnavailable, &[("text", &text), ("error", &e.to_string())]), &current_request_id()).into(),
+        }
+    };
+
+    // The user's verbosity, language and persona shape plain answers wherever they ask
+    let prefs = user_prefs(user_id);
+    // So does who they are, when personalization is on: name, time zone and locale
+    let base: Vec<String> = version.as_ref().map(|v| v.system.clone()).into_iter().chain(requester_context(user_id)).collect();
+    let base = (!base.is_empty()).then(|| base.join("\n\n"));
+
+    // Build reply content via OpenAI or fallback
+    let answer = if structured {
+        call_openai_structured(&text, None, &SamplingOptions::default()).map(|v| v.to_string())
+    } else if !earlier.is_empty() {
+        let turns: Vec<(&str, &str)> = earlier.iter().map(|e| (e.question.as_str(), e.answer.as_str())).collect();
+        call_openai_conversation(prefs.system_prompt(base.as_deref()).as_deref(), &turns, &text, prefs.max_tokens())
+    } else if let Some(system) = prefs.system_prompt(base.as_deref()) {
+        call_openai_with_system(&system, &text, prefs.max_tokens())
+    } else {
+        call_openai(&text)
+    };
+    match answer {
+        Ok(ai_response) => {
+            let text = render_for_slack(&ai_response);
+            // Feedback is best effort; an unrecorded answer is still delivered, just without buttons
+            let answer_id = version.and_then(|v| record_answer(&v.name, &chat_model(), user_id, channel_id).ok());
+            let preview = long_answer_as_file(&text, user_id, channel_id, private);
+            let shown = preview.clone().unwrap_or_else(|| text.clone());
+            let blocks = answer_blocks(&redact_banned_phrases(&shown), answer_id.as_deref(), exchange_key);
+            SlashAnswer { text: shown, blocks, full_text: preview.is_some().then_some(text) }
+        }
+        Err(e) => with_ref(&message(Msg::AiUnavailable, &[("text", &text), ("error", &e.to_string())]), &current_request_id()).into(),
+    }
+}
+
+/* ---- Follow-ups ("Explain more", "Regenerate") ---- */
+// A follow-up is recorded as an exchange of its own, with the visibility of the answer it
+// follows up on, so it gets buttons of its own and a private answer never turns public.
+const EXPLAIN_PROMPT: &str = "The user asked a question and received the answer below. Explain the answer in more \
+depth: the reasoning behind it, a concrete example and any caveats. Format for Slack mrkdwn.";
+const EXPLAIN_MAX_TOKENS: u32 = 800;
+
+/// Answers `earlier`'s question again, or explains its answer further when `explain` is set.
+pub(crate) fn slack_follow_up_answer(earlier: &Exchange, explain: bool, user_id: &str, channel_id: &str) -> SlashAnswer {
+    let key = exchange_key(channel_id, now_secs());
+    if !explain {
+        let answer = answer_command(&earlier.question, user_id, channel_id, key.as_deref(), earlier.private, &[]);
+        return remember_answer(&earlier.question, user_id, key, earlier.private, None, answer);
+    }
+    let context = format!("Question: {}\n\nAnswer: {}", earlier.question, earlier.answer);
+    let answer = match call_openai_with_system(EXPLAIN_PROMPT, &context, EXPLAIN_MAX_TOKENS) {
+        Ok(explanation) => {
+            let text = render_for_slack(&explanation);
+            let preview = long_answer_as_file(&text, user_id, channel_id, earlier.private);
+            let shown = preview.clone().unwrap_or_else(|| text.clone());
+            let blocks = answer_blocks(&redact_banned_phrases(&shown), None, key.as_deref());
+            SlashAnswer { text: shown, blocks, full_text: preview.is_some().then_some(text) }
+        }
+        Err(e) => with_ref(&message(Msg::AiUnavailable, &[("text", &earlier.question), ("error", &e.to_string())]), &current_request_id()).into(),
+    };
+    remember_answer(&format!("Explain more: {}", earlier.question), user_id, key, earlier.private, None, answer)
+}
+
+/* ---- /translate <lang> <text> ---- */
+const TRANSLATE_PROMPT: &str = "You are a professional translator. Detect the language of the user's text and \
+translate it into {target}. Preserve meaning, tone, formatting, names and code. Respond only with a JSON object: \
+{\"detected_language\": \"<English name of the source language>\", \"translation\": \"<translated text>\"}";
+
+struct Translation {
+    detected_language: String,
+    translation: String,
+}
+
+fn translate_text(target: &str, text: &str) -> Result<Translation, AgentError> {
+    let system = TRANSLATE_PROMPT.replace("{target}", target);
+    let json = call_openai_json(&system, text)?;
+    match json["translation"].as_str() {
+        Some(translation) => Ok(Translation {
+            detected_language: json["detected_language"].as_str().unwrap_or("unknown").to_string(),
+            translation: translation.to_string(),
+        }),
+        None => Err(AgentError::Provider { status: 200, message: "translation missing from model output".into(), retry_after: None }),
+    }
+}
+
+pub(crate) fn slack_translate_reply(text: &str) -> String {
+    let usage = "Usage: `/translate <language> <text>` (e.g. `/translate es Where is the station?`)";
+    let Some((target, source)) = text.trim().split_once(char::is_whitespace) else {
+        return usage.to_string();
+    };
+    if source.trim().is_empty() {
+        return usage.to_string();
+    }
+    match translate_text(target, source.trim()) {
+        Ok(t) => format!(
+            "*Translation* ({} → {})\n{}",
+            t.detected_language,
+            target,
+            t.translation.lines().map(|l| format!(">{l}")).collect::<Vec<_>>().join("\n")
+        ),
+        Err(e) => with_ref(&message(Msg::TranslationUnavailable, &[("error", &e.to_string())]), &current_request_id()),
+    }
+}
+
+/* ---- /triage <text>: classification with confidence ---- */
+const DEFAULT_TRIAGE_CATEGORIES: &str = "bug,incident,feature_request,question,billing,praise,other";
+
+struct Triage {
+    category: String,
+    confidence: f64,
+    sentiment: String,
+    rationale: String,
+}
+
+fn triage_categories() -> Vec<String> {
+    get_env_var("TRIAGE_CATEGORIES")
+        .unwrap_or_else(|| DEFAULT_TRIAGE_CATEGORIES.to_string())
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+fn triage_text(text: &str, categories: &[String]) -> Result<Triage, AgentError> {
+    let system = format!(
+        "Classify the user's text (e.g. customer feedback or an incident report) into exactly one of these \
+         categories: {}. Respond only with a JSON object: {{\"category\": \"<one of the categories>\", \
+         \"confidence\": <0.0-1.0>, \"sentiment\": \"positive|neutral|negative\", \"rationale\": \"<one or two sentences>\"}}",
+        categories.join(", ")
+    );
+    let json = call_openai_json(&system, text)?;
+    let category = json["category"].as_str().unwrap_or_default().trim().to_string();
+    // Reject labels outside the configured set rather than posting them
+    if !categories.iter().any(|c| c.eq_ignore_ascii_case(&category)) {
+        return Err(AgentError::Provider { status: 200, message: format!("model returned unknown category '{category}'"), retry_after: None });
+    }
+    Ok(Triage {
+        category,
+        confidence: json["confidence"].as_f64().unwrap_or(0.0).clamp(0.0, 1.0),
+        sentiment: json["sentiment"].as_str().unwrap_or("neutral").to_string(),
+        rationale: json["rationale"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+pub(crate) fn slack_triage_reply(text: &str) -> String {
+    let text = text.trim();
+    if text.is_empty() {
+        return "Usage: `/triage <text to classify>`".to_string();
+    }
+    match triage_text(text, &triage_categories()) {
+        Ok(t) => format!(
+            "*Triage:* `{}` ({:.0}% confidence)\n*Sentiment:* {}\n*Rationale:* {}",
+            t.category,
+            t.confidence * 100.0,
+            t.sentiment,
+            t.rationale
+        ),
+        Err(e) => with_ref(&message(Msg::TriageUnavailable, &[("error", &e.to_string())]), &current_request_id()),
+    }
+}
+
+
+/// Body posted to a slash command's `response_url`, visible to the whole channel.
+pub(crate) fn slash_response_payload(text: &str) -> serde_json::Value {
+    serde_json::json!({"response_type": "in_channel", "text": redact_banned_phrases(text)})
+}
+
+/* ---- Banned phrases (BANNED_PHRASES) ---- */
+// Comma-separated phrases (competitor names, internal codenames) that must never reach a channel.
+// Matching ignores ASCII case and each occurrence is replaced, so the rest of the answer still
+// posts. LLM_LOGIT_BIAS steers the model away from the tokens up front; this is the backstop.
+const REDACTED: &str = "[redacted]";
+
+fn banned_phrases() -> Vec<String> {
+    get_env_var("BANNED_PHRASES")
+        .unwrap_or_default()
+        .split(',')
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn redact_phrases(text: &str, phrases: &[String]) -> String {
+    let mut text = text.to_string();
+    for phrase in phrases {
+        let needle = phrase.to_ascii_lowercase();
+        let mut from = 0;
+        // ASCII lowercasing keeps byte offsets, so matches index the original text
+        while let Some(i) = text.to_ascii_lowercase()[from..].find(&needle) {
+            let start = from + i;
+            text.replace_range(start..start + needle.len(), REDACTED);
+            from = start + REDACTED.len();
+        }
+    }
+    text
+}
+
+pub(crate) fn redact_banned_phrases(text: &str) -> String {
+    let phrases = banned_phrases();
+    if phrases.is_empty() { text.to_string() } else { redact_phrases(text, &phrases) }
+}
+
+/* ---- Answer layout (Block Kit) ---- */
+// Answers are sent as blocks: Markdown headings (`# Title`) become header blocks, code fences get
+// sections of their own so a fence is never split by the surrounding text, and the rest is cut
+// into sections at line breaks to stay under Slack's per-section limit. Prompt-experiment answers
+// end with the feedback buttons. An answer that needs more than MAX_BLOCKS blocks is sent as
+// plain text (without buttons), since a message with too many blocks is rejected outright.
+const FENCE: &str = "```";
+
+/// The title of a Markdown heading, without emphasis (header blocks are plain text).
+fn heading(line: &str) -> Option<String> {
+    let title = line.trim_start_matches('#');
+    let level = line.len() - title.len();
+    ((1..=6).contains(&level) && title.starts_with(' ')).then(|| title.replace(['*', '`'], "").trim().to_string())
+}
+
+/// A line opening or closing a code fence; Slack ignores a language after the backticks.
+fn is_fence(line: &str) -> bool {
+    line.trim().strip_prefix(FENCE).is_some_and(|rest| !rest.contains('`'))
+}
+
+/// Cuts `text` into pieces of at most `max` characters, at line breaks where it can.
+fn chunk_lines(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = vec![String::new()];
+    for line in text.lines() {
+        let mut line = line.to_string();
+        loop {
+            let current = chunks.last_mut().expect("chunks starts non-empty");
+            let room = max.saturating_sub(current.chars().count() + usize::from(!current.is_empty()));
+            if line.chars().count() <= room {
+                if !current.is_empty() {
+                    current.push('\n');
+                }
+                current.push_str(&line);
+                break;
+            }
+            if current.is_empty() {
+                // A single line longer than a whole section
+                let rest = line.chars().skip(max).collect();
+                current.extend(line.chars().take(max));
+                line = rest;
+            }
+            chunks.push(String::new());
+        }
+    }
+    chunks.retain(|c| !c.trim().is_empty());
+    chunks
+}
+
+fn push_sections(blocks: &mut Vec<Block>, text: &str, fenced: bool) {
+    if fenced {
+        let wrapper = format!("{FENCE}\n\n{FENCE}").chars().count();
+        blocks.extend(chunk_lines(text, MAX_SECTION_TEXT - wrapper).iter().map(|c| Block::section(&format!("{FENCE}\n{c}\n{FENCE}"))));
+    } else {
+        blocks.extend(chunk_lines(text, MAX_SECTION_TEXT).iter().map(|c| Block::section(c)));
+    }
+}
+
+/// Block Kit for an answer (already redacted); `None` when it is too long for blocks.
+/// `exchange_key` adds the "Explain more" and "Regenerate" buttons.
+pub(crate) fn answer_blocks(text: &str, answer_id: Option<&str>, exchange_key: Option<&str>) -> Option<serde_json::Value> {
+    let mut blocks = Vec::new();
+    let mut pending = String::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if is_fence(line) {
+            push_sections(&mut blocks, &std::mem::take(&mut pending), in_fence);
+            in_fence = !in_fence;
+        } else if let Some(title) = heading(line).filter(|t| !in_fence && !t.is_empty()) {
+            push_sections(&mut blocks, &std::mem::take(&mut pending), false);
+            blocks.push(Block::header(&title));
+        } else {
+            pending.push_str(line);
+            pending.push('\n');
+        }
+    }
+    // An unclosed fence still renders as code
+    push_sections(&mut blocks, &pending, in_fence);
+    let buttons: Vec<Button> = answer_id.into_iter().flat_map(feedback_buttons).chain(exchange_key.into_iter().flat_map(follow_up_buttons)).collect();
+    if !buttons.is_empty() {
+        blocks.push(Block::actions(buttons));
+    }
+    (!blocks.is_empty() && blocks.len() <= MAX_BLOCKS).then(|| blocks_json(&blocks))
+}
+
+/* ---- Long answers as file snippets ---- */
+// Slack cuts message text at about 4,000 characters. A longer answer is uploaded whole as a
+// Markdown snippet (`answer.md`), and the message shows its beginning, cut at a line break, with
+// a note pointing to the file. The file is shared in the channel, or in the asker's DMs when their
+// answers are private. If the upload fails (e.g. without the `files:write` scope) the answer is
+// sent whole as before. History keeps the whole answer either way.
+const MAX_MESSAGE_CHARS: usize = 4_000;
+const PREVIEW_CHARS: usize = 1_500;
+const ANSWER_FILENAME: &str = "answer.md";
+
+/// The message to send instead of `text` once it has been uploaded; `None` when it fits a message or could not be uploaded.
+fn long_answer_as_file(text: &str, user_id: &str, channel_id: &str, private: bool) -> Option<String> {
+    let chars = text.chars().count();
+    if chars <= MAX_MESSAGE_CHARS {
+        return None;
+    }
+    let uploaded = (|| {
+        let (channel, place) = if private || channel_id.is_empty() {
+            (SlackApi::from_env()?.open_dm(user_id)?, "in your DMs")
+        } else {
+            (channel_id.to_string(), "in this channel")
+        };
+        let comment = if user_id.is_empty() { "The full answer".to_string() } else { format!("The full answer for <@{user_id}>") };
+        upload_slack_file(&channel, ANSWER_FILENAME, "text/markdown", redact_banned_phrases(text).as_bytes(), &comment)?;
+        Ok::<_, AgentError>(place)
+    })();
+    match uploaded {
+        Ok(place) => {
+            let preview = chunk_lines(text, PREVIEW_CHARS).into_iter().next().unwrap_or_default();
+            Some(format!("{preview}\n\n_The answer is {chars} characters long, so the rest is in `{ANSWER_FILENAME}` {place}._"))
+        }
+        Err(e) => {
+            log!("long answer not uploaded, sending it whole: {e}");
+            None
+        }
+    }
+}
+
+/// Slack has no table markup, so tabular JSON is shown as an aligned table in a code block.
+fn render_for_slack(reply: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(reply.trim()) else {
+        return reply.to_string();
+    };
+    match table_from_json(&value) {
+        Some(table) => format!("```\n{}\n```", render_markdown_table(&table)),
+        None if value.is_object() || value.is_array() => {
+            format!("```\n{}\n```", serde_json::to_string_pretty(&value).unwrap_or_else(|_| reply.to_string()))
+        }
+        None => reply.to_string(),
+    }
+}
+
+/* ---- Slack chat.postMessage (bot token) ---- */
+/// The current workspace's bot token when it was installed via OAuth, else SLACK_BOT_TOKEN.
+pub(crate) fn slack_bot_token() -> Result<String, AgentError> {
+    let team_id = current_workspace();
+    if !team_id.is_empty() {
+        if let Some(install) = installation(&team_id)? {
+            return Ok(install.bot_token);
+        }
+    }
+    get_env_var("SLACK_BOT_TOKEN").ok_or(AgentError::Config("SLACK_BOT_TOKEN not set".into()))
+}
+
+pub(crate) fn slack_post_message(channel: &str, text: &str) -> Result<(), AgentError> {
+    slack_post_message_as(&slack_bot_token()?, channel, text)
+}
+
+/// chat.postMessage with a given bot token, e.g. one from a new workspace installation.
+pub(crate) fn slack_post_message_as(token: &str, channel: &str, text: &str) -> Result<(), AgentError> {
+    slack_post_reply_as(token, channel, text, None)
+}
+
+/// Posts with `token` (a workspace's own bot token), as a reply in `thread_ts` if given.
+pub(crate) fn slack_post_reply_as(token: &str, channel: &str, text: &str, thread_ts: Option<&str>) -> Result<(), AgentError> {
+    SlackApi::new(token).post_message(&OutgoingMessage { channel, text, thread_ts, ..Default::default() }).map(|_| ())
+}
+
+/// Posts to `channel`, as a reply in `thread_ts` if given, and returns the new message's `ts`.
+pub(crate) fn slack_post_message_ts(channel: &str, text: &str, thread_ts: Option<&str>) -> Result<String, AgentError> {
+    SlackApi::from_env()?.post_message(&OutgoingMessage { channel, text, thread_ts, ..Default::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+    use crate::shortlinks::citation_footer;
+
+    // Golden files live in testdata/slack/; run with UPDATE_GOLDEN=1 to accept new output
+    fn assert_golden(name: &str, actual: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/slack").join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("{}: {e} (run with UPDATE_GOLDEN=1 to create it)", path.display()));
+        assert_eq!(actual, expected, "{name} differs from its golden file (run with UPDATE_GOLDEN=1 to accept)");
+    }
+
+    fn pretty(value: &serde_json::Value) -> String {
+        serde_json::to_string_pretty(value).unwrap() + "\n"
+    }
+
+    #[test]
+    fn golden_render_for_slack() {
+        let rows = r#"{"rows": [{"plan": "Free", "price": 0, "seats": 1}, {"plan": "Team", "price": 12.5, "seats": null}, {"plan": "Enterprise", "price": "contact us", "seats": 500}]}"#;
+        assert_golden("render_table.txt", &render_for_slack(rows));
+        assert_golden("render_object.txt", &render_for_slack(r#"{"answer": "42", "sources": ["a", "b"]}"#));
+        assert_eq!(render_for_slack("*Plain* mrkdwn stays as is"), "*Plain* mrkdwn stays as is");
+    }
+
+    #[test]
+    fn golden_slash_response_payload() {
+        let urls = vec!["https://example.com/a".to_string(), "https://example.org/b?x=1&y=2".to_string()];
+        let text = format!("*Summary*\n• first point\n• second point with `code` & <angle>{}", citation_footer(&urls));
+        assert_golden("slash_response_payload.json", &pretty(&slash_response_payload(&text)));
+    }
+
+    #[test]
+    fn golden_answer_blocks() {
+        let answer = "## Setting up *Redis*\nInstall it first:\n```bash\nbrew install redis\n\n# not a heading\n```\nThen run `redis-server`.";
+        assert_golden("answer_blocks.json", &pretty(&answer_blocks(answer, Some("a1"), Some("history:C1:1700000000:k1")).unwrap()));
+    }
+
+    #[test]
+    fn long_answers_are_split_into_sections_and_very_long_ones_stay_text() {
+        let line = "x".repeat(1200);
+        let code = format!("```\n{}\n```", vec![line.as_str(); 3].join("\n"));
+        let blocks = answer_blocks(&format!("{line}\n{line}\n{line}\n{code}"), None, None).unwrap();
+        let texts: Vec<&str> = blocks.as_array().unwrap().iter().map(|b| b["text"]["text"].as_str().unwrap()).collect();
+        assert_eq!(texts.len(), 4);
+        assert_eq!(texts[0].len(), 2 * 1200 + 1);
+        assert!(texts[2].starts_with("```\n") && texts[3].ends_with("\n```") && texts.iter().all(|t| t.chars().count() <= 3000));
+        assert!(answer_blocks(&"y".repeat(3001 * 50), None, None).is_none());
+        assert!(answer_blocks("  \n", None, None).is_none());
+    }
+
+    #[test]
+    fn answers_over_the_message_limit_are_uploaded_as_a_snippet() {
+        with_openai_env();
+        crate::clock::MockClock::install(1_700_000_000);
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        let long = format!("{}\n{}", "a".repeat(1_000), "b".repeat(3_500));
+        mock.respond(200, "application/json", &completion(&long));
+        mock.respond(200, "application/json", r#"{"ok":true,"upload_url":"https://files.slack.com/upload/v1/x","file_id":"F0ANS"}"#);
+        mock.respond(200, "text/plain", "OK");
+        mock.respond(200, "application/json", r#"{"ok":true}"#);
+        mock.respond(200, "application/json", &completion(&long));
+        mock.respond(200, "application/json", r#"{"ok":false,"error":"missing_scope"}"#);
+
+        let answer = slack_command_answer("Tell me everything", "U1", "C1", false);
+        assert_eq!(answer.text, format!("{}\n\n_The answer is 4501 characters long, so the rest is in `answer.md` in this channel._", "a".repeat(1_000)));
+        assert_eq!(answer.full_text.as_deref(), Some(long.as_str()));
+        {
+            let requests = mock.requests.borrow();
+            assert_eq!(requests[2].body, long.as_bytes());
+            let shared: serde_json::Value = serde_json::from_slice(&requests[3].body).unwrap();
+            assert_eq!((shared["channel_id"].as_str(), shared["initial_comment"].as_str()), (Some("C1"), Some("The full answer for <@U1>")));
+        }
+        let keys = crate::store::store_list_keys("history:C1:").unwrap();
+        assert_eq!(crate::history::load_exchange(&keys[0]).unwrap().unwrap().answer, long);
+
+        // Without the scope the answer is sent whole
+        assert_eq!(slack_command_answer("Tell me everything", "U1", "", false).text, long);
+    }
+
+    #[test]
+    fn personalized_answers_know_who_is_asking() {
+        with_openai_env();
+        crate::clock::MockClock::install(1_700_000_000);
+        crate::store::MockStore::install();
+        crate::workspaces::set_workspace("T1");
+        crate::store::store_set("workspace-config:T1", r#"{"personalize": true}"#).unwrap();
+        crate::store::store_set("prefs:U1", r#"{"verbosity": "brief"}"#).unwrap();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true, "user": {"id": "U1", "tz": "America/New_York", "tz_offset": -18000, "profile": {"real_name": "Grace"}}}"#);
+        mock.respond(200, "application/json", &completion("Tomorrow is Wednesday, Grace."));
+
+        assert_eq!(slack_command_answer("What day is tomorrow?", "U1", "", false).text, "Tomorrow is Wednesday, Grace.");
+        let prompt: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[1].body).unwrap();
+        let system = prompt["messages"][0]["content"].as_str().unwrap();
+        assert!(system.starts_with("You are talking to Grace;"), "{system}");
+        assert!(system.contains("America/New_York (UTC-05:00), where it is now Tuesday 2023-11-14 17:13."), "{system}");
+        assert!(system.ends_with("Keep answers to one or two sentences."), "{system}");
+    }
+
+    #[test]
+    fn thread_memory_keeps_the_latest_turns_that_fit() {
+        crate::store::MockStore::install();
+        let exchange = |i: u64, answer: String, private: bool| Exchange {
+            user_id: "U1".into(),
+            question: format!("q{i}"),
+            answer,
+            private,
+            thread_ts: Some("1.0".into()),
+            at: 1_700_000_000 + i,
+            region: None,
+        };
+        for i in 0..12 {
+            crate::history::record_test_exchange("C1", &exchange(i, format!("a{i}"), false));
+        }
+        crate::history::record_test_exchange("C1", &exchange(12, "mine".into(), true));
+        let questions = |user: &str| thread_memory("C1", "1.0", user).into_iter().map(|e| e.question).collect::<Vec<_>>();
+        assert_eq!(questions("U1"), (3..13).map(|i| format!("q{i}")).collect::<Vec<_>>());
+        assert_eq!(questions("U2").first().map(String::as_str), Some("q2"));
+
+        crate::history::record_test_exchange("C1", &exchange(13, "x".repeat(THREAD_MEMORY_CHARS - 3), false));
+        assert_eq!(questions("U2"), ["q13"]);
+    }
+
+    #[test]
+    fn golden_translate_and_triage_replies() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion(r#"{"detected_language": "French", "translation": "Where is the station?\nIs it far?"}"#));
+        mock.respond(
+            200,
+            "application/json",
+            &completion(r#"{"category": "billing", "confidence": 0.874, "sentiment": "negative", "rationale": "The customer was charged twice."}"#),
+        );
+
+        assert_golden("translate_reply.txt", &slack_translate_reply("en Où est la gare ? C'est loin ?"));
+        assert_golden("triage_reply.txt", &slack_triage_reply("I was charged twice this month!"));
+    }
+
+    #[test]
+    fn golden_chat_post_message_payload() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok":true}"#);
+
+        slack_post_message("#news", "*Daily digest*\n>quoted line\n<https://example.com|link>").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        assert_golden("chat_post_message.json", &pretty(&body));
+    }
+
+    #[test]
+    fn banned_phrases_are_redacted_ignoring_case() {
+        let phrases = vec!["Project Falcon".to_string(), "acme".to_string()];
+        assert_eq!(
+            redact_phrases("Unlike ACME Corp, project falcon ships Friday. Acme, acme.", &phrases),
+            "Unlike [redacted] Corp, [redacted] ships Friday. [redacted], [redacted]."
+        );
+        assert_eq!(redact_phrases("Über nothing to hide", &phrases), "Über nothing to hide");
+    }
+
+    #[test]
+    fn slack_post_message_sends_bot_token_and_checks_ok() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok":true}"#);
+        mock.respond(200, "application/json", r#"{"ok":false,"error":"channel_not_found"}"#);
+
+        slack_post_message("#news", "digest").unwrap();
+        let err = slack_post_message("#missing", "digest").unwrap_err();
+        assert_eq!(err, AgentError::Slack("chat.postMessage: channel_not_found".into()));
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/chat.postMessage");
+        assert!(requests[0].headers.contains(&("authorization".to_string(), "Bearer xoxb-test".to_string())));
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, serde_json::json!({"channel": "#news", "text": "digest"}));
+    }
+}
@@ -0,0 +1,281 @@
+//! Slack Web API client (chat.postMessage, chat.update, conversations.history, users.info, ...).
+
+use serde::Deserialize;
+
+use crate::error::AgentError;
+use crate::form::percent_encode;
+use crate::httpclient::{http_get_bytes, http_post_json};
+use crate::slack::blocks::View;
+use crate::slack::{redact_banned_phrases, slack_bot_token};
+
+/* ---- Web API client (SLACK_BOT_TOKEN or a workspace's bot token) ---- */
+// Write methods are called with a JSON body and read methods with query parameters, both with
+// the bot token as a bearer token. A reply with `ok: false` becomes AgentError::Slack naming the
+// method and Slack's error code (e.g. `chat.update: message_not_found`); an HTTP error status
+// does too, with the status. Message text goes through BANNED_PHRASES redaction like every
+// other post; blocks are the caller's to redact. Paged reads follow `next_cursor` up to `limit`.
+const MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+const PAGE_SIZE: usize = 200;
+
+/// A Web API response body, or its `error` when `ok` is false.
+pub(crate) fn slack_api_result(method: &str, body: &[u8]) -> Result<serde_json::Value, AgentError> {
+    let json: serde_json::Value = serde_json::from_slice(body).map_err(|e| AgentError::Slack(format!("{method}: {e}")))?;
+    if json["ok"].as_bool() == Some(true) {
+        Ok(json)
+    } else {
+        Err(AgentError::Slack(format!("{method}: {}", json["error"].as_str().unwrap_or("unknown error"))))
+    }
+}
+
+fn http_error(method: &str, e: AgentError) -> AgentError {
+    match e {
+        AgentError::Provider { status, message, .. } => AgentError::Slack(format!("{method} HTTP {status}: {message}")),
+        AgentError::Http(message) => AgentError::Slack(format!("{method}: {message}")),
+        other => other,
+    }
+}
+
+/// A message as conversations.history and conversations.replies return it.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct SlackMessage {
+    pub(crate) ts: String,
+    #[serde(default)]
+    pub(crate) text: String,
+    pub(crate) user: Option<String>,
+    pub(crate) bot_id: Option<String>,
+    pub(crate) thread_ts: Option<String>,
+    pub(crate) subtype: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub(crate) struct SlackProfile {
+    #[serde(default)]
+    pub(crate) display_name: String,
+    #[serde(default)]
+    pub(crate) real_name: String,
+    #[serde(default)]
+    pub(crate) title: String,
+}
+
+/// A user as users.info returns it.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct SlackUser {
+    pub(crate) id: String,
+    #[serde(default)]
+    pub(crate) name: String,
+    /// IANA time zone, e.g. `Europe/Paris`
+    pub(crate) tz: Option<String>,
+    /// Seconds east of UTC in that zone right now
+    #[serde(default)]
+    pub(crate) tz_offset: i64,
+    /// e.g. `en-US`; users.info sends it when asked with `include_locale`
+    pub(crate) locale: Option<String>,
+    #[serde(default)]
+    pub(crate) is_bot: bool,
+    #[serde(default)]
+    pub(crate) profile: SlackProfile,
+}
+
+impl SlackUser {
+    /// The name Slack shows for the user: display name, else real name, else handle.
+    pub(crate) fn display_name(&self) -> &str {
+        [&self.profile.display_name, &self.profile.real_name, &self.name].into_iter().find(|n| !n.is_empty()).map_or("", |n| n.as_str())
+    }
+}
+
+/// A message to post: plain `text`, or `blocks` with `text` as the notification fallback.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutgoingMessage<'a> {
+    pub(crate) channel: &'a str,
+    pub(crate) text: &'a str,
+    pub(crate) blocks: Option<serde_json::Value>,
+    /// Posts as a reply in this thread
+    pub(crate) thread_ts: Option<&'a str>,
+}
+
+impl OutgoingMessage<'_> {
+    fn payload(&self) -> serde_json::Value {
+        let mut payload = serde_json::json!({"channel": self.channel, "text": redact_banned_phrases(self.text)});
+        if let Some(blocks) = &self.blocks {
+            payload["blocks"] = blocks.clone();
+        }
+        if let Some(ts) = self.thread_ts {
+            payload["thread_ts"] = ts.into();
+        }
+        payload
+    }
+}
+
+pub(crate) struct SlackApi {
+    token: String,
+}
+
+impl SlackApi {
+    pub(crate) fn new(token: &str) -> SlackApi {
+        SlackApi { token: token.to_string() }
+    }
+
+    /// The client for the current workspace's bot token (see `slack_bot_token`).
+    pub(crate) fn from_env() -> Result<SlackApi, AgentError> {
+        Ok(SlackApi::new(&slack_bot_token()?))
+    }
+
+    /// Calls a write method with a JSON body.
+    pub(crate) fn call(&self, method: &str, payload: &serde_json::Value) -> Result<serde_json::Value, AgentError> {
+        let body = http_post_json(&format!("https://slack.com/api/{method}"), &payload.to_string(), &self.token).map_err(|e| http_error(method, e))?;
+        slack_api_result(method, body.as_bytes())
+    }
+
+    /// Calls a read method with query parameters.
+    pub(crate) fn get(&self, method: &str, params: &[(&str, &str)]) -> Result<serde_json::Value, AgentError> {
+        let query: Vec<String> = params.iter().map(|(name, value)| format!("{name}={}", percent_encode(value))).collect();
+        let url = if query.is_empty() {
+            format!("https://slack.com/api/{method}")
+        } else {
+            format!("https://slack.com/api/{method}?{}", query.join("&"))
+        };
+        let (_, body) = http_get_bytes(&url, &[("authorization", format!("Bearer {}", self.token))], MAX_RESPONSE_BYTES)
+            .map_err(|e| http_error(method, e))?;
+        slack_api_result(method, &body)
+    }
+
+    /// chat.postMessage; returns the new message's `ts`.
+    pub(crate) fn post_message(&self, message: &OutgoingMessage) -> Result<String, AgentError> {
+        let json = self.call("chat.postMessage", &message.payload())?;
+        Ok(json["ts"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// chat.postEphemeral: a message only `user` sees, until they reload Slack.
+    pub(crate) fn post_ephemeral(&self, message: &OutgoingMessage, user: &str) -> Result<(), AgentError> {
+        let mut payload = message.payload();
+        payload["user"] = user.into();
+        self.call("chat.postEphemeral", &payload).map(|_| ())
+    }
+
+    /// chat.update: replaces the text (and blocks) of the bot's message `ts`.
+    pub(crate) fn update_message(&self, message: &OutgoingMessage, ts: &str) -> Result<(), AgentError> {
+        let mut payload = message.payload();
+        payload["ts"] = ts.into();
+        if let Some(payload) = payload.as_object_mut() {
+            payload.remove("thread_ts");
+        }
+        self.call("chat.update", &payload).map(|_| ())
+    }
+
+    pub(crate) fn delete_message(&self, channel: &str, ts: &str) -> Result<(), AgentError> {
+        self.call("chat.delete", &serde_json::json!({"channel": channel, "ts": ts})).map(|_| ())
+    }
+
+    /// views.publish: replaces the user's App Home tab with `view`.
+    pub(crate) fn publish_view(&self, user: &str, view: &View) -> Result<(), AgentError> {
+        self.call("views.publish", &serde_json::json!({"user_id": user, "view": view})).map(|_| ())
+    }
+
+    /// views.open: shows the modal `view`; `trigger_id` comes from an interaction and expires after three seconds.
+    pub(crate) fn open_view(&self, trigger_id: &str, view: &View) -> Result<(), AgentError> {
+        self.call("views.open", &serde_json::json!({"trigger_id": trigger_id, "view": view})).map(|_| ())
+    }
+
+    /// conversations.history: up to `limit` messages, newest first, optionally only after `oldest`.
+    pub(crate) fn conversation_history(&self, channel: &str, oldest: Option<&str>, limit: usize) -> Result<Vec<SlackMessage>, AgentError> {
+        let mut params = vec![("channel", channel)];
+        params.extend(oldest.map(|ts| ("oldest", ts)));
+        self.paged_messages("conversations.history", params, limit)
+    }
+
+    /// conversations.replies: the thread under `ts`, parent first, up to `limit` messages.
+    pub(crate) fn conversation_replies(&self, channel: &str, ts: &str, limit: usize) -> Result<Vec<SlackMessage>, AgentError> {
+        self.paged_messages("conversations.replies", vec![("channel", channel), ("ts", ts)], limit)
+    }
+
+    fn paged_messages(&self, method: &str, params: Vec<(&str, &str)>, limit: usize) -> Result<Vec<SlackMessage>, AgentError> {
+        let mut messages = Vec::new();
+        let mut cursor = String::new();
+        while messages.len() < limit {
+            let page_size = (limit - messages.len()).min(PAGE_SIZE).to_string();
+            let mut page = params.clone();
+            page.push(("limit", &page_size));
+            if !cursor.is_empty() {
+                page.push(("cursor", &cursor));
+            }
+            let json = self.get(method, &page)?;
+            let batch: Vec<SlackMessage> =
+                serde_json::from_value(json["messages"].clone()).map_err(|e| AgentError::Slack(format!("{method}: messages: {e}")))?;
+            let done = batch.is_empty();
+            messages.extend(batch);
+            cursor = json["response_metadata"]["next_cursor"].as_str().unwrap_or_default().to_string();
+            if done || cursor.is_empty() {
+                break;
+            }
+        }
+        messages.truncate(limit);
+        Ok(messages)
+    }
+
+    /// users.info, with the user's locale.
+    pub(crate) fn user_info(&self, user: &str) -> Result<SlackUser, AgentError> {
+        let json = self.get("users.info", &[("user", user), ("include_locale", "true")])?;
+        serde_json::from_value(json["user"].clone()).map_err(|e| AgentError::Slack(format!("users.info: user: {e}")))
+    }
+
+    /// conversations.open: the ID of the bot's DM channel with `user`.
+    pub(crate) fn open_dm(&self, user: &str) -> Result<String, AgentError> {
+        let json = self.call("conversations.open", &serde_json::json!({"users": user}))?;
+        json["channel"]["id"].as_str().map(str::to_string).ok_or_else(|| AgentError::Slack("conversations.open: channel missing".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+
+    #[test]
+    fn messages_are_posted_updated_and_errors_name_the_method() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"ok": true, "ts": "1700000000.000100"}"#);
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+        mock.respond(200, "application/json", r#"{"ok": false, "error": "message_not_found"}"#);
+        mock.respond(500, "text/plain", "upstream down");
+
+        let api = SlackApi::from_env().unwrap();
+        let message = OutgoingMessage { channel: "C1", text: "hello", thread_ts: Some("1.2"), ..Default::default() };
+        assert_eq!(api.post_message(&message).unwrap(), "1700000000.000100");
+        api.update_message(&OutgoingMessage { text: "edited", ..message.clone() }, "1700000000.000100").unwrap();
+        let err = api.update_message(&message, "9.9").unwrap_err();
+        assert_eq!(err, AgentError::Slack("chat.update: message_not_found".into()));
+        assert_eq!(api.delete_message("C1", "9.9").unwrap_err(), AgentError::Slack("chat.delete HTTP 500: upstream down".into()));
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[1].url, "https://slack.com/api/chat.update");
+        assert!(requests[1].headers.contains(&("authorization".to_string(), "Bearer xoxb-test".to_string())));
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(body, serde_json::json!({"channel": "C1", "text": "edited", "ts": "1700000000.000100"}));
+    }
+
+    #[test]
+    fn history_follows_cursors_up_to_the_limit_and_users_are_typed() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        let page = |ts: &[&str], cursor: &str| {
+            let messages: Vec<_> = ts.iter().map(|ts| serde_json::json!({"ts": ts, "text": "hi", "user": "U1"})).collect();
+            serde_json::json!({"ok": true, "messages": messages, "response_metadata": {"next_cursor": cursor}}).to_string()
+        };
+        mock.respond(200, "application/json", &page(&["3.0", "2.0"], "dXNlcjpV"));
+        mock.respond(200, "application/json", &page(&["1.0"], ""));
+        mock.respond(200, "application/json", r#"{"ok": true, "user": {"id": "U1", "name": "ada", "tz": "Europe/London", "profile": {"real_name": "Ada Lovelace"}}}"#);
+
+        let api = SlackApi::from_env().unwrap();
+        let history = api.conversation_history("C1", Some("0.5"), 3).unwrap();
+        assert_eq!(history.iter().map(|m| m.ts.as_str()).collect::<Vec<_>>(), ["3.0", "2.0", "1.0"]);
+        let user = api.user_info("U1").unwrap();
+        assert_eq!((user.display_name(), user.tz.as_deref()), ("Ada Lovelace", Some("Europe/London")));
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/conversations.history?channel=C1&oldest=0.5&limit=3");
+        assert_eq!(requests[1].url, "https://slack.com/api/conversations.history?channel=C1&oldest=0.5&limit=1&cursor=dXNlcjpV");
+        assert_eq!(requests[2].url, "https://slack.com/api/users.info?user=U1&include_locale=true");
+    }
+}
@@ -0,0 +1,330 @@
+//! Typed Block Kit builders (sections, fields, headers, dividers, context, buttons, inputs and views).
+
+use serde::Serialize;
+
+/* ---- Block Kit limits ---- */
+// Slack rejects the whole message when one block breaks a limit, so the builders cut text to the
+// limit of the field it goes in (ending it with "…") and drop list items past the maximum count.
+// Empty text is rejected as well; callers leave such blocks out rather than build them.
+pub(crate) const MAX_BLOCKS: usize = 50;
+const MAX_VIEW_BLOCKS: usize = 100;
+pub(crate) const MAX_SECTION_TEXT: usize = 3000;
+const MAX_HEADER_TEXT: usize = 150;
+const MAX_FIELD_TEXT: usize = 2000;
+const MAX_FIELDS: usize = 10;
+const MAX_CONTEXT_ELEMENTS: usize = 10;
+const MAX_ACTION_ELEMENTS: usize = 25;
+const MAX_BUTTON_TEXT: usize = 75;
+const MAX_BUTTON_VALUE: usize = 2000;
+const MAX_ACTION_ID: usize = 255;
+const MAX_LABEL_TEXT: usize = 2000;
+const MAX_OPTION_TEXT: usize = 75;
+const MAX_OPTION_VALUE: usize = 150;
+const MAX_OPTIONS: usize = 100;
+const MAX_MODAL_TITLE: usize = 24;
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    text.chars().take(max - 1).chain(std::iter::once('…')).collect()
+}
+
+/// A text object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub(crate) enum Text {
+    #[serde(rename = "plain_text")]
+    Plain { text: String, emoji: bool },
+    #[serde(rename = "mrkdwn")]
+    Mrkdwn { text: String },
+}
+
+impl Text {
+    pub(crate) fn plain(text: &str) -> Text {
+        Text::Plain { text: text.to_string(), emoji: true }
+    }
+
+    pub(crate) fn mrkdwn(text: &str) -> Text {
+        Text::Mrkdwn { text: text.to_string() }
+    }
+
+    fn truncated(self, max: usize) -> Text {
+        match self {
+            Text::Plain { text, emoji } => Text::Plain { text: truncate(&text, max), emoji },
+            Text::Mrkdwn { text } => Text::Mrkdwn { text: truncate(&text, max) },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ButtonStyle {
+    Primary,
+}
+
+/// A button element; clicks arrive at /slack/interactive with its `action_id` and `value`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename = "button")]
+pub(crate) struct Button {
+    text: Text,
+    action_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<ButtonStyle>,
+}
+
+impl Button {
+    pub(crate) fn new(label: &str, action_id: &str) -> Button {
+        Button {
+            text: Text::plain(label).truncated(MAX_BUTTON_TEXT),
+            action_id: truncate(action_id, MAX_ACTION_ID),
+            value: None,
+            style: None,
+        }
+    }
+
+    pub(crate) fn value(mut self, value: &str) -> Button {
+        self.value = Some(truncate(value, MAX_BUTTON_VALUE));
+        self
+    }
+
+    pub(crate) fn style(mut self, style: ButtonStyle) -> Button {
+        self.style = Some(style);
+        self
+    }
+}
+
+/// An option of a select menu.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct SelectOption {
+    text: Text,
+    value: String,
+}
+
+impl SelectOption {
+    pub(crate) fn new(label: &str, value: &str) -> SelectOption {
+        SelectOption { text: Text::plain(label).truncated(MAX_OPTION_TEXT), value: truncate(value, MAX_OPTION_VALUE) }
+    }
+}
+
+/// The element of an input block; its value arrives with the modal's `view_submission`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum InputElement {
+    PlainTextInput {
+        action_id: String,
+        multiline: bool,
+    },
+    StaticSelect {
+        action_id: String,
+        options: Vec<SelectOption>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        initial_option: Option<SelectOption>,
+    },
+    NumberInput {
+        action_id: String,
+        is_decimal_allowed: bool,
+        min_value: String,
+        max_value: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        initial_value: Option<String>,
+    },
+    ConversationsSelect {
+        action_id: String,
+        default_to_current_conversation: bool,
+    },
+}
+
+impl InputElement {
+    pub(crate) fn text(action_id: &str, multiline: bool) -> InputElement {
+        InputElement::PlainTextInput { action_id: truncate(action_id, MAX_ACTION_ID), multiline }
+    }
+
+    /// A menu of `options`; `initial` is preselected when it is one of them.
+    pub(crate) fn select(action_id: &str, options: Vec<SelectOption>, initial: Option<&str>) -> InputElement {
+        let options: Vec<SelectOption> = options.into_iter().take(MAX_OPTIONS).collect();
+        let initial_option = options.iter().find(|o| Some(o.value.as_str()) == initial).cloned();
+        InputElement::StaticSelect { action_id: truncate(action_id, MAX_ACTION_ID), options, initial_option }
+    }
+
+    /// A decimal number from `min` to `max`.
+    pub(crate) fn decimal(action_id: &str, min: f64, max: f64, initial: Option<f64>) -> InputElement {
+        InputElement::NumberInput {
+            action_id: truncate(action_id, MAX_ACTION_ID),
+            is_decimal_allowed: true,
+            min_value: min.to_string(),
+            max_value: max.to_string(),
+            initial_value: initial.map(|v| v.to_string()),
+        }
+    }
+
+    /// A channel or DM picker, preset to the conversation the modal was opened from when Slack knows it.
+    pub(crate) fn conversation(action_id: &str) -> InputElement {
+        InputElement::ConversationsSelect { action_id: truncate(action_id, MAX_ACTION_ID), default_to_current_conversation: true }
+    }
+}
+
+/// A layout block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Block {
+    Header {
+        text: Text,
+    },
+    Section {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        text: Option<Text>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        fields: Vec<Text>,
+    },
+    Divider,
+    Context {
+        elements: Vec<Text>,
+    },
+    Actions {
+        elements: Vec<Button>,
+    },
+    /// Only valid in modals
+    Input {
+        block_id: String,
+        label: Text,
+        element: InputElement,
+        optional: bool,
+    },
+}
+
+impl Block {
+    /// A header in large plain text; mrkdwn is not rendered there.
+    pub(crate) fn header(text: &str) -> Block {
+        Block::Header { text: Text::plain(text).truncated(MAX_HEADER_TEXT) }
+    }
+
+    pub(crate) fn section(mrkdwn: &str) -> Block {
+        Block::Section { text: Some(Text::mrkdwn(mrkdwn).truncated(MAX_SECTION_TEXT)), fields: Vec::new() }
+    }
+
+    /// A section laid out as two columns of mrkdwn fields.
+    pub(crate) fn fields<I: IntoIterator<Item = String>>(fields: I) -> Block {
+        let fields = fields.into_iter().take(MAX_FIELDS).map(|f| Text::mrkdwn(&f).truncated(MAX_FIELD_TEXT)).collect();
+        Block::Section { text: None, fields }
+    }
+
+    pub(crate) fn divider() -> Block {
+        Block::Divider
+    }
+
+    /// Small grey text under a message, e.g. a source or a hint.
+    pub(crate) fn context<I: IntoIterator<Item = Text>>(elements: I) -> Block {
+        Block::Context { elements: elements.into_iter().take(MAX_CONTEXT_ELEMENTS).map(|t| t.truncated(MAX_SECTION_TEXT)).collect() }
+    }
+
+    pub(crate) fn actions<I: IntoIterator<Item = Button>>(buttons: I) -> Block {
+        Block::Actions { elements: buttons.into_iter().take(MAX_ACTION_ELEMENTS).collect() }
+    }
+
+    /// A labelled field of a modal; submitted values are keyed by `block_id`, then the element's `action_id`.
+    pub(crate) fn input(block_id: &str, label: &str, element: InputElement, optional: bool) -> Block {
+        Block::Input { block_id: truncate(block_id, MAX_ACTION_ID), label: Text::plain(label).truncated(MAX_LABEL_TEXT), element, optional }
+    }
+}
+
+/// A surface of its own rather than a message: the App Home tab published with views.publish, or a
+/// modal opened with views.open.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct View {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    blocks: Vec<Block>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    submit: Option<Text>,
+    /// Names the modal in its `view_submission`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callback_id: Option<String>,
+}
+
+impl View {
+    pub(crate) fn home<I: IntoIterator<Item = Block>>(blocks: I) -> View {
+        View { kind: "home", blocks: blocks.into_iter().take(MAX_VIEW_BLOCKS).collect(), title: None, submit: None, callback_id: None }
+    }
+
+    /// A modal with a `submit` button; its `view_submission` carries `callback_id`.
+    pub(crate) fn modal<I: IntoIterator<Item = Block>>(title: &str, submit: &str, callback_id: &str, blocks: I) -> View {
+        View {
+            kind: "modal",
+            blocks: blocks.into_iter().take(MAX_VIEW_BLOCKS).collect(),
+            title: Some(Text::plain(title).truncated(MAX_MODAL_TITLE)),
+            submit: Some(Text::plain(submit).truncated(MAX_MODAL_TITLE)),
+            callback_id: Some(truncate(callback_id, MAX_ACTION_ID)),
+        }
+    }
+}
+
+/// The `blocks` array of a message.
+pub(crate) fn blocks_json(blocks: &[Block]) -> serde_json::Value {
+    serde_json::to_value(blocks).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_serialize_to_block_kit_json() {
+        let blocks = [
+            Block::header("Weekly report"),
+            Block::section("*Done*"),
+            Block::divider(),
+            Block::fields(["*Requests*\n12".to_string(), "*Tokens*\n3400".to_string()]),
+            Block::context([Text::mrkdwn("_generated_")]),
+            Block::actions([Button::new("Approve", "approve").value("r1").style(ButtonStyle::Primary)]),
+        ];
+        assert_eq!(
+            blocks_json(&blocks),
+            serde_json::json!([
+                {"type": "header", "text": {"type": "plain_text", "text": "Weekly report", "emoji": true}},
+                {"type": "section", "text": {"type": "mrkdwn", "text": "*Done*"}},
+                {"type": "divider"},
+                {"type": "section", "fields": [{"type": "mrkdwn", "text": "*Requests*\n12"}, {"type": "mrkdwn", "text": "*Tokens*\n3400"}]},
+                {"type": "context", "elements": [{"type": "mrkdwn", "text": "_generated_"}]},
+                {"type": "actions", "elements": [{
+                    "type": "button", "text": {"type": "plain_text", "text": "Approve", "emoji": true}, "action_id": "approve", "value": "r1", "style": "primary",
+                }]},
+            ])
+        );
+    }
+
+    #[test]
+    fn text_and_lists_are_cut_to_slack_limits() {
+        let json = blocks_json(&[Block::header(&"é".repeat(200)), Block::fields((0..12).map(|i| i.to_string()))]);
+        let header = json[0]["text"]["text"].as_str().unwrap();
+        assert_eq!((header.chars().count(), header.ends_with('…')), (MAX_HEADER_TEXT, true));
+        assert_eq!(json[1]["fields"].as_array().unwrap().len(), MAX_FIELDS);
+        assert_eq!(json[1]["fields"][9]["text"], "9");
+        let button = serde_json::to_value(Button::new(&"x".repeat(80), "a")).unwrap();
+        assert_eq!(button["text"]["text"].as_str().unwrap().chars().count(), MAX_BUTTON_TEXT);
+    }
+
+    #[test]
+    fn modals_serialize_with_input_blocks() {
+        let options = vec![SelectOption::new("Small", "gpt-4o-mini"), SelectOption::new("Large", "gpt-4o")];
+        let modal = View::modal("Ask the assistant anything", "Ask", "prompt", [
+            Block::input("model", "Model", InputElement::select("model", options, Some("gpt-4o")), false),
+            Block::input("temperature", "Temperature", InputElement::decimal("temperature", 0.0, 2.0, Some(0.7)), true),
+        ]);
+        let json = serde_json::to_value(&modal).unwrap();
+        assert_eq!((json["type"].as_str(), json["callback_id"].as_str()), (Some("modal"), Some("prompt")));
+        assert_eq!(json["title"]["text"].as_str().unwrap().chars().count(), MAX_MODAL_TITLE);
+        assert_eq!(json["blocks"][0]["element"]["initial_option"]["value"], "gpt-4o");
+        assert_eq!(
+            json["blocks"][1],
+            serde_json::json!({"type": "input", "block_id": "temperature", "optional": true,
+                "label": {"type": "plain_text", "text": "Temperature", "emoji": true},
+                "element": {"type": "number_input", "action_id": "temperature", "is_decimal_allowed": true, "min_value": "0", "max_value": "2", "initial_value": "0.7"}})
+        );
+        assert!(serde_json::to_value(View::home([])).unwrap().get("title").is_none());
+    }
+}
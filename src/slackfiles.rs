@@ -0,0 +1,262 @@
+//! Slack file downloads and the commands that work on shared files (images, clips).
+
+use crate::documents::{MAX_DOCUMENT_CHARS, truncate_chars};
+use crate::error::AgentError;
+use crate::httpclient::{http_get_bytes, http_post_bytes};
+use crate::llm::{ImageInput, call_openai_vision, call_openai_with_system, transcribe_audio};
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::api::SlackApi;
+use crate::slack::slack_bot_token;
+
+/* ---- Slack files (files.info + url_private) ---- */
+// Slash commands carry no attachments, so files are referenced by pasting their Slack link
+// (`https://team.slack.com/files/U…/F…/name.png` or a `files.slack.com/files-pri/T…-F…/` URL).
+// The file ID is looked up with files.info and the private URL downloaded with the bot token,
+// which needs the `files:read` scope.
+const MAX_FILES_PER_COMMAND: usize = 4;
+const MAX_IMAGE_BYTES: usize = 8 * 1024 * 1024;
+
+pub(crate) struct SlackFile {
+    pub(crate) name: String,
+    pub(crate) mimetype: String,
+    url_private: String,
+}
+
+/// File IDs of the Slack file links in `text`, in order and without duplicates.
+pub(crate) fn slack_file_ids(text: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for token in text.split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '|')) {
+        let Some((_, path)) = token.split_once("slack.com/") else { continue };
+        let segments: Vec<&str> = path.split(['/', '?', '#']).collect();
+        let id = match segments.as_slice() {
+            ["files", _user, id, ..] => Some(*id),
+            ["files-pri" | "files-tmb", team_file, ..] => team_file.split_once('-').map(|(_, id)| id),
+            _ => None,
+        };
+        let is_file_id = |id: &str| id.len() > 1 && id.starts_with('F') && id.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+        if let Some(id) = id.filter(|id| is_file_id(id)) {
+            if !ids.iter().any(|known| known == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+    ids
+}
+
+/// `text` with the Slack file links removed, i.e. what the user asked about them.
+fn strip_file_links(text: &str) -> String {
+    let mut out = Vec::new();
+    for word in text.split_whitespace() {
+        if slack_file_ids(word).is_empty() {
+            out.push(word);
+        }
+    }
+    out.join(" ")
+}
+
+pub(crate) fn slack_file_info(id: &str) -> Result<SlackFile, AgentError> {
+    let json = SlackApi::from_env()?.get("files.info", &[("file", id)])?;
+    let file = &json["file"];
+    let field = |name: &str| file[name].as_str().unwrap_or_default().to_string();
+    let url_private = file["url_private_download"].as_str().map(str::to_string).unwrap_or_else(|| field("url_private"));
+    if url_private.is_empty() {
+        return Err(AgentError::Slack(format!("files.info: file {id} has no download URL")));
+    }
+    Ok(SlackFile { name: field("name"), mimetype: field("mimetype"), url_private })
+}
+
+/// Downloads a file's contents with the bot token.
+pub(crate) fn download_slack_file(file: &SlackFile, max_bytes: usize) -> Result<Vec<u8>, AgentError> {
+    let token = slack_bot_token()?;
+    let (meta, bytes) = http_get_bytes(&file.url_private, &[("authorization", format!("Bearer {token}"))], max_bytes)?;
+    // Without files:read Slack answers with its HTML sign-in page instead of the file
+    if meta.content_type.as_deref().is_some_and(|ct| ct.starts_with("text/html")) {
+        return Err(AgentError::Slack(format!("download of {} returned a web page; does the bot have files:read?", file.name)));
+    }
+    Ok(bytes)
+}
+
+/* ---- Uploads (files.getUploadURLExternal + files.completeUploadExternal) ---- */
+// Slack's upload flow: reserve an upload URL for the file's name and size, POST the bytes to it,
+// then complete the upload to share the file in a channel. Needs the `files:write` scope.
+
+/// Uploads `bytes` as `filename` and shares it in `channel` with `comment` as its message.
+pub(crate) fn upload_slack_file(channel: &str, filename: &str, content_type: &str, bytes: &[u8], comment: &str) -> Result<(), AgentError> {
+    let api = SlackApi::from_env()?;
+    let reserved = api.get("files.getUploadURLExternal", &[("filename", filename), ("length", &bytes.len().to_string())])?;
+    let (Some(upload_url), Some(file_id)) = (reserved["upload_url"].as_str(), reserved["file_id"].as_str()) else {
+        return Err(AgentError::Slack("files.getUploadURLExternal: upload_url missing from response".into()));
+    };
+
+    http_post_bytes(upload_url, bytes, content_type).map_err(|e| AgentError::Slack(format!("file upload: {e}")))?;
+
+    let payload = serde_json::json!({
+        "files": [{"id": file_id, "title": filename}],
+        "channel_id": channel,
+        "initial_comment": comment,
+    });
+    api.call("files.completeUploadExternal", &payload).map(|_| ())
+}
+
+/* ---- Questions about images ---- */
+const DEFAULT_IMAGE_QUESTION: &str = "Describe this image.";
+
+fn answer_about_images(question: &str, files: Vec<SlackFile>) -> Result<String, AgentError> {
+    let mut images = Vec::new();
+    for file in files {
+        let bytes = download_slack_file(&file, MAX_IMAGE_BYTES)?;
+        images.push(ImageInput { media_type: file.mimetype, bytes });
+    }
+    let question = if question.is_empty() { DEFAULT_IMAGE_QUESTION } else { question };
+    call_openai_vision(question, &images, 500)
+}
+
+/* ---- Transcripts of audio and video clips ---- */
+// The transcription endpoint accepts files up to 25 MB. Long transcripts are cut for the Slack
+// message; the optional summary is written from the whole transcript.
+const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+const MAX_TRANSCRIPT_REPLY_CHARS: usize = 3_000;
+
+fn is_clip(file: &SlackFile) -> bool {
+    file.mimetype.starts_with("audio/") || file.mimetype.starts_with("video/")
+}
+
+fn transcribe_clips(files: Vec<SlackFile>, summarize: bool) -> Result<String, AgentError> {
+    let mut sections = Vec::new();
+    for file in files {
+        let bytes = download_slack_file(&file, MAX_AUDIO_BYTES)?;
+        let transcript = transcribe_audio(&file.name, &file.mimetype, &bytes)?;
+        let mut section = format!("*Transcript of {}*\n{}", file.name, quote(&truncate_chars(&transcript, MAX_TRANSCRIPT_REPLY_CHARS)));
+        if transcript.chars().count() > MAX_TRANSCRIPT_REPLY_CHARS {
+            section.push_str("\n_(transcript shortened)_");
+        }
+        if summarize && !transcript.is_empty() {
+            let summary = call_openai_with_system(
+                "Summarize this transcript of a recorded clip: the main points as 3-5 bullets, then any decisions or action items.",
+                &truncate_chars(&transcript, MAX_DOCUMENT_CHARS),
+                400,
+            )?;
+            section.push_str(&format!("\n\n*Summary*\n{summary}"));
+        }
+        sections.push(section);
+    }
+    Ok(sections.join("\n\n"))
+}
+
+fn quote(text: &str) -> String {
+    text.lines().map(|l| format!(">{l}")).collect::<Vec<_>>().join("\n")
+}
+
+/* ---- Slash commands that link files ---- */
+fn answer_about_files(text: &str, ids: &[String]) -> Result<String, AgentError> {
+    // `--summary` asks for a summary under each transcript
+    let (text, summarize) = match text.strip_suffix("--summary").or_else(|| text.strip_prefix("--summary")) {
+        Some(rest) => (rest.trim(), true),
+        None => (text, false),
+    };
+    let files = ids.iter().take(MAX_FILES_PER_COMMAND).map(|id| slack_file_info(id)).collect::<Result<Vec<_>, _>>()?;
+    if files.iter().all(|f| f.mimetype.starts_with("image/")) {
+        answer_about_images(&strip_file_links(text), files)
+    } else if files.iter().all(is_clip) {
+        transcribe_clips(files, summarize)
+    } else {
+        let other = files.iter().find(|f| !f.mimetype.starts_with("image/") && !is_clip(f)).unwrap_or(&files[0]);
+        Err(AgentError::Validation(format!(
+            "{} is {}; link images to ask about them, or audio/video clips to transcribe them",
+            other.name, other.mimetype
+        )))
+    }
+}
+
+/// Reply to a slash command that links Slack files: images are answered about
+/// (`/ask what does this chart show? <link>`), clips transcribed (`/ask <link> --summary`).
+pub(crate) fn slack_file_reply(text: &str, ids: &[String]) -> String {
+    match answer_about_files(text, ids) {
+        Ok(answer) => answer,
+        Err(e) => with_ref(&format!("Couldn't process that file: {e}"), &current_request_id()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+
+    #[test]
+    fn file_ids_are_found_in_permalinks_and_private_urls() {
+        let text = "compare <https://acme.slack.com/files/U012AB3CD/F0ABC123/chart.png|chart.png> with \
+            https://files.slack.com/files-pri/T0TEAM1-F0DEF456/photo.jpg and again https://acme.slack.com/files/U1/F0ABC123/chart.png";
+        assert_eq!(slack_file_ids(text), ["F0ABC123", "F0DEF456"]);
+        assert!(slack_file_ids("see https://example.com/files/U1/F0ABC123/x.png or https://acme.slack.com/archives/C1").is_empty());
+        assert_eq!(strip_file_links("what is this? https://acme.slack.com/files/U1/F0ABC123/x.png"), "what is this?");
+    }
+
+    #[test]
+    fn image_questions_send_the_downloaded_file_inline() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(
+            200,
+            "application/json",
+            r#"{"ok":true,"file":{"id":"F0ABC123","name":"chart.png","mimetype":"image/png","url_private_download":"https://files.slack.com/files-pri/T1-F0ABC123/download/chart.png"}}"#,
+        );
+        mock.respond(200, "image/png", "PNG");
+        mock.respond(200, "application/json", &completion("Revenue doubled in Q3."));
+
+        let text = "what trend does this show? <https://acme.slack.com/files/U1/F0ABC123/chart.png>";
+        assert_eq!(slack_file_reply(text, &slack_file_ids(text)), "Revenue doubled in Q3.");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/files.info?file=F0ABC123");
+        assert_eq!(requests[1].url, "https://files.slack.com/files-pri/T1-F0ABC123/download/chart.png");
+        assert!(requests[1].headers.contains(&("authorization".to_string(), "Bearer xoxb-test".to_string())));
+        let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0], serde_json::json!({"type": "text", "text": "what trend does this show?"}));
+        assert_eq!(content[1]["image_url"]["url"], "data:image/png;base64,UE5H");
+    }
+
+    #[test]
+    fn clips_are_uploaded_for_transcription_and_summarized() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(
+            200,
+            "application/json",
+            r#"{"ok":true,"file":{"id":"F0CLIP1","name":"standup.m4a","mimetype":"audio/mp4","url_private_download":"https://files.slack.com/files-pri/T1-F0CLIP1/download/standup.m4a"}}"#,
+        );
+        mock.respond(200, "audio/mp4", "AUDIO");
+        mock.respond(200, "application/json", r#"{"text":" We ship on Friday. \nAlice owns the release notes."}"#);
+        mock.respond(200, "application/json", &completion("- Ship Friday\n- Alice: release notes"));
+
+        let text = "<https://acme.slack.com/files/U1/F0CLIP1/standup.m4a> --summary";
+        assert_eq!(
+            slack_file_reply(text, &slack_file_ids(text)),
+            "*Transcript of standup.m4a*\n>We ship on Friday. \n>Alice owns the release notes.\n\n*Summary*\n- Ship Friday\n- Alice: release notes"
+        );
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[2].url, "https://api.openai.com/v1/audio/transcriptions");
+        let content_type = &requests[2].headers.iter().find(|(k, _)| k == "content-type").unwrap().1;
+        assert!(content_type.starts_with("multipart/form-data; boundary="));
+        let body = String::from_utf8_lossy(&requests[2].body);
+        assert!(body.contains("name=\"model\"\r\n\r\nwhisper-1\r\n"));
+        assert!(body.contains("name=\"file\"; filename=\"standup.m4a\"\r\nContent-Type: audio/mp4\r\n\r\nAUDIO\r\n"));
+        assert_eq!(requests.len(), 4);
+    }
+
+    #[test]
+    fn other_file_types_are_refused() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(
+            200,
+            "application/json",
+            r#"{"ok":true,"file":{"name":"notes.pdf","mimetype":"application/pdf","url_private":"https://files.slack.com/files-pri/T1-F09/notes.pdf"}}"#,
+        );
+        let reply = slack_file_reply("summarize", &["F09".to_string()]);
+        assert_eq!(reply, "Couldn't process that file: notes.pdf is application/pdf; link images to ask about them, or audio/video clips to transcribe them");
+    }
+}
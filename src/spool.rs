@@ -0,0 +1,254 @@
+//! Spilling large fetched documents into the key-value store in chunks.
+
+use crate::config::get_env_var;
+use crate::documents::{MAX_DOCUMENT_CHARS, detect_media_type, extract_document_text};
+use crate::error::AgentError;
+use crate::llm::condense_text;
+use crate::store::{new_id, store_delete, store_get, store_set};
+
+/* ---- Body spooling ---- */
+// A fetched body stays in memory up to FETCH_MEMORY_CAP_BYTES. Past that it is written to the
+// store in chunks as it streams in, so memory holds at most the cap plus one chunk however large
+// the page is. Chunks are cut after a newline or `>` where possible, which keeps HTML tags and
+// UTF-8 sequences whole and lets each chunk be extracted on its own.
+const DEFAULT_MEMORY_CAP_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+const SPOOL_CHUNK_BYTES: usize = 256 * 1024;
+
+fn env_bytes(name: &str, default: usize) -> usize {
+    get_env_var(name).and_then(|v| v.parse().ok()).filter(|n| *n > 0).unwrap_or(default)
+}
+
+pub(crate) enum SpooledBody {
+    Memory(Vec<u8>),
+    Stored(StoredBody),
+}
+
+/// A body written to the store as `spool:{id}:{index}` chunks.
+pub(crate) struct StoredBody {
+    id: String,
+    pub(crate) chunks: usize,
+    pub(crate) bytes: usize,
+    /// The body exceeded FETCH_MAX_BYTES and the rest was discarded
+    pub(crate) truncated: bool,
+}
+
+type ChunkSink<'a> = &'a mut dyn FnMut(&str, usize, &[u8]) -> Result<(), AgentError>;
+
+/// Accumulates a streamed body; `sink(id, index, bytes)` persists each chunk once it spills.
+struct Spool<'a> {
+    memory_cap: usize,
+    max_bytes: usize,
+    chunk_bytes: usize,
+    buf: Vec<u8>,
+    // Allocated on the first spilled chunk, so bodies that fit in memory never touch the store
+    id: Option<String>,
+    make_id: fn() -> String,
+    chunks: usize,
+    bytes: usize,
+    truncated: bool,
+    sink: ChunkSink<'a>,
+    error: Option<AgentError>,
+}
+
+impl<'a> Spool<'a> {
+    fn with_limits(memory_cap: usize, max_bytes: usize, chunk_bytes: usize, make_id: fn() -> String, sink: ChunkSink<'a>) -> Spool<'a> {
+        Spool {
+            memory_cap,
+            max_bytes,
+            chunk_bytes,
+            buf: Vec::new(),
+            id: None,
+            make_id,
+            chunks: 0,
+            bytes: 0,
+            truncated: false,
+            sink,
+            error: None,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        if self.error.is_some() {
+            return;
+        }
+        let room = self.max_bytes - self.bytes;
+        if bytes.len() > room {
+            self.truncated = true;
+        }
+        let bytes = &bytes[..bytes.len().min(room)];
+        self.buf.extend_from_slice(bytes);
+        self.bytes += bytes.len();
+        if self.chunks == 0 && self.buf.len() <= self.memory_cap {
+            return;
+        }
+        while self.buf.len() >= self.chunk_bytes {
+            let cut = chunk_boundary(&self.buf[..self.chunk_bytes]);
+            if let Err(e) = self.write(cut) {
+                self.error = Some(e);
+                return;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Result<SpooledBody, AgentError> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let Some(id) = self.id.clone() else { return Ok(SpooledBody::Memory(self.buf)) };
+        if !self.buf.is_empty() {
+            self.write(self.buf.len())?;
+        }
+        Ok(SpooledBody::Stored(StoredBody { id, chunks: self.chunks, bytes: self.bytes, truncated: self.truncated }))
+    }
+
+    fn write(&mut self, len: usize) -> Result<(), AgentError> {
+        let id = self.id.get_or_insert_with(self.make_id);
+        (self.sink)(id, self.chunks, &self.buf[..len])?;
+        self.buf.drain(..len);
+        self.chunks += 1;
+        Ok(())
+    }
+}
+
+/// Length of the prefix to write: up to the last newline, else the last `>`, in the second half
+/// of `chunk`; failing both, up to a character cut short at the end.
+fn chunk_boundary(chunk: &[u8]) -> usize {
+    let half = chunk.len() / 2;
+    for delim in [b'\n', b'>'] {
+        if let Some(i) = chunk[half..].iter().rposition(|b| *b == delim) {
+            return half + i + 1;
+        }
+    }
+    match std::str::from_utf8(chunk) {
+        Err(e) if e.error_len().is_none() && e.valid_up_to() > 0 => e.valid_up_to(),
+        _ => chunk.len(),
+    }
+}
+
+fn chunk_key(id: &str, index: usize) -> String {
+    format!("spool:{id}:{index}")
+}
+
+/// Runs `fill` with a function that spools each body chunk it is given, using the limits from
+/// the environment. Chunks already stored are removed if the fetch or a store write fails.
+pub(crate) fn spool_body(fill: impl FnOnce(&mut dyn FnMut(&[u8])) -> Result<(), AgentError>) -> Result<SpooledBody, AgentError> {
+    let mut sink = |id: &str, index: usize, bytes: &[u8]| store_set(&chunk_key(id, index), &String::from_utf8_lossy(bytes));
+    let mut spool = Spool::with_limits(
+        env_bytes("FETCH_MEMORY_CAP_BYTES", DEFAULT_MEMORY_CAP_BYTES),
+        env_bytes("FETCH_MAX_BYTES", DEFAULT_MAX_BYTES),
+        SPOOL_CHUNK_BYTES,
+        new_id,
+        &mut sink,
+    );
+    let filled = fill(&mut |bytes| spool.push(bytes));
+    let written = spool.id.clone().map(|id| StoredBody { id, chunks: spool.chunks, bytes: 0, truncated: false });
+    let result = filled.and_then(|()| spool.finish());
+    if let (Err(_), Some(written)) = (&result, written) {
+        written.discard();
+    }
+    result
+}
+
+impl StoredBody {
+    fn chunk(&self, index: usize) -> Result<String, AgentError> {
+        store_get(&chunk_key(&self.id, index))?
+            .ok_or_else(|| AgentError::Store(format!("spooled chunk {index} of {} missing", self.id)))
+    }
+
+    /// Removes the chunks; called once the document has been processed.
+    pub(crate) fn discard(&self) {
+        for index in 0..self.chunks {
+            let _ = store_delete(&chunk_key(&self.id, index));
+        }
+    }
+}
+
+/* ---- Incremental processing of stored documents ---- */
+/// Extracts and condenses each stored chunk in turn, so only one chunk is in memory at a time,
+/// then condenses the joined notes again if they are still longer than a document prompt allows.
+/// The media type is detected once from the first chunk, since later chunks have no `<html>` head.
+pub(crate) fn condense_stored(body: &StoredBody, content_type: Option<&str>, url: &str) -> Result<String, AgentError> {
+    let first = body.chunk(0)?;
+    let media_type = detect_media_type(content_type, url, &first);
+    let mut notes = Vec::with_capacity(body.chunks);
+    for index in 0..body.chunks {
+        let raw = if index == 0 { first.clone() } else { body.chunk(index)? };
+        let text = extract_document_text(Some(&media_type), url, &raw);
+        if !text.trim().is_empty() {
+            // Depth 2: one pass over this chunk's pieces, without re-condensing per chunk
+            notes.push(condense_text(&text, 2)?);
+        }
+    }
+    if notes.is_empty() {
+        return Err(AgentError::Validation(format!("no text content at {url}")));
+    }
+    let joined = notes.join("\n\n");
+    if joined.chars().count() > MAX_DOCUMENT_CHARS { condense_text(&joined, 1) } else { Ok(joined) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spool(memory_cap: usize, max_bytes: usize, chunk_bytes: usize, pushes: &[&[u8]]) -> (Vec<Vec<u8>>, SpooledBody) {
+        let mut written = Vec::new();
+        let mut sink = |id: &str, index: usize, bytes: &[u8]| {
+            assert_eq!((id, index), ("doc1", written.len()));
+            written.push(bytes.to_vec());
+            Ok(())
+        };
+        let mut spool = Spool::with_limits(memory_cap, max_bytes, chunk_bytes, || "doc1".to_string(), &mut sink);
+        for bytes in pushes {
+            spool.push(bytes);
+        }
+        let body = spool.finish().unwrap();
+        (written, body)
+    }
+
+    #[test]
+    fn small_bodies_stay_in_memory() {
+        let (written, body) = spool(64, 1024, 16, &[b"<p>hello</p>", b"<p>world</p>"]);
+        assert!(written.is_empty());
+        assert!(matches!(body, SpooledBody::Memory(bytes) if bytes == b"<p>hello</p><p>world</p>"));
+    }
+
+    #[test]
+    fn large_bodies_are_written_in_chunks_cut_at_tags_and_lines() {
+        let page = "<html><body>\n".to_string() + &"<p>Revenue grew 12% this quarter.</p>\n".repeat(40) + "</body></html>";
+        let pushes: Vec<&[u8]> = page.as_bytes().chunks(50).collect();
+        let (written, body) = spool(100, 1 << 20, 128, &pushes);
+
+        let SpooledBody::Stored(stored) = body else { panic!("expected a stored body") };
+        assert_eq!((stored.chunks, stored.bytes, stored.truncated), (written.len(), page.len(), false));
+        assert!(written.len() > 10);
+        assert!(written.iter().all(|c| c.len() <= 128));
+        assert!(written[..written.len() - 1].iter().all(|c| c.ends_with(b"\n")));
+        assert_eq!(written.concat(), page.as_bytes());
+    }
+
+    #[test]
+    fn chunks_never_split_a_utf8_character() {
+        let text = "€".repeat(100);
+        let (written, _) = spool(10, 1 << 20, 16, &[text.as_bytes()]);
+        assert!(written.iter().all(|c| std::str::from_utf8(c).is_ok()));
+        assert_eq!(written.concat(), text.as_bytes());
+    }
+
+    #[test]
+    fn bodies_over_the_maximum_are_truncated() {
+        let (written, body) = spool(10, 40, 16, &[&[b'a'; 30], &[b'b'; 30]]);
+        let SpooledBody::Stored(stored) = body else { panic!("expected a stored body") };
+        assert!(stored.truncated);
+        assert_eq!(stored.bytes, 40);
+        assert_eq!(written.concat().len(), 40);
+    }
+
+    #[test]
+    fn sink_errors_surface_from_finish() {
+        let mut sink = |_: &str, _: usize, _: &[u8]| Err(AgentError::Store("bucket full".into()));
+        let mut spool = Spool::with_limits(4, 100, 8, || "doc1".to_string(), &mut sink);
+        spool.push(&[b'x'; 20]);
+        assert_eq!(spool.finish().err(), Some(AgentError::Store("bucket full".into())));
+    }
+}
@@ -0,0 +1,205 @@
+//! Key-value store (wasi:keyvalue, or in memory without the `keyvalue` feature) and record identifiers.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[cfg(feature = "keyvalue")]
+use crate::bindings::wasi::keyvalue::store as kv;
+use crate::bindings::wasi::random::random;
+use crate::config::get_env_var;
+use crate::encryption::{open, seal};
+use crate::error::AgentError;
+use crate::requestid::log;
+use crate::redis::RedisStore;
+
+/* ---- Random identifiers (wasi:random) ---- */
+pub(crate) fn new_id() -> String {
+    // Native unit tests have no wasi:random; there ids only need to be unique
+    #[cfg(test)]
+    return TEST_IDS.with(|n| {
+        n.set(n.get() + 1);
+        format!("{:016x}", n.get())
+    });
+    #[cfg(not(test))]
+    format!("{:016x}", random::get_random_u64())
+}
+
+/// `len` random bytes, e.g. for nonces.
+pub(crate) fn random_bytes(len: usize) -> Vec<u8> {
+    #[cfg(test)]
+    return TEST_IDS.with(|n| {
+        n.set(n.get() + 1);
+        n.get().to_be_bytes().into_iter().cycle().take(len).collect()
+    });
+    #[cfg(not(test))]
+    random::get_random_bytes(len as u64)
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_IDS: std::cell::Cell<u64> = const { std::cell::Cell::new(0) };
+}
+
+/* ---- Key-value store backends (STORE_BACKEND) ---- */
+// Store access goes through `kv_backend()`, chosen once per instance: `keyvalue` (wasi:keyvalue,
+// the default), `redis` (a Redis server over TCP, see REDIS_URL) or `memory`, which only lasts
+// as long as the instance. Built without the `keyvalue` feature, for hosts that do not offer
+// the interface, the default is `memory`. Native unit tests install a `MockStore`.
+pub(crate) trait KvBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, AgentError>;
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), AgentError>;
+    fn delete(&self, key: &str) -> Result<(), AgentError>;
+    /// Keys starting with `prefix`.
+    fn list_keys(&self, prefix: &str) -> Result<Vec<String>, AgentError>;
+    /// Whether values outlive the instance.
+    fn persistent(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "keyvalue")]
+struct WasiKv;
+
+#[cfg(feature = "keyvalue")]
+fn store_bucket() -> Result<kv::Bucket, AgentError> {
+    let name = get_env_var("KV_BUCKET").unwrap_or_default();
+    kv::open(&name).map_err(|e| AgentError::Store(format!("open bucket '{name}': {e:?}")))
+}
+
+#[cfg(feature = "keyvalue")]
+impl KvBackend for WasiKv {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, AgentError> {
+        store_bucket()?.get(key).map_err(|e| AgentError::Store(format!("get {key}: {e:?}")))
+    }
+
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), AgentError> {
+        store_bucket()?.set(key, value).map_err(|e| AgentError::Store(format!("set {key}: {e:?}")))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AgentError> {
+        store_bucket()?.delete(key).map_err(|e| AgentError::Store(format!("delete {key}: {e:?}")))
+    }
+
+    /// Follows the list-keys cursor to the end.
+    fn list_keys(&self, prefix: &str) -> Result<Vec<String>, AgentError> {
+        let bucket = store_bucket()?;
+        let mut keys = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = bucket.list_keys(cursor).map_err(|e| AgentError::Store(format!("list keys: {e:?}")))?;
+            keys.extend(page.keys.into_iter().filter(|k| k.starts_with(prefix)));
+            match page.cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(keys),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "keyvalue")]
+fn default_backend() -> Rc<dyn KvBackend> {
+    Rc::new(WasiKv)
+}
+
+#[cfg(not(feature = "keyvalue"))]
+fn default_backend() -> Rc<dyn KvBackend> {
+    Rc::new(MemoryStore::default())
+}
+
+// A backend that cannot be set up falls back to memory, which the health check reports as degraded
+fn configured_backend() -> Rc<dyn KvBackend> {
+    match get_env_var("STORE_BACKEND").as_deref().map(str::trim) {
+        None => default_backend(),
+        Some("keyvalue") if cfg!(feature = "keyvalue") => default_backend(),
+        Some("memory") => Rc::new(MemoryStore::default()),
+        Some("redis") => match RedisStore::from_env() {
+            Ok(redis) => Rc::new(redis),
+            Err(e) => {
+                log!("STORE_BACKEND=redis: {e}; using the in-memory store");
+                Rc::new(MemoryStore::default())
+            }
+        },
+        Some(other) => {
+            log!("STORE_BACKEND: '{other}' is not available in this build; using the default store");
+            default_backend()
+        }
+    }
+}
+
+thread_local! {
+    static KV_BACKEND: RefCell<Rc<dyn KvBackend>> = RefCell::new(configured_backend());
+}
+
+fn kv_backend() -> Rc<dyn KvBackend> {
+    KV_BACKEND.with(|b| b.borrow().clone())
+}
+
+pub(crate) fn store_get(key: &str) -> Result<Option<String>, AgentError> {
+    let stored = kv_backend().get(key)?.map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    stored.map(|value| open(key, value)).transpose()
+}
+
+pub(crate) fn store_set(key: &str, value: &str) -> Result<(), AgentError> {
+    kv_backend().set(key, seal(key, value)?.as_bytes())
+}
+
+/// Keys starting with `prefix`.
+pub(crate) fn store_list_keys(prefix: &str) -> Result<Vec<String>, AgentError> {
+    kv_backend().list_keys(prefix)
+}
+
+pub(crate) fn store_delete(key: &str) -> Result<(), AgentError> {
+    kv_backend().delete(key)
+}
+
+/// Whether stored data outlives the instance; reported by the health check.
+pub(crate) fn store_is_persistent() -> bool {
+    kv_backend().persistent()
+}
+
+/* ---- In-memory store (no `keyvalue` feature, and native unit tests) ---- */
+#[derive(Default)]
+pub(crate) struct MemoryStore {
+    values: RefCell<std::collections::BTreeMap<String, Vec<u8>>>,
+}
+
+#[cfg(test)]
+pub(crate) type MockStore = MemoryStore;
+
+#[cfg(test)]
+impl MemoryStore {
+    /// Installs an empty in-memory store as this thread's backend.
+    pub(crate) fn install() -> Rc<MemoryStore> {
+        let mock = Rc::new(MemoryStore::default());
+        KV_BACKEND.with(|b| *b.borrow_mut() = mock.clone());
+        mock
+    }
+
+    pub(crate) fn keys(&self) -> Vec<String> {
+        self.values.borrow().keys().cloned().collect()
+    }
+}
+
+impl KvBackend for MemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, AgentError> {
+        Ok(self.values.borrow().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &[u8]) -> Result<(), AgentError> {
+        self.values.borrow_mut().insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AgentError> {
+        self.values.borrow_mut().remove(key);
+        Ok(())
+    }
+
+    fn list_keys(&self, prefix: &str) -> Result<Vec<String>, AgentError> {
+        Ok(self.values.borrow().keys().filter(|k| k.starts_with(prefix)).cloned().collect())
+    }
+
+    fn persistent(&self) -> bool {
+        false
+    }
+}
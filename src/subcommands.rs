@@ -0,0 +1,113 @@
+//! `/ai <subcommand> [arguments]`: one slash command with `ask`, `summarize`, `summarize-channel`, `translate` and `help`.
+
+use crate::channelsummary::slack_summarize_channel;
+use crate::messages::{Msg, message};
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::{SlashAnswer, slack_command_answer, slack_translate_reply};
+
+/* ---- Subcommand router (/ai) ---- */
+// The first word of `/ai`'s text picks the subcommand and the rest is its argument. Other slash
+// commands keep answering their whole text. `/ai` on its own and `/ai help` list the
+// subcommands; an unknown subcommand gets the same list, naming what was not understood. The
+// usage text is generated from SUBCOMMANDS, so adding an entry documents it as well.
+pub(crate) const SUBCOMMAND_COMMAND: &str = "/ai";
+
+struct Subcommand {
+    name: &'static str,
+    /// Argument syntax for the usage text; empty when the subcommand takes none, in `[]` when optional
+    args: &'static str,
+    summary: &'static str,
+    /// Called with the argument text, the user, the channel and whether the answer is private
+    run: fn(&str, &str, &str, bool) -> SlashAnswer,
+}
+
+const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand { name: "ask", args: "<question>", summary: "answer a question", run: slack_command_answer },
+    Subcommand { name: "summarize", args: "<url>", summary: "summarize a web page or document", run: summarize },
+    Subcommand { name: "summarize-channel", args: "[count]", summary: "summarize this channel's latest messages, with action items", run: slack_summarize_channel },
+    Subcommand { name: "translate", args: "<language> <text>", summary: "translate text", run: |text, _, _, _| slack_translate_reply(text).into() },
+    Subcommand { name: "help", args: "", summary: "show this list", run: |_, _, _, _| usage(None).into() },
+];
+
+/// The subcommand name (lowercased) and the rest of the text.
+fn split_subcommand(text: &str) -> (String, &str) {
+    let text = text.trim();
+    let (name, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    (name.to_lowercase(), rest.trim())
+}
+
+fn syntax(sub: &Subcommand) -> String {
+    if sub.args.is_empty() { format!("`{SUBCOMMAND_COMMAND} {}`", sub.name) } else { format!("`{SUBCOMMAND_COMMAND} {} {}`", sub.name, sub.args) }
+}
+
+/// The list of subcommands, after a note about `unknown` when one was given.
+fn usage(unknown: Option<&str>) -> String {
+    let lines: Vec<String> = SUBCOMMANDS.iter().map(|sub| format!("• {}: {}", syntax(sub), sub.summary)).collect();
+    let intro = match unknown {
+        Some(name) => format!("Unknown subcommand `{name}`. Usage:"),
+        None => "Usage:".to_string(),
+    };
+    format!("{intro}\n{}", lines.join("\n"))
+}
+
+/// Answers `/ai` with the subcommand its text names.
+pub(crate) fn slack_subcommand_reply(text: &str, user_id: &str, channel_id: &str, private: bool) -> SlashAnswer {
+    let (name, rest) = split_subcommand(text);
+    if name.is_empty() {
+        return usage(None).into();
+    }
+    match SUBCOMMANDS.iter().find(|sub| sub.name == name) {
+        Some(sub) if rest.is_empty() && !sub.args.is_empty() && !sub.args.starts_with('[') => format!("Usage: {}", syntax(sub)).into(),
+        Some(sub) => (sub.run)(rest, user_id, channel_id, private),
+        None => usage(Some(&name)).into(),
+    }
+}
+
+fn summarize(text: &str, _user_id: &str, _channel_id: &str, _private: bool) -> SlashAnswer {
+    // Slack sends links as `<https://example.com>` or `<https://example.com|label>`
+    let url = text.split_whitespace().next().unwrap_or_default().trim_start_matches('<').trim_end_matches('>');
+    let url = url.split_once('|').map_or(url, |(url, _)| url);
+    match crate::fetch_and_process(url) {
+        Ok(summary) => format!("*Summary of* <{url}>\n{summary}").into(),
+        Err(e) => with_ref(&message(Msg::RequestFailed, &[("error", &e.to_string())]), &current_request_id()).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+
+    #[test]
+    fn usage_is_generated_for_help_and_unknown_subcommands() {
+        let help = slack_subcommand_reply("", "U1", "C1", false).text;
+        assert_eq!(
+            help,
+            "Usage:\n• `/ai ask <question>`: answer a question\n• `/ai summarize <url>`: summarize a web page or document\n\
+             • `/ai summarize-channel [count]`: summarize this channel's latest messages, with action items\n\
+             • `/ai translate <language> <text>`: translate text\n• `/ai help`: show this list"
+        );
+        assert_eq!(slack_subcommand_reply("HELP", "U1", "C1", false).text, help);
+        assert_eq!(slack_subcommand_reply("explain this", "U1", "C1", false).text, help.replacen("Usage:", "Unknown subcommand `explain`. Usage:", 1));
+        assert_eq!(slack_subcommand_reply("summarize  ", "U1", "C1", false).text, "Usage: `/ai summarize <url>`");
+    }
+
+    #[test]
+    fn subcommands_get_the_rest_of_the_text() {
+        with_openai_env();
+        crate::store::MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Paris"));
+        mock.respond(200, "text/plain", "");
+
+        assert_eq!(slack_subcommand_reply("Ask  capital of France?", "U1", "C1", false).text, "Paris");
+        let prompt: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        assert_eq!(prompt["messages"].as_array().unwrap().last().unwrap()["content"], "capital of France?");
+
+        let failed = slack_subcommand_reply("summarize <https://example.com/empty|example>", "U1", "C1", false).text;
+        assert!(failed.starts_with("Sorry, that request failed: no text content at https://example.com/empty"), "{failed}");
+        assert_eq!(mock.requests.borrow()[1].url, "https://example.com/empty");
+    }
+}
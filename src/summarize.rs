@@ -0,0 +1,157 @@
+//! Map-reduce summarization of documents longer than one prompt.
+
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::llm::call_openai_with_system;
+
+/* ---- Token-sized chunks with overlap ---- */
+// Documents over SUMMARY_CONTEXT_TOKENS are split into SUMMARY_CHUNK_TOKENS chunks that repeat
+// the last SUMMARY_CHUNK_OVERLAP_TOKENS of the previous chunk, so a fact cut at a boundary is
+// still seen whole. Tokens are estimated at four characters each; there is no tokenizer in the
+// component, and the estimate only has to keep prompts comfortably inside the model's window.
+const DEFAULT_CONTEXT_TOKENS: usize = 3_000;
+const DEFAULT_CHUNK_TOKENS: usize = 1_500;
+const DEFAULT_OVERLAP_TOKENS: usize = 150;
+const CHARS_PER_TOKEN: usize = 4;
+// Reduce passes over notes that are still too long before they are cut to fit
+const MAX_REDUCE_DEPTH: u32 = 3;
+
+fn token_setting(name: &str, default: usize) -> usize {
+    get_env_var(name).and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Splits at whitespace into chunks of at most about `chunk_tokens`, each starting with the last
+/// `overlap_tokens` of the one before.
+fn chunk_by_tokens(text: &str, chunk_tokens: usize, overlap_tokens: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_inclusive(char::is_whitespace).collect();
+    let max_chars = chunk_tokens.max(1) * CHARS_PER_TOKEN;
+    let overlap_chars = overlap_tokens.min(chunk_tokens / 2) * CHARS_PER_TOKEN;
+    let len = |w: &str| w.chars().count();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < words.len() {
+        let mut end = start;
+        let mut chars = 0;
+        while end < words.len() && (end == start || chars + len(words[end]) <= max_chars) {
+            chars += len(words[end]);
+            end += 1;
+        }
+        chunks.push(words[start..end].concat());
+        if end == words.len() {
+            break;
+        }
+        // Step back over the overlap, but always move forward
+        let mut next = end;
+        let mut overlap = 0;
+        while next > start + 1 && overlap + len(words[next - 1]) <= overlap_chars {
+            overlap += len(words[next - 1]);
+            next -= 1;
+        }
+        start = next;
+    }
+    chunks.retain(|c| !c.trim().is_empty());
+    chunks
+}
+
+/* ---- Map, then reduce ---- */
+/// Summarizes `text` following `instruction`. Text that fits the context is summarized in one
+/// call; longer text is summarized chunk by chunk (map), and the instruction is then applied to
+/// the joined chunk notes (reduce), condensing the notes again first if they are still too long.
+pub(crate) fn summarize_document(text: &str, instruction: &str, max_tokens: u32) -> Result<String, AgentError> {
+    let context = token_setting("SUMMARY_CONTEXT_TOKENS", DEFAULT_CONTEXT_TOKENS);
+    let mut notes = text.to_string();
+    let mut depth = 0;
+    while estimate_tokens(&notes) > context && depth < MAX_REDUCE_DEPTH {
+        notes = map_chunks(&notes, depth)?;
+        depth += 1;
+    }
+    let notes: String = notes.chars().take(context * CHARS_PER_TOKEN).collect();
+    let system = if depth == 0 {
+        instruction.to_string()
+    } else {
+        format!("{instruction}\n\nThe input is a set of notes taken in order from consecutive parts of a document too long to read at once; treat them as the document.")
+    };
+    call_openai_with_system(&system, &notes, max_tokens)
+}
+
+fn map_chunks(text: &str, depth: u32) -> Result<String, AgentError> {
+    let chunks = chunk_by_tokens(
+        text,
+        token_setting("SUMMARY_CHUNK_TOKENS", DEFAULT_CHUNK_TOKENS),
+        token_setting("SUMMARY_CHUNK_OVERLAP_TOKENS", DEFAULT_OVERLAP_TOKENS),
+    );
+    let total = chunks.len();
+    let what = if depth == 0 { "a long document" } else { "notes on a long document" };
+    let mut notes = Vec::with_capacity(total);
+    for (i, chunk) in chunks.iter().enumerate() {
+        notes.push(call_openai_with_system(
+            &format!(
+                "You are reading part {} of {total} of {what}; parts overlap slightly. Write concise notes on this part, \
+                 preserving names, numbers, dates, decisions and conclusions. Do not mention that it is a part.",
+                i + 1
+            ),
+            chunk,
+            300,
+        )?);
+    }
+    Ok(notes.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::llm::completion;
+
+    #[test]
+    fn chunks_respect_the_token_budget_and_overlap() {
+        // Four-character words, so one token each
+        let text = (0..100).map(|i| format!("w{i:02} ")).collect::<String>();
+        let chunks = chunk_by_tokens(&text, 40, 10);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| estimate_tokens(c) <= 40));
+        assert!(chunks[0].starts_with("w00 ") && chunks[0].ends_with("w39 "));
+        assert!(chunks[1].starts_with("w30 ") && chunks[1].ends_with("w69 "));
+        assert!(chunks[2].starts_with("w60 ") && chunks[2].ends_with("w99 "));
+        assert_eq!(chunk_by_tokens("short text", 40, 10), ["short text"]);
+    }
+
+    #[test]
+    fn long_documents_are_summarized_per_chunk_then_reduced() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        let text = "Revenue grew in every region this quarter. ".repeat(350);
+        assert!(estimate_tokens(&text) > DEFAULT_CONTEXT_TOKENS);
+        let chunks = chunk_by_tokens(&text, DEFAULT_CHUNK_TOKENS, DEFAULT_OVERLAP_TOKENS);
+        assert_eq!(chunks.len(), 3);
+        for i in 1..=3 {
+            mock.respond(200, "application/json", &completion(&format!("notes {i}")));
+        }
+        mock.respond(200, "application/json", &completion("The summary."));
+
+        assert_eq!(summarize_document(&text, "Summarize.", 400).unwrap(), "The summary.");
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests.len(), 4);
+        let first: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert!(first["messages"][0]["content"].as_str().unwrap().starts_with("You are reading part 1 of 3 of a long document"));
+        let last: serde_json::Value = serde_json::from_slice(&requests[3].body).unwrap();
+        assert!(last["messages"][0]["content"].as_str().unwrap().starts_with("Summarize.\n\nThe input is a set of notes"));
+        assert_eq!(last["messages"][1]["content"], "notes 1\n\nnotes 2\n\nnotes 3");
+    }
+
+    #[test]
+    fn short_documents_take_one_call() {
+        with_openai_env();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", &completion("Short."));
+        assert_eq!(summarize_document("A short page.", "Summarize.", 400).unwrap(), "Short.");
+        let body: serde_json::Value = serde_json::from_slice(&mock.requests.borrow()[0].body).unwrap();
+        assert_eq!(body["messages"][0]["content"], "Summarize.");
+    }
+}
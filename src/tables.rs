@@ -0,0 +1,89 @@
+//! Tabular rendering of structured results (Markdown, CSV).
+
+
+
+/* ---- Tabular rendering of structured results ---- */
+pub(crate) struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Accepts an array of objects, or an object wrapping one (e.g. `{"rows": [...]}`).
+pub(crate) fn table_from_json(value: &serde_json::Value) -> Option<Table> {
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(map) => {
+            return map.values().find(|v| v.is_array()).and_then(table_from_json);
+        }
+        _ => return None,
+    };
+    if items.is_empty() || !items.iter().all(|i| i.is_object()) {
+        return None;
+    }
+
+    // Column order: first appearance across rows
+    let mut headers: Vec<String> = Vec::new();
+    for item in items {
+        for key in item.as_object().into_iter().flat_map(|o| o.keys()) {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let rows = items
+        .iter()
+        .map(|item| headers.iter().map(|h| cell_text(&item[h.as_str()])).collect())
+        .collect();
+    Some(Table { headers, rows })
+}
+
+pub(crate) fn cell_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Pipe table with columns padded to equal width.
+pub(crate) fn render_markdown_table(table: &Table) -> String {
+    let clean = |s: &str| s.replace('|', "\\|").replace(['\n', '\r'], " ");
+    let headers: Vec<String> = table.headers.iter().map(|h| clean(h)).collect();
+    let rows: Vec<Vec<String>> = table.rows.iter().map(|r| r.iter().map(|c| clean(c)).collect()).collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count().max(3)).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{c}{}", " ".repeat(w - c.chars().count())))
+            .collect();
+        format!("| {} |", padded.join(" | "))
+    };
+
+    let mut out = vec![line(&headers)];
+    out.push(format!("|{}|", widths.iter().map(|w| "-".repeat(w + 2)).collect::<Vec<_>>().join("|")));
+    out.extend(rows.iter().map(|r| line(r)));
+    out.join("\n")
+}
+
+pub(crate) fn render_csv(table: &Table) -> String {
+    let field = |s: &str| {
+        if s.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
+        }
+    };
+    let mut out = String::new();
+    for record in std::iter::once(&table.headers).chain(table.rows.iter()) {
+        out.push_str(&record.iter().map(|c| field(c)).collect::<Vec<_>>().join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
@@ -0,0 +1,151 @@
+//! Per-host token buckets for outgoing requests.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::store::{store_get, store_set};
+
+/* ---- Outbound rate limits (OUTBOUND_RATE_LIMITS) ---- */
+// `api.openai.com=60/min,*.internal.example.com=5/s,*=300/min`: a bucket per destination host
+// holding up to N tokens and refilling at N per period. Buckets live in the key-value store so the
+// limit holds across requests and scheduled runs, not just within one instance. A request that
+// finds its bucket empty fails fast instead of waiting, which is what stops a runaway loop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limit {
+    requests: u32,
+    per_secs: u64,
+}
+
+fn parse_limits(spec: &str) -> Result<Vec<(String, Limit)>, AgentError> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let bad = || AgentError::Config(format!("OUTBOUND_RATE_LIMITS: bad entry '{entry}' (expected host=N/s|min|h)"));
+            let (host, rate) = entry.split_once('=').ok_or_else(bad)?;
+            let (n, unit) = rate.trim().split_once('/').ok_or_else(bad)?;
+            let requests: u32 = n.trim().parse().map_err(|_| bad())?;
+            let per_secs = match unit.trim() {
+                "s" | "sec" => 1,
+                "m" | "min" => 60,
+                "h" | "hour" => 3600,
+                _ => return Err(bad()),
+            };
+            if requests == 0 {
+                return Err(bad());
+            }
+            Ok((host.trim().to_ascii_lowercase(), Limit { requests, per_secs }))
+        })
+        .collect()
+}
+
+/// The most specific rule for `host`: an exact match, then the longest `*.suffix`, then `*`.
+fn limit_for(host: &str, limits: &[(String, Limit)]) -> Option<Limit> {
+    if let Some((_, limit)) = limits.iter().find(|(pattern, _)| pattern == host) {
+        return Some(*limit);
+    }
+    limits
+        .iter()
+        .filter_map(|(pattern, limit)| {
+            let suffix = pattern.strip_prefix("*.")?;
+            host.strip_suffix(suffix).filter(|rest| rest.ends_with('.')).map(|_| (suffix.len(), *limit))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, limit)| limit)
+        .or_else(|| limits.iter().find(|(pattern, _)| pattern == "*").map(|(_, limit)| *limit))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Bucket {
+    tokens: f64,
+    updated: u64,
+}
+
+/// Refills the bucket for the time elapsed since it was last touched, then takes one token.
+/// Returns the updated bucket and, when empty, the seconds until a token is available.
+fn take(bucket: Option<Bucket>, limit: Limit, now: u64) -> (Bucket, Result<(), u64>) {
+    let capacity = limit.requests as f64;
+    let rate = capacity / limit.per_secs as f64;
+    let mut bucket = bucket.unwrap_or(Bucket { tokens: capacity, updated: now });
+    bucket.tokens = (bucket.tokens + now.saturating_sub(bucket.updated) as f64 * rate).min(capacity);
+    bucket.updated = now;
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        (bucket, Ok(()))
+    } else {
+        let wait = ((1.0 - bucket.tokens) / rate).ceil() as u64;
+        (bucket, Err(wait.max(1)))
+    }
+}
+
+/// Takes a token for a request to `host`. Without OUTBOUND_RATE_LIMITS nothing is limited; if
+/// the store is unavailable requests are let through rather than failing every outgoing call.
+pub(crate) fn acquire(host: &str) -> Result<(), AgentError> {
+    let Some(spec) = get_env_var("OUTBOUND_RATE_LIMITS") else { return Ok(()) };
+    let host = host.to_ascii_lowercase();
+    let Some(limit) = limit_for(&host, &parse_limits(&spec)?) else { return Ok(()) };
+    let key = format!("throttle:{host}");
+    let Ok(stored) = store_get(&key) else { return Ok(()) };
+    let bucket = stored.and_then(|raw| serde_json::from_str(&raw).ok());
+    let (bucket, result) = take(bucket, limit, now_secs());
+    let _ = store_set(&key, &serde_json::to_string(&bucket).unwrap_or_default());
+    result.map_err(|retry_after| AgentError::RateLimited { host, retry_after })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(requests: u32, per_secs: u64) -> Limit {
+        Limit { requests, per_secs }
+    }
+
+    #[test]
+    fn limits_parse_units_and_reject_malformed_entries() {
+        let limits = parse_limits(" api.openai.com=60/min, *.internal=5/s ,*=1000/h").unwrap();
+        assert_eq!(limits, [
+            ("api.openai.com".to_string(), limit(60, 60)),
+            ("*.internal".to_string(), limit(5, 1)),
+            ("*".to_string(), limit(1000, 3600)),
+        ]);
+        for bad in ["api.openai.com", "x=5", "x=five/s", "x=5/day", "x=0/s"] {
+            assert!(matches!(parse_limits(bad), Err(AgentError::Config(_))), "{bad}");
+        }
+    }
+
+    #[test]
+    fn most_specific_rule_wins() {
+        let limits = parse_limits("api.example.com=1/s,*.example.com=2/s,*.eu.example.com=3/s,*=4/s").unwrap();
+        assert_eq!(limit_for("api.example.com", &limits), Some(limit(1, 1)));
+        assert_eq!(limit_for("www.example.com", &limits), Some(limit(2, 1)));
+        assert_eq!(limit_for("db.eu.example.com", &limits), Some(limit(3, 1)));
+        assert_eq!(limit_for("example.com", &limits), Some(limit(4, 1)));
+        assert_eq!(limit_for("notexample.com", &limits), Some(limit(4, 1)));
+        assert_eq!(limit_for("other.org", &parse_limits("*.example.com=2/s").unwrap()), None);
+    }
+
+    #[test]
+    fn bucket_allows_a_burst_then_refills_at_the_configured_rate() {
+        let per_minute = limit(3, 60);
+        let mut bucket = None;
+        for _ in 0..3 {
+            let (b, result) = take(bucket, per_minute, 1_000);
+            assert_eq!(result, Ok(()));
+            bucket = Some(b);
+        }
+        let (b, result) = take(bucket, per_minute, 1_000);
+        assert_eq!(result, Err(20));
+
+        // One token every 20 seconds
+        let (b, result) = take(Some(b), per_minute, 1_019);
+        assert_eq!(result, Err(1));
+        let (b, result) = take(Some(b), per_minute, 1_020);
+        assert_eq!(result, Ok(()));
+
+        // A long idle period never banks more than the burst size
+        let (b, _) = take(Some(b), per_minute, 100_000);
+        assert_eq!(b.tokens, 2.0);
+    }
+}
@@ -0,0 +1,266 @@
+//! Token usage tracking per day and the `/agent-usage` report.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::messages::{Msg, message};
+use crate::requestid::{current_request_id, with_ref};
+use crate::slack::blocks::{Block, blocks_json};
+use crate::store::{store_get, store_list_keys, store_set};
+
+/* ---- Requester of the current request ---- */
+// Slash commands name the user and channel they came from; model calls made while handling one
+// (including its deferred work) are attributed to them. API and scheduled calls count as unattributed.
+thread_local! {
+    static REQUESTER: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_requester(user_id: &str, channel_id: &str) {
+    let requester = (!user_id.is_empty() || !channel_id.is_empty()).then(|| (user_id.to_string(), channel_id.to_string()));
+    REQUESTER.with(|r| *r.borrow_mut() = requester);
+}
+
+/* ---- Daily totals (`usage:{day}`) ---- */
+// One record per UTC day holds request and token counts per model, user and channel. Updates are
+// read-modify-write, so concurrent instances can lose an increment; the report is an estimate.
+// Tracking never fails the call it measures.
+#[derive(Serialize, Deserialize, Default)]
+struct DailyUsage {
+    requests: u64,
+    models: BTreeMap<String, TokenCount>,
+    users: BTreeMap<String, TokenCount>,
+    channels: BTreeMap<String, TokenCount>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+struct TokenCount {
+    requests: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+impl TokenCount {
+    fn add(&mut self, other: TokenCount) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+
+    fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+fn usage_key(day: u64) -> String {
+    format!("usage:{day}")
+}
+
+/// Adds a chat completion response's `usage` to today's totals; responses without one are skipped.
+pub(crate) fn record_completion_usage(response: &serde_json::Value) {
+    let usage = &response["usage"];
+    let (Some(prompt_tokens), Some(completion_tokens)) = (usage["prompt_tokens"].as_u64(), usage["completion_tokens"].as_u64()) else {
+        return;
+    };
+    let count = TokenCount { requests: 1, prompt_tokens, completion_tokens };
+    let model = response["model"].as_str().unwrap_or("unknown").to_string();
+    let key = usage_key(now_secs() / 86_400);
+    let Ok(existing) = store_get(&key) else { return };
+    let mut day: DailyUsage = existing.and_then(|raw| serde_json::from_str(&raw).ok()).unwrap_or_default();
+    day.requests += 1;
+    day.models.entry(model).or_default().add(count);
+    if let Some((user, channel)) = REQUESTER.with(|r| r.borrow().clone()) {
+        if !user.is_empty() {
+            day.users.entry(user).or_default().add(count);
+        }
+        if !channel.is_empty() {
+            day.channels.entry(channel).or_default().add(count);
+        }
+    }
+    if let Ok(raw) = serde_json::to_string(&day) {
+        let _ = store_set(&key, &raw);
+    }
+}
+
+/// Drops the user's and/or channel's breakdown from every day, leaving model totals as they are.
+/// Returns how many days mention them; the records are only rewritten when `apply` is set.
+pub(crate) fn forget_usage(user_id: Option<&str>, channel_id: Option<&str>, apply: bool) -> Result<usize, AgentError> {
+    let mut matched = 0;
+    for key in store_list_keys("usage:")? {
+        let Some(mut day) = store_get(&key)?.and_then(|raw| serde_json::from_str::<DailyUsage>(&raw).ok()) else { continue };
+        let removed_user = user_id.is_some_and(|u| day.users.remove(u).is_some());
+        let removed_channel = channel_id.is_some_and(|c| day.channels.remove(c).is_some());
+        if !removed_user && !removed_channel {
+            continue;
+        }
+        matched += 1;
+        if apply {
+            store_set(&key, &serde_json::to_string(&day).map_err(|e| AgentError::Store(e.to_string()))?)?;
+        }
+    }
+    Ok(matched)
+}
+
+/* ---- Cost estimate (LLM_PRICES) ---- */
+// USD per million input/output tokens. A response's model (e.g. `gpt-4o-mini-2024-07-18`) is
+// priced by the longest configured name it starts with; unknown models are left out of the cost.
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[("gpt-4o-mini", 0.15, 0.60), ("gpt-4o", 2.50, 10.00)];
+
+fn prices() -> Vec<(String, f64, f64)> {
+    let configured = get_env_var("LLM_PRICES").and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok());
+    match configured.as_ref().and_then(|v| v.as_object()) {
+        Some(map) => map
+            .iter()
+            .filter_map(|(model, p)| Some((model.clone(), p["input"].as_f64()?, p["output"].as_f64()?)))
+            .collect(),
+        None => DEFAULT_PRICES.iter().map(|(m, i, o)| (m.to_string(), *i, *o)).collect(),
+    }
+}
+
+fn estimated_cost(models: &BTreeMap<String, TokenCount>) -> (f64, bool) {
+    let prices = prices();
+    let mut cost = 0.0;
+    let mut complete = true;
+    for (model, count) in models {
+        match prices.iter().filter(|(name, _, _)| model.starts_with(name.as_str())).max_by_key(|(name, _, _)| name.len()) {
+            Some((_, input, output)) => {
+                cost += (count.prompt_tokens as f64 * input + count.completion_tokens as f64 * output) / 1_000_000.0
+            }
+            None => complete = false,
+        }
+    }
+    (cost, complete)
+}
+
+/* ---- /agent-usage [today|<N>d] ---- */
+const USAGE_HELP: &str = "Usage: `/agent-usage [today|7d|30d|<N>d]` (default 7d, at most 90 days)";
+const MAX_WINDOW_DAYS: u64 = 90;
+const TOP_N: usize = 5;
+
+/// Days in a `today`/`<N>d` window; empty means the default week.
+pub(crate) fn parse_window(text: &str) -> Result<u64, AgentError> {
+    match text.trim() {
+        "" => Ok(7),
+        "today" => Ok(1),
+        other => other
+            .strip_suffix('d')
+            .and_then(|n| n.parse::<u64>().ok())
+            .filter(|n| (1..=MAX_WINDOW_DAYS).contains(n))
+            .ok_or_else(|| AgentError::Validation(format!("unknown window '{other}'"))),
+    }
+}
+
+fn merged_usage(days: u64) -> Result<DailyUsage, AgentError> {
+    let today = now_secs() / 86_400;
+    let mut total = DailyUsage::default();
+    for day in (today + 1).saturating_sub(days)..=today {
+        let Some(raw) = store_get(&usage_key(day))? else { continue };
+        let Ok(usage) = serde_json::from_str::<DailyUsage>(&raw) else { continue };
+        total.requests += usage.requests;
+        for (totals, counts) in [(&mut total.models, usage.models), (&mut total.users, usage.users), (&mut total.channels, usage.channels)] {
+            for (name, count) in counts {
+                totals.entry(name).or_default().add(count);
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// The requests and tokens attributed to `user_id` in the last `days` days.
+pub(crate) fn user_usage(user_id: &str, days: u64) -> Result<(u64, u64), AgentError> {
+    Ok(merged_usage(days)?.users.get(user_id).map_or((0, 0), |count| (count.requests, count.total())))
+}
+
+fn top_list(counts: &BTreeMap<String, TokenCount>, mention: impl Fn(&str) -> String) -> String {
+    let mut ranked: Vec<(&String, &TokenCount)> = counts.iter().collect();
+    ranked.sort_by(|a, b| b.1.total().cmp(&a.1.total()).then(a.0.cmp(b.0)));
+    if ranked.is_empty() {
+        return "_none_".to_string();
+    }
+    ranked
+        .iter()
+        .take(TOP_N)
+        .enumerate()
+        .map(|(i, (name, count))| format!("{}. {} — {} requests, {} tokens", i + 1, mention(name), count.requests, count.total()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The report as fallback text and Block Kit blocks.
+pub(crate) fn slack_usage_report(text: &str) -> (String, Option<serde_json::Value>) {
+    let days = match parse_window(text) {
+        Ok(days) => days,
+        Err(e) => return (format!("{e}\n{USAGE_HELP}"), None),
+    };
+    let usage = match merged_usage(days) {
+        Ok(usage) => usage,
+        Err(e) => return (with_ref(&message(Msg::UsageUnavailable, &[("error", &e.to_string())]), &current_request_id()), None),
+    };
+    let window = if days == 1 { "today (UTC)".to_string() } else { format!("the last {days} days") };
+    let mut tokens = TokenCount::default();
+    usage.models.values().for_each(|c| tokens.add(*c));
+    let (cost, complete) = estimated_cost(&usage.models);
+    let cost = format!("${cost:.2}{}", if complete { "" } else { " (some models unpriced)" });
+
+    let summary = format!("{} requests, {} tokens, ~{cost} in {window}", usage.requests, tokens.total());
+    let field = |label: &str, value: String| format!("*{label}*\n{value}");
+    let blocks = [
+        Block::header(&format!("Agent usage, {window}")),
+        Block::fields([
+            field("Requests", usage.requests.to_string()),
+            field("Tokens", format!("{} ({} in / {} out)", tokens.total(), tokens.prompt_tokens, tokens.completion_tokens)),
+            field("Estimated cost", cost),
+        ]),
+        Block::section(&field("Top users", top_list(&usage.users, |u| format!("<@{u}>")))),
+        Block::section(&field("Top channels", top_list(&usage.channels, |c| format!("<#{c}>")))),
+    ];
+    (summary, Some(blocks_json(&blocks)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::store::MockStore;
+
+    fn response(model: &str, prompt: u64, completion: u64) -> serde_json::Value {
+        serde_json::json!({"model": model, "usage": {"prompt_tokens": prompt, "completion_tokens": completion}})
+    }
+
+    #[test]
+    fn completions_are_totalled_per_day_model_user_and_channel() {
+        let clock = MockClock::install(1_700_000_000);
+        MockStore::install();
+        set_requester("U1", "C1");
+        record_completion_usage(&response("gpt-4o-mini-2024-07-18", 1_000_000, 0));
+        set_requester("U2", "C1");
+        record_completion_usage(&response("gpt-4o", 0, 100_000));
+        set_requester("", "");
+        clock.advance(86_400);
+        record_completion_usage(&response("my-finetune", 10, 10));
+        record_completion_usage(&serde_json::json!({"model": "gpt-4o"}));
+
+        let (summary, blocks) = slack_usage_report("today");
+        assert_eq!(summary, "1 requests, 20 tokens, ~$0.00 (some models unpriced) in today (UTC)");
+        assert_eq!(blocks.unwrap()[2]["text"]["text"], "*Top users*\n_none_");
+
+        let (summary, blocks) = slack_usage_report("");
+        assert_eq!(summary, "3 requests, 1100020 tokens, ~$1.15 (some models unpriced) in the last 7 days");
+        let blocks = blocks.unwrap();
+        assert_eq!(blocks[2]["text"]["text"], "*Top users*\n1. <@U1> — 1 requests, 1000000 tokens\n2. <@U2> — 1 requests, 100000 tokens");
+        assert_eq!(blocks[3]["text"]["text"], "*Top channels*\n1. <#C1> — 2 requests, 1100000 tokens");
+    }
+
+    #[test]
+    fn windows_are_whole_days() {
+        assert_eq!(parse_window("").unwrap(), 7);
+        assert_eq!(parse_window("today").unwrap(), 1);
+        assert_eq!(parse_window(" 30d ").unwrap(), 30);
+        assert!(parse_window("91d").is_err());
+        assert_eq!(slack_usage_report("week").0, format!("unknown window 'week'\n{USAGE_HELP}"));
+    }
+}
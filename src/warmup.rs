@@ -0,0 +1,268 @@
+//! Warmup / preflight checks and the deep health probe.
+
+use crate::bindings::wasi::sockets::instance_network::instance_network;
+use crate::bindings::wasi::sockets::network as net;
+use crate::clock::{clock, now_secs};
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::httpclient::{FetchedDocument, http_get_aws_document_within, http_get_document, http_get_document_within, split_url};
+use crate::llm::{chat_model, mock_enabled};
+use crate::providers::{BEDROCK_SERVICE, credentials_probe};
+use crate::store::{store_delete, store_get, store_is_persistent, store_set};
+use crate::tcpnet::{format_ip, resolve_live, resolve_within};
+
+/* ---- Warmup / preflight ---- */
+pub(crate) const WARMUP_KEY: &str = "warmup:last";
+const WARMUP_HOSTS: &[&str] = &["api.openai.com", "slack.com"];
+
+/// Resolves critical hosts (caching IPv4 answers for the TCP paths) and validates credentials
+/// with cheap calls: OpenAI's model list and Slack's `auth.test`. The report is cached in the store.
+pub(crate) fn run_warmup() -> serde_json::Value {
+    let nw = instance_network();
+    let mut hosts: Vec<String> = WARMUP_HOSTS.iter().map(|h| h.to_string()).collect();
+    if let Some(extra) = get_env_var("WARMUP_HOSTS") {
+        hosts.extend(extra.split(',').map(|h| h.trim().to_string()).filter(|h| !h.is_empty()));
+    }
+
+    let mut dns = serde_json::Map::new();
+    for host in &hosts {
+        let started = clock().monotonic_ns();
+        let result = resolve_live(&nw, host);
+        let elapsed_ms = (clock().monotonic_ns() - started) / 1_000_000;
+        let entry = match result {
+            Ok(ip) => {
+                let addr = format_ip(&ip);
+                if matches!(ip, net::IpAddress::Ipv4(_)) {
+                    let _ = store_set(&format!("dns:{host}"), &addr);
+                }
+                serde_json::json!({"ok": true, "address": addr, "ms": elapsed_ms})
+            }
+            Err(e) => serde_json::json!({"ok": false, "error": e.to_string(), "ms": elapsed_ms}),
+        };
+        dns.insert(host.clone(), entry);
+    }
+
+    let openai = match get_env_var("OPENAI_API_KEY") {
+        _ if mock_enabled() => serde_json::json!({"ok": true, "detail": "LLM_PROVIDER=mock"}),
+        None => serde_json::json!({"ok": false, "error": "OPENAI_API_KEY not set"}),
+        Some(key) => timed_check(|| {
+            http_get_document("https://api.openai.com/v1/models", &[("authorization", format!("Bearer {key}"))]).map(|_| None)
+        }),
+    };
+    let (slack, _) = slack_check(None);
+
+    let report = serde_json::json!({
+        "at": now_secs(),
+        "dns": dns,
+        "openai": openai,
+        "slack": slack,
+    });
+    let _ = store_set(WARMUP_KEY, &report.to_string());
+    report
+}
+
+/// Slack's `auth.test` with the bot token, optionally limited to `timeout_ms`, and Slack's `Date`.
+fn slack_check(timeout_ms: Option<u64>) -> (serde_json::Value, Option<String>) {
+    let Some(token) = get_env_var("SLACK_BOT_TOKEN") else {
+        return (serde_json::json!({"ok": false, "error": "SLACK_BOT_TOKEN not set"}), None);
+    };
+    let mut date = None;
+    let check = timed_check(|| {
+        let url = "https://slack.com/api/auth.test";
+        let headers = [("authorization", format!("Bearer {token}"))];
+        let doc: FetchedDocument = match timeout_ms {
+            Some(ms) => http_get_document_within(url, &headers, ms)?,
+            None => http_get_document(url, &headers)?,
+        };
+        date = doc.meta.date.clone();
+        let json: serde_json::Value = serde_json::from_str(&doc.body).map_err(|e| AgentError::Slack(format!("auth.test: {e}")))?;
+        if json["ok"].as_bool() == Some(true) {
+            Ok(json["team"].as_str().map(|t| format!("team {t}")))
+        } else {
+            Err(AgentError::Slack(format!("auth.test: {}", json["error"].as_str().unwrap_or("unknown error"))))
+        }
+    });
+    (check, date)
+}
+
+/* ---- Deep health (/health/deep, HEALTH_PROBE_TIMEOUT_MS) ---- */
+// Every dependency is probed live with a tight timeout and nothing is cached: the chat model's
+// provider (its model list; no completion is run), Slack's `auth.test`, DNS for the hosts both
+// are reached at, and a write/read/delete round trip through the store. One failed probe makes
+// the report `failing` with status 503; an in-memory store makes it `degraded`.
+const DEFAULT_PROBE_TIMEOUT_MS: u64 = 2_000;
+const PROBE_KEY: &str = "health:probe";
+
+pub(crate) fn deep_health() -> (u16, serde_json::Value) {
+    let timeout_ms = get_env_var("HEALTH_PROBE_TIMEOUT_MS").and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_PROBE_TIMEOUT_MS);
+    let (llm, llm_host, llm_date) = llm_check(timeout_ms);
+    let (slack, slack_date) = slack_check(Some(timeout_ms));
+    let server_date = slack_date.map(|date| ("slack.com".to_string(), date)).or_else(|| llm_host.clone().zip(llm_date));
+    let clock = clock_skew_check(server_date.as_ref().map(|(host, date)| (host.as_str(), date.as_str())));
+
+    let nw = instance_network();
+    let mut dns = serde_json::Map::new();
+    for host in llm_host.into_iter().chain(["slack.com".to_string()]) {
+        let check = timed_check(|| resolve_within(&nw, &host, timeout_ms).map(|ip| Some(format_ip(&ip))));
+        dns.insert(host, check);
+    }
+
+    health_report(serde_json::json!({"llm": llm, "slack": slack, "dns": dns, "store": store_check(), "clock": clock}))
+}
+
+/// The provider check, the host it was reached at for the DNS probe, and the provider's `Date`.
+fn llm_check(timeout_ms: u64) -> (serde_json::Value, Option<String>, Option<String>) {
+    if mock_enabled() {
+        return (serde_json::json!({"ok": true, "detail": "LLM_PROVIDER=mock", "ms": 0}), None, None);
+    }
+    match credentials_probe(&chat_model()) {
+        Err(e) => (serde_json::json!({"ok": false, "error": e.to_string(), "ms": 0}), None, None),
+        Ok((url, headers, aws_region)) => {
+            let host = split_url(&url).ok().map(|(_, authority, _)| authority.split(':').next().unwrap_or_default().to_string());
+            let mut date = None;
+            let check = timed_check(|| {
+                let document = match &aws_region {
+                    Some(region) => http_get_aws_document_within(&url, region, BEDROCK_SERVICE, timeout_ms)?,
+                    None => http_get_document_within(&url, &headers, timeout_ms)?,
+                };
+                date = document.meta.date;
+                Ok(None)
+            });
+            (check, host, date)
+        }
+    }
+}
+
+/* ---- Clock skew (CLOCK_SKEW_TOLERANCE_SECS) ---- */
+// The Slack routes refuse requests whose signed timestamp is more than five minutes off this
+// component's clock (see slack::signature), and receivers of signed job callbacks usually reject
+// stale timestamps too, so a drifting wall clock breaks both with no hint of the cause. Deep
+// health compares the clock with the `Date` of the Slack (else the provider) response; beyond the
+// tolerance the check carries a warning and the report is `degraded`. `Date` has one-second
+// resolution, far below the tolerance.
+const DEFAULT_CLOCK_SKEW_TOLERANCE_SECS: u64 = 300;
+
+/// Compares `now_secs()` with a server's `Date` header, given as (host, value).
+fn clock_skew_check(server_date: Option<(&str, &str)>) -> serde_json::Value {
+    let Some((host, date)) = server_date else {
+        return serde_json::json!({"ok": true, "detail": "no server Date to compare with"});
+    };
+    let Ok(server) = chrono::DateTime::parse_from_rfc2822(&date.replace("GMT", "+0000")) else {
+        return serde_json::json!({"ok": true, "detail": format!("unreadable Date from {host}: {date}")});
+    };
+    let tolerance = get_env_var("CLOCK_SKEW_TOLERANCE_SECS").and_then(|v| v.trim().parse().ok()).unwrap_or(DEFAULT_CLOCK_SKEW_TOLERANCE_SECS);
+    let skew = now_secs() as i64 - server.timestamp();
+    let mut check = serde_json::json!({"ok": true, "skew_secs": skew, "source": host});
+    if skew.unsigned_abs() > tolerance {
+        let direction = if skew > 0 { "ahead of" } else { "behind" };
+        check["warning"] = serde_json::json!(format!(
+            "clock is {}s {direction} {host}, beyond the {tolerance}s tolerance; requests from Slack will fail their signature check and signed callbacks may be refused",
+            skew.unsigned_abs()
+        ));
+    }
+    check
+}
+
+fn store_check() -> serde_json::Value {
+    timed_check(|| {
+        let value = now_secs().to_string();
+        store_set(PROBE_KEY, &value)?;
+        let read = store_get(PROBE_KEY)?;
+        store_delete(PROBE_KEY)?;
+        match read {
+            Some(read) if read == value => Ok((!store_is_persistent()).then(|| "in-memory, not persistent".to_string())),
+            _ => Err(AgentError::Store("read back a different value than was written".into())),
+        }
+    })
+}
+
+/// Overall status over the checks (one level of nesting, as for `dns`) and the HTTP status for it.
+fn health_report(checks: serde_json::Value) -> (u16, serde_json::Value) {
+    let failed = checks.as_object().into_iter().flat_map(|map| map.values()).any(|check| match check.get("ok") {
+        Some(ok) => ok != true,
+        None => check.as_object().is_some_and(|nested| nested.values().any(|c| c["ok"] != true)),
+    });
+    let warned = checks.as_object().into_iter().flat_map(|map| map.values()).any(|check| check.get("warning").is_some());
+    let (code, status) = if failed {
+        (503, "failing")
+    } else if warned || !store_is_persistent() {
+        (200, "degraded")
+    } else {
+        (200, "ok")
+    };
+    (code, serde_json::json!({"status": status, "at": now_secs(), "checks": checks}))
+}
+
+fn timed_check(check: impl FnOnce() -> Result<Option<String>, AgentError>) -> serde_json::Value {
+    let started = clock().monotonic_ns();
+    let result = check();
+    let elapsed_ms = (clock().monotonic_ns() - started) / 1_000_000;
+    match result {
+        Ok(detail) => serde_json::json!({"ok": true, "detail": detail, "ms": elapsed_ms}),
+        Err(e) => serde_json::json!({"ok": false, "error": e.to_string(), "ms": elapsed_ms}),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::with_openai_env;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+
+    #[test]
+    fn timed_check_measures_with_monotonic_clock() {
+        let clock = MockClock::install(0);
+        let report = timed_check(|| {
+            clock.advance(2);
+            Ok(Some("fine".into()))
+        });
+        assert_eq!(report, serde_json::json!({"ok": true, "detail": "fine", "ms": 2000}));
+    }
+
+    #[test]
+    fn deep_health_probes_without_running_a_completion() {
+        with_openai_env();
+        MockClock::install(1_700_000_000);
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", r#"{"data": []}"#);
+        mock.respond(200, "application/json", r#"{"ok": false, "error": "invalid_auth"}"#);
+
+        let (llm, host, _) = llm_check(1_500);
+        assert_eq!((&llm["ok"], host.as_deref()), (&serde_json::json!(true), Some("api.openai.com")));
+        assert_eq!(slack_check(Some(1_500)).0["error"], "auth.test: invalid_auth");
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://api.openai.com/v1/models");
+        assert!(requests.iter().all(|r| r.timeout_ms == Some(1_500)));
+
+        let store = store_check();
+        assert_eq!(store["ok"], true);
+        let dns = serde_json::json!({"slack.com": {"ok": true, "ms": 3}});
+        let (code, report) = health_report(serde_json::json!({"llm": llm, "store": store, "dns": dns}));
+        assert_eq!((code, &report["status"]), (200, &serde_json::json!("degraded")));
+        let dns = serde_json::json!({"slack.com": {"ok": false, "error": "timed out", "ms": 1_500}});
+        assert_eq!(health_report(serde_json::json!({"llm": llm, "dns": dns})).0, 503);
+    }
+
+    #[test]
+    fn clock_skew_beyond_the_tolerance_is_a_warning() {
+        MockClock::install(1_700_000_000);
+        let mock = MockHttpClient::install();
+        with_openai_env();
+        // 1_700_000_000 is Tue, 14 Nov 2023 22:13:20 GMT; Slack's clock is 412 s ahead
+        mock.respond_with_headers(200, &[("content-type", "application/json"), ("date", "Tue, 14 Nov 2023 22:20:12 GMT")], r#"{"ok": true}"#);
+        let (_, date) = slack_check(None);
+        let check = clock_skew_check(date.as_deref().map(|d| ("slack.com", d)));
+        assert_eq!((&check["skew_secs"], &check["ok"]), (&serde_json::json!(-412), &serde_json::json!(true)));
+        assert!(check["warning"].as_str().unwrap().starts_with("clock is 412s behind slack.com, beyond the 300s tolerance"));
+        let (code, report) = health_report(serde_json::json!({"clock": check}));
+        assert_eq!(code, 200);
+        assert_eq!(report["status"], "degraded");
+
+        let close = clock_skew_check(Some(("slack.com", "Tue, 14 Nov 2023 22:13:22 GMT")));
+        assert_eq!((&close["skew_secs"], close.get("warning")), (&serde_json::json!(-2), None));
+        assert_eq!(clock_skew_check(None)["detail"], "no server Date to compare with");
+    }
+}
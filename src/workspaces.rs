@@ -0,0 +1,296 @@
+//! Slack OAuth installs: the stored installation, default workspace settings and onboarding messages.
+
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::now_secs;
+use crate::config::get_env_var;
+use crate::error::AgentError;
+use crate::form::percent_encode;
+use crate::httpclient::{basic_auth, http_post_form};
+use crate::slack::slack_post_message_as;
+use crate::slack::api::slack_api_result;
+use crate::store::{random_bytes, store_delete, store_get, store_set};
+
+/* ---- Installation (SLACK_CLIENT_ID, SLACK_CLIENT_SECRET, SLACK_REDIRECT_URI, SLACK_SCOPES) ---- */
+// "Add to Slack" links to /slack/oauth/start, which sends the user to Slack's consent page with a
+// one-time `state`. Slack redirects back to /slack/oauth/callback?code=...&state=...; a state
+// this app did not issue in the last ten minutes is refused, so an install cannot be started by
+// another site. The code is exchanged with oauth.v2.access (client credentials as HTTP Basic
+// auth) for the workspace's bot token. The installation is stored under `workspace:{team_id}`;
+// reinstalling replaces it.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Installation {
+    pub(crate) team_name: String,
+    pub(crate) bot_token: String,
+    pub(crate) bot_user_id: String,
+    pub(crate) installed_by: String,
+    pub(crate) installed_at: u64,
+}
+
+/// Per-workspace settings, created with defaults on first install and kept across reinstalls.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct WorkspaceConfig {
+    /// Channel that got the intro message; ONBOARDING_CHANNEL or the one picked during install
+    pub(crate) intro_channel: Option<String>,
+    /// Post a capability summary when the bot is added to a channel
+    #[serde(default = "default_true")]
+    pub(crate) greet_on_join: bool,
+    /// Replaces the default channel-join greeting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) join_greeting: Option<String>,
+    /// Provider region the workspace's data must stay in, e.g. `eu` (see residency.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) region: Option<String>,
+    /// Locale of the agent's own messages, e.g. `es` or `pt-BR` (see messages.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) locale: Option<String>,
+    /// Adds the asker's name, time zone and locale to answers' system prompts; PERSONALIZE_ANSWERS when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) personalize: Option<bool>,
+}
+
+const INSTALL_STATE_SECS: u64 = 600;
+const DEFAULT_SCOPES: &str = "commands,chat:write,app_mentions:read,im:history,im:write,channels:history,groups:history,\
+users:read,files:read,files:write,usergroups:read,incoming-webhook";
+
+fn default_true() -> bool {
+    true
+}
+
+pub(crate) fn workspace_config(team_id: &str) -> Result<Option<WorkspaceConfig>, AgentError> {
+    let Some(raw) = store_get(&format!("workspace-config:{team_id}"))? else { return Ok(None) };
+    serde_json::from_str(&raw).map(Some).map_err(|e| AgentError::Store(format!("workspace-config:{team_id}: {e}")))
+}
+
+/// The installation for `team_id`, if the app was installed there through OAuth.
+pub(crate) fn installation(team_id: &str) -> Result<Option<Installation>, AgentError> {
+    let Some(raw) = store_get(&format!("workspace:{team_id}"))? else { return Ok(None) };
+    serde_json::from_str(&raw).map(Some).map_err(|e| AgentError::Store(format!("workspace:{team_id}: {e}")))
+}
+
+/* ---- Current workspace ---- */
+// Slack calls made for a request or job use the bot token of the workspace it came from (see
+// `slack_bot_token`), so one deployment serves every workspace that installed it. Workspaces
+// without an OAuth install, and work that comes from no workspace, use SLACK_BOT_TOKEN.
+thread_local! {
+    static WORKSPACE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Makes `team_id` the workspace of the current request (or job); empty for none.
+pub(crate) fn set_workspace(team_id: &str) {
+    WORKSPACE.with(|current| *current.borrow_mut() = team_id.to_string());
+}
+
+pub(crate) fn current_workspace() -> String {
+    WORKSPACE.with(|current| current.borrow().clone())
+}
+
+/// The Slack consent page to send an installing user to, with a fresh `state` stored for the callback.
+pub(crate) fn install_url() -> Result<String, AgentError> {
+    let client_id = get_env_var("SLACK_CLIENT_ID").ok_or(AgentError::Config("SLACK_CLIENT_ID must be set for OAuth installs".into()))?;
+    let state: String = random_bytes(16).iter().map(|b| format!("{b:02x}")).collect();
+    store_set(&format!("oauth-state:{state}"), &now_secs().to_string())?;
+    let scopes = get_env_var("SLACK_SCOPES").unwrap_or_else(|| DEFAULT_SCOPES.to_string());
+    let mut url = format!("https://slack.com/oauth/v2/authorize?client_id={}&scope={}&state={state}", percent_encode(&client_id), percent_encode(&scopes));
+    if let Some(redirect_uri) = get_env_var("SLACK_REDIRECT_URI").filter(|u| !u.is_empty()) {
+        url.push_str(&format!("&redirect_uri={}", percent_encode(&redirect_uri)));
+    }
+    Ok(url)
+}
+
+/// Uses up `state`; fails unless install_url issued it within the last ten minutes.
+fn take_install_state(state: &str) -> Result<(), AgentError> {
+    let invalid = || AgentError::Validation("install link expired or not issued here; start again from /slack/oauth/start".into());
+    if state.is_empty() || !state.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let key = format!("oauth-state:{state}");
+    let issued = store_get(&key)?.and_then(|at| at.parse::<u64>().ok()).ok_or_else(invalid)?;
+    store_delete(&key)?;
+    if now_secs().saturating_sub(issued) >= INSTALL_STATE_SECS {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+fn exchange_code(code: &str) -> Result<serde_json::Value, AgentError> {
+    let (Some(client_id), Some(secret)) = (get_env_var("SLACK_CLIENT_ID"), get_env_var("SLACK_CLIENT_SECRET")) else {
+        return Err(AgentError::Config("SLACK_CLIENT_ID and SLACK_CLIENT_SECRET must be set for OAuth installs".into()));
+    };
+    let redirect_uri = get_env_var("SLACK_REDIRECT_URI").unwrap_or_default();
+    let mut fields = vec![("code", code)];
+    if !redirect_uri.is_empty() {
+        fields.push(("redirect_uri", redirect_uri.as_str()));
+    }
+    let body = http_post_form("https://slack.com/api/oauth.v2.access", &fields, &[("authorization", basic_auth(&client_id, &secret))])
+        .map_err(|e| AgentError::Slack(format!("oauth.v2.access: {e}")))?;
+    slack_api_result("oauth.v2.access", body.as_bytes())
+}
+
+/* ---- Onboarding messages ---- */
+pub(crate) const CAPABILITIES: &str = "• `/ask <question>` (or your configured command): answers in the channel; paste a Slack image or clip link to ask about it or transcribe it\n\
+• `/translate <language> <text>`: translation with the source language detected\n\
+• `/triage <text>`: classifies feedback or incident reports\n\
+• `/imagine <prompt>`: generates an image\n\
+• `/agent-prefs`: your answer length, language, persona and visibility\n\
+• `/agent-export`: this channel's conversation history as a file in your DMs\n\
+• `/agent-forget me|channel|thread <link>`: deletes what I stored, after you confirm\n\
+• Admins: `/agent-usage`, `/agent-broadcast`, `/prompt-report` and `/agent-quality`";
+
+fn welcome_dm(team_name: &str, intro_channel: Option<&str>) -> String {
+    let intro = intro_channel.map(|c| format!(" I've introduced myself in <#{c}>.")).unwrap_or_default();
+    format!("Thanks for installing me in *{team_name}*!{intro} Here is what I can do:\n{CAPABILITIES}")
+}
+
+fn channel_intro(installed_by: &str) -> String {
+    format!("Hi everyone, I'm the AI assistant <@{installed_by}> just added to this workspace. Here is what I can do:\n{CAPABILITIES}")
+}
+
+/// Completes an install: stores the token and defaults, then greets the installer and the intro
+/// channel. Message failures are reported in the result but do not undo the install.
+pub(crate) fn complete_install(code: &str, state: &str) -> Result<String, AgentError> {
+    take_install_state(state)?;
+    let access = exchange_code(code)?;
+    let team_id = access["team"]["id"].as_str().unwrap_or_default().to_string();
+    let bot_token = access["access_token"].as_str().unwrap_or_default().to_string();
+    if team_id.is_empty() || bot_token.is_empty() {
+        return Err(AgentError::Slack("oauth.v2.access: team or bot token missing from response".into()));
+    }
+    let team_name = access["team"]["name"].as_str().unwrap_or(&team_id).to_string();
+    let installed_by = access["authed_user"]["id"].as_str().unwrap_or_default().to_string();
+    let install = Installation {
+        team_name: team_name.clone(),
+        bot_token: bot_token.clone(),
+        bot_user_id: access["bot_user_id"].as_str().unwrap_or_default().to_string(),
+        installed_by: installed_by.clone(),
+        installed_at: now_secs(),
+    };
+    store_set(&format!("workspace:{team_id}"), &serde_json::to_string(&install).map_err(|e| AgentError::Store(e.to_string()))?)?;
+
+    let config = match workspace_config(&team_id)? {
+        Some(config) => config,
+        None => {
+            let intro_channel = get_env_var("ONBOARDING_CHANNEL")
+                .or_else(|| access["incoming_webhook"]["channel_id"].as_str().map(str::to_string))
+                .filter(|c| !c.is_empty());
+            let config = WorkspaceConfig { intro_channel, greet_on_join: true, join_greeting: None, region: None, locale: None, personalize: None };
+            let raw = serde_json::to_string(&config).map_err(|e| AgentError::Store(e.to_string()))?;
+            store_set(&format!("workspace-config:{team_id}"), &raw)?;
+            config
+        }
+    };
+
+    let mut problems = Vec::new();
+    if let Some(channel) = &config.intro_channel {
+        if let Err(e) = slack_post_message_as(&bot_token, channel, &channel_intro(&installed_by)) {
+            problems.push(format!("intro message: {e}"));
+        }
+    }
+    if !installed_by.is_empty() {
+        let intro = config.intro_channel.as_deref().filter(|_| problems.is_empty());
+        if let Err(e) = slack_post_message_as(&bot_token, &installed_by, &welcome_dm(&team_name, intro)) {
+            problems.push(format!("welcome message: {e}"));
+        }
+    }
+    let mut reply = format!("Installed in {team_name}. You can close this window and return to Slack.");
+    if !problems.is_empty() {
+        reply.push_str(&format!("\n\nSome onboarding messages could not be sent: {}", problems.join("; ")));
+    }
+    Ok(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::httpclient::MockHttpClient;
+    use crate::store::MockStore;
+    use std::env;
+
+    const ACCESS: &str = r#"{"ok": true, "access_token": "xoxb-new", "bot_user_id": "U0BOT",
+        "team": {"id": "T0NEW", "name": "Acme"}, "authed_user": {"id": "U0INSTALLER"},
+        "incoming_webhook": {"channel_id": "C0GENERAL"}}"#;
+
+    /// The `state` of a fresh install link.
+    fn issued_state() -> String {
+        let url = install_url().unwrap();
+        url.split("state=").nth(1).unwrap().split('&').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn install_link_carries_a_one_time_state() {
+        env::set_var("SLACK_CLIENT_ID", "123.456");
+        env::set_var("SLACK_REDIRECT_URI", "https://agent.example.com/slack/oauth/callback");
+        let clock = MockClock::install(1_700_000_000);
+        MockStore::install();
+        let url = install_url().unwrap();
+        env::remove_var("SLACK_REDIRECT_URI");
+        assert!(url.starts_with("https://slack.com/oauth/v2/authorize?client_id=123.456&scope=commands%2Cchat%3Awrite%2C"), "{url}");
+        assert!(url.ends_with("&redirect_uri=https%3A%2F%2Fagent.example.com%2Fslack%2Foauth%2Fcallback"), "{url}");
+
+        let state = issued_state();
+        assert!(take_install_state(&state).is_ok());
+        assert!(matches!(take_install_state(&state), Err(AgentError::Validation(_))));
+        assert!(matches!(complete_install("code", "0123abcd"), Err(AgentError::Validation(_))));
+        let stale = issued_state();
+        clock.advance(INSTALL_STATE_SECS);
+        assert!(matches!(take_install_state(&stale), Err(AgentError::Validation(_))));
+    }
+
+    #[test]
+    fn install_stores_the_workspace_and_greets_the_installer_and_channel() {
+        env::set_var("SLACK_CLIENT_ID", "123.456");
+        env::set_var("SLACK_CLIENT_SECRET", "shh");
+        MockClock::install(1_700_000_000);
+        MockStore::install();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", ACCESS);
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+        mock.respond(200, "application/json", r#"{"ok": true}"#);
+
+        assert_eq!(complete_install("code-1", &issued_state()).unwrap(), "Installed in Acme. You can close this window and return to Slack.");
+
+        let stored = installation("T0NEW").unwrap().unwrap();
+        assert_eq!((stored.bot_token.as_str(), stored.installed_by.as_str()), ("xoxb-new", "U0INSTALLER"));
+        let config = workspace_config("T0NEW").unwrap().unwrap();
+        assert_eq!((config.intro_channel.as_deref(), config.greet_on_join), (Some("C0GENERAL"), true));
+
+        let requests = mock.requests.borrow();
+        assert_eq!(requests[0].url, "https://slack.com/api/oauth.v2.access");
+        assert_eq!(requests[0].body, b"code=code-1");
+        assert!(requests[0].headers.contains(&("authorization".to_string(), basic_auth("123.456", "shh"))));
+        let intro: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(intro["channel"], "C0GENERAL");
+        assert!(requests[1].headers.contains(&("authorization".to_string(), "Bearer xoxb-new".to_string())));
+        let dm: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+        assert_eq!(dm["channel"], "U0INSTALLER");
+        assert!(dm["text"].as_str().unwrap().starts_with("Thanks for installing me in *Acme*! I've introduced myself in <#C0GENERAL>."));
+
+        // Slack calls made for the workspace use its own token
+        crate::config::with_openai_env();
+        set_workspace("T0NEW");
+        assert_eq!(crate::slack::slack_bot_token().unwrap(), "xoxb-new");
+        set_workspace("T0OTHER");
+        assert_eq!(crate::slack::slack_bot_token().unwrap(), "xoxb-test");
+        set_workspace("");
+    }
+
+    #[test]
+    fn reinstall_keeps_settings_and_reports_failed_messages() {
+        env::set_var("SLACK_CLIENT_ID", "123.456");
+        env::set_var("SLACK_CLIENT_SECRET", "shh");
+        MockClock::install(1_700_000_000);
+        MockStore::install();
+        store_set("workspace-config:T0NEW", r#"{"intro_channel": null, "greet_on_join": false}"#).unwrap();
+        let mock = MockHttpClient::install();
+        mock.respond(200, "application/json", ACCESS);
+        mock.respond(200, "application/json", r#"{"ok": false, "error": "cannot_dm_bot"}"#);
+
+        let reply = complete_install("code-2", &issued_state()).unwrap();
+        assert!(reply.ends_with("Some onboarding messages could not be sent: welcome message: chat.postMessage: cannot_dm_bot"), "{reply}");
+        assert!(!workspace_config("T0NEW").unwrap().unwrap().greet_on_join);
+        assert_eq!(mock.requests.borrow().len(), 2);
+    }
+}